@@ -26,3 +26,4 @@ mod tests;
 
 pub use errors::*;
 pub use patcher::*;
+pub use symbols::{extract_symbols, ExtractedSymbol, ExtractedSymbols};
@@ -33,6 +33,9 @@ uint32_t lucet_vmctx_current_memory(struct lucet_vmctx const *);
 // the call on success, or -1 on failure.
 int32_t lucet_vmctx_grow_memory(struct lucet_vmctx const *, uint32_t additional_pages);
 
+// like lucet_vmctx_grow_memory, but zeroes the new pages before returning.
+int32_t lucet_vmctx_grow_memory_zeroing(struct lucet_vmctx const *, uint32_t additional_pages);
+
 // returns the address of a function given its ID
 void *lucet_vmctx_get_func_from_idx(struct lucet_vmctx const *ctx, uint32_t table_id,
                                     uint32_t func_id);
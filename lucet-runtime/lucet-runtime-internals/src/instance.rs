@@ -335,9 +335,16 @@ impl Instance {
     ///
     /// On success, returns the number of pages that existed before the call.
     pub fn grow_memory(&mut self, additional_pages: u32) -> Result<u32, Error> {
+        let additional_bytes =
+            additional_pages
+                .checked_mul(WASM_PAGE_SIZE)
+                .ok_or(Error::LimitsExceeded(format!(
+                    "growing by {} pages would overflow address space",
+                    additional_pages
+                )))?;
         let orig_len = self
             .alloc
-            .expand_heap(additional_pages * WASM_PAGE_SIZE, self.module.as_ref())?;
+            .expand_heap(additional_bytes, self.module.as_ref())?;
         Ok(orig_len / WASM_PAGE_SIZE)
     }
 
@@ -0,0 +1,12 @@
+(module
+  (memory 4 5)
+  (func $main (export "main")
+    ;; Past the declared maximum.
+    (i32.store (i32.const 0) (grow_memory (i32.const 2)))
+    ;; So large that the new size in bytes doesn't fit in 32 bits.
+    (i32.store (i32.const 4) (grow_memory (i32.const 0x10000)))
+    ;; Failed attempts leave the heap as it was, so this one succeeds.
+    (i32.store (i32.const 8) (grow_memory (i32.const 1)))
+    (i32.store (i32.const 12) (current_memory))
+  )
+)
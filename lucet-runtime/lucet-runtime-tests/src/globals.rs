@@ -41,5 +41,26 @@ macro_rules! globals_tests {
             let heap_u32 = unsafe { inst.alloc().heap_u32() };
             assert_eq!(heap_u32[0..=2], [3, 2, 6]);
         }
+
+        #[no_mangle]
+        pub static lucet_runtime_tests_global_x: i32 = 42;
+
+        #[test]
+        fn imported_global_bound_to_symbol() {
+            use $crate::build::{guest_file, wasm_test};
+            let module = wasm_test(
+                guest_file("globals", "import.wat"),
+                guest_file("globals", "import_bindings.json"),
+            )
+            .expect("module compiled and loaded");
+            let region = TestRegion::create(1, &Limits::default()).expect("region can be created");
+            // The start function stores the global to the heap when the instance is reset.
+            let inst = region
+                .new_instance(module)
+                .expect("instance can be created");
+
+            let heap_u32 = unsafe { inst.alloc().heap_u32() };
+            assert_eq!(heap_u32[0], 42);
+        }
     };
 }
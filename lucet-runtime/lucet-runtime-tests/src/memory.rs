@@ -38,5 +38,26 @@ macro_rules! memory_tests {
             // guest then puts the result of the current memory call in heap[4] (indexed by bytes)
             assert_eq!(heap[1], 5);
         }
+
+        #[test]
+        fn grow_memory_failure() {
+            let module = test_module_wasm("memory", "grow_memory_fail.wat")
+                .expect("compile and load grow_memory_fail.wasm");
+            let region = TestRegion::create(1, &Limits::default()).expect("region can be created");
+            let mut inst = region
+                .new_instance(module)
+                .expect("instance can be created");
+
+            inst.run(b"main", &[]).expect("instance runs");
+
+            let heap = inst.heap_u32();
+            // growing past the maximum, or by more pages than fit in the address space, fails
+            // with -1 rather than trapping
+            assert_eq!(heap[0], 0xFFFF_FFFF);
+            assert_eq!(heap[1], 0xFFFF_FFFF);
+            // the heap is untouched by the failures, so growing to the maximum still works
+            assert_eq!(heap[2], 4);
+            assert_eq!(heap[3], 5);
+        }
     };
 }
@@ -358,6 +358,7 @@ pub fn ensure_linked() {
         read_volatile(lucet_vmctx_get_heap as *const extern "C" fn());
         read_volatile(lucet_vmctx_current_memory as *const extern "C" fn());
         read_volatile(lucet_vmctx_grow_memory as *const extern "C" fn());
+        read_volatile(lucet_vmctx_grow_memory_zeroing as *const extern "C" fn());
     });
 }
 
@@ -393,6 +394,25 @@ pub unsafe extern "C" fn lucet_vmctx_grow_memory(
     }
 }
 
+#[no_mangle]
+/// Like `lucet_vmctx_grow_memory`, but the new pages are cleared before returning rather than
+/// assumed to be zero already. Modules compiled with `GrowZeroing::Eager` call this instead.
+pub unsafe extern "C" fn lucet_vmctx_grow_memory_zeroing(
+    vmctx: *mut lucet_vmctx,
+    additional_pages: libc::uint32_t,
+) -> libc::int32_t {
+    let inst = instance_from_vmctx(vmctx);
+    if let Ok(old_pages) = inst.grow_memory(additional_pages) {
+        let start = old_pages as usize * WASM_PAGE_SIZE as usize;
+        for byte in inst.heap_mut()[start..].iter_mut() {
+            *byte = 0;
+        }
+        old_pages as libc::int32_t
+    } else {
+        -1
+    }
+}
+
 #[no_mangle]
 /// Check if a memory region is inside the instance heap.
 pub unsafe extern "C" fn lucet_vmctx_check_heap(
@@ -0,0 +1,400 @@
+//! Lowering the atomic instructions of the threads proposal to plain memory accesses, for
+//! `Atomics::SingleThreaded`.
+//!
+//! The decoder doesn't know the `0xfe` prefix, so this works on the module binary before it is
+//! decoded. Each atomic instruction in the code section is replaced by non-atomic instructions
+//! with the same effect when nothing else can touch the heap, and shared memories are declared
+//! unshared. Unlike the atomic instructions, the replacements don't trap on misaligned addresses.
+
+use crate::error::LucetcErrorKind;
+use crate::load::{raw_sections, write_u32, Atomics, RawSection, Reader};
+use failure::{bail, format_err, Error};
+
+const TYPE_SECTION_ID: u8 = 1;
+const IMPORT_SECTION_ID: u8 = 2;
+const FUNCTION_SECTION_ID: u8 = 3;
+const MEMORY_SECTION_ID: u8 = 5;
+const CODE_SECTION_ID: u8 = 10;
+
+const ATOMIC_PREFIX: u8 = 0xfe;
+const MISC_PREFIX: u8 = 0xfc;
+/// The limits flag bit that marks a memory as shared.
+const SHARED_FLAG: u8 = 0x2;
+
+const I32: u8 = 0x7f;
+const I64: u8 = 0x7e;
+
+const NOP: u8 = 0x01;
+const DROP: u8 = 0x1a;
+const SELECT: u8 = 0x1b;
+const LOCAL_GET: u8 = 0x20;
+const LOCAL_SET: u8 = 0x21;
+const LOCAL_TEE: u8 = 0x22;
+const I32_CONST: u8 = 0x41;
+const I64_CONST: u8 = 0x42;
+
+/// For the seven access widths, in the order the atomic opcodes use them: the value type, and
+/// the plain load and store of that width. Narrow loads zero-extend, as atomic ones do.
+const WIDTHS: [(u8, u8, u8); 7] = [
+    (I32, 0x28, 0x36), // i32
+    (I64, 0x29, 0x37), // i64
+    (I32, 0x2d, 0x3a), // i32, 8 bits
+    (I32, 0x2f, 0x3b), // i32, 16 bits
+    (I64, 0x31, 0x3c), // i64, 8 bits
+    (I64, 0x33, 0x3d), // i64, 16 bits
+    (I64, 0x35, 0x3e), // i64, 32 bits
+];
+
+/// The bits of the expected value a narrow `cmpxchg` compares, by width.
+const MASKS: [i64; 7] = [-1, -1, 0xff, 0xffff, 0xff, 0xffff, 0xffff_ffff];
+
+/// Opcodes of the read-modify-write operations, in the order the atomic opcodes use them: the
+/// i32 and i64 instruction combining the old value with the operand, or `None` for `xchg`.
+const RMW_OPS: [Option<(u8, u8)>; 6] = [
+    Some((0x6a, 0x7c)), // add
+    Some((0x6b, 0x7d)), // sub
+    Some((0x71, 0x83)), // and
+    Some((0x72, 0x84)), // or
+    Some((0x73, 0x85)), // xor
+    None,               // xchg
+];
+
+/// Rewrite `wasm` so it uses no atomic instructions or shared memories. A function the
+/// rewriting doesn't understand is left alone for the decoder to report.
+pub fn lower_single_threaded(wasm: Vec<u8>) -> Result<Vec<u8>, Error> {
+    let sections = match raw_sections(&wasm) {
+        Some(sections) => sections,
+        None => return Ok(wasm),
+    };
+    let section = |id: u8| sections.iter().find(|s| s.id == id);
+    let mut out = wasm.clone();
+
+    // The limits flags are patched in place, since clearing a bit doesn't change their size.
+    if let Some(s) = section(IMPORT_SECTION_ID) {
+        for at in imported_memory_flags(&wasm, s)? {
+            out[at] &= !SHARED_FLAG;
+        }
+    }
+    if let Some(s) = section(MEMORY_SECTION_ID) {
+        for at in memory_flags(&wasm, s)? {
+            out[at] &= !SHARED_FLAG;
+        }
+    }
+
+    let code = match section(CODE_SECTION_ID) {
+        Some(code) => code,
+        None => return Ok(out),
+    };
+    let types = match section(TYPE_SECTION_ID) {
+        Some(s) => param_counts(&wasm, s)?,
+        None => vec![],
+    };
+    let functions = match section(FUNCTION_SECTION_ID) {
+        Some(s) => {
+            let mut r = Reader::new(&wasm[s.payload..s.end]);
+            (0..r.u32()?)
+                .map(|_| r.u32())
+                .collect::<Result<Vec<_>, _>>()?
+        }
+        None => vec![],
+    };
+
+    let mut r = Reader::new(&wasm[code.payload..code.end]);
+    let count = r.u32()?;
+    let mut payload = vec![];
+    write_u32(&mut payload, count);
+    for index in 0..count as usize {
+        let size = r.u32()? as usize;
+        let body = r.bytes(size)?;
+        let params = functions
+            .get(index)
+            .and_then(|ty| types.get(*ty as usize))
+            .ok_or(format_err!("function {} has no type", index))?;
+        let body = lower_body(body, *params)?.unwrap_or_else(|| body.to_vec());
+        write_u32(&mut payload, body.len() as u32);
+        payload.extend(body);
+    }
+
+    let mut section = vec![CODE_SECTION_ID];
+    write_u32(&mut section, payload.len() as u32);
+    section.extend(payload);
+    out.splice(code.start..code.end, section);
+    Ok(out)
+}
+
+/// Scratch locals added to a function that needs them: an address, and an operand, expected
+/// value, and old value of each type.
+struct Scratch {
+    address: u32,
+    operand: [u32; 2],
+    expected: [u32; 2],
+    old: [u32; 2],
+}
+
+impl Scratch {
+    fn new(base: u32) -> Self {
+        Self {
+            address: base,
+            operand: [base + 1, base + 4],
+            expected: [base + 2, base + 5],
+            old: [base + 3, base + 6],
+        }
+    }
+}
+
+/// The lowered body, or `None` if it uses an instruction that isn't understood here.
+fn lower_body(body: &[u8], params: u32) -> Result<Option<Vec<u8>>, Error> {
+    let mut r = Reader::new(body);
+    let groups = r.u32()?;
+    let mut locals = params as u64;
+    for _ in 0..groups {
+        locals += r.u32()? as u64;
+        r.byte()?;
+    }
+    if locals + 7 > u32::max_value() as u64 {
+        bail!("function has too many locals to add scratch locals to");
+    }
+    let declarations = &body[..r.pos];
+    let scratch = Scratch::new(locals as u32);
+    let mut needs_scratch = false;
+    let mut lowered = false;
+
+    let mut code = vec![];
+    while r.pos < body.len() {
+        let start = r.pos;
+        let op = r.byte()?;
+        match op {
+            0x00 | 0x01 | 0x05 | 0x0b | 0x0f | 0x1a | 0x1b | 0x45..=0xc4 => {}
+            0x02 | 0x03 | 0x04 | 0x0c | 0x0d | 0x10 | 0x20..=0x24 | 0x3f | 0x40 | 0x41 | 0x42 => {
+                r.leb()?
+            }
+            0x0e => {
+                for _ in 0..=r.u32()? {
+                    r.leb()?;
+                }
+            }
+            0x11 | 0x28..=0x3e => {
+                r.leb()?;
+                r.leb()?;
+            }
+            0x43 => {
+                r.bytes(4)?;
+            }
+            0x44 => {
+                r.bytes(8)?;
+            }
+            MISC_PREFIX => {
+                // Only the saturating truncations have no immediates.
+                if r.u32()? > 7 {
+                    return Ok(None);
+                }
+            }
+            ATOMIC_PREFIX => {
+                let sub = r.u32()?;
+                if sub == 0x03 {
+                    // `atomic.fence` orders nothing without other threads.
+                    r.byte()?;
+                    code.push(NOP);
+                    lowered = true;
+                    continue;
+                }
+                let memarg_start = r.pos;
+                r.leb()?;
+                r.leb()?;
+                let memarg = &body[memarg_start..r.pos];
+                match sub {
+                    // `memory.atomic.notify` on an unshared memory wakes no one.
+                    0x00 => code.extend(&[DROP, DROP, I32_CONST, 0]),
+                    // `memory.atomic.wait` on an unshared memory traps.
+                    0x01 | 0x02 => code.extend(&[DROP, DROP, DROP, 0x00]),
+                    0x10..=0x16 => {
+                        code.push(WIDTHS[(sub - 0x10) as usize].1);
+                        code.extend(memarg);
+                    }
+                    0x17..=0x1d => {
+                        code.push(WIDTHS[(sub - 0x17) as usize].2);
+                        code.extend(memarg);
+                    }
+                    0x1e..=0x47 => {
+                        let op = RMW_OPS[((sub - 0x1e) / 7) as usize];
+                        rmw(&mut code, &scratch, (sub - 0x1e) as usize % 7, op, memarg);
+                        needs_scratch = true;
+                    }
+                    0x48..=0x4e => {
+                        cmpxchg(&mut code, &scratch, (sub - 0x48) as usize, memarg);
+                        needs_scratch = true;
+                    }
+                    _ => return Ok(None),
+                }
+                lowered = true;
+                continue;
+            }
+            _ => return Ok(None),
+        }
+        code.extend(&body[start..r.pos]);
+    }
+
+    if !lowered {
+        return Ok(None);
+    }
+    let mut out = vec![];
+    if needs_scratch {
+        write_u32(&mut out, groups + 2);
+        out.extend(&declarations[leb_len(declarations)..]);
+        out.extend(&[4, I32, 3, I64]);
+    } else {
+        out.extend(declarations);
+    }
+    out.extend(code);
+    Ok(Some(out))
+}
+
+/// `[address, operand] -> [old]`, storing `old op operand`, or just `operand` for `xchg`.
+fn rmw(code: &mut Vec<u8>, s: &Scratch, width: usize, op: Option<(u8, u8)>, memarg: &[u8]) {
+    let (ty, load, store) = WIDTHS[width];
+    let t = (ty == I64) as usize;
+    local(code, LOCAL_SET, s.operand[t]);
+    local(code, LOCAL_SET, s.address);
+    local(code, LOCAL_GET, s.address);
+    local(code, LOCAL_GET, s.address);
+    code.push(load);
+    code.extend(memarg);
+    local(code, LOCAL_TEE, s.old[t]);
+    match op {
+        Some((op32, op64)) => {
+            local(code, LOCAL_GET, s.operand[t]);
+            code.push(if t == 1 { op64 } else { op32 });
+        }
+        None => {
+            code.push(DROP);
+            local(code, LOCAL_GET, s.operand[t]);
+        }
+    }
+    code.push(store);
+    code.extend(memarg);
+    local(code, LOCAL_GET, s.old[t]);
+}
+
+/// `[address, expected, replacement] -> [old]`, storing `replacement` if `old` equals the
+/// accessed bits of `expected`.
+fn cmpxchg(code: &mut Vec<u8>, s: &Scratch, width: usize, memarg: &[u8]) {
+    let (ty, load, store) = WIDTHS[width];
+    let t = (ty == I64) as usize;
+    local(code, LOCAL_SET, s.operand[t]);
+    local(code, LOCAL_SET, s.expected[t]);
+    local(code, LOCAL_SET, s.address);
+    local(code, LOCAL_GET, s.address);
+    local(code, LOCAL_GET, s.address);
+    code.push(load);
+    code.extend(memarg);
+    local(code, LOCAL_SET, s.old[t]);
+    local(code, LOCAL_GET, s.operand[t]);
+    local(code, LOCAL_GET, s.old[t]);
+    local(code, LOCAL_GET, s.old[t]);
+    local(code, LOCAL_GET, s.expected[t]);
+    if MASKS[width] != -1 {
+        code.push(if t == 1 { I64_CONST } else { I32_CONST });
+        write_i64(code, MASKS[width]);
+        code.push(if t == 1 { 0x83 } else { 0x71 });
+    }
+    code.push(if t == 1 { 0x51 } else { 0x46 });
+    code.push(SELECT);
+    code.push(store);
+    code.extend(memarg);
+    local(code, LOCAL_GET, s.old[t]);
+}
+
+fn local(code: &mut Vec<u8>, op: u8, index: u32) {
+    code.push(op);
+    write_u32(code, index);
+}
+
+/// The number of parameters of each function type.
+fn param_counts(wasm: &[u8], s: &RawSection) -> Result<Vec<u32>, Error> {
+    let mut r = Reader::new(&wasm[s.payload..s.end]);
+    let mut counts = vec![];
+    for _ in 0..r.u32()? {
+        if r.byte()? != 0x60 {
+            bail!("malformed function type");
+        }
+        let params = r.u32()?;
+        r.bytes(params as usize)?;
+        let results = r.u32()?;
+        r.bytes(results as usize)?;
+        counts.push(params);
+    }
+    Ok(counts)
+}
+
+/// Positions of the limits flags of each memory the memory section defines.
+fn memory_flags(wasm: &[u8], s: &RawSection) -> Result<Vec<usize>, Error> {
+    let mut r = Reader::new(&wasm[s.payload..s.end]);
+    let mut flags = vec![];
+    for _ in 0..r.u32()? {
+        flags.push(s.payload + r.pos);
+        r.limits()?;
+    }
+    Ok(flags)
+}
+
+/// Positions of the limits flags of each imported memory.
+fn imported_memory_flags(wasm: &[u8], s: &RawSection) -> Result<Vec<usize>, Error> {
+    let mut r = Reader::new(&wasm[s.payload..s.end]);
+    let mut flags = vec![];
+    for _ in 0..r.u32()? {
+        for _ in 0..2 {
+            let len = r.u32()?;
+            r.bytes(len as usize)?;
+        }
+        match r.byte()? {
+            0x00 => r.leb()?,
+            0x01 => {
+                r.byte()?;
+                r.limits()?;
+            }
+            0x02 => {
+                flags.push(s.payload + r.pos);
+                r.limits()?;
+            }
+            0x03 => {
+                r.bytes(2)?;
+            }
+            kind => bail!("malformed import kind {:#x}", kind),
+        }
+    }
+    Ok(flags)
+}
+
+fn leb_len(bytes: &[u8]) -> usize {
+    bytes
+        .iter()
+        .position(|b| b & 0x80 == 0)
+        .map_or(0, |p| p + 1)
+}
+
+fn write_i64(out: &mut Vec<u8>, mut value: i64) {
+    loop {
+        let b = (value & 0x7f) as u8;
+        value >>= 7;
+        let done = (value == 0 && b & 0x40 == 0) || (value == -1 && b & 0x40 != 0);
+        if done {
+            out.push(b);
+            return;
+        }
+        out.push(b | 0x80);
+    }
+}
+
+/// The error for a module using atomic instructions that `atomics` doesn't let it use.
+pub fn rejected(atomics: Atomics) -> Error {
+    match atomics {
+        Atomics::Disallow => {
+            format_err!("module uses atomic instructions, but `Atomics::Disallow` is set")
+        }
+        _ => format_err!("module uses atomic instructions")
+            .context(LucetcErrorKind::Unsupported(
+                "threads proposal (atomics on shared memory)".to_owned(),
+            ))
+            .into(),
+    }
+}
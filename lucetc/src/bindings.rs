@@ -1,46 +1,106 @@
 use failure::{format_err, Error};
-use serde_json::{self, Map, Value};
+use parity_wasm::elements::{External, FunctionType, Module, Type};
+use serde_json::{self, json, Map, Value};
 use std::collections::{hash_map::Entry, HashMap};
 use std::fs;
 use std::path::Path;
 
-fn parse_modules(
-    m: &Map<String, Value>,
-) -> Result<HashMap<String, HashMap<String, String>>, Error> {
+type ModuleMap<T> = HashMap<String, HashMap<String, T>>;
+
+fn parse_modules(m: &Map<String, Value>) -> Result<(ModuleMap<String>, ModuleMap<i64>), Error> {
     let mut res = HashMap::new();
+    let mut addends = HashMap::new();
     for (modulename, values) in m {
         match values.as_object() {
             Some(methods) => {
-                let methodmap = parse_methods(methods)?;
+                let (methodmap, methodaddends) = parse_methods(methods)?;
                 res.insert(modulename.to_owned(), methodmap);
+                if !methodaddends.is_empty() {
+                    addends.insert(modulename.to_owned(), methodaddends);
+                }
             }
             None => Err(format_err!(""))?,
         }
     }
-    Ok(res)
+    Ok((res, addends))
 }
 
-fn parse_methods(m: &Map<String, Value>) -> Result<HashMap<String, String>, Error> {
+/// A binding is either a symbol, or an object with a `symbol` and an integer `addend`.
+fn parse_methods(
+    m: &Map<String, Value>,
+) -> Result<(HashMap<String, String>, HashMap<String, i64>), Error> {
     let mut res = HashMap::new();
+    let mut addends = HashMap::new();
     for (method, i) in m {
-        match i.as_str() {
-            Some(importbinding) => {
+        match i {
+            Value::String(importbinding) => {
                 res.insert(method.to_owned(), importbinding.to_owned());
             }
-            None => Err(format_err!(""))?,
+            Value::Object(binding) => {
+                let symbol = binding
+                    .get("symbol")
+                    .and_then(|s| s.as_str())
+                    .ok_or(format_err!("binding for {} has no symbol", method))?;
+                let addend = match binding.get("addend") {
+                    Some(addend) => addend
+                        .as_i64()
+                        .ok_or(format_err!("addend for {} is not an integer", method))?,
+                    None => 0,
+                };
+                res.insert(method.to_owned(), symbol.to_owned());
+                if addend != 0 {
+                    addends.insert(method.to_owned(), addend);
+                }
+            }
+            _ => Err(format_err!(""))?,
         }
     }
-    Ok(res)
+    Ok((res, addends))
+}
+
+fn addend_in(addends: &ModuleMap<i64>, module: &str, field: &str) -> i64 {
+    addends
+        .get(module)
+        .and_then(|m| m.get(field))
+        .cloned()
+        .unwrap_or(0)
+}
+
+/// Why a function import can't be bound.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UnboundReason {
+    /// The bindings have nothing for the import's module.
+    MissingModule,
+    /// The import's module has bindings, but not for this field.
+    MissingField,
+    /// The import is bound to `symbol`, which the import `other` is also bound to with a
+    /// different signature.
+    SignatureMismatch {
+        symbol: String,
+        other: (String, String),
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnboundImport {
+    pub module: String,
+    pub field: String,
+    pub reason: UnboundReason,
 }
 
 #[derive(Debug, Clone)]
 pub struct Bindings {
-    bindings: HashMap<String, HashMap<String, String>>,
+    bindings: ModuleMap<String>,
+    /// Offsets from the bound symbol, for the imports that have one.
+    addends: ModuleMap<i64>,
 }
 
 impl Bindings {
     pub fn new(bindings: HashMap<String, HashMap<String, String>>) -> Bindings {
-        Self { bindings: bindings }
+        Self {
+            bindings: bindings,
+            addends: HashMap::new(),
+        }
     }
 
     pub fn env(env: HashMap<String, String>) -> Bindings {
@@ -54,11 +114,11 @@ impl Bindings {
     }
 
     pub fn from_json(v: &Value) -> Result<Bindings, Error> {
-        let bindings = match v.as_object() {
+        let (bindings, addends) = match v.as_object() {
             Some(modules) => parse_modules(modules)?,
             None => Err(format_err!("top level json expected to be object"))?,
         };
-        Ok(Self::new(bindings))
+        Ok(Self { bindings, addends })
     }
 
     pub fn from_str(s: &str) -> Result<Bindings, Error> {
@@ -71,6 +131,25 @@ impl Bindings {
         Ok(Self::from_str(&contents)?)
     }
 
+    /// The bindings in the form `from_json` reads.
+    pub fn to_json(&self) -> Value {
+        let mut modules = Map::new();
+        for (modname, methods) in self.bindings.iter() {
+            let methods = methods
+                .iter()
+                .map(|(method, binding)| {
+                    let value = match addend_in(&self.addends, modname, method) {
+                        0 => Value::from(binding.as_str()),
+                        addend => json!({ "symbol": binding, "addend": addend }),
+                    };
+                    (method.clone(), value)
+                })
+                .collect();
+            modules.insert(modname.clone(), Value::Object(methods));
+        }
+        Value::Object(modules)
+    }
+
     pub fn extend(&mut self, other: &Bindings) -> Result<(), Error> {
         //self.bindings.extend(other.bindings);
         for (modname, othermodbindings) in other.bindings.iter() {
@@ -91,6 +170,18 @@ impl Bindings {
                                         e.get()
                                     ))?;
                                 }
+                                let addend = addend_in(&other.addends, modname, bindname);
+                                let existing = addend_in(&self.addends, modname, bindname);
+                                if addend != existing {
+                                    Err(format_err!(
+                                        "cannot re-bind {} from {}{:+} to {}{:+}",
+                                        e.key(),
+                                        binding,
+                                        addend,
+                                        e.get(),
+                                        existing
+                                    ))?;
+                                }
                             }
                         }
                     }
@@ -100,9 +191,94 @@ impl Bindings {
                 }
             }
         }
+        for (modname, otheraddends) in other.addends.iter() {
+            self.addends
+                .entry(modname.clone())
+                .or_insert_with(HashMap::new)
+                .extend(otheraddends.iter().map(|(k, v)| (k.clone(), *v)));
+        }
         Ok(())
     }
 
+    /// Bind `module::field` to the address `addend` bytes past `symbol`, for hosts that reach
+    /// their functions through a table of entry points rather than one symbol each. The
+    /// relocations to the import carry the addend.
+    pub fn bind_with_addend(&mut self, module: &str, field: &str, symbol: &str, addend: i64) {
+        self.bindings
+            .entry(module.to_owned())
+            .or_insert_with(HashMap::new)
+            .insert(field.to_owned(), symbol.to_owned());
+        let addends = self
+            .addends
+            .entry(module.to_owned())
+            .or_insert_with(HashMap::new);
+        if addend == 0 {
+            addends.remove(field);
+        } else {
+            addends.insert(field.to_owned(), addend);
+        }
+    }
+
+    /// The offset from its bound symbol of the import `module::field`, which is 0 unless it was
+    /// bound with one.
+    pub fn addend(&self, module: &str, field: &str) -> i64 {
+        addend_in(&self.addends, module, field)
+    }
+
+    /// Every function import of `module` that can't be bound, in import order. An empty list
+    /// means compiling the module won't fail for want of a binding. Imports bound to the same
+    /// symbol with different addends are different functions, so their signatures may differ.
+    pub fn verify(&self, module: &Module) -> Result<Vec<UnboundImport>, Error> {
+        let types = module.type_section().map(|s| s.types()).unwrap_or(&[]);
+        let entries = module.import_section().map(|s| s.entries()).unwrap_or(&[]);
+        let mut bound: HashMap<(String, i64), (&str, &str, &FunctionType)> = HashMap::new();
+        let mut unbound = vec![];
+        for entry in entries {
+            let typeix = match entry.external() {
+                External::Function(typeix) => *typeix,
+                _ => continue,
+            };
+            let ftype = match types
+                .get(typeix as usize)
+                .ok_or(format_err!("import refers to nonexistent type {}", typeix))?
+            {
+                Type::Function(ftype) => ftype,
+            };
+            let (module, field) = (entry.module(), entry.field());
+            let reason = match self.bindings.get(module).map(|m| m.get(field)) {
+                None => Some(UnboundReason::MissingModule),
+                Some(None) => Some(UnboundReason::MissingField),
+                Some(Some(symbol)) => {
+                    match bound.entry((symbol.clone(), self.addend(module, field))) {
+                        Entry::Vacant(vac) => {
+                            vac.insert((module, field, ftype));
+                            None
+                        }
+                        Entry::Occupied(occ) => {
+                            let (other_module, other_field, other_type) = *occ.get();
+                            if other_type != ftype {
+                                Some(UnboundReason::SignatureMismatch {
+                                    symbol: symbol.clone(),
+                                    other: (other_module.to_owned(), other_field.to_owned()),
+                                })
+                            } else {
+                                None
+                            }
+                        }
+                    }
+                }
+            };
+            if let Some(reason) = reason {
+                unbound.push(UnboundImport {
+                    module: module.to_owned(),
+                    field: field.to_owned(),
+                    reason,
+                });
+            }
+        }
+        Ok(unbound)
+    }
+
     pub fn translate(&self, module: &str, symbol: &str) -> Result<String, Error> {
         match self.bindings.get(module) {
             Some(m) => match m.get(symbol) {
@@ -176,4 +352,22 @@ mod tests {
             "bindings from file returned value for non-existent symbol"
         );
     }
+
+    #[test]
+    fn addends() {
+        let map = Bindings::from_str(
+            r#"{"env": {"hello": "hello", "inc": {"symbol": "host_table", "addend": 16}}}"#,
+        )
+        .expect("load bindings with an addend");
+        assert_eq!(map.translate("env", "inc").unwrap(), "host_table");
+        assert_eq!(map.addend("env", "inc"), 16);
+        assert_eq!(map.addend("env", "hello"), 0);
+
+        let round_trip = Bindings::from_json(&map.to_json()).expect("reload bindings");
+        assert_eq!(round_trip.addend("env", "inc"), 16);
+
+        let mut other = Bindings::empty();
+        other.bind_with_addend("env", "inc", "host_table", 24);
+        assert!(round_trip.clone().extend(&other).is_err());
+    }
 }
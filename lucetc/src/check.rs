@@ -0,0 +1,110 @@
+//! Every check that can be made on a module without translating it, collected into one report.
+
+use crate::bindings::{Bindings, UnboundImport, UnboundReason};
+use crate::load::check_features;
+use crate::program::memory::create_heap_spec;
+use crate::program::{check_type_indices, memory_spec, HeapSettings};
+use failure::Error;
+use parity_wasm::elements::{External, Module};
+use pwasm_validation::validate_module;
+use std::fmt;
+
+/// One reason the module would fail to compile.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Problem {
+    /// The module couldn't be read or decoded, so nothing else was checked.
+    Unreadable(String),
+    /// The module uses a proposal that is not enabled, or that lucetc can't compile.
+    Feature(String),
+    UnboundImport(UnboundImport),
+    /// The module is not valid WebAssembly.
+    Invalid(String),
+    /// The module's memory doesn't fit the heap settings.
+    Limit(String),
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Problem::Unreadable(e) => write!(f, "unreadable module: {}", e),
+            Problem::Feature(e) => write!(f, "unsupported feature: {}", e),
+            Problem::UnboundImport(import) => {
+                write!(f, "unbound import {}::{}: ", import.module, import.field)?;
+                match import.reason {
+                    UnboundReason::MissingModule => write!(f, "no bindings for the module"),
+                    UnboundReason::MissingField => write!(f, "no binding for the field"),
+                    UnboundReason::SignatureMismatch {
+                        ref symbol,
+                        ref other,
+                    } => write!(
+                        f,
+                        "bound to {}, which {}::{} imports with another signature",
+                        symbol, other.0, other.1
+                    ),
+                }
+            }
+            Problem::Invalid(e) => write!(f, "invalid module: {}", e),
+            Problem::Limit(e) => write!(f, "heap limit: {}", e),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CheckReport {
+    pub problems: Vec<Problem>,
+}
+
+impl CheckReport {
+    pub fn passed(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+/// Run every check on `module` that doesn't need it translated, carrying on past failures.
+/// Validation is skipped when a function's type index is out of range, since the validator
+/// can't say which function it is.
+pub fn check_module(module: &Module, bindings: &Bindings, heap: &HeapSettings) -> CheckReport {
+    let mut problems = vec![];
+
+    if let Err(e) = check_features(module) {
+        problems.push(Problem::Feature(chain(&e)));
+    }
+
+    match bindings.verify(module) {
+        Ok(unbound) => problems.extend(unbound.into_iter().map(Problem::UnboundImport)),
+        Err(e) => problems.push(Problem::Invalid(chain(&e))),
+    }
+
+    match check_type_indices(module) {
+        Ok(()) => {
+            if let Err(e) = validate_module(module.clone()) {
+                problems.push(Problem::Invalid(e.to_string()));
+            }
+        }
+        Err(e) => problems.push(Problem::Invalid(e.to_string())),
+    }
+
+    let imported = module
+        .import_section()
+        .map(|s| s.entries())
+        .unwrap_or(&[])
+        .iter()
+        .filter_map(|e| match e.external() {
+            External::Memory(m) => Some(m),
+            _ => None,
+        });
+    let defined = module.memory_section().map(|s| s.entries()).unwrap_or(&[]);
+    if let Some(mem) = imported.chain(defined).next() {
+        if let Err(e) = create_heap_spec(&memory_spec(mem), heap) {
+            problems.push(Problem::Limit(e.to_string()));
+        }
+    }
+
+    CheckReport { problems }
+}
+
+/// `e` and its causes, outermost first.
+fn chain(e: &Error) -> String {
+    let causes: Vec<String> = e.iter_chain().map(|c| c.to_string()).collect();
+    causes.join(": ")
+}
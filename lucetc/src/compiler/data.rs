@@ -1,7 +1,16 @@
-use crate::compiler::Compiler;
+use crate::compiler::entity::{EntityCreator, NATIVE_POINTER};
+use crate::compiler::name::Name;
+use crate::compiler::naming::MetadataSymbol;
+use crate::compiler::{Compiler, DataInit, SegmentOffsetPolicy};
+use crate::error::{LucetcError, LucetcErrorKind};
 use byteorder::{LittleEndian, WriteBytesExt};
-use cranelift_module::{DataContext, Linkage};
-use failure::Error;
+use cranelift_codegen::ir::condcodes::IntCC;
+use cranelift_codegen::ir::types::{I32, I64};
+use cranelift_codegen::ir::{self, InstBuilder, MemFlags, TrapCode};
+use cranelift_codegen::isa::CallConv;
+use cranelift_frontend::{FunctionBuilder, FunctionBuilderContext};
+use cranelift_module::{Backend, DataContext, Linkage};
+use failure::{format_err, Error};
 
 /// Formats and stores WASM data segment initializers[0] from the program to
 /// symbols in the data section of obj.
@@ -21,9 +30,8 @@ use failure::Error;
 /// - Does not coalesce data segments
 /// - Uses an implicit data format for the serialized segment data, defined
 ///    only in the code below
-pub fn compile_data_initializers(compiler: &mut Compiler) -> Result<(), Error> {
+pub fn compile_data_initializers<B: Backend>(compiler: &mut Compiler<B>) -> Result<(), Error> {
     let mut serialized: Vec<u8> = Vec::new();
-
     for initializer in compiler.prog.data_initializers()? {
         // Data segment has been validated in program::data.
         // memory_index is always 0 per spec, so we dont put it in data.
@@ -54,26 +62,134 @@ pub fn compile_data_initializers(compiler: &mut Compiler) -> Result<(), Error> {
     seg_len_ctx.define(serialized_len.into_boxed_slice());
 
     let writeable = false;
-    let seg_len_decl =
-        compiler
-            .module
-            .declare_data("wasm_data_segments_len", Linkage::Export, writeable)?;
+    let seg_len_decl = compiler.module.declare_data(
+        &compiler.metadata_symbol(MetadataSymbol::DataSegmentsLen),
+        Linkage::Export,
+        writeable,
+    )?;
     compiler.module.define_data(seg_len_decl, &seg_len_ctx)?;
 
     let mut seg_ctx = DataContext::new();
     seg_ctx.define(serialized.into_boxed_slice());
-    let seg_decl =
-        compiler
-            .module
-            .declare_data("wasm_data_segments", Linkage::Export, writeable)?;
+    let seg_decl = compiler.module.declare_data(
+        &compiler.metadata_symbol(MetadataSymbol::DataSegments),
+        Linkage::Export,
+        writeable,
+    )?;
     compiler.module.define_data(seg_decl, &seg_ctx)?;
 
     Ok(())
 }
 
+/// Function copying the data segments placed at imported globals into the heap. It takes only
+/// the vmctx, and traps with `HeapOutOfBounds` if a segment doesn't fit in the current heap.
+pub const DEFERRED_DATA_INIT_SYM: &str = "guest_deferred_data_init";
+
+const WASM_PAGE_SIZE: i64 = 64 * 1024;
+
+/// Handles the segments `compile_data_initializers` leaves out because their offsets are
+/// imported globals, as the settings' `SegmentOffsetPolicy` says. With `InitAtRuntime`, each
+/// segment's contents go in a local data symbol, and `DEFERRED_DATA_INIT_SYM` is defined to
+/// copy them in. Nothing is defined for a module without such segments.
+pub fn compile_deferred_data<B: Backend>(compiler: &mut Compiler<B>) -> Result<(), Error> {
+    let prog = compiler.prog;
+    let deferred = prog.deferred_data_initializers();
+    if let Some(first) = deferred.first() {
+        if compiler.settings.non_constant_segment_offset == SegmentOffsetPolicy::Fail {
+            Err(LucetcError::from(
+                LucetcErrorKind::NonConstantSegmentOffset {
+                    segment: first.segment,
+                    global: first.global,
+                },
+            ))?;
+        }
+    } else {
+        return Ok(());
+    }
+
+    let mut sources = Vec::with_capacity(deferred.len());
+    for segment in deferred.iter() {
+        let symbol = format!("guest_deferred_data_{}", segment.segment);
+        let name = compiler.declare_data(&symbol, Linkage::Local, false)?;
+        let mut ctx = DataContext::new();
+        ctx.define(segment.data.to_vec().into_boxed_slice());
+        compiler.define_data(name.clone(), &ctx)?;
+        sources.push(name);
+    }
+
+    let mut sig = ir::Signature::new(CallConv::SystemV);
+    sig.params.push(ir::AbiParam::special(
+        NATIVE_POINTER,
+        ir::ArgumentPurpose::VMContext,
+    ));
+    let funcid = compiler
+        .module
+        .declare_function(DEFERRED_DATA_INIT_SYM, Linkage::Export, &sig)?;
+    let name = Name::new_func(DEFERRED_DATA_INIT_SYM.to_owned(), funcid);
+    let frontend_config = compiler.isa().frontend_config();
+
+    let mut func = ir::Function::with_name_signature(name.clone().into(), sig);
+    {
+        let mut entity_creator = EntityCreator::new(prog);
+        let mut ctx = FunctionBuilderContext::new();
+        let mut builder = FunctionBuilder::new(&mut func, &mut ctx);
+        let entry_block = builder.create_ebb();
+        builder.append_ebb_params_for_function_params(entry_block);
+        builder.switch_to_block(entry_block);
+        builder.seal_block(entry_block);
+        let vmctx = builder.ebb_params(entry_block)[0];
+
+        let current_memory = entity_creator
+            .get_runtime_func(builder.func, "lucet_vmctx_current_memory".into(), compiler)?
+            .0;
+        let call = builder.ins().call(current_memory, &[vmctx]);
+        let pages = builder.inst_results(call)[0];
+        let pages = builder.ins().uextend(I64, pages);
+        let heap_size = builder.ins().imul_imm(pages, WASM_PAGE_SIZE);
+        let heap = entity_creator.get_heap(builder.func, 0, compiler)?;
+        let heap_base = builder.func.heaps[heap].base;
+        let heap_base = builder.ins().global_value(NATIVE_POINTER, heap_base);
+
+        for (segment, source) in deferred.iter().zip(sources) {
+            let global = entity_creator.get_global(builder.func, segment.global, compiler)?;
+            if global.ty != I32 {
+                return Err(format_err!(
+                    "data segment {} is placed at global {}, which is not an i32",
+                    segment.segment,
+                    segment.global
+                ));
+            }
+            let global_addr = builder.ins().global_value(NATIVE_POINTER, global.var);
+            let offset = builder.ins().load(I32, MemFlags::new(), global_addr, 0);
+            let offset = builder.ins().uextend(I64, offset);
+            let end = builder.ins().iadd_imm(offset, segment.data.len() as i64);
+            let past_end = builder
+                .ins()
+                .icmp(IntCC::UnsignedGreaterThan, end, heap_size);
+            builder.ins().trapnz(past_end, TrapCode::HeapOutOfBounds);
+
+            let dest = builder.ins().iadd(heap_base, offset);
+            let source = source
+                .into_dataid()
+                .ok_or(format_err!("data defined with invalid name {:?}", source))?;
+            let source = compiler.module.declare_data_in_func(source, builder.func);
+            let source = builder.ins().global_value(NATIVE_POINTER, source);
+            let len = builder.ins().iconst(I64, segment.data.len() as i64);
+            builder.call_memcpy(frontend_config, dest, source, len);
+        }
+        builder.ins().return_(&[]);
+        builder.finalize();
+    }
+    compiler.define_function(name.clone(), func)?;
+    compiler.layout.push(name);
+    Ok(())
+}
+
 use std::io::Cursor;
 
-pub fn compile_sparse_page_data(compiler: &mut Compiler) -> Result<(), Error> {
+const PAGE_SIZE: usize = 4096;
+
+pub fn compile_sparse_page_data<B: Backend>(compiler: &mut Compiler<B>) -> Result<(), Error> {
     use crate::program::data::sparse::OwnedSparseData;
     let owned_data = OwnedSparseData::new(
         &compiler.prog.data_initializers()?,
@@ -105,7 +221,9 @@ pub fn compile_sparse_page_data(compiler: &mut Compiler) -> Result<(), Error> {
             seg_ctx.define(vs.to_vec().into_boxed_slice());
             compiler.module.define_data(seg_decl, &seg_ctx)?;
 
-            // Put a relocation to that array into the table:
+            // Put a relocation to that array into the table. It is an absolute relocation
+            // against a local symbol, which the linker turns into a relative relocation in a
+            // shared object, so the table is correct wherever the object is loaded.
             let seg_gv = compiler
                 .module
                 .declare_data_in_data(seg_decl, &mut table_ctx);
@@ -115,11 +233,116 @@ pub fn compile_sparse_page_data(compiler: &mut Compiler) -> Result<(), Error> {
     }
 
     table_ctx.define(table_data.into_inner().into_boxed_slice());
-    let table_decl =
-        compiler
-            .module
-            .declare_data("guest_sparse_page_data", Linkage::Export, false)?;
+    let table_decl = compiler.module.declare_data(
+        &compiler.metadata_symbol(MetadataSymbol::SparsePageData),
+        Linkage::Export,
+        false,
+    )?;
     compiler.module.define_data(table_decl, &table_ctx)?;
 
     Ok(())
 }
+
+/// With `separate_rodata`, write the initialized pages of the heap to `lucet_rodata`, so that a
+/// runtime can map them copy-on-write and share them between instances rather than copying them
+/// in for each one.
+///
+/// `lucet_rodata_spec` says where they go: a u64 count of runs of consecutive initialized pages,
+/// then the u64 heap offset, u64 offset within `lucet_rodata`, and u64 length in bytes of each
+/// run. Neither symbol is defined without `separate_rodata`. The segments and the sparse page
+/// table are still written as usual, so a runtime that doesn't know about `lucet_rodata` can
+/// ignore it.
+pub fn compile_rodata<B: Backend>(compiler: &mut Compiler<B>) -> Result<(), Error> {
+    use crate::program::data::sparse::OwnedSparseData;
+    if !compiler.settings.separate_rodata {
+        return Ok(());
+    }
+    let owned_data = OwnedSparseData::new(
+        &compiler.prog.data_initializers()?,
+        compiler.prog.heap_spec()?,
+    );
+    let sparse_data = owned_data.sparse_data();
+
+    let mut rodata: Vec<u8> = Vec::new();
+    // (heap offset, rodata offset, length)
+    let mut runs: Vec<(u64, u64, u64)> = Vec::new();
+    for (dix, d) in sparse_data.pages().iter().enumerate() {
+        if let Some(vs) = d {
+            let heap_offset = (dix * PAGE_SIZE) as u64;
+            match runs.last_mut() {
+                Some(run) if run.0 + run.2 == heap_offset => run.2 += vs.len() as u64,
+                _ => runs.push((heap_offset, rodata.len() as u64, vs.len() as u64)),
+            }
+            rodata.extend_from_slice(vs);
+        }
+    }
+
+    let mut spec: Vec<u8> = Vec::with_capacity(8 + runs.len() * 24);
+    spec.write_u64::<LittleEndian>(runs.len() as u64).unwrap();
+    for (heap_offset, rodata_offset, len) in runs {
+        spec.write_u64::<LittleEndian>(heap_offset).unwrap();
+        spec.write_u64::<LittleEndian>(rodata_offset).unwrap();
+        spec.write_u64::<LittleEndian>(len).unwrap();
+    }
+
+    for (symbol, contents) in vec![
+        (MetadataSymbol::Rodata, rodata),
+        (MetadataSymbol::RodataSpec, spec),
+    ] {
+        let decl = compiler.module.declare_data(
+            &compiler.metadata_symbol(symbol),
+            Linkage::Export,
+            false,
+        )?;
+        let mut ctx = DataContext::new();
+        ctx.define(contents.into_boxed_slice());
+        compiler.module.define_data(decl, &ctx)?;
+    }
+    Ok(())
+}
+
+/// With `DataInit::Snapshot`, write the initial heap to `lucet_data_snapshot`: every byte from
+/// offset 0 up to the last one a data segment initializes, with later segments overwriting
+/// earlier ones as they would at instantiation. `lucet_data_snapshot_len` holds its u64 length.
+/// Neither symbol is defined otherwise. The segments and the sparse page table are still written
+/// as usual, so a runtime that doesn't know about the snapshot can ignore it.
+pub fn compile_data_snapshot<B: Backend>(compiler: &mut Compiler<B>) -> Result<(), Error> {
+    if compiler.settings.data_init != DataInit::Snapshot {
+        return Ok(());
+    }
+    if compiler.settings.separate_rodata {
+        return Err(format_err!(
+            "a data snapshot can't be combined with separate rodata"
+        ));
+    }
+
+    let initializers = compiler.prog.data_initializers()?;
+    let len = initializers
+        .iter()
+        .map(|i| i.offset as usize + i.data.len())
+        .max()
+        .unwrap_or(0);
+    let mut snapshot = vec![0u8; len];
+    for initializer in initializers {
+        let start = initializer.offset as usize;
+        snapshot[start..start + initializer.data.len()].copy_from_slice(initializer.data);
+    }
+
+    let mut snapshot_len: Vec<u8> = Vec::new();
+    snapshot_len.write_u64::<LittleEndian>(len as u64).unwrap();
+
+    for (symbol, contents) in vec![
+        (MetadataSymbol::DataSnapshot, snapshot),
+        (MetadataSymbol::DataSnapshotLen, snapshot_len),
+    ] {
+        let decl = compiler.module.declare_data(
+            &compiler.metadata_symbol(symbol),
+            Linkage::Export,
+            false,
+        )?;
+        let mut ctx = DataContext::new();
+        ctx.define(contents.into_boxed_slice());
+        compiler.module.define_data(decl, &ctx)?;
+    }
+    Ok(())
+}
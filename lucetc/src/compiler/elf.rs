@@ -0,0 +1,320 @@
+//! Just enough ELF reading to post-process the objects lucetc writes.
+//!
+//! Only 64-bit little-endian ELF is understood, which is everything lucetc emits on Linux.
+
+use byteorder::{ByteOrder, LittleEndian};
+use failure::{bail, format_err, Error};
+use sha1::{Digest, Sha1};
+
+pub const SHT_SYMTAB: u32 = 2;
+const SHT_NOTE: u32 = 7;
+const SHF_ALLOC: u64 = 0x2;
+pub const STT_SECTION: u8 = 3;
+pub const STB_GLOBAL: u8 = 1;
+pub const SHN_UNDEF: u16 = 0;
+
+pub const SHDR_SIZE: usize = 64;
+pub const SYM_SIZE: usize = 24;
+
+/// Whether `obj` starts like an ELF object of any class or byte order.
+pub fn is_elf(obj: &[u8]) -> bool {
+    obj.starts_with(b"\x7fELF")
+}
+
+pub struct SectionHeader {
+    pub name: u32,
+    pub kind: u32,
+    pub flags: u64,
+    pub offset: usize,
+    pub size: usize,
+    pub link: u32,
+    pub info: u32,
+    pub addralign: u64,
+}
+
+pub struct Elf<'a> {
+    pub obj: &'a [u8],
+    pub sections: Vec<SectionHeader>,
+    /// Byte position of the section header table.
+    pub shoff: usize,
+    shstrndx: usize,
+}
+
+impl<'a> Elf<'a> {
+    pub fn parse(obj: &'a [u8]) -> Result<Self, Error> {
+        if obj.len() < 64 || &obj[0..4] != b"\x7fELF" || obj[4] != 2 || obj[5] != 1 {
+            bail!("only 64-bit little-endian ELF objects can be post-processed");
+        }
+        let shoff = LittleEndian::read_u64(&obj[0x28..]) as usize;
+        let shnum = LittleEndian::read_u16(&obj[0x3c..]) as usize;
+        let shstrndx = LittleEndian::read_u16(&obj[0x3e..]) as usize;
+
+        let mut sections = Vec::with_capacity(shnum);
+        for i in 0..shnum {
+            let h = section_header_range(shoff, i)
+                .and_then(|range| obj.get(range))
+                .ok_or(format_err!("section header {} is out of bounds", i))?;
+            sections.push(SectionHeader {
+                name: LittleEndian::read_u32(&h[0x00..]),
+                kind: LittleEndian::read_u32(&h[0x04..]),
+                flags: LittleEndian::read_u64(&h[0x08..]),
+                offset: LittleEndian::read_u64(&h[0x18..]) as usize,
+                size: LittleEndian::read_u64(&h[0x20..]) as usize,
+                link: LittleEndian::read_u32(&h[0x28..]),
+                info: LittleEndian::read_u32(&h[0x2c..]),
+                addralign: LittleEndian::read_u64(&h[0x30..]),
+            });
+        }
+        Ok(Self {
+            obj,
+            sections,
+            shoff,
+            shstrndx,
+        })
+    }
+
+    pub fn section_data(&self, index: usize) -> Result<&'a [u8], Error> {
+        let s = self
+            .sections
+            .get(index)
+            .ok_or(format_err!("section {} does not exist", index))?;
+        s.offset
+            .checked_add(s.size)
+            .and_then(|end| self.obj.get(s.offset..end))
+            .ok_or(format_err!("section {} is out of bounds", index))
+    }
+
+    pub fn string(&self, strtab: usize, offset: u32) -> Result<String, Error> {
+        let data = self
+            .section_data(strtab)?
+            .get(offset as usize..)
+            .ok_or(format_err!(
+                "string {} is out of bounds of section {}",
+                offset,
+                strtab
+            ))?;
+        let len = data.iter().position(|b| *b == 0).unwrap_or(data.len());
+        Ok(String::from_utf8_lossy(&data[..len]).into_owned())
+    }
+
+    pub fn section_name(&self, index: usize) -> Result<String, Error> {
+        let name = self
+            .sections
+            .get(index)
+            .ok_or(format_err!("section {} does not exist", index))?
+            .name;
+        self.string(self.shstrndx, name)
+    }
+
+    /// The name of symbol `index` in symbol table `symtab`.
+    pub fn symbol_name(&self, symtab: usize, index: usize) -> Result<String, Error> {
+        let data = self.section_data(symtab)?;
+        let sym = data
+            .get(index * SYM_SIZE..(index + 1) * SYM_SIZE)
+            .ok_or(format_err!("symbol {} is out of bounds", index))?;
+        if sym[4] & 0xf == STT_SECTION {
+            self.section_name(LittleEndian::read_u16(&sym[6..]) as usize)
+        } else {
+            let strtab = self.sections[symtab].link as usize;
+            self.string(strtab, LittleEndian::read_u32(&sym[0..]))
+        }
+    }
+
+    /// Names of the symbols in `symtab` that are global and defined in this object.
+    pub fn global_definitions(&self, symtab: usize) -> Result<Vec<String>, Error> {
+        let data = self.section_data(symtab)?;
+        let mut names = vec![];
+        for index in 1..data.len() / SYM_SIZE {
+            let sym = &data[index * SYM_SIZE..(index + 1) * SYM_SIZE];
+            let global = sym[4] >> 4 == STB_GLOBAL;
+            let defined = LittleEndian::read_u16(&sym[6..]) != SHN_UNDEF;
+            if global && defined {
+                names.push(self.symbol_name(symtab, index)?);
+            }
+        }
+        Ok(names)
+    }
+
+    pub fn symbol_index(&self, symtab: usize, name: &str) -> Result<Option<usize>, Error> {
+        let count = self.section_data(symtab)?.len() / SYM_SIZE;
+        for index in 1..count {
+            if self.symbol_name(symtab, index)? == name {
+                return Ok(Some(index));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// The bytes of section header `index` in a table at `shoff`, unless they overflow.
+fn section_header_range(shoff: usize, index: usize) -> Option<std::ops::Range<usize>> {
+    let start = index.checked_mul(SHDR_SIZE)?.checked_add(shoff)?;
+    Some(start..start.checked_add(SHDR_SIZE)?)
+}
+
+/// Point section header `index` of `out` at `size` bytes starting at `offset`.
+fn set_section_bounds(
+    out: &mut [u8],
+    shoff: usize,
+    index: usize,
+    offset: usize,
+    size: usize,
+) -> Result<(), Error> {
+    let header = section_header_range(shoff, index)
+        .and_then(|range| out.get_mut(range))
+        .ok_or(format_err!("section header {} is out of bounds", index))?;
+    LittleEndian::write_u64(&mut header[0x18..], offset as u64);
+    LittleEndian::write_u64(&mut header[0x20..], size as u64);
+    Ok(())
+}
+
+/// Add a global symbol for each `(symbol, alias)` pair, at the same address as `symbol`.
+///
+/// The grown symbol and string tables are moved to the end of the object, so existing symbol
+/// indices, and the relocations that use them, are unaffected.
+pub fn add_symbol_aliases(obj: &[u8], aliases: &[(String, String)]) -> Result<Vec<u8>, Error> {
+    if aliases.is_empty() {
+        return Ok(obj.to_vec());
+    }
+    let elf = Elf::parse(obj)?;
+    let symtab = elf
+        .sections
+        .iter()
+        .position(|s| s.kind == SHT_SYMTAB)
+        .ok_or(format_err!("object has no symbol table"))?;
+    let strtab = elf.sections[symtab].link as usize;
+
+    let mut syms = elf.section_data(symtab)?.to_vec();
+    let mut strs = elf.section_data(strtab)?.to_vec();
+    for (symbol, alias) in aliases {
+        if elf.symbol_index(symtab, alias)?.is_some() {
+            bail!(
+                "cannot alias {} as {}: the name is already taken",
+                symbol,
+                alias
+            );
+        }
+        let index = elf
+            .symbol_index(symtab, symbol)?
+            .ok_or(format_err!("cannot alias {}: no such symbol", symbol))?;
+        let mut sym = syms[index * SYM_SIZE..(index + 1) * SYM_SIZE].to_vec();
+        LittleEndian::write_u32(&mut sym[0..], strs.len() as u32);
+        // STB_GLOBAL, keeping the symbol's type.
+        sym[4] = (STB_GLOBAL << 4) | (sym[4] & 0xf);
+        syms.extend(sym);
+        strs.extend(alias.as_bytes());
+        strs.push(0);
+    }
+
+    let mut out = obj.to_vec();
+    for (index, data) in vec![(strtab, strs), (symtab, syms)] {
+        while out.len() % 8 != 0 {
+            out.push(0);
+        }
+        let offset = out.len();
+        set_section_bounds(&mut out, elf.shoff, index, offset, data.len())?;
+        out.extend(data);
+    }
+    Ok(out)
+}
+
+/// Give each symbol `index` in the object's symbol table the name paired with it.
+///
+/// The grown string table is moved to the end of the object; the symbol table keeps its place
+/// and size, so relocations are unaffected.
+pub fn rename_symbols(obj: &[u8], renames: &[(usize, String)]) -> Result<Vec<u8>, Error> {
+    if renames.is_empty() {
+        return Ok(obj.to_vec());
+    }
+    let elf = Elf::parse(obj)?;
+    let symtab = elf
+        .sections
+        .iter()
+        .position(|s| s.kind == SHT_SYMTAB)
+        .ok_or(format_err!("object has no symbol table"))?;
+    let strtab = elf.sections[symtab].link as usize;
+    let symtab_offset = elf.sections[symtab].offset;
+    // Both tables must lie within the object before the symbols are patched in place.
+    let count = elf.section_data(symtab)?.len() / SYM_SIZE;
+
+    let mut out = obj.to_vec();
+    let mut strs = elf.section_data(strtab)?.to_vec();
+    for (index, name) in renames {
+        if *index == 0 || *index >= count {
+            bail!("cannot rename symbol {}: it is out of bounds", index);
+        }
+        let sym = symtab_offset + index * SYM_SIZE;
+        let sym = out
+            .get_mut(sym..sym + SYM_SIZE)
+            .ok_or(format_err!("symbol {} is out of bounds", index))?;
+        LittleEndian::write_u32(sym, strs.len() as u32);
+        strs.extend(name.as_bytes());
+        strs.push(0);
+    }
+
+    while out.len() % 8 != 0 {
+        out.push(0);
+    }
+    let offset = out.len();
+    set_section_bounds(&mut out, elf.shoff, strtab, offset, strs.len())?;
+    out.extend(strs);
+    Ok(out)
+}
+
+/// The note type of a GNU build ID.
+const NT_GNU_BUILD_ID: u32 = 3;
+
+/// Add a `.note.gnu.build-id` section holding a SHA-1 hash of `obj`, as `ld --build-id=sha1`
+/// would for a linked image. The linker discards it, and writes its own if asked to.
+///
+/// The grown section name table and the section header table are moved to the end of the
+/// object, so existing section indices are unaffected.
+pub fn add_build_id_note(obj: &[u8]) -> Result<Vec<u8>, Error> {
+    let elf = Elf::parse(obj)?;
+    let shnum = elf.sections.len();
+    // Larger section counts are stored out of line, which isn't handled here.
+    if shnum + 1 >= 0xff00 {
+        bail!("object has too many sections to add a build ID note");
+    }
+    let mut strs = elf.section_data(elf.shstrndx)?.to_vec();
+    let name = strs.len() as u32;
+    strs.extend(b".note.gnu.build-id\0");
+
+    let hash = Sha1::digest(obj);
+    let mut note = vec![0u8; 12];
+    LittleEndian::write_u32(&mut note[0..], 4);
+    LittleEndian::write_u32(&mut note[4..], hash.len() as u32);
+    LittleEndian::write_u32(&mut note[8..], NT_GNU_BUILD_ID);
+    note.extend(b"GNU\0");
+    note.extend(hash.as_slice());
+
+    let mut out = obj.to_vec();
+    while out.len() % 4 != 0 {
+        out.push(0);
+    }
+    let note_offset = out.len();
+    out.extend(&note);
+    while out.len() % 8 != 0 {
+        out.push(0);
+    }
+    let strs_offset = out.len();
+    out.extend(&strs);
+    while out.len() % 8 != 0 {
+        out.push(0);
+    }
+    let shoff = out.len();
+    // Every header was read from within the object by `Elf::parse`.
+    out.extend(&obj[elf.shoff..elf.shoff + shnum * SHDR_SIZE]);
+    let mut header = [0u8; SHDR_SIZE];
+    LittleEndian::write_u32(&mut header[0x00..], name);
+    LittleEndian::write_u32(&mut header[0x04..], SHT_NOTE);
+    LittleEndian::write_u64(&mut header[0x08..], SHF_ALLOC);
+    LittleEndian::write_u64(&mut header[0x30..], 4);
+    out.extend(&header[..]);
+    set_section_bounds(&mut out, shoff, shnum, note_offset, note.len())?;
+    set_section_bounds(&mut out, shoff, elf.shstrndx, strs_offset, strs.len())?;
+
+    LittleEndian::write_u64(&mut out[0x28..], shoff as u64);
+    LittleEndian::write_u16(&mut out[0x3c..], shnum as u16 + 1);
+    Ok(out)
+}
@@ -1,10 +1,11 @@
-use super::POINTER_SIZE;
-use crate::compiler::Compiler;
+use crate::compiler::naming::MetadataSymbol;
+use crate::compiler::vmctx::GLOBALS_POINTER_OFFSET;
+use crate::compiler::{Compiler, HeapBase};
 use cranelift_codegen::ir::{self, types::I64, GlobalValueData};
+use cranelift_module::Backend;
 
-// VMContext points directly to the heap (offset 0).
-// Directly before the heap is a pointer to the globals (offset -POINTER_SIZE).
-const GLOBAL_BASE_OFFSET: i32 = -1 * POINTER_SIZE as i32;
+// VMContext points directly to the heap, unless the heap is at a fixed address. Directly before
+// the heap is a pointer to the globals. See `vmctx::VmctxLayout`.
 
 pub struct GlobalBases {
     heap: Option<ir::GlobalValue>,
@@ -21,7 +22,11 @@ impl GlobalBases {
         }
     }
 
-    pub fn table(&mut self, func: &mut ir::Function, compiler: &Compiler) -> ir::GlobalValue {
+    pub fn table<B: Backend>(
+        &mut self,
+        func: &mut ir::Function,
+        compiler: &Compiler<B>,
+    ) -> ir::GlobalValue {
         self.table.unwrap_or_else(|| {
             let table = &compiler.prog.tables()[0];
             let gv = func.create_global_value(GlobalValueData::Symbol {
@@ -37,20 +42,46 @@ impl GlobalBases {
         })
     }
 
-    pub fn heap(&mut self, func: &mut ir::Function, _compiler: &Compiler) -> ir::GlobalValue {
+    pub fn heap<B: Backend>(
+        &mut self,
+        func: &mut ir::Function,
+        compiler: &Compiler<B>,
+    ) -> ir::GlobalValue {
         self.heap.unwrap_or_else(|| {
-            let gv = func.create_global_value(GlobalValueData::VMContext);
+            let gv = match compiler.settings.heap_base {
+                HeapBase::Vmctx => func.create_global_value(GlobalValueData::VMContext),
+                HeapBase::FixedOffset(_) => {
+                    let sym = func.create_global_value(GlobalValueData::Symbol {
+                        name: compiler
+                            .get_data(&compiler.metadata_symbol(MetadataSymbol::HeapBase))
+                            .expect("heap base must be declared")
+                            .into(),
+                        offset: 0.into(),
+                        colocated: true,
+                    });
+                    func.create_global_value(GlobalValueData::Load {
+                        base: sym,
+                        offset: 0.into(),
+                        global_type: I64,
+                        readonly: true,
+                    })
+                }
+            };
             self.heap = Some(gv);
             gv
         })
     }
 
-    pub fn globals(&mut self, func: &mut ir::Function, _compiler: &Compiler) -> ir::GlobalValue {
+    pub fn globals<B: Backend>(
+        &mut self,
+        func: &mut ir::Function,
+        _compiler: &Compiler<B>,
+    ) -> ir::GlobalValue {
         self.globals.unwrap_or_else(|| {
             let vmctx = func.create_global_value(GlobalValueData::VMContext);
             let gv = func.create_global_value(GlobalValueData::Load {
                 base: vmctx,
-                offset: GLOBAL_BASE_OFFSET.into(),
+                offset: GLOBALS_POINTER_OFFSET.into(),
                 global_type: I64,
                 readonly: false,
             });
@@ -6,11 +6,12 @@ pub use self::global::GlobalValue;
 
 use self::bases::GlobalBases;
 use self::cache::{Cache, FunctionCacheIndex};
+use crate::compiler::vmctx::GLOBAL_STRIDE;
 use crate::compiler::Compiler;
-use crate::program::{CtonSignature, Function, FunctionSig, Program, TableDef};
+use crate::program::{CtonSignature, Function, FunctionSig, GuardPlacement, Program, TableDef};
 use cranelift_codegen::ir;
 use cranelift_codegen::ir::types::{I32, I64};
-use cranelift_module::Linkage;
+use cranelift_module::{Backend, Linkage};
 use failure::{format_err, Error, ResultExt};
 use std::fmt;
 
@@ -21,7 +22,7 @@ pub type FunctionIndex = u32;
 pub type TableIndex = u32;
 
 fn global_var_offset(index: isize) -> isize {
-    index * POINTER_SIZE as isize
+    index * GLOBAL_STRIDE as isize
 }
 
 // For readability
@@ -43,17 +44,32 @@ impl<'p> EntityCreator<'p> {
         }
     }
 
-    pub fn get_global(
+    pub fn get_global<B: Backend>(
         &mut self,
         func: &mut ir::Function,
         index: GlobalIndex,
-        compiler: &Compiler,
+        compiler: &Compiler<B>,
     ) -> Result<GlobalValue, Error> {
         let global = self
             .program
             .globals()
             .get(index as usize)
             .ok_or_else(|| format_err!("global out of range: {}", index))?;
+        // An import bound to a host symbol lives there, and the instance's slot goes unused.
+        if let Some(symbol) = global.as_import().and_then(|g| g.symbol()) {
+            let name = compiler.get_data(symbol)?;
+            return self.cache.global(index, || {
+                let gv = func.create_global_value(ir::GlobalValueData::Symbol {
+                    name: name.into(),
+                    offset: 0.into(),
+                    colocated: false,
+                });
+                Ok(GlobalValue {
+                    var: gv,
+                    ty: global.cton_type(),
+                })
+            });
+        }
         let base = self.bases.globals(func, compiler);
         self.cache.global(index, || {
             let offset = global_var_offset(index as isize);
@@ -69,14 +85,20 @@ impl<'p> EntityCreator<'p> {
         })
     }
 
-    pub fn get_heap(
+    pub fn get_heap<B: Backend>(
         &mut self,
         func: &mut ir::Function,
         index: MemoryIndex,
-        compiler: &Compiler,
+        compiler: &Compiler<B>,
     ) -> Result<ir::Heap, Error> {
         let base = self.bases.heap(func, compiler);
         let heap_spec = self.program.heap_spec()?;
+        // The guard after a heap is only there to catch its own instance's accesses when the
+        // instance owns it.
+        let offset_guard_size = match self.program.guard_placement() {
+            GuardPlacement::PerInstance => heap_spec.guard_size,
+            GuardPlacement::Shared => 0,
+        };
 
         self.cache.heap(index, || {
             if index != 0 {
@@ -88,7 +110,7 @@ impl<'p> EntityCreator<'p> {
             Ok(func.create_heap(ir::HeapData {
                 base,
                 min_size: heap_spec.initial_size.into(),
-                offset_guard_size: heap_spec.guard_size.into(),
+                offset_guard_size: offset_guard_size.into(),
                 style: ir::HeapStyle::Static {
                     bound: heap_spec.reserved_size.into(),
                 },
@@ -97,11 +119,11 @@ impl<'p> EntityCreator<'p> {
         })
     }
 
-    pub fn get_table(
+    pub fn get_table<B: Backend>(
         &mut self,
         ix: TableIndex,
         func: &mut ir::Function,
-        compiler: &Compiler,
+        compiler: &Compiler<B>,
     ) -> Result<(TableDef, ir::GlobalValue), Error> {
         let tbl = self.program.get_table(ix)?;
         let base = self.bases.table(func, compiler);
@@ -120,11 +142,11 @@ impl<'p> EntityCreator<'p> {
         })
     }
 
-    pub fn get_direct_func(
+    pub fn get_direct_func<B: Backend>(
         &mut self,
         func: &mut ir::Function,
         index: FunctionIndex,
-        compiler: &Compiler,
+        compiler: &Compiler<B>,
     ) -> Result<&(ir::FuncRef, &'p Function), Error> {
         let f = self.program.get_function(index)?;
         self.cache
@@ -142,11 +164,11 @@ impl<'p> EntityCreator<'p> {
             })
     }
 
-    pub fn get_runtime_func(
+    pub fn get_runtime_func<B: Backend>(
         &mut self,
         func: &mut ir::Function,
         name: String,
-        compiler: &Compiler,
+        compiler: &Compiler<B>,
     ) -> Result<&(ir::FuncRef, &'p Function), Error> {
         let f = self.program.get_runtime_function(&name)?;
         self.cache
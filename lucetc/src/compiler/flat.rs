@@ -0,0 +1,149 @@
+//! Linking an emitted object into a flat image at a fixed address, for targets that load code
+//! without a dynamic loader.
+//!
+//! Every relocation is resolved against the given base, so the image has none left. That only
+//! works if the object defines every symbol it refers to.
+
+use super::elf::{Elf, SHN_UNDEF, SHT_SYMTAB, STT_SECTION, SYM_SIZE};
+use super::relocations::{entries, RelocKind};
+use byteorder::{ByteOrder, LittleEndian};
+use failure::{bail, format_err, Error};
+use std::collections::BTreeMap;
+
+const SHF_ALLOC: u64 = 0x2;
+const SHT_NOBITS: u32 = 8;
+const SHN_ABS: u16 = 0xfff1;
+
+fn align_to(offset: u64, align: u64) -> u64 {
+    if align <= 1 {
+        offset
+    } else {
+        (offset + align - 1) / align * align
+    }
+}
+
+/// Lay out the allocated sections of the serialized object `obj` one after another from
+/// address `base`, apply every relocation, and return the resulting image.
+///
+/// Entries the code reaches through the GOT are placed in a table at the end of the image.
+pub fn flatten(obj: &[u8], base: u64) -> Result<Vec<u8>, Error> {
+    let elf = Elf::parse(obj)?;
+    let symtab = elf
+        .sections
+        .iter()
+        .position(|s| s.kind == SHT_SYMTAB)
+        .ok_or(format_err!("object has no symbol table"))?;
+
+    // Offset of each allocated section within the image.
+    let mut placement = BTreeMap::new();
+    let mut size = 0;
+    for (index, section) in elf.sections.iter().enumerate() {
+        if section.flags & SHF_ALLOC == 0 {
+            continue;
+        }
+        size = align_to(size, section.addralign);
+        placement.insert(index, size);
+        size += section.size as u64;
+    }
+
+    let relocs = entries(&elf)?
+        .into_iter()
+        .filter(|e| placement.contains_key(&e.target))
+        .collect::<Vec<_>>();
+
+    let symbols = elf.section_data(symtab)?;
+    let address = |index: usize| -> Result<Option<u64>, Error> {
+        let sym = symbols
+            .get(index * SYM_SIZE..(index + 1) * SYM_SIZE)
+            .ok_or(format_err!("symbol {} is out of bounds", index))?;
+        let shndx = LittleEndian::read_u16(&sym[6..]);
+        let value = LittleEndian::read_u64(&sym[8..]);
+        Ok(match shndx {
+            SHN_UNDEF => None,
+            SHN_ABS => Some(value),
+            _ => {
+                let offset = placement.get(&(shndx as usize)).ok_or(format_err!(
+                    "symbol {} is in a section that is not loaded",
+                    elf.symbol_name(symtab, index)?
+                ))?;
+                let value = if sym[4] & 0xf == STT_SECTION {
+                    0
+                } else {
+                    value
+                };
+                Some(base + offset + value)
+            }
+        })
+    };
+
+    let mut unresolved = vec![];
+    let mut got = BTreeMap::new();
+    for e in relocs.iter() {
+        if address(e.symbol)?.is_none() {
+            unresolved.push(e.reloc.symbol.clone());
+        } else if e.reloc.kind == RelocKind::GotPcRel4 {
+            let next = got.len() as u64;
+            got.entry(e.symbol).or_insert(next);
+        }
+    }
+    if !unresolved.is_empty() {
+        unresolved.sort();
+        unresolved.dedup();
+        bail!(
+            "an image at a fixed address can't refer to symbols it doesn't define: {}",
+            unresolved.join(", ")
+        );
+    }
+
+    let got_offset = align_to(size, 8);
+    let mut image = vec![0u8; (got_offset + got.len() as u64 * 8) as usize];
+    for (index, offset) in placement.iter() {
+        let section = &elf.sections[*index];
+        if section.kind != SHT_NOBITS {
+            let start = *offset as usize;
+            image[start..start + section.size].copy_from_slice(elf.section_data(*index)?);
+        }
+    }
+    for (symbol, slot) in got.iter() {
+        let at = (got_offset + slot * 8) as usize;
+        LittleEndian::write_u64(&mut image[at..], address(*symbol)?.unwrap());
+    }
+
+    for e in relocs.iter() {
+        let at = placement[&e.target] + e.reloc.offset;
+        let place = base + at;
+        let target = address(e.symbol)?.unwrap();
+        let at = at as usize;
+        let pc_relative = |to: u64| -> Result<i32, Error> {
+            let value = to.wrapping_add(e.reloc.addend as u64).wrapping_sub(place) as i64;
+            if value < i32::min_value() as i64 || value > i32::max_value() as i64 {
+                bail!(
+                    "relocation at {}+{:#x} is out of range",
+                    e.reloc.section,
+                    e.reloc.offset
+                );
+            }
+            Ok(value as i32)
+        };
+        match e.reloc.kind {
+            RelocKind::Abs8 => LittleEndian::write_u64(
+                &mut image[at..],
+                target.wrapping_add(e.reloc.addend as u64),
+            ),
+            RelocKind::PcRel4 | RelocKind::PltRel4 => {
+                LittleEndian::write_i32(&mut image[at..], pc_relative(target)?)
+            }
+            RelocKind::GotPcRel4 => {
+                let slot = base + got_offset + got[&e.symbol] * 8;
+                LittleEndian::write_i32(&mut image[at..], pc_relative(slot)?)
+            }
+            RelocKind::Other(kind) => bail!(
+                "cannot resolve relocation type {} at {}+{:#x}",
+                kind,
+                e.reloc.section,
+                e.reloc.offset
+            ),
+        }
+    }
+    Ok(image)
+}
@@ -1,19 +1,134 @@
 use crate::compiler::entity::EntityCreator;
 use crate::compiler::opcode::translate_opcode;
 use crate::compiler::state::TranslationState;
-use crate::compiler::Compiler;
+use crate::compiler::traps::UNSUPPORTED_FUNCTION;
+use crate::compiler::{Compiler, UnsupportedFunctionPolicy};
+use crate::error::{LucetcError, LucetcErrorKind};
 use crate::program::types::cton_valuetype;
-use crate::program::FunctionDef;
+use crate::program::{FunctionDef, Runtime};
 use cranelift_codegen::ir::{self, InstBuilder};
 use cranelift_frontend::{FunctionBuilder, FunctionBuilderContext, Variable};
-use failure::{format_err, Error};
-use parity_wasm::elements::{self, FuncBody, ValueType};
+use cranelift_module::Backend;
+use failure::{format_err, Context, Error};
+use parity_wasm::elements::{self, FuncBody, Instruction, ValueType};
 
-pub fn compile_function<'p>(
-    compiler: &mut Compiler<'p>,
+pub fn compile_function<'p, B: Backend>(
+    compiler: &mut Compiler<'p, B>,
     function: &FunctionDef,
     body: &FuncBody,
 ) -> Result<(), Error> {
+    if let Some(ref hot) = compiler.settings.lazy_compile {
+        if !function.name().map_or(false, |name| hot.contains(name)) {
+            return compile_lazy_stub(compiler, function);
+        }
+    }
+    match translate_function(compiler, function, body) {
+        Err(ref e)
+            if compiler.settings.on_unsupported_function == UnsupportedFunctionPolicy::Stub
+                && is_unsupported(e) =>
+        {
+            compiler.settings.diagnostics.warn(format!(
+                "{} is compiled as a stub that traps: {}",
+                function.symbol(),
+                e
+            ));
+            compile_stub(compiler, function)
+        }
+        res => res,
+    }
+}
+
+fn is_unsupported(e: &Error) -> bool {
+    e.iter_chain().any(
+        |cause| match cause.downcast_ref::<Context<LucetcErrorKind>>() {
+            Some(ctx) => match ctx.get_context() {
+                LucetcErrorKind::Unsupported(_) => true,
+                _ => false,
+            },
+            None => false,
+        },
+    )
+}
+
+/// Define `function` with a body that does nothing but trap with `UNSUPPORTED_FUNCTION`.
+fn compile_stub<'p, B: Backend>(
+    compiler: &mut Compiler<'p, B>,
+    function: &FunctionDef,
+) -> Result<(), Error> {
+    let name = compiler.get_function(function)?;
+    let mut func = ir::Function::with_name_signature(name.clone().into(), function.signature());
+    {
+        let mut ctx = FunctionBuilderContext::new();
+        let mut builder = FunctionBuilder::new(&mut func, &mut ctx);
+        let entry_block = builder.create_ebb();
+        builder.append_ebb_params_for_function_params(entry_block);
+        builder.switch_to_block(entry_block);
+        builder.seal_block(entry_block);
+        builder.ins().trap(UNSUPPORTED_FUNCTION);
+        builder.finalize();
+    }
+    compiler.define_function(name, func)?;
+    Ok(())
+}
+
+/// Define `function` with a body that asks the runtime for the function's code, through
+/// `Runtime::compile_on_demand`, and calls it with the same arguments.
+fn compile_lazy_stub<'p, B: Backend>(
+    compiler: &mut Compiler<'p, B>,
+    function: &FunctionDef,
+) -> Result<(), Error> {
+    let name = compiler.get_function(function)?;
+    let helper = Runtime::compile_on_demand();
+    let helper_name = compiler.get_function(&helper)?;
+    let sig = function.signature();
+    let vmctx_ix = sig
+        .special_param_index(ir::ArgumentPurpose::VMContext)
+        .expect("functions take a vmctx");
+    let mut func = ir::Function::with_name_signature(name.clone().into(), sig.clone());
+    let helper_sig = func.import_signature(helper.signature());
+    let helper_ref = func.import_function(ir::ExtFuncData {
+        name: helper_name.into(),
+        signature: helper_sig,
+        colocated: false,
+    });
+    let callee_sig = func.import_signature(sig);
+    {
+        let mut ctx = FunctionBuilderContext::new();
+        let mut builder = FunctionBuilder::new(&mut func, &mut ctx);
+        let entry_block = builder.create_ebb();
+        builder.append_ebb_params_for_function_params(entry_block);
+        builder.switch_to_block(entry_block);
+        builder.seal_block(entry_block);
+        let args = builder.ebb_params(entry_block).to_vec();
+        let index = builder
+            .ins()
+            .iconst(ir::types::I32, function.wasmidx as i64);
+        let call = builder.ins().call(helper_ref, &[args[vmctx_ix], index]);
+        let code = builder.inst_results(call)[0];
+        let call = builder.ins().call_indirect(callee_sig, code, &args);
+        let results = builder.inst_results(call).to_vec();
+        builder.ins().return_(&results);
+        builder.finalize();
+    }
+    compiler.define_function(name, func)?;
+    Ok(())
+}
+
+fn translate_function<'p, B: Backend>(
+    compiler: &mut Compiler<'p, B>,
+    function: &FunctionDef,
+    body: &FuncBody,
+) -> Result<(), Error> {
+    if let Some(max) = compiler.settings.max_locals_per_function {
+        let count: u64 = body.locals().iter().map(|l| l.count() as u64).sum();
+        if count > max as u64 {
+            Err(LucetcError::from(LucetcErrorKind::TooManyLocals {
+                func: function.symbol().to_owned(),
+                count,
+            }))?;
+        }
+    }
+
     let sig = function.signature();
 
     let name = compiler.get_function(function)?;
@@ -42,7 +157,7 @@ pub fn compile_function<'p>(
             }
         }
         // local decls
-        declare_locals(&mut builder, &mut vargen, body.locals());
+        declare_locals(&mut builder, &mut vargen, body.locals())?;
 
         // Create exit block.
         let exit_block = builder.create_ebb();
@@ -57,11 +172,21 @@ pub fn compile_function<'p>(
         let mut entity_creator = EntityCreator::new(&compiler.prog);
 
         // Function body
-        let mut op_iter = body.code().elements().iter();
+        let mut op_iter = body.code().elements().iter().enumerate();
+        // Index of the first instruction of each stretch of dead code. `end` and `else` only
+        // close frames, so they don't start a stretch.
+        let mut dead_code = vec![];
+        let mut in_dead_code = false;
         while !translation.control_stack.is_empty() {
-            let op = op_iter
+            let (index, op) = op_iter
                 .next()
                 .ok_or(format_err!("ran out of opcodes before control stack"))?;
+            if translation.reachable {
+                in_dead_code = false;
+            } else if !in_dead_code && *op != Instruction::End && *op != Instruction::Else {
+                dead_code.push(index);
+                in_dead_code = true;
+            }
             translate_opcode(
                 op,
                 &mut builder,
@@ -81,6 +206,16 @@ pub fn compile_function<'p>(
         }
 
         builder.finalize();
+
+        if compiler.settings.warn_unreachable_code {
+            for index in dead_code {
+                compiler.settings.diagnostics.warn(format!(
+                    "{} has unreachable code starting at instruction {}",
+                    function.symbol(),
+                    index
+                ));
+            }
+        }
     }
 
     compiler.define_function(name, func)?;
@@ -91,7 +226,7 @@ fn declare_locals(
     builder: &mut FunctionBuilder,
     vargen: &mut VariableGen,
     locals: &[elements::Local],
-) {
+) -> Result<(), Error> {
     for local in locals {
         let localtype = local.value_type();
         let zeroval = match localtype {
@@ -99,7 +234,8 @@ fn declare_locals(
             ValueType::I64 => builder.ins().iconst(ir::types::I64, 0),
             ValueType::F32 => builder.ins().f32const(ir::immediates::Ieee32::with_bits(0)),
             ValueType::F64 => builder.ins().f64const(ir::immediates::Ieee64::with_bits(0)),
-            ValueType::V128 => unimplemented!(),
+            ValueType::V128 => Err(format_err!("cannot declare a v128 local")
+                .context(LucetcErrorKind::Unsupported("SIMD proposal".to_owned())))?,
         };
         for _ in 0..local.count() {
             let lvar = vargen.mint();
@@ -107,6 +243,7 @@ fn declare_locals(
             builder.def_var(lvar, zeroval);
         }
     }
+    Ok(())
 }
 
 /// `VariableGen` is a source of fresh `Variable`s. It is never used directly by Cretonne.
@@ -1,11 +1,12 @@
+use crate::compiler::naming::MetadataSymbol;
 use crate::compiler::Compiler;
 use crate::program::globals::Global;
 use byteorder::{LittleEndian, WriteBytesExt};
-use cranelift_module::{DataContext, Linkage};
+use cranelift_module::{Backend, DataContext, Linkage};
 use failure::Error;
 use std::io::Cursor;
 
-pub fn compile_global_specs(compiler: &mut Compiler) -> Result<(), Error> {
+pub fn compile_global_specs<B: Backend>(compiler: &mut Compiler<B>) -> Result<(), Error> {
     let globals = compiler.prog.globals();
     let len = globals.len();
 
@@ -41,9 +42,11 @@ pub fn compile_global_specs(compiler: &mut Compiler) -> Result<(), Error> {
             .unwrap();
     }
     spec_ctx.define(spec_contents.into_inner().into_boxed_slice());
-    let spec_decl = compiler
-        .module
-        .declare_data("lucet_globals_spec", Linkage::Export, false)?;
+    let spec_decl = compiler.module.declare_data(
+        &compiler.metadata_symbol(MetadataSymbol::GlobalsSpec),
+        Linkage::Export,
+        false,
+    )?;
     compiler.module.define_data(spec_decl, &spec_ctx)?;
 
     Ok(())
@@ -52,7 +55,8 @@ pub fn compile_global_specs(compiler: &mut Compiler) -> Result<(), Error> {
 fn flags(g: &Global) -> u64 {
     let mut flags = 0;
     match g {
-        &Global::Import(_) => {
+        // A bound import's slot is a placeholder, as in `Global::to_spec`.
+        &Global::Import(ref import) if import.symbol().is_none() => {
             flags |= 1;
         }
         _ => {}
@@ -0,0 +1,375 @@
+//! Compiling a module straight into executable memory in the current process.
+//!
+//! This skips the object file and the linker entirely, which makes it handy for tests and quick
+//! experiments. It is not a replacement for the runtime: there are no guard pages and no signal
+//! handling, so a guest trap takes the whole process down with it.
+
+use super::vmctx::GLOBALS_POINTER_OFFSET;
+use super::{set_flag, shared_flags_builder, CodegenSettings, Compiler, HeapBase};
+use crate::program::globals::Global;
+use crate::program::Program;
+use cranelift_codegen::ir::{self, types, ArgumentPurpose};
+use cranelift_codegen::isa;
+use cranelift_codegen::settings;
+use cranelift_module::{FuncOrDataId, Linkage, Module};
+use cranelift_native;
+use cranelift_simplejit::{SimpleJITBackend, SimpleJITBuilder};
+use failure::{format_err, Error};
+use parity_wasm::elements::Internal;
+use std::alloc::{self, Layout};
+use std::collections::HashMap;
+use std::fmt;
+use std::mem;
+
+const WASM_PAGE_SIZE: u64 = 64 * 1024;
+
+/// Space reserved in front of the heap. Its last three words hold the most pages the heap may
+/// grow to, the current heap size in pages, and the globals pointer that generated code loads
+/// from `vmctx::GLOBALS_POINTER_OFFSET`.
+const HEADER_SIZE: usize = 4096;
+const LIMIT_PAGES_OFFSET: isize = -24;
+const PAGES_OFFSET: isize = -16;
+const GLOBALS_OFFSET: isize = GLOBALS_POINTER_OFFSET as isize;
+
+fn jit_isa(settings: &CodegenSettings) -> Result<Box<isa::TargetIsa>, Error> {
+    let mut flags_builder = shared_flags_builder(settings)?;
+    let isa_builder = cranelift_native::builder().expect("host machine is not a supported target");
+    // The code is placed at a known address, so it doesn't have to be position-independent.
+    set_flag(&mut flags_builder, "is_pic", "false")?;
+    // The stack probe is defined in the object file, so there's nothing to call here.
+    set_flag(&mut flags_builder, "probestack_enabled", "false")?;
+    Ok(isa_builder.finish(settings::Flags::new(flags_builder)))
+}
+
+impl<'p> Compiler<'p, SimpleJITBackend> {
+    /// Create a compiler that emits into executable memory rather than an object file.
+    ///
+    /// Imported functions are resolved by looking up their bound symbol in the current process.
+    pub fn new_jit(prog: &'p Program, settings: &CodegenSettings) -> Result<Self, Error> {
+        if settings.heap_base != HeapBase::Vmctx {
+            return Err(format_err!("the JIT only supports a vmctx-relative heap"));
+        }
+        if let Some(import) = prog.import_functions().iter().find(|f| f.addend() != 0) {
+            return Err(format_err!(
+                "the JIT can't call {}::{}, which is bound with an addend",
+                import.module(),
+                import.field()
+            ));
+        }
+        let mut builder = SimpleJITBuilder::with_isa(
+            jit_isa(settings)?,
+            cranelift_module::default_libcall_names(),
+        );
+        builder.symbol(
+            "lucet_vmctx_current_memory",
+            lucet_vmctx_current_memory as *const u8,
+        );
+        builder.symbol(
+            "lucet_vmctx_grow_memory",
+            lucet_vmctx_grow_memory as *const u8,
+        );
+        builder.symbol(
+            "lucet_vmctx_grow_memory_zeroing",
+            lucet_vmctx_grow_memory_zeroing as *const u8,
+        );
+        Self::with_module(Module::new(builder), prog, settings)
+    }
+
+    /// Generate code for every function and set up a heap and globals to run it against.
+    pub fn jit(mut self) -> Result<JitModule, Error> {
+        self.define_functions_streaming()?;
+        self.module.finalize_definitions();
+        let instance = Instance::new(self.prog)?;
+        let mut signatures = HashMap::new();
+        let exports = self
+            .prog
+            .module()
+            .export_section()
+            .map(|s| s.entries())
+            .unwrap_or(&[]);
+        for entry in exports {
+            if let Internal::Function(index) = entry.internal() {
+                let func = self.prog.get_function(*index)?;
+                if func.linkage() != Linkage::Import {
+                    signatures.insert(entry.field().to_owned(), func.signature());
+                }
+            }
+        }
+        Ok(JitModule {
+            module: self.module,
+            instance,
+            signatures,
+        })
+    }
+}
+
+/// A compiled module living in the current process, along with a single instance of its heap
+/// and globals.
+pub struct JitModule {
+    module: Module<SimpleJITBackend>,
+    instance: Instance,
+    /// The signature of each exported function, by export name.
+    signatures: HashMap<String, ir::Signature>,
+}
+
+/// An argument to or result of a wasm function.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Value {
+    I32(i32),
+    I64(i64),
+    F32(f32),
+    F64(f64),
+}
+
+impl Value {
+    fn ty(&self) -> ir::Type {
+        match self {
+            Value::I32(_) => types::I32,
+            Value::I64(_) => types::I64,
+            Value::F32(_) => types::F32,
+            Value::F64(_) => types::F64,
+        }
+    }
+
+    /// Whether `self` and `other` are the same value. Unlike `==`, any NaN is the same as any
+    /// other NaN of its type, since wasm doesn't pin down which one an operation produces.
+    pub fn same_as(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::F32(a), Value::F32(b)) => {
+                (a.is_nan() && b.is_nan()) || a.to_bits() == b.to_bits()
+            }
+            (Value::F64(a), Value::F64(b)) => {
+                (a.is_nan() && b.is_nan()) || a.to_bits() == b.to_bits()
+            }
+            _ => self == other,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::I32(v) => write!(f, "{}:i32", v),
+            Value::I64(v) => write!(f, "{}:i64", v),
+            Value::F32(v) => write!(f, "{}:f32", v),
+            Value::F64(v) => write!(f, "{}:f64", v),
+        }
+    }
+}
+
+/// Integer and floating-point arguments after the vmctx that fit in registers on x86-64.
+const INT_ARG_REGS: usize = 5;
+const FLOAT_ARG_REGS: usize = 8;
+
+type IntResultFn =
+    extern "C" fn(*mut u8, u64, u64, u64, u64, u64, f64, f64, f64, f64, f64, f64, f64, f64) -> u64;
+type FloatResultFn =
+    extern "C" fn(*mut u8, u64, u64, u64, u64, u64, f64, f64, f64, f64, f64, f64, f64, f64) -> f64;
+
+impl JitModule {
+    /// The address of the exported function `name`.
+    ///
+    /// Exported functions take the value of `vmctx()` as their first argument, followed by their
+    /// wasm parameters, and use the host's C calling convention.
+    pub fn export(&mut self, name: &str) -> Result<*const u8, Error> {
+        let symbol = format!("guest_func_{}", name);
+        match self.module.get_name(&symbol) {
+            Some(FuncOrDataId::Func(id)) => Ok(self.module.get_finalized_function(id)),
+            _ => Err(format_err!("no exported function named {}", name)),
+        }
+    }
+
+    /// Call the exported function `name` with `args`, which must match its wasm parameters, and
+    /// return its result, if it has one.
+    ///
+    /// Only functions whose arguments all fit in registers can be called this way: up to five
+    /// integers and eight floats. Every argument register is loaded, so a function with fewer
+    /// parameters can be called through one generic signature; the System V calling convention
+    /// leaves the extra registers unread. A trap in the callee is not caught.
+    pub fn call(&mut self, name: &str, args: &[Value]) -> Result<Option<Value>, Error> {
+        let signature = self
+            .signatures
+            .get(name)
+            .ok_or(format_err!("no exported function named {}", name))?
+            .clone();
+        let params: Vec<ir::Type> = signature
+            .params
+            .iter()
+            .filter(|p| p.purpose == ArgumentPurpose::Normal)
+            .map(|p| p.value_type)
+            .collect();
+        let given: Vec<ir::Type> = args.iter().map(|a| a.ty()).collect();
+        if params != given {
+            return Err(format_err!(
+                "{} takes {:?}, but was given {:?}",
+                name,
+                params,
+                given
+            ));
+        }
+
+        let mut ints = [0u64; INT_ARG_REGS];
+        let mut floats = [0f64; FLOAT_ARG_REGS];
+        let (mut next_int, mut next_float) = (0, 0);
+        for arg in args {
+            let (regs_used, limit) = match arg {
+                Value::I32(_) | Value::I64(_) => (&mut next_int, INT_ARG_REGS),
+                Value::F32(_) | Value::F64(_) => (&mut next_float, FLOAT_ARG_REGS),
+            };
+            if *regs_used == limit {
+                return Err(format_err!(
+                    "{} takes too many arguments to be called",
+                    name
+                ));
+            }
+            match arg {
+                Value::I32(v) => ints[*regs_used] = *v as u32 as u64,
+                Value::I64(v) => ints[*regs_used] = *v as u64,
+                // Only the low 32 bits of the register are read for an f32.
+                Value::F32(v) => floats[*regs_used] = f64::from_bits(v.to_bits() as u64),
+                Value::F64(v) => floats[*regs_used] = *v,
+            }
+            *regs_used += 1;
+        }
+
+        let func = self.export(name)?;
+        let vmctx = self.vmctx();
+        let [i0, i1, i2, i3, i4] = ints;
+        let [f0, f1, f2, f3, f4, f5, f6, f7] = floats;
+        let result = signature.returns.first().map(|r| r.value_type);
+        Ok(match result {
+            Some(types::F32) | Some(types::F64) => {
+                let func: FloatResultFn = unsafe { mem::transmute(func) };
+                let ret = func(vmctx, i0, i1, i2, i3, i4, f0, f1, f2, f3, f4, f5, f6, f7);
+                if result == Some(types::F32) {
+                    Some(Value::F32(f32::from_bits(ret.to_bits() as u32)))
+                } else {
+                    Some(Value::F64(ret))
+                }
+            }
+            _ => {
+                let func: IntResultFn = unsafe { mem::transmute(func) };
+                let ret = func(vmctx, i0, i1, i2, i3, i4, f0, f1, f2, f3, f4, f5, f6, f7);
+                match result {
+                    Some(types::I32) => Some(Value::I32(ret as u32 as i32)),
+                    Some(types::I64) => Some(Value::I64(ret as i64)),
+                    _ => None,
+                }
+            }
+        })
+    }
+
+    /// The context pointer to pass to exported functions. It points at the start of the heap.
+    pub fn vmctx(&self) -> *mut u8 {
+        self.instance.vmctx()
+    }
+}
+
+struct Instance {
+    alloc: *mut u8,
+    layout: Layout,
+    globals: Box<[i64]>,
+}
+
+impl Instance {
+    fn new(prog: &Program) -> Result<Self, Error> {
+        let heap = prog.heap_spec()?;
+        let size = HEADER_SIZE as u64 + heap.reserved_size + heap.guard_size;
+        if size > isize::max_value() as u64 {
+            return Err(format_err!(
+                "heap of {} bytes is too large to allocate",
+                heap.reserved_size
+            ));
+        }
+        let layout = Layout::from_size_align(size as usize, HEADER_SIZE)?;
+
+        let mut globals = Vec::with_capacity(prog.globals().len());
+        for g in prog.globals() {
+            match g {
+                Global::Def(def) => globals.push(def.value()),
+                // Code reaches bound imports through their symbols, which resolve in-process.
+                Global::Import(import) if import.symbol().is_some() => globals.push(0),
+                Global::Import(import) => {
+                    return Err(format_err!(
+                        "cannot provide imported global {}::{}",
+                        import.module(),
+                        import.field()
+                    ));
+                }
+            }
+        }
+
+        let alloc = unsafe { alloc::alloc_zeroed(layout) };
+        if alloc.is_null() {
+            alloc::handle_alloc_error(layout);
+        }
+        let mut instance = Self {
+            alloc,
+            layout,
+            globals: globals.into_boxed_slice(),
+        };
+
+        let vmctx = instance.vmctx();
+        let heap_mem =
+            unsafe { std::slice::from_raw_parts_mut(vmctx, heap.reserved_size as usize) };
+        for init in prog.data_initializers()? {
+            let start = init.offset as usize;
+            let end = start + init.data.len();
+            if end as u64 > heap.initial_size {
+                return Err(format_err!(
+                    "data segment at {} runs past the initial heap size",
+                    init.offset
+                ));
+            }
+            heap_mem[start..end].copy_from_slice(init.data);
+        }
+
+        let limit = match heap.max_size {
+            Some(max_size) => std::cmp::min(max_size, heap.reserved_size),
+            None => heap.reserved_size,
+        };
+        unsafe {
+            *(vmctx.offset(LIMIT_PAGES_OFFSET) as *mut u64) = limit / WASM_PAGE_SIZE;
+            *(vmctx.offset(PAGES_OFFSET) as *mut u64) = heap.initial_size / WASM_PAGE_SIZE;
+            *(vmctx.offset(GLOBALS_OFFSET) as *mut *mut i64) = instance.globals.as_mut_ptr();
+        }
+        Ok(instance)
+    }
+
+    fn vmctx(&self) -> *mut u8 {
+        unsafe { self.alloc.add(HEADER_SIZE) }
+    }
+}
+
+impl Drop for Instance {
+    fn drop(&mut self) {
+        unsafe { alloc::dealloc(self.alloc, self.layout) }
+    }
+}
+
+extern "C" fn lucet_vmctx_current_memory(vmctx: *mut u8) -> u32 {
+    unsafe { *(vmctx.offset(PAGES_OFFSET) as *const u64) as u32 }
+}
+
+extern "C" fn lucet_vmctx_grow_memory(vmctx: *mut u8, additional_pages: u32) -> i32 {
+    // The whole reservation is allocated up front, so growing is only bookkeeping.
+    let pages = unsafe { &mut *(vmctx.offset(PAGES_OFFSET) as *mut u64) };
+    let limit_pages = unsafe { *(vmctx.offset(LIMIT_PAGES_OFFSET) as *const u64) };
+    let new_pages = *pages + additional_pages as u64;
+    if new_pages > limit_pages {
+        return -1;
+    }
+    let old_pages = *pages;
+    *pages = new_pages;
+    old_pages as i32
+}
+
+extern "C" fn lucet_vmctx_grow_memory_zeroing(vmctx: *mut u8, additional_pages: u32) -> i32 {
+    let old_pages = lucet_vmctx_grow_memory(vmctx, additional_pages);
+    if old_pages >= 0 {
+        let start = old_pages as u64 * WASM_PAGE_SIZE;
+        let len = additional_pages as u64 * WASM_PAGE_SIZE;
+        unsafe { std::ptr::write_bytes(vmctx.offset(start as isize), 0, len as usize) };
+    }
+    old_pages
+}
@@ -0,0 +1,70 @@
+//! Where each function ended up in an emitted object.
+
+use super::elf::{Elf, SHN_UNDEF, SHT_SYMTAB, SYM_SIZE};
+use byteorder::{ByteOrder, LittleEndian};
+use failure::{format_err, Error};
+use serde_json::{json, Value};
+
+const STT_FUNC: u8 = 2;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionLayout {
+    pub symbol: String,
+    /// The section holding the function's code, e.g. `.text.guest_func_main`.
+    pub section: String,
+    /// Offset of the function's first instruction within `section`.
+    pub offset: u64,
+    /// Offset of the function's first instruction within the object file.
+    pub file_offset: u64,
+    pub size: u64,
+}
+
+/// Every function defined in the serialized object `obj`, ordered by section and offset.
+pub fn function_layout(obj: &[u8]) -> Result<Vec<FunctionLayout>, Error> {
+    let elf = Elf::parse(obj)?;
+    let symtab = elf
+        .sections
+        .iter()
+        .position(|s| s.kind == SHT_SYMTAB)
+        .ok_or(format_err!("object has no symbol table"))?;
+    let data = elf.section_data(symtab)?;
+    let mut functions = vec![];
+    for index in 1..data.len() / SYM_SIZE {
+        let sym = &data[index * SYM_SIZE..(index + 1) * SYM_SIZE];
+        let shndx = LittleEndian::read_u16(&sym[6..]);
+        if sym[4] & 0xf != STT_FUNC || shndx == SHN_UNDEF {
+            continue;
+        }
+        let section = elf
+            .sections
+            .get(shndx as usize)
+            .ok_or(format_err!("function symbol {} is in no section", index))?;
+        let offset = LittleEndian::read_u64(&sym[8..]);
+        functions.push(FunctionLayout {
+            symbol: elf.symbol_name(symtab, index)?,
+            section: elf.section_name(shndx as usize)?,
+            offset,
+            file_offset: section.offset as u64 + offset,
+            size: LittleEndian::read_u64(&sym[16..]),
+        });
+    }
+    functions.sort_by(|a, b| (&a.section, a.offset).cmp(&(&b.section, b.offset)));
+    Ok(functions)
+}
+
+/// The layout as the JSON map `Lucetc::object_file_with_map` writes.
+pub fn function_layout_json(functions: &[FunctionLayout]) -> Value {
+    let functions: Vec<Value> = functions
+        .iter()
+        .map(|f| {
+            json!({
+                "symbol": f.symbol,
+                "section": f.section,
+                "offset": f.offset,
+                "file_offset": f.file_offset,
+                "size": f.size,
+            })
+        })
+        .collect();
+    json!({ "functions": functions })
+}
@@ -1,25 +1,32 @@
+use super::naming::MetadataSymbol;
 use super::Compiler;
-use crate::program::memory::HeapSpec;
+use crate::program::memory::{GuardPlacement, HeapSpec};
 use byteorder::{LittleEndian, WriteBytesExt};
-use cranelift_module::{DataContext, Linkage};
+use cranelift_module::{Backend, DataContext, Linkage};
 use failure::Error;
 
-pub fn compile_memory_specs(compiler: &mut Compiler) -> Result<(), Error> {
+pub fn compile_memory_specs<B: Backend>(compiler: &mut Compiler<B>) -> Result<(), Error> {
     let heap = compiler.prog.heap_spec()?;
 
     let mut heap_spec_ctx = DataContext::new();
-    heap_spec_ctx.define(serialize_spec(&heap).into_boxed_slice());
-    let heap_spec_decl = compiler
-        .module
-        .declare_data("lucet_heap_spec", Linkage::Export, false)?;
+    let spec = serialize_spec(&heap, compiler.prog.guard_placement());
+    heap_spec_ctx.define(spec.into_boxed_slice());
+    let heap_spec_decl = compiler.module.declare_data(
+        &compiler.metadata_symbol(MetadataSymbol::HeapSpec),
+        Linkage::Export,
+        false,
+    )?;
     compiler
         .module
         .define_data(heap_spec_decl, &heap_spec_ctx)?;
     Ok(())
 }
 
-fn serialize_spec(spec: &HeapSpec) -> Vec<u8> {
-    let mut serialized: Vec<u8> = Vec::with_capacity(5 * 8);
+/// The fields of `HeapSpec` as u64s, in the order `lucet_heap_spec` declares them, followed by
+/// a u64 that is 1 if the code was compiled for a `GuardPlacement::Shared` guard and 0 if each
+/// instance owns its guard.
+fn serialize_spec(spec: &HeapSpec, guard_placement: GuardPlacement) -> Vec<u8> {
+    let mut serialized: Vec<u8> = Vec::with_capacity(6 * 8);
 
     serialized
         .write_u64::<LittleEndian>(spec.reserved_size)
@@ -37,4 +44,10 @@ fn serialize_spec(spec: &HeapSpec) -> Vec<u8> {
         .write_u64::<LittleEndian>(if spec.max_size.is_none() { 0 } else { 1 })
         .unwrap();
     serialized
+        .write_u64::<LittleEndian>(match guard_placement {
+            GuardPlacement::PerInstance => 0,
+            GuardPlacement::Shared => 1,
+        })
+        .unwrap();
+    serialized
 }
@@ -1,35 +1,57 @@
 pub mod data;
 pub mod entity;
+pub mod flat;
 pub mod function;
 pub mod globals;
+#[cfg(feature = "jit")]
+pub mod jit;
+pub mod layout;
 pub mod memory;
 pub mod module_data;
+pub mod naming;
 pub mod opcode;
+pub mod recompile;
+pub mod relocations;
 pub mod state;
 pub mod table;
 pub mod traps;
+pub mod user_metadata;
+pub mod vmctx;
 
+mod elf;
 mod name;
 mod stack_probe;
 
 pub use self::name::Name;
 
-use crate::compiler::traps::write_trap_manifest;
-use crate::program::{Function, Program, TableDef};
+use crate::compiler::elf::{add_build_id_note, add_symbol_aliases, is_elf, Elf, SHT_SYMTAB};
+use crate::compiler::flat::flatten;
+use crate::compiler::naming::{MetadataSymbol, Naming};
+use crate::compiler::relocations::{relocations, rewrite_relocations, Relocation};
+use crate::compiler::traps::{write_trap_manifest, write_trap_messages};
+use crate::diagnostics::Diagnostics;
+use crate::error::{LucetcError, LucetcErrorKind};
+use crate::program::{
+    Function, FunctionDef, FunctionImport, GuardPlacement, Program, Runtime, TableDef,
+};
 use byteorder::{LittleEndian, WriteBytesExt};
 use cranelift_codegen::settings::{self, Configurable};
 use cranelift_codegen::{ir, isa, print_errors::pretty_error, CodegenError};
+use cranelift_faerie::traps::{FaerieTrapManifest, FaerieTrapSite};
 use cranelift_faerie::{FaerieBackend, FaerieBuilder, FaerieProduct, FaerieTrapCollection};
-use cranelift_module::{DataContext, Linkage, Module, ModuleError};
+use cranelift_module::{Backend, DataContext, Linkage, Module, ModuleError};
 use cranelift_native;
 use faerie::Artifact;
 use failure::{format_err, Error, ResultExt};
-use std::collections::HashMap;
+use parity_wasm::elements::{Instruction, Internal};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs::File;
 use std::io::Write;
 use std::path::Path;
+use std::time::{Duration, Instant};
+use target_lexicon::{Endianness, Triple};
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum OptLevel {
     Default,
     Best,
@@ -43,7 +65,7 @@ impl Default for OptLevel {
 }
 
 impl OptLevel {
-    fn to_flag(&self) -> &str {
+    pub(crate) fn to_flag(&self) -> &str {
         match self {
             OptLevel::Default => "default",
             OptLevel::Best => "best",
@@ -52,65 +74,525 @@ impl OptLevel {
     }
 }
 
-fn isa(opt_level: OptLevel) -> Box<isa::TargetIsa> {
+/// A code layout hint for a defined function. Hints only change the order functions are emitted
+/// in, not the sections they are emitted to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hint {
+    /// Emitted first, ahead of unhinted functions.
+    Hot,
+    /// Emitted last, after all other functions.
+    Cold,
+}
+
+/// Where generated code finds the start of linear memory.
+///
+/// There is no thread-local base: the code generator has no thread-local global values and
+/// can't emit the TLS relocations needed to address one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeapBase {
+    /// The heap starts at the vmctx pointer passed to every function. This is what the runtime
+    /// expects.
+    Vmctx,
+    /// The heap starts at this absolute address in every instance, for specialized sandboxes
+    /// that place memory at a known location.
+    ///
+    /// The address is stored in the read-only `lucet_heap_base` symbol and the vmctx is only
+    /// used to find globals. This is only sound if the embedder maps the heap's full reservation
+    /// and guard at that address before running any guest code, and never runs two instances at
+    /// once in one address space: every instance of the module shares the same memory.
+    FixedOffset(u64),
+}
+
+impl Default for HeapBase {
+    fn default() -> Self {
+        HeapBase::Vmctx
+    }
+}
+
+/// Data symbol holding the heap's address when compiling with `HeapBase::FixedOffset`.
+pub const HEAP_BASE_SYM: &str = "lucet_heap_base";
+
+/// Which WASI lifecycle a module follows.
+///
+/// The object records it in `WASI_ABI_SYM`, a little-endian `u32` that is 1 for a command and 2
+/// for a reactor, along with `WASI_ENTRY_SYM`, a pointer to the function the host calls first.
+/// Modules that follow neither get neither symbol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WasiAbi {
+    /// The module exports `_start`, which the host calls once. The instance isn't used again
+    /// after it returns.
+    Command,
+    /// The module may export `_initialize`, which the host calls once before any of its other
+    /// exports. The entry pointer is null if it doesn't. Reactors can't export `_start`.
+    Reactor,
+}
+
+impl WasiAbi {
+    fn tag(&self) -> u32 {
+        match self {
+            WasiAbi::Command => 1,
+            WasiAbi::Reactor => 2,
+        }
+    }
+}
+
+/// Data symbol holding the module's `WasiAbi`.
+pub const WASI_ABI_SYM: &str = "lucet_wasi_abi";
+/// Data symbol pointing at `_start` for a command, or `_initialize` for a reactor.
+pub const WASI_ENTRY_SYM: &str = "lucet_wasi_entry";
+
+/// What to do with a defined function that uses instructions or types lucetc can't translate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnsupportedFunctionPolicy {
+    /// Fail the whole compilation.
+    Fail,
+    /// Compile the function as a stub that traps with `traps::UNSUPPORTED_FUNCTION` when called,
+    /// and raise a warning naming it.
+    Stub,
+}
+
+impl Default for UnsupportedFunctionPolicy {
+    fn default() -> Self {
+        UnsupportedFunctionPolicy::Fail
+    }
+}
+
+/// What to do with a data segment whose offset is an imported global.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SegmentOffsetPolicy {
+    /// Fail the compilation with `LucetcErrorKind::NonConstantSegmentOffset`.
+    Fail,
+    /// Leave the segment out of the data initializers, and copy it into the heap from
+    /// `data::DEFERRED_DATA_INIT_SYM`, which the runtime calls once the globals are set.
+    InitAtRuntime,
+}
+
+impl Default for SegmentOffsetPolicy {
+    fn default() -> Self {
+        SegmentOffsetPolicy::Fail
+    }
+}
+
+/// How the initial contents of the heap are written to the object.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataInit {
+    /// The data segments, in `wasm_data_segments`, and the initialized pages, in
+    /// `guest_sparse_page_data`.
+    Sparse,
+    /// Also one image of the heap from offset 0 to the last initialized byte, in
+    /// `lucet_data_snapshot`, that the runtime can copy in with a single `memcpy` instead. See
+    /// `data::compile_data_snapshot`.
+    Snapshot,
+}
+
+impl Default for DataInit {
+    fn default() -> Self {
+        DataInit::Sparse
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CodegenSettings {
+    pub opt_level: OptLevel,
+    /// Layout hints, keyed by the function's name in the export or name section.
+    pub function_hints: HashMap<String, Hint>,
+    /// Compile only the functions with these names ahead of time, and every other defined
+    /// function as a stub that has the runtime compile it when it is first called.
+    pub lazy_compile: Option<HashSet<String>>,
+    /// Largest stack frame, in bytes, any defined function may have.
+    pub max_stack_per_function: Option<u32>,
+    /// Most locals any defined function may declare, not counting its parameters.
+    pub max_locals_per_function: Option<u32>,
+    /// The machine to generate code for, if not the host.
+    pub target: Option<Triple>,
+    pub heap_base: HeapBase,
+    /// Keep per-function trap tables out of the exported symbols. See `LucetcOpts::strip`.
+    pub strip: bool,
+    /// Leave out the bounds check of heap accesses at constant addresses that are inside the
+    /// reserved region.
+    pub elide_bounds_checks: bool,
+    /// Don't probe the stack in the prologue of functions with frames larger than a page. Any
+    /// frame must then fit in the heap's guard region, or overflowing it could skip the guard.
+    pub elide_stack_probes: bool,
+    /// Trap with `traps::SIGNED_OVERFLOW` when an integer `add`, `sub`, or `mul` overflows as a
+    /// signed operation. WebAssembly defines them to wrap, so this is not spec-conforming.
+    pub trap_on_signed_overflow: bool,
+    /// Ask the runtime to unmask hardware floating-point exceptions, so that, for example,
+    /// dividing by zero raises `SIGFPE`. Not spec-conforming. See `Compiler::define_fp_traps`.
+    pub fp_traps: bool,
+    pub on_unsupported_function: UnsupportedFunctionPolicy,
+    pub non_constant_segment_offset: SegmentOffsetPolicy,
+    /// The WASI lifecycle to record, or `None` to detect it from whether the module exports
+    /// `_start` or `_initialize`.
+    pub wasi_abi: Option<WasiAbi>,
+    /// Declare imported functions in `(module, field)` order rather than in the order of the
+    /// import section, so the object's symbols don't depend on how the module was assembled.
+    pub sort_imports: bool,
+    /// How long translating and generating code may take, measured from when the `Compiler` is
+    /// created. It is checked between functions, so a single function can overrun it.
+    pub time_budget: Option<Duration>,
+    /// Write the initialized pages of the heap to `lucet_rodata` rather than to the data segment
+    /// and sparse page tables. See `data::compile_rodata`.
+    pub separate_rodata: bool,
+    pub data_init: DataInit,
+    /// Leave functions that nothing can reach out of the trap manifest, so a linker run with
+    /// `--gc-sections` can drop their sections. See `unreachable_functions`.
+    pub function_sections: bool,
+    /// Names the metadata symbols. See `naming::SymbolNaming`.
+    pub symbol_naming: Naming,
+    /// Messages for the `unreachable` traps of each function, by the function's export or
+    /// name-section name, written to `lucet_trap_messages`. Named functions without a message
+    /// get one naming them. With `None` the table is empty. See `traps::write_trap_messages`.
+    pub trap_messages: Option<BTreeMap<String, String>>,
+    /// Pairs to write to `lucet_user_metadata`, in order. See `user_metadata`.
+    pub user_metadata: Vec<(String, Vec<u8>)>,
+    /// Warn about each stretch of code that can't be reached, such as code after a `br`.
+    pub warn_unreachable_code: bool,
+    /// Receives warnings about settings that were ignored or fallbacks that were taken.
+    pub diagnostics: Diagnostics,
+}
+
+impl CodegenSettings {
+    pub fn new(opt_level: OptLevel) -> Self {
+        Self {
+            opt_level,
+            ..Self::default()
+        }
+    }
+}
+
+fn isa(settings: &CodegenSettings) -> Result<Box<isa::TargetIsa>, Error> {
+    Ok(isa_builder(settings)?.finish(shared_flags(settings)?))
+}
+
+pub(crate) fn isa_builder(settings: &CodegenSettings) -> Result<isa::Builder, Error> {
+    Ok(match settings.target {
+        None => cranelift_native::builder().expect("host machine is not a supported target"),
+        Some(ref triple) => {
+            // Data segments, tables, and the various specs are all serialized little-endian.
+            if triple.endianness() != Ok(Endianness::Little) {
+                Err(format_err!("cannot compile for {}", triple).context(
+                    LucetcErrorKind::Unsupported("big-endian targets".to_owned()),
+                ))?;
+            }
+            isa::lookup(triple.clone())
+                .map_err(|e| format_err!("unsupported target {}: {:?}", triple, e))?
+        }
+    })
+}
+
+pub(crate) fn shared_flags(settings: &CodegenSettings) -> Result<settings::Flags, Error> {
+    Ok(settings::Flags::new(shared_flags_builder(settings)?))
+}
+
+/// The flags builder behind `shared_flags`, for backends that adjust a few flags on top.
+pub(crate) fn shared_flags_builder(settings: &CodegenSettings) -> Result<settings::Builder, Error> {
     let mut flags_builder = settings::builder();
-    let isa_builder = cranelift_native::builder().expect("host machine is not a supported target");
-    flags_builder.enable("enable_verifier").unwrap();
-    flags_builder.enable("is_pic").unwrap();
-    flags_builder.set("opt_level", opt_level.to_flag()).unwrap();
-    isa_builder.finish(settings::Flags::new(flags_builder))
+    set_flag(&mut flags_builder, "enable_verifier", "true")?;
+    set_flag(&mut flags_builder, "is_pic", "true")?;
+    set_flag(
+        &mut flags_builder,
+        "opt_level",
+        settings.opt_level.to_flag(),
+    )?;
+    if settings.elide_stack_probes {
+        set_flag(&mut flags_builder, "probestack_enabled", "false")?;
+    }
+    Ok(flags_builder)
 }
 
-pub struct Compiler<'p> {
+pub(crate) fn set_flag(
+    flags_builder: &mut settings::Builder,
+    name: &str,
+    value: &str,
+) -> Result<(), Error> {
+    flags_builder
+        .set(name, value)
+        .map_err(|e| format_err!("setting cranelift flag {} = {}: {:?}", name, value, e))
+}
+
+/// Translates a `Program` into Cranelift functions and data, declared in a `cranelift_module`
+/// module. The default backend writes an object file; see `crate::jit` for the alternative.
+pub struct Compiler<'p, B: Backend = FaerieBackend> {
     pub prog: &'p Program,
     funcs: HashMap<Name, ir::Function>,
-    module: Module<FaerieBackend>,
-    opt_level: OptLevel,
+    module: Module<B>,
+    settings: CodegenSettings,
+    /// Defined functions in the order they are laid out in the text section.
+    layout: Vec<Name>,
+    /// Frame size of each function, filled in as code is generated.
+    stack_sizes: Vec<(String, u32)>,
+    /// Relocations to move once the object is emitted, since the object writer can only point
+    /// data at the start of a symbol. See `ObjectFile::addends`.
+    addends: Vec<(String, u64, i64)>,
+    deadline: Option<Instant>,
 }
 
 impl<'p> Compiler<'p> {
-    pub fn new(name: String, prog: &'p Program, opt_level: OptLevel) -> Result<Self, Error> {
+    pub fn new(name: String, prog: &'p Program, settings: &CodegenSettings) -> Result<Self, Error> {
+        Self::with_isa(name, prog, settings, isa(settings)?)
+    }
+
+    /// Like `new`, but generate code with `isa`, which must have been built for `settings`.
+    pub fn with_isa(
+        name: String,
+        prog: &'p Program,
+        settings: &CodegenSettings,
+        isa: Box<isa::TargetIsa>,
+    ) -> Result<Self, Error> {
         let libcalls = Box::new(move |libcall| match libcall {
             ir::LibCall::Probestack => stack_probe::STACK_PROBE_SYM.to_owned(),
             _ => (FaerieBuilder::default_libcall_names())(libcall),
         });
 
+        let module = Module::new(FaerieBuilder::new(
+            isa,
+            name,
+            FaerieTrapCollection::Enabled,
+            libcalls,
+        )?);
+        Self::with_module(module, prog, settings)
+    }
+
+    pub fn cranelift_funcs(self) -> CraneliftFuncs {
+        let isa = self.isa();
+        CraneliftFuncs {
+            funcs: self.funcs,
+            isa: isa,
+        }
+    }
+
+    pub fn codegen(mut self) -> Result<ObjectFile, Error> {
+        self.define_functions_streaming()?;
+        self.check_frames_fit_guard()?;
+        let exports = self.exported_definitions()?;
+        let unreachable = if self.settings.function_sections {
+            unreachable_functions(self.prog)
+        } else {
+            HashSet::new()
+        };
+        let messages = self.trap_messages()?;
+        let mut obj = ObjectFile::from_product(
+            self.module.finish(),
+            self.settings.strip,
+            &unreachable,
+            &messages,
+            &self.settings.symbol_naming,
+        )?;
+        obj.stack_sizes = self.stack_sizes;
+        obj.addends = self.addends;
+        obj.exports = exports;
+        obj.function_symbols = self
+            .prog
+            .defined_functions()
+            .iter()
+            .map(|f| (f.wasmidx, f.symbol().to_owned()))
+            .collect();
+        for f in self.prog.defined_functions() {
+            for alias in f.export_aliases() {
+                obj.alias(f.symbol(), alias);
+            }
+        }
+        Ok(obj)
+    }
+
+    /// The message for the `unreachable` traps of each defined function that has one, by symbol.
+    fn trap_messages(&self) -> Result<HashMap<String, String>, Error> {
+        let given = match self.settings.trap_messages {
+            Some(ref given) => given,
+            None => return Ok(HashMap::new()),
+        };
+        let defined = self.prog.defined_functions();
+        for name in given.keys() {
+            if !defined.iter().any(|f| f.name() == Some(name.as_str())) {
+                Err(format_err!(
+                    "cannot give a trap message for {}: no function has that name",
+                    name
+                ))?;
+            }
+        }
+        Ok(defined
+            .iter()
+            .filter_map(|f| {
+                let name = f.name()?;
+                let message = given
+                    .get(name)
+                    .cloned()
+                    .unwrap_or_else(|| format!("unreachable executed in {}", name));
+                Some((f.symbol().to_owned(), message))
+            })
+            .collect())
+    }
+
+    /// The stack sits directly below the heap's guard region, so without probes a frame larger
+    /// than the guard can step over it and into mapped memory.
+    fn check_frames_fit_guard(&self) -> Result<(), Error> {
+        if !self.settings.elide_stack_probes {
+            return Ok(());
+        }
+        let guard_size = self.prog.heap_spec()?.guard_size;
+        let largest = self.stack_sizes.iter().max_by_key(|(_, size)| *size);
+        if let Some((name, frame_size)) = largest {
+            if *frame_size as u64 > guard_size {
+                return Err(format_err!(
+                    "function {} has a {}-byte frame, larger than the {}-byte guard, and stack probes are disabled",
+                    name,
+                    frame_size,
+                    guard_size
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Export names of the functions the object defines, rather than re-exports of imports.
+    fn exported_definitions(&self) -> Result<Vec<String>, Error> {
+        let entries = self
+            .prog
+            .module()
+            .export_section()
+            .map(|s| s.entries())
+            .unwrap_or(&[]);
+        let mut exports = vec![];
+        for entry in entries {
+            if let Internal::Function(index) = entry.internal() {
+                if self.prog.get_function(*index)?.linkage() != Linkage::Import {
+                    exports.push(entry.field().to_owned());
+                }
+            }
+        }
+        Ok(exports)
+    }
+}
+
+impl<'p, B: Backend> Compiler<'p, B> {
+    /// Declare everything in `prog` into a fresh `module`.
+    pub fn with_module(
+        module: Module<B>,
+        prog: &'p Program,
+        settings: &CodegenSettings,
+    ) -> Result<Self, Error> {
+        let heap = prog.heap_spec()?;
+        let unguarded = heap.guard_size == 0 || prog.guard_placement() == GuardPlacement::Shared;
+        if unguarded && heap.reserved_size >= u32::max_value() as u64 {
+            Err(format_err!(
+                "a heap without guard pages of its own must reserve less than 4GiB, not {} bytes",
+                heap.reserved_size
+            )
+            .context(LucetcErrorKind::MemorySpecs))?;
+        }
+        let mut names = settings.symbol_naming.names();
+        names.sort();
+        if names.iter().any(|n| n.is_empty()) {
+            Err(format_err!(
+                "the naming policy gave a metadata symbol an empty name"
+            ))?;
+        }
+        if let Some(pair) = names.windows(2).find(|pair| pair[0] == pair[1]) {
+            Err(format_err!(
+                "the naming policy gave more than one metadata symbol the name {}",
+                pair[0]
+            ))?;
+        }
         let mut compiler = Self {
             funcs: HashMap::new(),
-            module: Module::new(FaerieBuilder::new(
-                isa(opt_level),
-                name,
-                FaerieTrapCollection::Enabled,
-                libcalls,
-            )?),
+            module,
             prog: prog,
-            opt_level: opt_level,
+            settings: settings.clone(),
+            layout: Vec::new(),
+            stack_sizes: Vec::new(),
+            addends: Vec::new(),
+            deadline: settings.time_budget.map(|budget| Instant::now() + budget),
         };
 
-        for f in prog.import_functions() {
-            compiler.declare_function(f)?;
+        let mut imports: Vec<_> = prog.import_functions().iter().collect();
+        if settings.sort_imports {
+            imports.sort_by(|a, b| (a.module(), a.field()).cmp(&(b.module(), b.field())));
+        }
+        for f in imports {
+            if f.addend() == 0 {
+                compiler.declare_function(f)?;
+            } else {
+                compiler.define_import_cell(f)?;
+            }
+        }
+        for g in prog.globals().iter().filter_map(|g| g.as_import()) {
+            if let Some(symbol) = g.symbol() {
+                compiler
+                    .declare_data(symbol, Linkage::Import, g.global_type.is_mutable())
+                    .context(format!(
+                        "declaration of {} for global {}::{}",
+                        symbol,
+                        g.module(),
+                        g.field()
+                    ))?;
+            }
+        }
+
+        for hinted in settings.function_hints.keys() {
+            if !prog
+                .defined_functions()
+                .iter()
+                .any(|f| f.name() == Some(hinted.as_str()))
+            {
+                settings.diagnostics.warn(format!(
+                    "ignoring layout hint for unknown function `{}`",
+                    hinted
+                ));
+            }
         }
 
         let start_section = prog.module().start_section();
-        for f in prog.defined_functions() {
+        for f in layout_order(prog.defined_functions(), &settings.function_hints) {
             let name = compiler.declare_function(f)?;
             if Some(f.wasmidx) == start_section {
                 compiler.define_start_symbol(&name)?;
             }
+            compiler.layout.push(name);
+        }
+
+        if let Some(abi) = wasi_abi(prog, settings.wasi_abi, &settings.diagnostics)? {
+            compiler.define_wasi_symbols(abi)?;
         }
+        compiler.define_fp_traps()?;
 
         for f in prog.runtime_functions() {
             compiler.declare_function(f)?;
         }
+        if let Some(ref hot) = settings.lazy_compile {
+            compiler.declare_function(&Runtime::compile_on_demand())?;
+            for name in hot {
+                if !prog
+                    .defined_functions()
+                    .iter()
+                    .any(|f| f.name() == Some(name.as_str()))
+                {
+                    settings.diagnostics.warn(format!(
+                        "function `{}` is not defined, so it can't be compiled ahead of time",
+                        name
+                    ));
+                }
+            }
+        }
 
         for t in prog.tables() {
             compiler.declare_table(t)?;
         }
+
+        if let HeapBase::FixedOffset(base) = settings.heap_base {
+            let symbol = compiler.metadata_symbol(MetadataSymbol::HeapBase);
+            let name = compiler.declare_data(&symbol, Linkage::Export, false)?;
+            let mut serialized_base: Vec<u8> = Vec::new();
+            serialized_base.write_u64::<LittleEndian>(base).unwrap();
+            let mut ctx = DataContext::new();
+            ctx.define(serialized_base.into_boxed_slice());
+            compiler.define_data(name, &ctx)?;
+        }
         Ok(compiler)
     }
 
     pub fn isa(&self) -> Box<isa::TargetIsa> {
-        isa(self.opt_level)
+        isa(&self.settings).expect("target was checked when the compiler was created")
     }
 
     /// Add a `guest_start` data symbol pointing to the `start` section.
@@ -122,7 +604,8 @@ impl<'p> Compiler<'p> {
     /// symbols, we add a data symbol with a reloc pointer to the
     /// function's normal symbol.
     pub fn define_start_symbol(&mut self, start_func: &Name) -> Result<(), Error> {
-        let name = self.declare_data("guest_start", Linkage::Export, false)?;
+        let symbol = self.metadata_symbol(MetadataSymbol::Start);
+        let name = self.declare_data(&symbol, Linkage::Export, false)?;
         let mut ctx = DataContext::new();
         ctx.define_zeroinit(8);
         let fid = start_func
@@ -133,6 +616,81 @@ impl<'p> Compiler<'p> {
         self.define_data(name, &ctx)
     }
 
+    /// Add the `WASI_ABI_SYM` and `WASI_ENTRY_SYM` data symbols for a module following `abi`.
+    fn define_wasi_symbols(&mut self, abi: WasiAbi) -> Result<(), Error> {
+        let symbol = self.metadata_symbol(MetadataSymbol::WasiAbi);
+        let name = self.declare_data(&symbol, Linkage::Export, false)?;
+        let mut serialized_abi: Vec<u8> = Vec::new();
+        serialized_abi.write_u32::<LittleEndian>(abi.tag()).unwrap();
+        let mut ctx = DataContext::new();
+        ctx.define(serialized_abi.into_boxed_slice());
+        self.define_data(name, &ctx)?;
+
+        let symbol = self.metadata_symbol(MetadataSymbol::WasiEntry);
+        let name = self.declare_data(&symbol, Linkage::Export, false)?;
+        let mut ctx = DataContext::new();
+        ctx.define_zeroinit(8);
+        let entry = match abi {
+            WasiAbi::Command => "_start",
+            WasiAbi::Reactor => "_initialize",
+        };
+        let prog = self.prog;
+        if let Some(index) = exported_function(prog, entry) {
+            let func = self.declare_function(prog.get_function(index)?)?;
+            let fid = func
+                .into_funcid()
+                .ok_or(format_err!("{} is not a function", entry))?;
+            let fref = self.module.declare_func_in_data(fid, &mut ctx);
+            ctx.write_function_addr(0, fref);
+        }
+        self.define_data(name, &ctx)
+    }
+
+    /// Add the `lucet_fp_traps` data symbol, a little-endian `u32` that is 1 if the runtime should
+    /// unmask floating-point exceptions before entering the guest and 0 if it should leave them
+    /// masked. Cranelift emits SSE arithmetic, which raises no exceptions while they are masked,
+    /// so whether they trap is down to the `MXCSR` register the runtime runs the guest with,
+    /// rather than anything in the generated code.
+    fn define_fp_traps(&mut self) -> Result<(), Error> {
+        let symbol = self.metadata_symbol(MetadataSymbol::FpTraps);
+        let name = self.declare_data(&symbol, Linkage::Export, false)?;
+        let mut serialized: Vec<u8> = Vec::new();
+        serialized
+            .write_u32::<LittleEndian>(self.settings.fp_traps as u32)
+            .unwrap();
+        let mut ctx = DataContext::new();
+        ctx.define(serialized.into_boxed_slice());
+        self.define_data(name, &ctx)
+    }
+
+    /// Define the pointer an import bound with an addend is called through. The bound symbol is
+    /// declared as data, since it is only the base of the function's address, and the pointer's
+    /// relocation gets the addend once the object is emitted. Imports with the same symbol and
+    /// addend share a pointer.
+    fn define_import_cell(&mut self, import: &FunctionImport) -> Result<(), Error> {
+        let symbol = import_cell_symbol(import);
+        if self.module.get_name(&symbol).is_some() {
+            return Ok(());
+        }
+        let target = self
+            .module
+            .declare_data(import.symbol(), Linkage::Import, false)
+            .context(format!("declaration of {}", import.symbol()))?;
+        let name = self.declare_data(&symbol, Linkage::Local, false)?;
+        let mut ctx = DataContext::new();
+        ctx.define_zeroinit(8);
+        let target = self.module.declare_data_in_data(target, &mut ctx);
+        ctx.write_data_addr(0, target, 0);
+        self.define_data(name, &ctx)?;
+        self.addends.push((symbol, 0, import.addend()));
+        Ok(())
+    }
+
+    /// Record that the relocation at `offset` in the data `symbol` needs `addend` added to it.
+    pub(crate) fn add_addend(&mut self, symbol: &str, offset: u64, addend: i64) {
+        self.addends.push((symbol.to_owned(), offset, addend));
+    }
+
     pub fn declare_function(&mut self, func: &Function) -> Result<Name, Error> {
         let funcid = self
             .module
@@ -159,6 +717,11 @@ impl<'p> Compiler<'p> {
         Ok(Name::new_data(table.symbol(), dataid))
     }
 
+    /// The name the settings' naming policy gives `symbol`.
+    pub fn metadata_symbol(&self, symbol: MetadataSymbol) -> String {
+        self.settings.symbol_naming.name(symbol)
+    }
+
     pub fn declare_data(
         &mut self,
         sym: &str,
@@ -217,26 +780,50 @@ impl<'p> Compiler<'p> {
         Ok(())
     }
 
-    pub fn cranelift_funcs(self) -> CraneliftFuncs {
-        let isa = self.isa();
-        CraneliftFuncs {
-            funcs: self.funcs,
-            isa: isa,
+    /// Fail with `LucetcErrorKind::Cancelled` once the time budget has run out.
+    pub fn check_deadline(&self) -> Result<(), LucetcError> {
+        match self.deadline {
+            Some(deadline) if Instant::now() >= deadline => {
+                Err(LucetcError::from(LucetcErrorKind::Cancelled))
+            }
+            _ => Ok(()),
         }
     }
 
-    pub fn codegen(self) -> Result<ObjectFile, Error> {
+    /// Run Cranelift code generation on every translated function, defining it in the module.
+    /// The translated functions are kept, for `recompile_function`.
+    pub fn define_functions(&mut self) -> Result<(), Error> {
+        self.generate_code(true)
+    }
+
+    /// Like `define_functions`, but each translated function is dropped as soon as its code has
+    /// been generated and handed to the module. Only one function's IR and compilation context
+    /// are alive beyond the module's own output, so for a module with many functions the peak
+    /// is roughly the larger of all the IR and all the machine code, rather than their sum.
+    fn define_functions_streaming(&mut self) -> Result<(), Error> {
+        self.generate_code(false)
+    }
+
+    fn generate_code(&mut self, keep_translated: bool) -> Result<(), Error> {
         use cranelift_codegen::Context;
 
-        let isa = &*self.isa();
         let mut ctx = Context::new();
-        let mut module = self.module;
 
-        for (name, func) in self.funcs.iter() {
-            ctx.func = func.clone();
+        for name in self.layout.iter() {
+            self.check_deadline()?;
+            let func = if keep_translated {
+                self.funcs.get(name).cloned()
+            } else {
+                self.funcs.remove(name)
+            };
+            ctx.func = match func {
+                Some(func) => func,
+                None => continue,
+            };
             let id = name
                 .into_funcid()
                 .ok_or(format_err!("function defined with invalid name {:?}", name,))?;
+            let module = &mut self.module;
             module.define_function(id, &mut ctx).map_err(|e| match e {
                 ModuleError::Compilation(ce) => match ce {
                     CodegenError::Verifier(_) =>
@@ -245,20 +832,150 @@ impl<'p> Compiler<'p> {
                     {
                         format_err!(
                             "code generation error:\n{}",
-                            pretty_error(func, Some(isa), ce)
+                            pretty_error(&ctx.func, Some(module.isa()), ce)
                         )
                     }
                     _ => ModuleError::Compilation(ce).into(),
                 },
                 _ => e.into(),
             })?;
+
+            let frame_size = ctx.func.stack_slots.frame_size.unwrap_or(0);
+            if let Some(max) = self.settings.max_stack_per_function {
+                if frame_size > max {
+                    return Err(format_err!(
+                        "function {} needs {} bytes of stack, more than the limit of {}",
+                        name.symbol(),
+                        frame_size,
+                        max
+                    ));
+                }
+            }
+            self.stack_sizes
+                .push((name.symbol().to_owned(), frame_size));
             ctx.clear();
         }
+        Ok(())
+    }
+}
+
+/// Symbol of the pointer a call to `import`, which is bound with an addend, loads its callee
+/// from.
+pub(crate) fn import_cell_symbol(import: &FunctionImport) -> String {
+    format!("guest_import_{}_{}", import.symbol(), import.addend())
+}
+
+/// Index of the function `prog` exports as `field`, if any.
+fn exported_function(prog: &Program, field: &str) -> Option<u32> {
+    prog.module()
+        .export_section()
+        .map(|s| s.entries())
+        .unwrap_or(&[])
+        .iter()
+        .filter(|e| e.field() == field)
+        .filter_map(|e| match e.internal() {
+            Internal::Function(index) => Some(*index),
+            _ => None,
+        })
+        .next()
+}
+
+/// Symbols of the defined functions that no call path reaches from an export, a table entry,
+/// or the start function.
+fn unreachable_functions(prog: &Program) -> HashSet<String> {
+    let module = prog.module();
+    let mut pending: Vec<u32> = module
+        .export_section()
+        .map(|s| s.entries())
+        .unwrap_or(&[])
+        .iter()
+        .filter_map(|e| match e.internal() {
+            Internal::Function(index) => Some(*index),
+            _ => None,
+        })
+        .collect();
+    for segment in module
+        .elements_section()
+        .map(|s| s.entries())
+        .unwrap_or(&[])
+    {
+        pending.extend(segment.members());
+    }
+    pending.extend(module.start_section());
+
+    let mut reached = HashSet::new();
+    while let Some(index) = pending.pop() {
+        if !reached.insert(index) {
+            continue;
+        }
+        let defined = index
+            .checked_sub(prog.import_functions().len() as u32)
+            .and_then(|i| prog.defined_functions().get(i as usize));
+        if let Some(def) = defined {
+            for op in prog.function_body(def).code().elements() {
+                if let Instruction::Call(callee) = op {
+                    pending.push(*callee);
+                }
+            }
+        }
+    }
 
-        ObjectFile::new(module.finish())
+    prog.defined_functions()
+        .iter()
+        .filter(|f| !reached.contains(&f.wasmidx))
+        .map(|f| f.symbol().to_owned())
+        .collect()
+}
+
+/// The WASI lifecycle `prog` follows: `requested`, after checking the module's exports agree
+/// with it, or the one its exports imply. A module whose exports imply neither is compiled
+/// without one, with a warning.
+fn wasi_abi(
+    prog: &Program,
+    requested: Option<WasiAbi>,
+    diagnostics: &Diagnostics,
+) -> Result<Option<WasiAbi>, Error> {
+    let start = exported_function(prog, "_start").is_some();
+    let initialize = exported_function(prog, "_initialize").is_some();
+    match requested {
+        Some(WasiAbi::Command) if !start => Err(format_err!(
+            "a WASI command must export a `_start` function"
+        )),
+        Some(WasiAbi::Command) if initialize => Err(format_err!(
+            "a WASI command can't export an `_initialize` function"
+        )),
+        Some(WasiAbi::Reactor) if start => Err(format_err!(
+            "a WASI reactor can't export a `_start` function"
+        )),
+        Some(abi) => Ok(Some(abi)),
+        None if start && initialize => {
+            diagnostics.warn(
+                "module exports both `_start` and `_initialize`, so it is neither a WASI command nor a reactor"
+                    .to_owned(),
+            );
+            Ok(None)
+        }
+        None if start => Ok(Some(WasiAbi::Command)),
+        None if initialize => Ok(Some(WasiAbi::Reactor)),
+        None => Ok(None),
     }
 }
 
+/// Orders defined functions for code layout: hot functions first, then unhinted ones, then cold
+/// ones. Within each group, functions keep their wasm index order.
+fn layout_order<'a>(
+    funcs: &'a [FunctionDef],
+    hints: &HashMap<String, Hint>,
+) -> Vec<&'a FunctionDef> {
+    let mut order: Vec<&FunctionDef> = funcs.iter().collect();
+    order.sort_by_key(|f| match f.name().and_then(|n| hints.get(n)) {
+        Some(Hint::Hot) => 0,
+        None => 1,
+        Some(Hint::Cold) => 2,
+    });
+    order
+}
+
 pub struct CraneliftFuncs {
     funcs: HashMap<Name, ir::Function>,
     isa: Box<isa::TargetIsa>,
@@ -278,29 +995,239 @@ impl CraneliftFuncs {
         file.write_all(buffer.as_bytes())?;
         Ok(())
     }
+
+    /// Write the control flow graph of the function with symbol `func`, or of every function in
+    /// symbol order, as Graphviz digraphs.
+    pub fn write_cfg<P: AsRef<Path>>(&self, func: Option<&str>, path: P) -> Result<(), Error> {
+        use cranelift_codegen::cfg_printer::CFGPrinter;
+        let mut funcs: Vec<_> = self
+            .funcs
+            .iter()
+            .filter(|(n, _)| func.map_or(true, |f| n.symbol() == f))
+            .collect();
+        if let Some(f) = func {
+            if funcs.is_empty() {
+                return Err(format_err!("no function has the symbol {}", f));
+            }
+        }
+        funcs.sort_by(|a, b| a.0.symbol().cmp(b.0.symbol()));
+        let mut buffer = String::new();
+        for (_, func) in funcs {
+            buffer.push_str(&CFGPrinter::new(func).to_string());
+        }
+        let mut file = File::create(path)?;
+        file.write_all(buffer.as_bytes())?;
+        Ok(())
+    }
 }
 
 pub struct ObjectFile {
     artifact: Artifact,
+    trap_manifest: FaerieTrapManifest,
+    stack_sizes: Vec<(String, u32)>,
+    /// Extra names for defined symbols, as `(symbol, alias)`.
+    aliases: Vec<(String, String)>,
+    /// Function exports that must each have a `guest_func_` symbol.
+    exports: Vec<String>,
+    /// The symbol of each defined function, by index in the module's function index space.
+    function_symbols: Vec<(u32, String)>,
+    /// Amounts to add to relocations in data, as `(symbol, offset, addend)`, for pointers to
+    /// imports bound with an addend.
+    addends: Vec<(String, u64, i64)>,
+    /// Whether the written object carries a GNU build ID note.
+    build_id: bool,
 }
 impl ObjectFile {
-    pub fn new(mut product: FaerieProduct) -> Result<Self, Error> {
+    pub fn new(product: FaerieProduct) -> Result<Self, Error> {
+        Self::from_product(
+            product,
+            false,
+            &HashSet::new(),
+            &HashMap::new(),
+            &Naming::default(),
+        )
+    }
+    /// With `strip`, the per-function trap tables are local symbols, reachable only through
+    /// `lucet_trap_manifest`. The functions in `unreachable` are left out of the manifest.
+    /// `messages` are the trap messages of each function, by symbol.
+    fn from_product(
+        mut product: FaerieProduct,
+        strip: bool,
+        unreachable: &HashSet<String>,
+        messages: &HashMap<String, String>,
+        naming: &Naming,
+    ) -> Result<Self, Error> {
         stack_probe::declare_and_define(&mut product)?;
-        let trap_manifest = &product
+        let trap_manifest = product
             .trap_manifest
             .expect("trap manifest will be present");
-        write_trap_manifest(trap_manifest, &mut product.artifact)?;
+        write_trap_manifest(
+            &trap_manifest,
+            &mut product.artifact,
+            !strip,
+            unreachable,
+            naming,
+        )?;
+        write_trap_messages(
+            &trap_manifest,
+            &mut product.artifact,
+            unreachable,
+            messages,
+            naming,
+        )?;
         Ok(Self {
             artifact: product.artifact,
+            trap_manifest,
+            stack_sizes: Vec::new(),
+            aliases: Vec::new(),
+            exports: Vec::new(),
+            function_symbols: Vec::new(),
+            addends: Vec::new(),
+            build_id: false,
         })
     }
+    /// The trap sites of every function in the object, as written to `lucet_trap_manifest`.
+    pub fn trap_manifest(&self) -> &FaerieTrapManifest {
+        &self.trap_manifest
+    }
+    /// The trap sites of the defined function at `func_index` in the module's function index
+    /// space, which counts imported functions first. Empty if the object doesn't define that
+    /// function, or it has nothing that can trap.
+    pub fn traps_for(&self, func_index: u32) -> Vec<&FaerieTrapSite> {
+        let symbol = match self.function_symbols.iter().find(|(i, _)| *i == func_index) {
+            Some((_, symbol)) => symbol,
+            None => return vec![],
+        };
+        self.trap_manifest
+            .sinks
+            .iter()
+            .filter(|sink| &sink.name == symbol)
+            .flat_map(|sink| sink.sites.iter())
+            .collect()
+    }
+    /// The stack frame size in bytes of each defined function, by symbol, in text order.
+    pub fn stack_sizes(&self) -> &[(String, u32)] {
+        &self.stack_sizes
+    }
+    /// Also define `alias` at the address of the defined symbol `symbol`.
+    pub fn alias(&mut self, symbol: &str, alias: &str) {
+        self.aliases.push((symbol.to_owned(), alias.to_owned()));
+    }
+    /// Write a `.note.gnu.build-id` section, holding a SHA-1 hash of the rest of the object, to
+    /// the ELF objects written by `write` and `write_rewriting_relocations`. Flat images and
+    /// other object formats have no such note.
+    pub fn build_id(&mut self, build_id: bool) {
+        self.build_id = build_id;
+    }
+    /// Every relocation in the object, as it would be written.
+    pub fn relocations(&self) -> Result<Vec<Relocation>, Error> {
+        relocations(&self.emit()?)
+    }
+    fn emit(&self) -> Result<Vec<u8>, Error> {
+        let obj = self.artifact.emit()?;
+        // Aliases, addends, and the export check work on the ELF symbol and relocation tables.
+        if !is_elf(&obj) {
+            if !self.aliases.is_empty() || !self.addends.is_empty() {
+                Err(format_err!(
+                    "symbol aliases and import addends can only be written to ELF objects"
+                )
+                .context(LucetcErrorKind::Unsupported(
+                    "non-ELF object format".to_owned(),
+                )))?;
+            }
+            return Ok(obj);
+        }
+        let mut obj = add_symbol_aliases(&obj, &self.aliases)?;
+        apply_addends(&mut obj, &self.addends)?;
+        check_export_symbols(&obj, &self.exports)?;
+        Ok(obj)
+    }
     pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
         let _ = path.as_ref().file_name().ok_or(format_err!(
             "path {:?} needs to have filename",
             path.as_ref()
         ));
-        let file = File::create(path)?;
-        self.artifact.write(file)?;
+        let obj = self.with_build_id(self.emit()?)?;
+        let mut file = File::create(path)?;
+        file.write_all(&obj)?;
         Ok(())
     }
+    fn with_build_id(&self, obj: Vec<u8>) -> Result<Vec<u8>, Error> {
+        if self.build_id && is_elf(&obj) {
+            add_build_id_note(&obj)
+        } else {
+            Ok(obj)
+        }
+    }
+    /// Like `write`, but each relocation is passed through `rewrite` before the object is
+    /// written. See `rewrite_relocations` for what may be changed.
+    pub fn write_rewriting_relocations<P, F>(&self, path: P, rewrite: F) -> Result<(), Error>
+    where
+        P: AsRef<Path>,
+        F: FnMut(&mut Relocation),
+    {
+        let mut obj = self.emit()?;
+        rewrite_relocations(&mut obj, rewrite)?;
+        let obj = self.with_build_id(obj)?;
+        let mut file = File::create(path)?;
+        file.write_all(&obj)?;
+        Ok(())
+    }
+    /// Like `write_rewriting_relocations`, but write a flat image loaded at `base` instead of
+    /// the object. See `flat::flatten`.
+    pub fn write_flat<P, F>(&self, path: P, base: u64, rewrite: F) -> Result<(), Error>
+    where
+        P: AsRef<Path>,
+        F: FnMut(&mut Relocation),
+    {
+        let mut obj = self.emit()?;
+        rewrite_relocations(&mut obj, rewrite)?;
+        let image = flatten(&obj, base)?;
+        let mut file = File::create(path)?;
+        file.write_all(&image)?;
+        Ok(())
+    }
+}
+
+/// Add each of `addends` to the relocation at that offset in the data symbol's section.
+fn apply_addends(obj: &mut [u8], addends: &[(String, u64, i64)]) -> Result<(), Error> {
+    if addends.is_empty() {
+        return Ok(());
+    }
+    rewrite_relocations(obj, |reloc| {
+        let symbol = reloc
+            .section
+            .trim_start_matches(".rodata.")
+            .trim_start_matches(".data.");
+        for (target, offset, addend) in addends {
+            if symbol == target && reloc.offset == *offset {
+                reloc.addend += addend;
+            }
+        }
+    })
+}
+
+/// Make sure the runtime will find every function export in `obj`. A mismatch between how
+/// exports are named and how their symbols are declared would otherwise only show up when the
+/// module is loaded.
+fn check_export_symbols(obj: &[u8], exports: &[String]) -> Result<(), Error> {
+    if exports.is_empty() {
+        return Ok(());
+    }
+    let elf = Elf::parse(obj)?;
+    let symtab = elf
+        .sections
+        .iter()
+        .position(|s| s.kind == SHT_SYMTAB)
+        .ok_or(format_err!("object has no symbol table"))?;
+    let defined = elf.global_definitions(symtab)?;
+    for export in exports {
+        let symbol = format!("guest_func_{}", export);
+        if !defined.contains(&symbol) {
+            Err(LucetcError::from(LucetcErrorKind::MissingExportSymbol(
+                export.clone(),
+            )))?;
+        }
+    }
+    Ok(())
 }
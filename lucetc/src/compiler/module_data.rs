@@ -1,11 +1,12 @@
+use crate::compiler::naming::MetadataSymbol;
 use crate::compiler::Compiler;
 use crate::program::data::sparse::OwnedSparseData;
 use byteorder::{LittleEndian, WriteBytesExt};
-use cranelift_module::{DataContext, Linkage};
+use cranelift_module::{Backend, DataContext, Linkage};
 use failure::Error;
 use lucet_module_data::ModuleData;
 
-pub fn compile_module_data(compiler: &mut Compiler) -> Result<(), Error> {
+pub fn compile_module_data<B: Backend>(compiler: &mut Compiler<B>) -> Result<(), Error> {
     let module_data_serialized: Vec<u8> = {
         let heap_spec = compiler.prog.heap_spec()?;
         let compiled_data = OwnedSparseData::new(
@@ -29,10 +30,11 @@ pub fn compile_module_data(compiler: &mut Compiler) -> Result<(), Error> {
         let mut data_len_ctx = DataContext::new();
         data_len_ctx.define(serialized_len.into_boxed_slice());
 
-        let data_len_decl =
-            compiler
-                .module
-                .declare_data("lucet_module_data_len", Linkage::Export, false)?;
+        let data_len_decl = compiler.module.declare_data(
+            &compiler.metadata_symbol(MetadataSymbol::ModuleDataLen),
+            Linkage::Export,
+            false,
+        )?;
         compiler.module.define_data(data_len_decl, &data_len_ctx)?;
     }
 
@@ -40,10 +42,11 @@ pub fn compile_module_data(compiler: &mut Compiler) -> Result<(), Error> {
         let mut module_data_ctx = DataContext::new();
         module_data_ctx.define(module_data_serialized.into_boxed_slice());
 
-        let module_data_decl =
-            compiler
-                .module
-                .declare_data("lucet_module_data", Linkage::Export, true)?;
+        let module_data_decl = compiler.module.declare_data(
+            &compiler.metadata_symbol(MetadataSymbol::ModuleData),
+            Linkage::Export,
+            true,
+        )?;
         compiler
             .module
             .define_data(module_data_decl, &module_data_ctx)?;
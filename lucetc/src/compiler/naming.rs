@@ -0,0 +1,142 @@
+//! Names of the metadata symbols emitted alongside a module's functions.
+//!
+//! The runtime finds a module's specs and tables by symbol name, so an integration that loads
+//! modules some other way may want them named to suit it. Function symbols (`guest_func_*`) and
+//! the per-function trap tables are named as they always are.
+
+use crate::compiler::{HEAP_BASE_SYM, WASI_ABI_SYM, WASI_ENTRY_SYM};
+use std::fmt;
+use std::rc::Rc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MetadataSymbol {
+    HeapSpec,
+    GlobalsSpec,
+    ModuleData,
+    ModuleDataLen,
+    DataSegments,
+    DataSegmentsLen,
+    SparsePageData,
+    Rodata,
+    RodataSpec,
+    DataSnapshot,
+    DataSnapshotLen,
+    Elements,
+    ElementsLen,
+    TrapManifest,
+    TrapManifestLen,
+    TrapMessages,
+    TrapMessagesLen,
+    Start,
+    HeapBase,
+    WasiAbi,
+    WasiEntry,
+    VmctxLayout,
+    UserMetadata,
+    FpTraps,
+}
+
+impl MetadataSymbol {
+    pub const ALL: &'static [MetadataSymbol] = &[
+        MetadataSymbol::HeapSpec,
+        MetadataSymbol::GlobalsSpec,
+        MetadataSymbol::ModuleData,
+        MetadataSymbol::ModuleDataLen,
+        MetadataSymbol::DataSegments,
+        MetadataSymbol::DataSegmentsLen,
+        MetadataSymbol::SparsePageData,
+        MetadataSymbol::Rodata,
+        MetadataSymbol::RodataSpec,
+        MetadataSymbol::DataSnapshot,
+        MetadataSymbol::DataSnapshotLen,
+        MetadataSymbol::Elements,
+        MetadataSymbol::ElementsLen,
+        MetadataSymbol::TrapManifest,
+        MetadataSymbol::TrapManifestLen,
+        MetadataSymbol::TrapMessages,
+        MetadataSymbol::TrapMessagesLen,
+        MetadataSymbol::Start,
+        MetadataSymbol::HeapBase,
+        MetadataSymbol::WasiAbi,
+        MetadataSymbol::WasiEntry,
+        MetadataSymbol::VmctxLayout,
+        MetadataSymbol::UserMetadata,
+        MetadataSymbol::FpTraps,
+    ];
+
+    /// The name lucet-runtime looks the symbol up by.
+    pub fn default_name(&self) -> &'static str {
+        match self {
+            MetadataSymbol::HeapSpec => "lucet_heap_spec",
+            MetadataSymbol::GlobalsSpec => "lucet_globals_spec",
+            MetadataSymbol::ModuleData => "lucet_module_data",
+            MetadataSymbol::ModuleDataLen => "lucet_module_data_len",
+            MetadataSymbol::DataSegments => "wasm_data_segments",
+            MetadataSymbol::DataSegmentsLen => "wasm_data_segments_len",
+            MetadataSymbol::SparsePageData => "guest_sparse_page_data",
+            MetadataSymbol::Rodata => "lucet_rodata",
+            MetadataSymbol::RodataSpec => "lucet_rodata_spec",
+            MetadataSymbol::DataSnapshot => "lucet_data_snapshot",
+            MetadataSymbol::DataSnapshotLen => "lucet_data_snapshot_len",
+            MetadataSymbol::Elements => "lucet_elements",
+            MetadataSymbol::ElementsLen => "lucet_elements_len",
+            MetadataSymbol::TrapManifest => "lucet_trap_manifest",
+            MetadataSymbol::TrapManifestLen => "lucet_trap_manifest_len",
+            MetadataSymbol::TrapMessages => "lucet_trap_messages",
+            MetadataSymbol::TrapMessagesLen => "lucet_trap_messages_len",
+            MetadataSymbol::Start => "guest_start",
+            MetadataSymbol::HeapBase => HEAP_BASE_SYM,
+            MetadataSymbol::WasiAbi => WASI_ABI_SYM,
+            MetadataSymbol::WasiEntry => WASI_ENTRY_SYM,
+            MetadataSymbol::VmctxLayout => "lucet_vmctx_layout",
+            MetadataSymbol::UserMetadata => "lucet_user_metadata",
+            MetadataSymbol::FpTraps => "lucet_fp_traps",
+        }
+    }
+}
+
+/// Chooses the name of each metadata symbol. Every symbol must get a distinct name.
+pub trait SymbolNaming {
+    fn name(&self, symbol: MetadataSymbol) -> String;
+}
+
+/// The names lucet-runtime expects.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultNaming;
+
+impl SymbolNaming for DefaultNaming {
+    fn name(&self, symbol: MetadataSymbol) -> String {
+        symbol.default_name().to_owned()
+    }
+}
+
+/// The naming policy a compilation uses, `DefaultNaming` unless another is given.
+#[derive(Clone)]
+pub struct Naming(Rc<SymbolNaming>);
+
+impl Naming {
+    pub fn new<N: SymbolNaming + 'static>(policy: N) -> Self {
+        Naming(Rc::new(policy))
+    }
+
+    pub fn name(&self, symbol: MetadataSymbol) -> String {
+        self.0.name(symbol)
+    }
+
+    /// The name of every metadata symbol, in `MetadataSymbol::ALL` order.
+    pub fn names(&self) -> Vec<String> {
+        MetadataSymbol::ALL.iter().map(|s| self.name(*s)).collect()
+    }
+}
+
+impl Default for Naming {
+    fn default() -> Self {
+        Naming::new(DefaultNaming)
+    }
+}
+
+impl fmt::Debug for Naming {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Naming").field(&self.names()).finish()
+    }
+}
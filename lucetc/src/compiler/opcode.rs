@@ -3,7 +3,9 @@
 
 use crate::compiler::entity::{EntityCreator, NATIVE_POINTER, POINTER_SIZE};
 use crate::compiler::state::{ControlVariant, TranslationState};
-use crate::compiler::Compiler;
+use crate::compiler::traps::SIGNED_OVERFLOW;
+use crate::compiler::{import_cell_symbol, Compiler};
+use crate::error::LucetcErrorKind;
 use crate::program::types::cton_valuetype;
 use crate::program::CtonSignature;
 use cranelift_codegen::ir::condcodes::{FloatCC, IntCC};
@@ -11,16 +13,17 @@ use cranelift_codegen::ir::types::{F32, F64, I32, I64};
 use cranelift_codegen::ir::{self, InstBuilder, JumpTableData, MemFlags};
 use cranelift_codegen::packed_option::ReservedValue;
 use cranelift_frontend::{FunctionBuilder, Variable};
+use cranelift_module::Backend;
 use failure::{format_err, Error};
 use parity_wasm::elements::{BlockType, Instruction};
 use std::{i32, u32};
 
-pub fn translate_opcode(
+pub fn translate_opcode<B: Backend>(
     op: &Instruction,
     builder: &mut FunctionBuilder,
     state: &mut TranslationState,
     entity_creator: &mut EntityCreator,
-    compiler: &Compiler,
+    compiler: &Compiler<B>,
 ) -> Result<(), Error> {
     if !state.reachable {
         return translate_unreachable_opcode(op, builder, state);
@@ -343,6 +346,39 @@ pub fn translate_opcode(
          * return values to it.
          ************************************ Calls ****************************************/
         Instruction::Call(callee_index) => {
+            let bound_with_addend = compiler
+                .prog
+                .import_functions()
+                .get(callee_index as usize)
+                .filter(|f| f.addend() != 0);
+            if let Some(import) = bound_with_addend {
+                // The callee is only known as an offset from a symbol, so load its address from
+                // the pointer `Compiler::define_import_cell` defined and call that.
+                let cell = compiler.get_data(&import_cell_symbol(import))?;
+                let cell = cell
+                    .into_dataid()
+                    .ok_or(format_err!("import cell {:?} is not data", cell))?;
+                let cell = compiler.module.declare_data_in_func(cell, builder.func);
+                let cell_addr = builder.ins().global_value(NATIVE_POINTER, cell);
+                let callee = builder
+                    .ins()
+                    .load(NATIVE_POINTER, MemFlags::new(), cell_addr, 0);
+
+                let sig = import.signature();
+                let num_args = normal_args(&sig);
+                let call_args = with_vmctx(builder.func, state.peekn(num_args))?;
+                let sig_ref = builder.import_signature(sig);
+
+                let call = builder
+                    .cursor()
+                    .ins()
+                    .call_indirect(sig_ref, callee, &call_args);
+
+                state.dropn(num_args);
+                state.pushn(builder.inst_results(call));
+                return Ok(());
+            }
+
             let &(ref callee_ref, ref callee_func) =
                 entity_creator.get_direct_func(builder.func, callee_index, compiler)?;
 
@@ -365,6 +401,7 @@ pub fn translate_opcode(
             let num_args = normal_args(&fnsig.cton_signature());
 
             let callee = state.pop1();
+            let type_id = compiler.prog.type_id(type_index)?;
 
             // Indirect calls are performed by looking up the callee function and type in a table that
             // is present in the same object file.
@@ -381,13 +418,14 @@ pub fn translate_opcode(
                 // `callee` is an integer value that may represent a valid offset into the
                 // icall table.
                 let calleebound = table.elements().len();
-                // First see if the callee is even a valid index into the table.
+                // First see if the callee is even a valid index into the table. This gets its
+                // own trap code so the runtime can tell a bad index from a bad signature.
                 let inbounds = pos.ins().icmp_imm(
                     ir::condcodes::IntCC::UnsignedLessThan,
                     callee,
                     calleebound as i64,
                 );
-                pos.ins().trapz(inbounds, ir::TrapCode::IndirectCallToNull);
+                pos.ins().trapz(inbounds, ir::TrapCode::TableOutOfBounds);
 
                 let table_addr = pos.ins().global_value(NATIVE_POINTER, table_base);
                 let callee_64 = pos.ins().uextend(ir::Type::int(64).unwrap(), callee);
@@ -402,7 +440,9 @@ pub fn translate_opcode(
                 );
                 let valid_type =
                     pos.ins()
-                        .icmp_imm(ir::condcodes::IntCC::Equal, typ, type_index as i64);
+                        .icmp_imm(ir::condcodes::IntCC::Equal, typ, type_id as i64);
+                // Empty table entries have a type ID no signature has, so calling one is reported
+                // as a signature mismatch.
                 pos.ins().trapz(valid_type, ir::TrapCode::BadSignature);
                 // Get the function ptr from memory:
                 let func_addr = pos.ins().load(
@@ -427,7 +467,7 @@ pub fn translate_opcode(
         Instruction::GrowMemory(_reserved) => {
             let &(ref callee_ref, ref _callee_func) = entity_creator.get_runtime_func(
                 builder.func,
-                "lucet_vmctx_grow_memory".into(),
+                compiler.prog.grow_memory_function().symbol().into(),
                 compiler,
             )?;
 
@@ -671,6 +711,9 @@ pub fn translate_opcode(
             state.push1(builder.ins().f64const(f64_translation(value)));
         }
         /******************************* Unary Opcodes *************************************/
+        // Without `lzcnt`, `tzcnt`, or `popcnt`, which only the native ISA enables when the host
+        // has them, Cranelift legalizes these to `bsr`/`bsf` with a check for zero, or to a bit
+        // twiddling sequence. Either way a zero input counts the full bit width, as Wasm requires.
         Instruction::I32Clz | Instruction::I64Clz => {
             let arg = state.pop1();
             state.push1(builder.ins().clz(arg));
@@ -747,6 +790,9 @@ pub fn translate_opcode(
             let val = state.pop1();
             state.push1(builder.ins().fdemote(F32, val));
         }
+        // The trapping float-to-int conversions are legalized by Cranelift into a checked
+        // sequence: NaN inputs trap with `BadConversionToInteger`, and inputs outside the range of
+        // the result type trap with `IntegerOverflow`. Both trap sites end up in the trap manifest.
         Instruction::I64TruncSF64 | Instruction::I64TruncSF32 => {
             let val = state.pop1();
             state.push1(builder.ins().fcvt_to_sint(I64, val));
@@ -782,7 +828,15 @@ pub fn translate_opcode(
         /****************************** Binary Opcodes ************************************/
         Instruction::I32Add | Instruction::I64Add => {
             let (arg1, arg2) = state.pop2();
-            state.push1(builder.ins().iadd(arg1, arg2));
+            let sum = builder.ins().iadd(arg1, arg2);
+            if compiler.settings.trap_on_signed_overflow {
+                // The sum overflowed if it has a different sign than both operands.
+                let x = builder.ins().bxor(arg1, sum);
+                let y = builder.ins().bxor(arg2, sum);
+                let both = builder.ins().band(x, y);
+                trap_if_negative(both, builder);
+            }
+            state.push1(sum);
         }
         Instruction::I32And | Instruction::I64And => {
             let (arg1, arg2) = state.pop2();
@@ -808,6 +862,7 @@ pub fn translate_opcode(
             let (arg1, arg2) = state.pop2();
             state.push1(builder.ins().ushr(arg1, arg2));
         }
+        // x86 masks the rotate amount to the operand width, which is Wasm's modulo semantics.
         Instruction::I32Rotl | Instruction::I64Rotl => {
             let (arg1, arg2) = state.pop2();
             state.push1(builder.ins().rotl(arg1, arg2));
@@ -822,7 +877,16 @@ pub fn translate_opcode(
         }
         Instruction::I32Sub | Instruction::I64Sub => {
             let (arg1, arg2) = state.pop2();
-            state.push1(builder.ins().isub(arg1, arg2));
+            let difference = builder.ins().isub(arg1, arg2);
+            if compiler.settings.trap_on_signed_overflow {
+                // The difference overflowed if the operands have different signs and it has a
+                // different sign than the first.
+                let x = builder.ins().bxor(arg1, arg2);
+                let y = builder.ins().bxor(arg1, difference);
+                let both = builder.ins().band(x, y);
+                trap_if_negative(both, builder);
+            }
+            state.push1(difference);
         }
         Instruction::F32Sub | Instruction::F64Sub => {
             let (arg1, arg2) = state.pop2();
@@ -830,7 +894,26 @@ pub fn translate_opcode(
         }
         Instruction::I32Mul | Instruction::I64Mul => {
             let (arg1, arg2) = state.pop2();
-            state.push1(builder.ins().imul(arg1, arg2));
+            let product = builder.ins().imul(arg1, arg2);
+            if compiler.settings.trap_on_signed_overflow {
+                // The product fits if the high half of the full product is just the sign
+                // extension of the low half.
+                let ty = builder.func.dfg.value_type(product);
+                let (high, sign) = if ty == I32 {
+                    let wide1 = builder.ins().sextend(I64, arg1);
+                    let wide2 = builder.ins().sextend(I64, arg2);
+                    let wide = builder.ins().imul(wide1, wide2);
+                    let high = builder.ins().sshr_imm(wide, 32);
+                    let high = builder.ins().ireduce(I32, high);
+                    (high, builder.ins().sshr_imm(product, 31))
+                } else {
+                    let high = builder.ins().smulhi(arg1, arg2);
+                    (high, builder.ins().sshr_imm(product, 63))
+                };
+                let overflowed = builder.ins().icmp(IntCC::NotEqual, high, sign);
+                builder.ins().trapnz(overflowed, SIGNED_OVERFLOW);
+            }
+            state.push1(product);
         }
         Instruction::F32Mul | Instruction::F64Mul => {
             let (arg1, arg2) = state.pop2();
@@ -960,7 +1043,11 @@ pub fn translate_opcode(
             let val = builder.ins().fcmp(FloatCC::LessThanOrEqual, arg1, arg2);
             state.push1(builder.ins().bint(I32, val));
         }
-        _ => panic!("Unimplemented opcode: {:?}", *op),
+        _ => Err(
+            format_err!("cannot translate {:?}", *op).context(LucetcErrorKind::Unsupported(
+                format!("instruction {:?}", *op),
+            )),
+        )?,
     }
 
     Ok(())
@@ -1064,19 +1151,27 @@ fn num_return_values(bt: &BlockType) -> usize {
 // Translate a load instruction.
 // XXX we could take the flags argument from the wasm load instruction,
 // and use that to generate a cton memflags.
-fn translate_load<'m>(
+fn translate_load<'m, B: Backend>(
     offset: u32,
     opcode: ir::Opcode,
     result_ty: ir::Type,
     builder: &mut FunctionBuilder,
     state: &mut TranslationState,
     entity_creator: &mut EntityCreator<'m>,
-    compiler: &Compiler,
+    compiler: &Compiler<B>,
 ) -> Result<(), Error> {
     let addr32 = state.pop1();
     // We don't yet support multiple linear memories.
     let heap = entity_creator.get_heap(builder.func, 0, compiler)?;
-    let (base, offset) = get_heap_addr(heap, addr32, offset, NATIVE_POINTER, builder);
+    let (base, offset) = get_heap_addr(
+        heap,
+        addr32,
+        offset,
+        result_ty.bytes(),
+        NATIVE_POINTER,
+        compiler.settings.elide_bounds_checks,
+        builder,
+    );
     let flags = MemFlags::new();
     let (load, dfg) = builder
         .ins()
@@ -1088,20 +1183,28 @@ fn translate_load<'m>(
 // Translate a store instruction.
 // XXX we could take the flags argument from the wasm store instruction,
 // and use that to generate a cton memflags.
-fn translate_store<'m>(
+fn translate_store<'m, B: Backend>(
     offset: u32,
     opcode: ir::Opcode,
     builder: &mut FunctionBuilder,
     state: &mut TranslationState,
     entity_creator: &mut EntityCreator<'m>,
-    compiler: &Compiler,
+    compiler: &Compiler<B>,
 ) -> Result<(), Error> {
     let (addr32, val) = state.pop2();
     let val_ty = builder.func.dfg.value_type(val);
 
     // We don't yet support multiple linear memories.
     let heap = entity_creator.get_heap(builder.func, 0, compiler)?;
-    let (base, offset) = get_heap_addr(heap, addr32, offset, NATIVE_POINTER, builder);
+    let (base, offset) = get_heap_addr(
+        heap,
+        addr32,
+        offset,
+        val_ty.bytes(),
+        NATIVE_POINTER,
+        compiler.settings.elide_bounds_checks,
+        builder,
+    );
     let flags = MemFlags::new();
     builder
         .ins()
@@ -1109,31 +1212,51 @@ fn translate_store<'m>(
     Ok(())
 }
 
-// Get the address+offset to use for a heap access.
+// Get the address+offset to use for a heap access. `access_size` is at least the number of
+// bytes accessed.
 fn get_heap_addr(
     heap: ir::Heap,
     addr32: ir::Value,
     offset: u32,
+    access_size: u32,
     addr_ty: ir::Type,
+    elide_bounds_checks: bool,
     builder: &mut FunctionBuilder,
 ) -> (ir::Value, i32) {
     use std::cmp::min;
 
     let guard_size: u64 = builder.func.heaps[heap].offset_guard_size.into();
-    assert!(guard_size > 0, "Heap guard pages currently required");
 
-    // Generate `heap_addr` instructions that are friendly to CSE by checking offsets that are
-    // multiples of the guard size. Add one to make sure that we check the pointer itself is in
-    // bounds.
-    //
-    // For accesses on the outer skirts of the guard pages, we expect that we get a trap
-    // even if the access goes beyond the guard pages. This is because the first byte pointed to is
-    // inside the guard pages.
-    let check_size = min(
-        u32::MAX as u64,
-        1 + (offset as u64 / guard_size) * guard_size,
-    ) as u32;
-    let base = builder.ins().heap_addr(addr_ty, heap, addr32, check_size);
+    let base = match safe_constant_index(heap, addr32, offset, access_size, builder) {
+        Some(index) if elide_bounds_checks => {
+            // The whole access is inside the reserved region, which is as far as the check
+            // would have allowed it anyway.
+            let heap_base = builder.func.heaps[heap].base;
+            let heap_base = builder.ins().global_value(addr_ty, heap_base);
+            builder.ins().iadd_imm(heap_base, i64::from(index))
+        }
+        _ if guard_size == 0 => {
+            // Nothing past the reserved region faults, so the check covers every byte of the
+            // access. A size that doesn't fit in 32 bits is still rejected, since a heap without
+            // guard pages is always smaller than 4GiB.
+            let check_size = min(u32::MAX as u64, offset as u64 + access_size as u64) as u32;
+            builder.ins().heap_addr(addr_ty, heap, addr32, check_size)
+        }
+        _ => {
+            // Generate `heap_addr` instructions that are friendly to CSE by checking offsets
+            // that are multiples of the guard size. Add one to make sure that we check the
+            // pointer itself is in bounds.
+            //
+            // For accesses on the outer skirts of the guard pages, we expect that we get a trap
+            // even if the access goes beyond the guard pages. This is because the first byte
+            // pointed to is inside the guard pages.
+            let check_size = min(
+                u32::MAX as u64,
+                1 + (offset as u64 / guard_size) * guard_size,
+            ) as u32;
+            builder.ins().heap_addr(addr_ty, heap, addr32, check_size)
+        }
+    };
 
     // Native load/store instructions take a signed `Offset32` immediate, so adjust the base
     // pointer if necessary.
@@ -1146,6 +1269,47 @@ fn get_heap_addr(
     }
 }
 
+/// Trap with `SIGNED_OVERFLOW` if `value`'s sign bit is set.
+fn trap_if_negative(value: ir::Value, builder: &mut FunctionBuilder) {
+    let negative = builder.ins().icmp_imm(IntCC::SignedLessThan, value, 0);
+    builder.ins().trapnz(negative, SIGNED_OVERFLOW);
+}
+
+// The heap index of an access, if it is a constant and every byte the access could touch is
+// within the heap's reserved region.
+fn safe_constant_index(
+    heap: ir::Heap,
+    addr32: ir::Value,
+    offset: u32,
+    access_size: u32,
+    builder: &FunctionBuilder,
+) -> Option<u32> {
+    let dfg = &builder.func.dfg;
+    let index = match dfg.value_def(addr32) {
+        ir::ValueDef::Result(inst, _) => match dfg[inst] {
+            ir::InstructionData::UnaryImm {
+                opcode: ir::Opcode::Iconst,
+                imm,
+            } => {
+                let imm: i64 = imm.into();
+                imm as u32
+            }
+            _ => return None,
+        },
+        _ => return None,
+    };
+    let bound: u64 = match builder.func.heaps[heap].style {
+        ir::HeapStyle::Static { bound } => bound.into(),
+        _ => return None,
+    };
+    let end = index as u64 + offset as u64 + access_size as u64;
+    if end <= bound {
+        Some(index)
+    } else {
+        None
+    }
+}
+
 fn f32_translation(x: u32) -> ir::immediates::Ieee32 {
     ir::immediates::Ieee32::with_bits(x)
 }
@@ -0,0 +1,110 @@
+//! Compiling a single translated function again with different settings, to compare the code
+//! each produces. This is a tool for tuning hot functions; the result isn't linked into anything.
+
+use super::{isa, Compiler, OptLevel};
+use cranelift_codegen::binemit::{Addend, CodeOffset, Reloc, RelocSink, TrapSink};
+use cranelift_codegen::ir::{self, ExternalName, JumpTable, SourceLoc, TrapCode};
+use cranelift_codegen::print_errors::pretty_error;
+use cranelift_codegen::Context;
+use cranelift_module::{Backend, Linkage};
+use failure::{format_err, Error};
+
+/// Machine code for one function, as `Compiler::recompile_function` generated it.
+#[derive(Debug, Clone)]
+pub struct CompiledFunc {
+    pub symbol: String,
+    pub opt_level: OptLevel,
+    /// The function's code, with relocations left unapplied.
+    pub code: Vec<u8>,
+    pub frame_size: u32,
+    /// Offsets into `code` that refer to other functions or data.
+    pub relocations: Vec<CodeOffset>,
+    /// Offsets into `code` of instructions that can trap, with the trap each raises.
+    pub traps: Vec<(CodeOffset, TrapCode)>,
+}
+
+impl CompiledFunc {
+    pub fn code_size(&self) -> usize {
+        self.code.len()
+    }
+}
+
+struct Relocations(Vec<CodeOffset>);
+
+impl RelocSink for Relocations {
+    fn reloc_ebb(&mut self, _offset: CodeOffset, _reloc: Reloc, _ebb_offset: CodeOffset) {
+        // Branches within the function are resolved as the code is emitted.
+    }
+
+    fn reloc_external(
+        &mut self,
+        offset: CodeOffset,
+        _reloc: Reloc,
+        _name: &ExternalName,
+        _addend: Addend,
+    ) {
+        self.0.push(offset);
+    }
+
+    fn reloc_jt(&mut self, offset: CodeOffset, _reloc: Reloc, _jt: JumpTable) {
+        self.0.push(offset);
+    }
+}
+
+struct Traps(Vec<(CodeOffset, TrapCode)>);
+
+impl TrapSink for Traps {
+    fn trap(&mut self, offset: CodeOffset, _srcloc: SourceLoc, code: TrapCode) {
+        self.0.push((offset, code));
+    }
+}
+
+impl<'p, B: Backend> Compiler<'p, B> {
+    /// Generate code for the defined function with wasm index `index` at `opt_level`, keeping
+    /// every other setting the module was compiled with.
+    ///
+    /// The function must already be translated, and the compiler is left as it was, so this can be
+    /// called before or after `define_functions`, as often as needed.
+    pub fn recompile_function(
+        &self,
+        index: u32,
+        opt_level: OptLevel,
+    ) -> Result<CompiledFunc, Error> {
+        let func = self.prog.get_function(index)?;
+        if func.linkage() == Linkage::Import {
+            return Err(format_err!(
+                "function {} is imported, so there is no code to recompile",
+                index
+            ));
+        }
+        let name = self.get_function(func)?;
+        let translated: &ir::Function = self
+            .funcs
+            .get(&name)
+            .ok_or(format_err!("function {} has not been translated", index))?;
+
+        let mut settings = self.settings.clone();
+        settings.opt_level = opt_level;
+        let isa = isa(&settings)?;
+
+        let mut ctx = Context::for_function(translated.clone());
+        let mut code = vec![];
+        let mut relocations = Relocations(vec![]);
+        let mut traps = Traps(vec![]);
+        ctx.compile_and_emit(isa.as_ref(), &mut code, &mut relocations, &mut traps)
+            .map_err(|e| {
+                format_err!(
+                    "code generation error:\n{}",
+                    pretty_error(translated, Some(isa.as_ref()), e)
+                )
+            })?;
+        Ok(CompiledFunc {
+            symbol: name.symbol().to_owned(),
+            opt_level,
+            code,
+            frame_size: ctx.func.stack_slots.frame_size.unwrap_or(0),
+            relocations: relocations.0,
+            traps: traps.0,
+        })
+    }
+}
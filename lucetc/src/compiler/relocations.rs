@@ -0,0 +1,225 @@
+//! Inspecting and rewriting the relocations of an emitted object.
+//!
+//! The object writer doesn't expose the relocations it produces, so they are read back out of the
+//! serialized ELF.
+
+use super::elf::{rename_symbols, Elf, SHN_UNDEF, SHT_SYMTAB, SYM_SIZE};
+use byteorder::{ByteOrder, LittleEndian};
+use failure::{bail, format_err, Error};
+use std::collections::HashMap;
+
+const SHT_RELA: u32 = 4;
+const RELA_SIZE: usize = 24;
+
+/// The kinds of relocation lucetc's x86-64 output uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelocKind {
+    /// `R_X86_64_64`: an absolute 8-byte address. Used by data that points at code or other data,
+    /// such as the indirect call table, the trap manifest, and `guest_start`.
+    Abs8,
+    /// `R_X86_64_PC32`: a 4-byte PC-relative offset. Used for calls between functions defined in
+    /// the module and for references to the module's own data.
+    PcRel4,
+    /// `R_X86_64_PLT32`: a 4-byte PC-relative offset to a PLT entry. Used for calls to imported
+    /// host functions and to the runtime's heap helpers.
+    PltRel4,
+    /// `R_X86_64_GOTPCREL`: a 4-byte PC-relative offset to a GOT entry. Used for addresses of
+    /// data defined outside the module.
+    GotPcRel4,
+    /// Any other ELF relocation type.
+    Other(u32),
+}
+
+impl RelocKind {
+    fn from_elf(r_type: u32) -> Self {
+        match r_type {
+            1 => RelocKind::Abs8,
+            2 => RelocKind::PcRel4,
+            4 => RelocKind::PltRel4,
+            9 => RelocKind::GotPcRel4,
+            other => RelocKind::Other(other),
+        }
+    }
+
+    fn to_elf(&self) -> u32 {
+        match self {
+            RelocKind::Abs8 => 1,
+            RelocKind::PcRel4 => 2,
+            RelocKind::PltRel4 => 4,
+            RelocKind::GotPcRel4 => 9,
+            RelocKind::Other(other) => *other,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Relocation {
+    /// The section being patched, e.g. `.text.guest_func_main`.
+    pub section: String,
+    /// Offset of the patched bytes within `section`.
+    pub offset: u64,
+    /// The symbol whose address is being taken. References to a section rather than a symbol use
+    /// the section's name.
+    pub symbol: String,
+    pub kind: RelocKind,
+    pub addend: i64,
+}
+
+/// A relocation along with where its entry lives in the object.
+pub(super) struct Entry {
+    /// Byte position of the `Elf64_Rela` entry.
+    pub position: usize,
+    /// Index of the symbol table section the entry refers into.
+    pub symtab: usize,
+    /// Index of the section being patched.
+    pub target: usize,
+    /// Index of the symbol within `symtab`.
+    pub symbol: usize,
+    pub reloc: Relocation,
+}
+
+pub(super) fn entries(elf: &Elf) -> Result<Vec<Entry>, Error> {
+    let mut entries = vec![];
+    for (index, s) in elf.sections.iter().enumerate() {
+        if s.kind != SHT_RELA {
+            continue;
+        }
+        let symtab = s.link as usize;
+        if elf.sections.get(symtab).map(|t| t.kind) != Some(SHT_SYMTAB) {
+            bail!("relocation section does not refer to a symbol table");
+        }
+        let section = elf.section_name(s.info as usize)?;
+        let data = elf.section_data(index)?;
+        for (i, rela) in data.chunks_exact(RELA_SIZE).enumerate() {
+            let position = s.offset + i * RELA_SIZE;
+            let info = LittleEndian::read_u64(&rela[8..]);
+            entries.push(Entry {
+                position,
+                symtab,
+                target: s.info as usize,
+                symbol: (info >> 32) as usize,
+                reloc: Relocation {
+                    section: section.clone(),
+                    offset: LittleEndian::read_u64(&rela[0..]),
+                    symbol: elf.symbol_name(symtab, (info >> 32) as usize)?,
+                    kind: RelocKind::from_elf(info as u32),
+                    addend: LittleEndian::read_i64(&rela[16..]),
+                },
+            });
+        }
+    }
+    Ok(entries)
+}
+
+/// Every relocation in the serialized object `obj`.
+pub fn relocations(obj: &[u8]) -> Result<Vec<Relocation>, Error> {
+    Ok(entries(&Elf::parse(obj)?)?
+        .into_iter()
+        .map(|e| e.reloc)
+        .collect())
+}
+
+/// Pass every relocation in `obj` through `rewrite`, and patch the object with the result.
+///
+/// The kind, addend, and symbol of a relocation may be changed, but a new symbol must already be
+/// in the object's symbol table. The section and offset identify the relocation and must be left
+/// alone.
+pub fn rewrite_relocations<F>(obj: &mut [u8], mut rewrite: F) -> Result<(), Error>
+where
+    F: FnMut(&mut Relocation),
+{
+    let mut patches = vec![];
+    {
+        let elf = Elf::parse(obj)?;
+        for entry in entries(&elf)? {
+            let mut reloc = entry.reloc.clone();
+            rewrite(&mut reloc);
+            if reloc == entry.reloc {
+                continue;
+            }
+            if reloc.section != entry.reloc.section || reloc.offset != entry.reloc.offset {
+                bail!(
+                    "relocation at {}+{:#x} cannot be moved",
+                    entry.reloc.section,
+                    entry.reloc.offset
+                );
+            }
+            let info = &obj[entry.position + 8..];
+            let mut sym = LittleEndian::read_u64(info) >> 32;
+            if reloc.symbol != entry.reloc.symbol {
+                sym = elf
+                    .symbol_index(entry.symtab, &reloc.symbol)?
+                    .ok_or(format_err!(
+                        "relocation target {} is not in the symbol table",
+                        reloc.symbol
+                    ))? as u64;
+            }
+            patches.push((
+                entry.position,
+                (sym << 32) | reloc.kind.to_elf() as u64,
+                reloc.addend,
+            ));
+        }
+    }
+    for (position, info, addend) in patches {
+        LittleEndian::write_u64(&mut obj[position + 8..], info);
+        LittleEndian::write_i64(&mut obj[position + 16..], addend);
+    }
+    Ok(())
+}
+
+/// Make the references in `obj` to each undefined symbol named by a key of `renames` refer to
+/// the symbol named by its value instead, as if the object had been compiled against those
+/// names. An undefined symbol is renamed in place, unless another symbol already has or is
+/// getting its new name, in which case its relocations are moved to that symbol. Names the
+/// object doesn't import are ignored.
+pub fn rebind_symbols(obj: &[u8], renames: &HashMap<String, String>) -> Result<Vec<u8>, Error> {
+    let (redirects, in_place) = {
+        let elf = Elf::parse(obj)?;
+        let symtab = elf
+            .sections
+            .iter()
+            .position(|s| s.kind == SHT_SYMTAB)
+            .ok_or(format_err!("object has no symbol table"))?;
+        let data = elf.section_data(symtab)?;
+        let mut symbols = vec![];
+        for index in 1..data.len() / SYM_SIZE {
+            let sym = &data[index * SYM_SIZE..(index + 1) * SYM_SIZE];
+            let undefined = LittleEndian::read_u16(&sym[6..]) == SHN_UNDEF;
+            let name = elf.symbol_name(symtab, index)?;
+            let new_name = match renames.get(&name) {
+                Some(new_name) if undefined => Some(new_name.clone()),
+                _ => None,
+            };
+            symbols.push((index, name, new_name));
+        }
+
+        // The first symbol to end up with each name keeps it; the others' references move there.
+        let mut owners: HashMap<&str, &str> = HashMap::new();
+        for (_, name, new_name) in symbols.iter() {
+            let final_name = new_name.as_ref().unwrap_or(name);
+            owners.entry(final_name.as_str()).or_insert(name.as_str());
+        }
+        let mut redirects = HashMap::new();
+        let mut in_place = vec![];
+        for (index, name, new_name) in symbols.iter() {
+            if let Some(new_name) = new_name {
+                let owner = owners[new_name.as_str()];
+                if owner == name.as_str() {
+                    in_place.push((*index, new_name.clone()));
+                } else {
+                    redirects.insert(name.clone(), owner.to_owned());
+                }
+            }
+        }
+        (redirects, in_place)
+    };
+
+    let mut obj = obj.to_vec();
+    rewrite_relocations(&mut obj, |reloc| {
+        if let Some(owner) = redirects.get(&reloc.symbol) {
+            reloc.symbol = owner.clone();
+        }
+    })?;
+    rename_symbols(&obj, &in_place)
+}
@@ -1,11 +1,15 @@
+use crate::compiler::naming::MetadataSymbol;
 use crate::compiler::Compiler;
 use crate::program::table::{TableDef, TableElem};
 use byteorder::{LittleEndian, WriteBytesExt};
-use cranelift_module::DataContext;
+use cranelift_module::{Backend, DataContext, Linkage};
 use failure::{format_err, Error, ResultExt};
 use std::io::Cursor;
 
-pub fn compile_table<'p>(compiler: &mut Compiler<'p>, table: &TableDef) -> Result<(), Error> {
+pub fn compile_table<'p, B: Backend>(
+    compiler: &mut Compiler<'p, B>,
+    table: &TableDef,
+) -> Result<(), Error> {
     // Indirect calls are performed by looking up the callee function and type in a table that
     // is present in the same object file.
     // The table is an array of pairs of (type index, function pointer). Both elements in the
@@ -35,16 +39,32 @@ pub fn compile_table<'p>(compiler: &mut Compiler<'p>, table: &TableDef) -> Resul
                     .signature_index()
                     .ok_or(format_err!("table function should have a signature index"))?;
 
-                // First element in row is the SignatureIndex for the function
-                putelem(&mut table_data, sig_ix as u64);
+                // First element in row is the signature ID for the function
+                let type_id = compiler.prog.type_id(sig_ix)?;
+                putelem(&mut table_data, type_id as u64);
 
                 // Second element in row is the pointer to the function. The Reloc is doing the work
                 // here. We put a 0 in the table data itself to be overwritten at link time.
-                let funcname = compiler.get_function(func)?;
-                let funcref = table_ctx.import_function(funcname.into());
                 let position = table_data.position();
                 assert!(position < <u32>::max_value() as u64);
-                table_ctx.write_function_addr(position as u32, funcref);
+                let addend = compiler
+                    .prog
+                    .import_functions()
+                    .get(*func_index as usize)
+                    .map(|import| import.addend())
+                    .unwrap_or(0);
+                if addend == 0 {
+                    let funcname = compiler.get_function(func)?;
+                    let funcref = table_ctx.import_function(funcname.into());
+                    table_ctx.write_function_addr(position as u32, funcref);
+                } else {
+                    // Bound with an addend, so the symbol is declared as data. See
+                    // `Compiler::define_import_cell`.
+                    let target = compiler.get_data(func.symbol())?;
+                    let target = table_ctx.import_global_value(target.into());
+                    table_ctx.write_data_addr(position as u32, target, 0);
+                    compiler.add_addend(&table.symbol(), position, addend);
+                }
                 putelem(&mut table_data, 0);
             }
             &TableElem::Empty => {
@@ -65,3 +85,58 @@ pub fn compile_table<'p>(compiler: &mut Compiler<'p>, table: &TableDef) -> Resul
     compiler.module.define_data(table_id, &table_ctx)?;
     Ok(())
 }
+
+/// Serialize the module's element segments to the `lucet_elements` symbol, and the number of
+/// bytes stored there to `lucet_elements_len`.
+///
+/// Active segments are already applied to the tables by `compile_table`; this is for a runtime
+/// that needs to know where table entries came from, or that implements `table.init`.
+///
+/// Each segment is stored as a u32 table index, a u32 that is 1 for a passive segment and 0
+/// otherwise, the u32 offset (0 for passive segments), the u32 number of functions, and then the
+/// u32 index of each function, padded to 8 bytes.
+pub fn compile_elements<'p, B: Backend>(compiler: &mut Compiler<'p, B>) -> Result<(), Error> {
+    let mut serialized: Vec<u8> = Vec::new();
+    for element in compiler.prog.element_initializers()? {
+        serialized
+            .write_u32::<LittleEndian>(element.table_index)
+            .unwrap();
+        serialized
+            .write_u32::<LittleEndian>(element.offset.is_none() as u32)
+            .unwrap();
+        serialized
+            .write_u32::<LittleEndian>(element.offset.unwrap_or(0) as u32)
+            .unwrap();
+        serialized
+            .write_u32::<LittleEndian>(element.functions.len() as u32)
+            .unwrap();
+        for func_index in element.functions {
+            serialized.write_u32::<LittleEndian>(*func_index).unwrap();
+        }
+        let pad = vec![0u8; (8 - serialized.len() % 8) % 8];
+        serialized.extend(pad);
+    }
+
+    let mut serialized_len: Vec<u8> = Vec::new();
+    serialized_len
+        .write_u32::<LittleEndian>(serialized.len() as u32)
+        .unwrap();
+    let mut len_ctx = DataContext::new();
+    len_ctx.define(serialized_len.into_boxed_slice());
+    let len_decl = compiler.module.declare_data(
+        &compiler.metadata_symbol(MetadataSymbol::ElementsLen),
+        Linkage::Export,
+        false,
+    )?;
+    compiler.module.define_data(len_decl, &len_ctx)?;
+
+    let mut ctx = DataContext::new();
+    ctx.define(serialized.into_boxed_slice());
+    let decl = compiler.module.declare_data(
+        &compiler.metadata_symbol(MetadataSymbol::Elements),
+        Linkage::Export,
+        false,
+    )?;
+    compiler.module.define_data(decl, &ctx)?;
+    Ok(())
+}
@@ -1,22 +1,47 @@
+use crate::compiler::naming::{MetadataSymbol, Naming};
 use cranelift_codegen::ir;
 use cranelift_faerie::traps::FaerieTrapManifest;
 
 use byteorder::{LittleEndian, WriteBytesExt};
 use faerie::{Artifact, Decl, Link};
 use failure::{Error, ResultExt};
-use std::io::Cursor;
+use std::collections::{HashMap, HashSet};
+use std::io::{Cursor, Write};
+
+/// Raised by the stub of a function that was not compiled because it uses something lucetc
+/// doesn't support. See `UnsupportedFunctionPolicy::Stub`.
+pub const UNSUPPORTED_FUNCTION: ir::TrapCode = ir::TrapCode::User(1);
+
+/// Raised by a signed `add`, `sub`, or `mul` whose result doesn't fit, when compiled with
+/// `CodegenSettings::trap_on_signed_overflow`.
+pub const SIGNED_OVERFLOW: ir::TrapCode = ir::TrapCode::User(2);
+
+/// Write `lucet_trap_manifest` and the trap table of each function it points to. The tables are
+/// only given global symbols if `export_tables` is set. The functions in `omit` get neither a
+/// row nor a table, so nothing in the manifest keeps their sections alive.
+pub fn write_trap_manifest(
+    manifest: &FaerieTrapManifest,
+    obj: &mut Artifact,
+    export_tables: bool,
+    omit: &HashSet<String>,
+    naming: &Naming,
+) -> Result<(), Error> {
+    let sinks: Vec<_> = manifest
+        .sinks
+        .iter()
+        .filter(|sink| !omit.contains(&sink.name))
+        .collect();
 
-pub fn write_trap_manifest(manifest: &FaerieTrapManifest, obj: &mut Artifact) -> Result<(), Error> {
     // declare traptable symbol
-    let manifest_len_sym = "lucet_trap_manifest_len";
+    let manifest_len_sym = naming.name(MetadataSymbol::TrapManifestLen);
     obj.declare(&manifest_len_sym, Decl::data().global())
         .context(format!("declaring {}", &manifest_len_sym))?;
 
-    let manifest_sym = "lucet_trap_manifest";
+    let manifest_sym = naming.name(MetadataSymbol::TrapManifest);
     obj.declare(&manifest_sym, Decl::data().global())
         .context(format!("declaring {}", &manifest_sym))?;
 
-    let manifest_len = manifest.sinks.len();
+    let manifest_len = sinks.len();
     let mut manifest_len_buf: Vec<u8> = Vec::new();
     manifest_len_buf
         .write_u32::<LittleEndian>(manifest_len as u32)
@@ -30,12 +55,17 @@ pub fn write_trap_manifest(manifest: &FaerieTrapManifest, obj: &mut Artifact) ->
     let mut manifest_buf: Cursor<Vec<u8>> =
         Cursor::new(Vec::with_capacity(manifest_len * manifest_row_size));
 
-    for sink in manifest.sinks.iter() {
+    for sink in sinks {
         let func_sym = &sink.name;
         let trap_sym = trap_sym_for_func(func_sym);
 
         // declare function-level trap table
-        obj.declare(&trap_sym, Decl::data().global())
+        let trap_decl = if export_tables {
+            Decl::data().global()
+        } else {
+            Decl::data()
+        };
+        obj.declare(&trap_sym, trap_decl)
             .context(format!("declaring {}", &trap_sym))?;
 
         // function symbol is provided via a link (abs8 relocation)
@@ -98,6 +128,63 @@ pub fn write_trap_manifest(manifest: &FaerieTrapManifest, obj: &mut Artifact) ->
     Ok(())
 }
 
+/// Write `lucet_trap_messages`, pairing each `unreachable` trap site with the message of its
+/// function in `messages`, keyed by symbol, so the runtime can say more than the trap code.
+/// `lucet_trap_messages_len` holds the number of rows, as a u32. Each row is
+/// `{ func_start: ptr, offset: u32, message_len: u32 }` followed by the UTF-8 message, padded
+/// with zeros to a multiple of 8 bytes. `offset` is from the start of the function, as in the
+/// function's trap table. The functions in `omit` get no rows.
+pub fn write_trap_messages(
+    manifest: &FaerieTrapManifest,
+    obj: &mut Artifact,
+    omit: &HashSet<String>,
+    messages: &HashMap<String, String>,
+    naming: &Naming,
+) -> Result<(), Error> {
+    let len_sym = naming.name(MetadataSymbol::TrapMessagesLen);
+    obj.declare(&len_sym, Decl::data().global())
+        .context(format!("declaring {}", &len_sym))?;
+    let messages_sym = naming.name(MetadataSymbol::TrapMessages);
+    obj.declare(&messages_sym, Decl::data().global())
+        .context(format!("declaring {}", &messages_sym))?;
+
+    let mut rows = 0;
+    let mut buf: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+    for sink in manifest.sinks.iter().filter(|s| !omit.contains(&s.name)) {
+        let message = match messages.get(&sink.name) {
+            Some(message) => message,
+            None => continue,
+        };
+        let sites = sink
+            .sites
+            .iter()
+            .filter(|site| site.code == ir::TrapCode::UnreachableCodeReached);
+        for site in sites {
+            obj.link(Link {
+                from: &messages_sym,
+                to: &sink.name,
+                at: buf.position(),
+            })
+            .context("linking function sym into trap messages")?;
+            buf.write_u64::<LittleEndian>(0).unwrap();
+            buf.write_u32::<LittleEndian>(site.offset as u32).unwrap();
+            buf.write_u32::<LittleEndian>(message.len() as u32).unwrap();
+            let mut bytes = message.as_bytes().to_vec();
+            bytes.resize((bytes.len() + 7) / 8 * 8, 0);
+            buf.write_all(&bytes).unwrap();
+            rows += 1;
+        }
+    }
+
+    let mut len_buf: Vec<u8> = Vec::new();
+    len_buf.write_u32::<LittleEndian>(rows).unwrap();
+    obj.define(&len_sym, len_buf)
+        .context(format!("defining {}", &len_sym))?;
+    obj.define(&messages_sym, buf.into_inner())
+        .context(format!("defining {}", &messages_sym))?;
+    Ok(())
+}
+
 fn trap_sym_for_func(sym: &str) -> String {
     return format!("lucet_trap_table_{}", sym);
 }
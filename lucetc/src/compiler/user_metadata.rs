@@ -0,0 +1,70 @@
+//! Key/value pairs given to lucetc, such as a source commit or build time, written to the
+//! `lucet_user_metadata` symbol so they can be read back from the object. They come from the
+//! caller, not the module; custom sections of the wasm are handled by `CustomSectionPolicy`.
+
+use crate::compiler::naming::MetadataSymbol;
+use crate::compiler::Compiler;
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use cranelift_module::{Backend, DataContext, Linkage};
+use failure::{format_err, Error};
+use std::io::{Cursor, Read};
+
+/// The pairs as stored in `lucet_user_metadata`: a u32 count, then for each pair the u32 length
+/// and UTF-8 bytes of the key and the u32 length and bytes of the value, all little-endian.
+pub fn serialize_user_metadata(pairs: &[(String, Vec<u8>)]) -> Vec<u8> {
+    let mut serialized = Vec::new();
+    serialized
+        .write_u32::<LittleEndian>(pairs.len() as u32)
+        .unwrap();
+    for (key, value) in pairs {
+        for field in &[key.as_bytes(), value.as_slice()] {
+            serialized
+                .write_u32::<LittleEndian>(field.len() as u32)
+                .unwrap();
+            serialized.extend_from_slice(field);
+        }
+    }
+    serialized
+}
+
+/// The pairs in the contents of a `lucet_user_metadata` symbol, in the order they were given.
+pub fn parse_user_metadata(bytes: &[u8]) -> Result<Vec<(String, Vec<u8>)>, Error> {
+    fn field(cursor: &mut Cursor<&[u8]>) -> Result<Vec<u8>, Error> {
+        let len = cursor.read_u32::<LittleEndian>()? as usize;
+        let remaining = cursor.get_ref().len() - cursor.position() as usize;
+        if len > remaining {
+            return Err(format_err!(
+                "user metadata field of {} bytes is truncated",
+                len
+            ));
+        }
+        let mut contents = vec![0; len];
+        cursor.read_exact(&mut contents)?;
+        Ok(contents)
+    }
+    let mut cursor = Cursor::new(bytes);
+    let count = cursor.read_u32::<LittleEndian>()?;
+    let mut pairs = Vec::new();
+    for _ in 0..count {
+        let key = String::from_utf8(field(&mut cursor)?)
+            .map_err(|_| format_err!("user metadata key is not UTF-8"))?;
+        let value = field(&mut cursor)?;
+        pairs.push((key, value));
+    }
+    if cursor.position() != bytes.len() as u64 {
+        return Err(format_err!("trailing bytes after user metadata"));
+    }
+    Ok(pairs)
+}
+
+pub fn compile_user_metadata<B: Backend>(compiler: &mut Compiler<B>) -> Result<(), Error> {
+    let mut ctx = DataContext::new();
+    ctx.define(serialize_user_metadata(&compiler.settings.user_metadata).into_boxed_slice());
+    let decl = compiler.module.declare_data(
+        &compiler.metadata_symbol(MetadataSymbol::UserMetadata),
+        Linkage::Export,
+        false,
+    )?;
+    compiler.module.define_data(decl, &ctx)?;
+    Ok(())
+}
@@ -0,0 +1,73 @@
+//! Where generated code finds the heap and globals relative to the vmctx pointer every function
+//! is passed. The runtime has to lay instances out to match, so the layout is also written to
+//! the `lucet_vmctx_layout` symbol.
+
+use crate::compiler::entity::POINTER_SIZE;
+use crate::compiler::naming::MetadataSymbol;
+use crate::compiler::{Compiler, HeapBase};
+use byteorder::{LittleEndian, WriteBytesExt};
+use cranelift_module::{Backend, DataContext, Linkage};
+use failure::Error;
+
+/// Offset of the start of the heap, with `HeapBase::Vmctx`.
+pub const HEAP_OFFSET: i32 = 0;
+/// Offset of the pointer to the globals, which sits directly before the heap.
+pub const GLOBALS_POINTER_OFFSET: i32 = -(POINTER_SIZE as i32);
+/// Bytes from the start of one global to the next. Each is stored at the start of its slot,
+/// whatever its type.
+pub const GLOBAL_STRIDE: u32 = POINTER_SIZE as u32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VmctxLayout {
+    /// Offset of the start of the heap, or `None` if the heap isn't found through the vmctx.
+    pub heap: Option<i32>,
+    pub globals_pointer: i32,
+    pub global_stride: u32,
+}
+
+impl VmctxLayout {
+    /// The layout code generated with `heap_base` relies on.
+    pub fn new(heap_base: &HeapBase) -> Self {
+        Self {
+            heap: match heap_base {
+                HeapBase::Vmctx => Some(HEAP_OFFSET),
+                HeapBase::FixedOffset(_) => None,
+            },
+            globals_pointer: GLOBALS_POINTER_OFFSET,
+            global_stride: GLOBAL_STRIDE,
+        }
+    }
+
+    /// The layout as stored in `lucet_vmctx_layout`: a u32 that is 1 if the heap is found through
+    /// the vmctx and 0 otherwise, then the i32 heap offset (0 when there is none), the i32
+    /// offset of the globals pointer, and the u32 global stride, all little-endian.
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut serialized = Vec::with_capacity(16);
+        serialized
+            .write_u32::<LittleEndian>(self.heap.is_some() as u32)
+            .unwrap();
+        serialized
+            .write_i32::<LittleEndian>(self.heap.unwrap_or(0))
+            .unwrap();
+        serialized
+            .write_i32::<LittleEndian>(self.globals_pointer)
+            .unwrap();
+        serialized
+            .write_u32::<LittleEndian>(self.global_stride)
+            .unwrap();
+        serialized
+    }
+}
+
+pub fn compile_vmctx_layout<B: Backend>(compiler: &mut Compiler<B>) -> Result<(), Error> {
+    let layout = VmctxLayout::new(&compiler.settings.heap_base);
+    let mut ctx = DataContext::new();
+    ctx.define(layout.serialize().into_boxed_slice());
+    let decl = compiler.module.declare_data(
+        &compiler.metadata_symbol(MetadataSymbol::VmctxLayout),
+        Linkage::Export,
+        false,
+    )?;
+    compiler.module.define_data(decl, &ctx)?;
+    Ok(())
+}
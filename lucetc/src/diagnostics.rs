@@ -0,0 +1,58 @@
+//! Warnings and notes about a compilation that don't stop it.
+
+use log::{info, warn};
+use std::fmt;
+use std::rc::Rc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// Something the user may want to know, such as a builtin that wasn't substituted.
+    Note,
+    /// A setting or part of the module that was ignored, or a fallback that was taken.
+    Warning,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// Where diagnostics are sent. Every diagnostic is also logged, so without a sink this behaves
+/// as lucetc always has.
+#[derive(Clone, Default)]
+pub struct Diagnostics {
+    sink: Option<Rc<Fn(Diagnostic)>>,
+}
+
+impl Diagnostics {
+    pub fn new<F: Fn(Diagnostic) + 'static>(sink: F) -> Self {
+        Self {
+            sink: Some(Rc::new(sink)),
+        }
+    }
+
+    pub fn note(&self, message: String) {
+        info!("{}", message);
+        self.emit(Severity::Note, message);
+    }
+
+    pub fn warn(&self, message: String) {
+        warn!("{}", message);
+        self.emit(Severity::Warning, message);
+    }
+
+    fn emit(&self, severity: Severity, message: String) {
+        if let Some(ref sink) = self.sink {
+            sink(Diagnostic { severity, message });
+        }
+    }
+}
+
+impl fmt::Debug for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Diagnostics")
+            .field("sink", &self.sink.is_some())
+            .finish()
+    }
+}
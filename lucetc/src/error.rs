@@ -62,6 +62,10 @@ pub enum LucetcErrorKind {
     MemorySpecs,
     #[fail(display = "Global specs")]
     GlobalSpecs,
+    #[fail(display = "Vmctx layout")]
+    VmctxLayout,
+    #[fail(display = "User metadata")]
+    UserMetadata,
     #[fail(display = "Module data")]
     ModuleData,
     #[fail(display = "Function {}", _0)]
@@ -71,6 +75,39 @@ pub enum LucetcErrorKind {
     #[fail(display = "Validation")]
     Validation,
 
+    #[fail(display = "Builtin substitution cycle: {}", _0)]
+    BuiltinCycle(String),
+
+    #[fail(
+        display = "Function {} has type {}, past the end of the type section",
+        func, index
+    )]
+    InvalidTypeIndex { func: u32, index: u32 },
+
+    #[fail(
+        display = "Data segment {} is placed at imported global {}, which isn't known until instantiation",
+        segment, global
+    )]
+    NonConstantSegmentOffset { segment: u32, global: u32 },
+
+    #[fail(
+        display = "Function {} declares {} locals, more than the limit",
+        func, count
+    )]
+    TooManyLocals { func: String, count: u64 },
+
+    #[fail(
+        display = "Linker {} not found: install binutils, or set `LD` or `LucetcOpts::linker` to the linker to use",
+        path
+    )]
+    LinkerNotFound { path: String },
+
+    #[fail(display = "Exported function {} has no symbol in the object", _0)]
+    MissingExportSymbol(String),
+
+    #[fail(display = "Compilation ran past its time budget")]
+    Cancelled,
+
     #[fail(display = "Unsupported: {}", _0)]
     Unsupported(String),
 
@@ -0,0 +1,419 @@
+//! Finding the WebAssembly proposals a module uses beyond the MVP.
+//!
+//! The decoder rejects most of the instructions and types this looks for, so the module binary
+//! is scanned before it is decoded. Nothing is validated: a module is reported as using a
+//! proposal if anything encoded the way only that proposal allows appears in it.
+
+use crate::load::{raw_sections, Reader};
+use failure::{bail, Error};
+use std::fmt;
+use std::ops::{BitOr, BitOrAssign};
+
+const TYPE_SECTION_ID: u8 = 1;
+const IMPORT_SECTION_ID: u8 = 2;
+const TABLE_SECTION_ID: u8 = 4;
+const MEMORY_SECTION_ID: u8 = 5;
+const GLOBAL_SECTION_ID: u8 = 6;
+const ELEMENT_SECTION_ID: u8 = 9;
+const CODE_SECTION_ID: u8 = 10;
+const DATA_SECTION_ID: u8 = 11;
+const DATA_COUNT_SECTION_ID: u8 = 12;
+
+const MISC_PREFIX: u8 = 0xfc;
+const SIMD_PREFIX: u8 = 0xfd;
+const ATOMIC_PREFIX: u8 = 0xfe;
+/// The limits flag bit that marks a memory as shared.
+const SHARED_FLAG: u8 = 0x2;
+
+const V128: u8 = 0x7b;
+const FUNCREF: u8 = 0x70;
+const EXTERNREF: u8 = 0x6f;
+
+/// A set of WebAssembly proposals, combined with `|`.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct FeatureSet(u32);
+
+impl FeatureSet {
+    pub const SIMD: FeatureSet = FeatureSet(1 << 0);
+    pub const BULK_MEMORY: FeatureSet = FeatureSet(1 << 1);
+    pub const REFERENCE_TYPES: FeatureSet = FeatureSet(1 << 2);
+    pub const MULTI_VALUE: FeatureSet = FeatureSet(1 << 3);
+    pub const ATOMICS: FeatureSet = FeatureSet(1 << 4);
+    pub const SIGN_EXTENSION: FeatureSet = FeatureSet(1 << 5);
+    pub const TAIL_CALL: FeatureSet = FeatureSet(1 << 6);
+
+    const NAMES: [(FeatureSet, &'static str); 7] = [
+        (FeatureSet::SIMD, "SIMD"),
+        (FeatureSet::BULK_MEMORY, "BULK_MEMORY"),
+        (FeatureSet::REFERENCE_TYPES, "REFERENCE_TYPES"),
+        (FeatureSet::MULTI_VALUE, "MULTI_VALUE"),
+        (FeatureSet::ATOMICS, "ATOMICS"),
+        (FeatureSet::SIGN_EXTENSION, "SIGN_EXTENSION"),
+        (FeatureSet::TAIL_CALL, "TAIL_CALL"),
+    ];
+
+    pub fn empty() -> Self {
+        FeatureSet(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Whether every proposal in `other` is in this set.
+    pub fn contains(&self, other: FeatureSet) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn insert(&mut self, other: FeatureSet) {
+        self.0 |= other.0;
+    }
+
+    /// The proposals in this set that are not in `other`.
+    pub fn difference(&self, other: FeatureSet) -> FeatureSet {
+        FeatureSet(self.0 & !other.0)
+    }
+
+    /// The name of each proposal in the set, as its constant is named.
+    pub fn names(&self) -> Vec<&'static str> {
+        FeatureSet::NAMES
+            .iter()
+            .filter(|(feature, _)| self.contains(*feature))
+            .map(|(_, name)| *name)
+            .collect()
+    }
+}
+
+impl BitOr for FeatureSet {
+    type Output = FeatureSet;
+
+    fn bitor(self, other: FeatureSet) -> FeatureSet {
+        FeatureSet(self.0 | other.0)
+    }
+}
+
+impl BitOrAssign for FeatureSet {
+    fn bitor_assign(&mut self, other: FeatureSet) {
+        self.insert(other);
+    }
+}
+
+impl fmt::Debug for FeatureSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "FeatureSet({})", self.names().join(" | "))
+    }
+}
+
+/// The proposals `wasm` uses. If part of it is malformed, whatever was found before that part
+/// is reported.
+pub fn detect_features(wasm: &[u8]) -> FeatureSet {
+    let mut found = FeatureSet::empty();
+    let _ = scan_module(wasm, &mut found);
+    found
+}
+
+fn scan_module(wasm: &[u8], found: &mut FeatureSet) -> Result<(), Error> {
+    let sections = match raw_sections(wasm) {
+        Some(sections) => sections,
+        None => bail!("malformed sections"),
+    };
+    for s in sections.iter() {
+        let mut r = Reader::new(&wasm[s.payload..s.end]);
+        match s.id {
+            TYPE_SECTION_ID => {
+                for _ in 0..r.u32()? {
+                    r.byte()?;
+                    let params = r.u32()?;
+                    value_types(r.bytes(params as usize)?, found);
+                    let results = r.u32()?;
+                    value_types(r.bytes(results as usize)?, found);
+                    if results > 1 {
+                        found.insert(FeatureSet::MULTI_VALUE);
+                    }
+                }
+            }
+            IMPORT_SECTION_ID => {
+                for _ in 0..r.u32()? {
+                    for _ in 0..2 {
+                        let len = r.u32()?;
+                        r.bytes(len as usize)?;
+                    }
+                    match r.byte()? {
+                        0x00 => r.leb()?,
+                        0x01 => table(&mut r, found)?,
+                        0x02 => memory(&mut r, found)?,
+                        0x03 => {
+                            value_types(r.bytes(1)?, found);
+                            r.byte()?;
+                        }
+                        kind => bail!("malformed import kind {:#x}", kind),
+                    }
+                }
+            }
+            TABLE_SECTION_ID => {
+                let count = r.u32()?;
+                if count > 1 {
+                    found.insert(FeatureSet::REFERENCE_TYPES);
+                }
+                for _ in 0..count {
+                    table(&mut r, found)?;
+                }
+            }
+            MEMORY_SECTION_ID => {
+                for _ in 0..r.u32()? {
+                    memory(&mut r, found)?;
+                }
+            }
+            GLOBAL_SECTION_ID => {
+                for _ in 0..r.u32()? {
+                    value_types(r.bytes(1)?, found);
+                    r.byte()?;
+                    instructions(&mut r, found)?;
+                }
+            }
+            ELEMENT_SECTION_ID => {
+                for _ in 0..r.u32()? {
+                    // Every segment encoding but the MVP's active one comes from bulk-memory.
+                    // What follows them differs by encoding, so scanning stops at the first.
+                    if r.u32()? != 0 {
+                        found.insert(FeatureSet::BULK_MEMORY);
+                        break;
+                    }
+                    instructions(&mut r, found)?;
+                    for _ in 0..r.u32()? {
+                        r.leb()?;
+                    }
+                }
+            }
+            CODE_SECTION_ID => {
+                for _ in 0..r.u32()? {
+                    let size = r.u32()?;
+                    let mut body = Reader::new(r.bytes(size as usize)?);
+                    for _ in 0..body.u32()? {
+                        body.leb()?;
+                        value_types(body.bytes(1)?, found);
+                    }
+                    instructions(&mut body, found)?;
+                }
+            }
+            DATA_SECTION_ID => {
+                for _ in 0..r.u32()? {
+                    match r.u32()? {
+                        0 => instructions(&mut r, found)?,
+                        // Passive segments and ones naming their memory.
+                        1 => found.insert(FeatureSet::BULK_MEMORY),
+                        2 => {
+                            found.insert(FeatureSet::BULK_MEMORY);
+                            r.leb()?;
+                            instructions(&mut r, found)?;
+                        }
+                        flags => bail!("malformed data segment flags {:#x}", flags),
+                    }
+                    let len = r.u32()?;
+                    r.bytes(len as usize)?;
+                }
+            }
+            DATA_COUNT_SECTION_ID => found.insert(FeatureSet::BULK_MEMORY),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn value_types(types: &[u8], found: &mut FeatureSet) {
+    for ty in types {
+        match *ty {
+            V128 => found.insert(FeatureSet::SIMD),
+            FUNCREF | EXTERNREF => found.insert(FeatureSet::REFERENCE_TYPES),
+            _ => {}
+        }
+    }
+}
+
+fn table(r: &mut Reader, found: &mut FeatureSet) -> Result<(), Error> {
+    // `funcref` tables are the MVP's; only other element types are new.
+    if r.byte()? == EXTERNREF {
+        found.insert(FeatureSet::REFERENCE_TYPES);
+    }
+    r.limits()
+}
+
+fn memory(r: &mut Reader, found: &mut FeatureSet) -> Result<(), Error> {
+    let flags = r.byte()?;
+    if flags & SHARED_FLAG != 0 {
+        found.insert(FeatureSet::ATOMICS);
+    }
+    r.leb()?;
+    if flags & 0x1 != 0 {
+        r.leb()?;
+    }
+    Ok(())
+}
+
+/// Scan instructions up to and including the `end` that closes the function body or constant
+/// expression `r` is at.
+fn instructions(r: &mut Reader, found: &mut FeatureSet) -> Result<(), Error> {
+    let mut depth = 0;
+    loop {
+        match r.byte()? {
+            0x00 | 0x01 | 0x05 | 0x0f | 0x1a | 0x1b | 0x45..=0xbf => {}
+            0x02 | 0x03 | 0x04 => {
+                depth += 1;
+                block_type(r, found)?;
+            }
+            0x0b => {
+                if depth == 0 {
+                    return Ok(());
+                }
+                depth -= 1;
+            }
+            0x0c | 0x0d | 0x10 | 0x20..=0x24 | 0x3f | 0x40 | 0x41 | 0x42 => r.leb()?,
+            0x0e => {
+                for _ in 0..=r.u32()? {
+                    r.leb()?;
+                }
+            }
+            // `return_call`
+            0x12 => {
+                found.insert(FeatureSet::TAIL_CALL);
+                r.leb()?;
+            }
+            // `return_call_indirect`
+            0x13 => {
+                found.insert(FeatureSet::TAIL_CALL);
+                r.leb()?;
+                r.leb()?;
+            }
+            0x11 => {
+                r.leb()?;
+                // Only table 0 exists without reference-types.
+                if r.u32()? != 0 {
+                    found.insert(FeatureSet::REFERENCE_TYPES);
+                }
+            }
+            0x1c => {
+                // `select` with explicit types.
+                found.insert(FeatureSet::REFERENCE_TYPES);
+                let count = r.u32()?;
+                value_types(r.bytes(count as usize)?, found);
+            }
+            0x25 | 0x26 | 0xd2 => {
+                found.insert(FeatureSet::REFERENCE_TYPES);
+                r.leb()?;
+            }
+            0xd0 => {
+                found.insert(FeatureSet::REFERENCE_TYPES);
+                r.byte()?;
+            }
+            0xd1 => found.insert(FeatureSet::REFERENCE_TYPES),
+            0x28..=0x3e => {
+                r.leb()?;
+                r.leb()?;
+            }
+            0x43 => {
+                r.bytes(4)?;
+            }
+            0x44 => {
+                r.bytes(8)?;
+            }
+            0xc0..=0xc4 => found.insert(FeatureSet::SIGN_EXTENSION),
+            MISC_PREFIX => misc(r, found)?,
+            SIMD_PREFIX => {
+                found.insert(FeatureSet::SIMD);
+                simd(r)?;
+            }
+            ATOMIC_PREFIX => {
+                found.insert(FeatureSet::ATOMICS);
+                if r.u32()? == 0x03 {
+                    // `atomic.fence`
+                    r.byte()?;
+                } else {
+                    r.leb()?;
+                    r.leb()?;
+                }
+            }
+            op => bail!("unknown opcode {:#x}", op),
+        }
+    }
+}
+
+fn block_type(r: &mut Reader, found: &mut FeatureSet) -> Result<(), Error> {
+    let first = r.byte()?;
+    match first {
+        0x40 | 0x7c..=0x7f => {}
+        V128 | FUNCREF | EXTERNREF => value_types(&[first], found),
+        _ => {
+            // An index into the type section, as a signed LEB128 number.
+            found.insert(FeatureSet::MULTI_VALUE);
+            let mut b = first;
+            while b & 0x80 != 0 {
+                b = r.byte()?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn misc(r: &mut Reader, found: &mut FeatureSet) -> Result<(), Error> {
+    let sub = r.u32()?;
+    match sub {
+        // The saturating truncations.
+        0x00..=0x07 => {}
+        // `memory.init`
+        0x08 => {
+            r.leb()?;
+            r.byte()?;
+        }
+        // `data.drop`, `elem.drop`
+        0x09 | 0x0d => r.leb()?,
+        // `memory.copy`
+        0x0a => {
+            r.bytes(2)?;
+        }
+        // `memory.fill`
+        0x0b => {
+            r.byte()?;
+        }
+        // `table.init`, `table.copy`
+        0x0c | 0x0e => {
+            r.leb()?;
+            r.leb()?;
+        }
+        // `table.grow`, `table.size`, `table.fill`
+        0x0f..=0x11 => {
+            found.insert(FeatureSet::REFERENCE_TYPES);
+            r.leb()?;
+        }
+        _ => bail!("unknown opcode {:#x} {:#x}", MISC_PREFIX, sub),
+    }
+    if (0x08..=0x0e).contains(&sub) {
+        found.insert(FeatureSet::BULK_MEMORY);
+    }
+    Ok(())
+}
+
+fn simd(r: &mut Reader) -> Result<(), Error> {
+    match r.u32()? {
+        // Loads and stores.
+        0x00..=0x0b | 0x5c | 0x5d => {
+            r.leb()?;
+            r.leb()?;
+        }
+        // `v128.const`, `i8x16.shuffle`
+        0x0c | 0x0d => {
+            r.bytes(16)?;
+        }
+        // Lane extracts and replacements.
+        0x15..=0x22 => {
+            r.byte()?;
+        }
+        // Single-lane loads and stores.
+        0x54..=0x5b => {
+            r.leb()?;
+            r.leb()?;
+            r.byte()?;
+        }
+        _ => {}
+    }
+    Ok(())
+}
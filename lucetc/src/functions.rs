@@ -0,0 +1,156 @@
+//! Decoded function bodies, for tools that analyze a module rather than compile it.
+
+use crate::program::names::module_names;
+use failure::{format_err, Error};
+use parity_wasm::elements::{ImportCountType, Instruction, Local, Module};
+
+/// A function defined in the module, along with its decoded body.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionBody {
+    /// The function's index in the wasm function index space, which counts imports first.
+    pub index: u32,
+    /// The function's name from the export or name section, if it has one.
+    pub name: Option<String>,
+    /// Local variable declarations, not counting parameters.
+    pub locals: Vec<Local>,
+    /// The body's instructions in order, ending with the function's final `end`.
+    pub instructions: Vec<Instruction>,
+    /// Whether no path through the body returns, as for a body that is just `unreachable` or
+    /// an infinite loop. Code after a call to such a function is dead.
+    pub noreturn: bool,
+}
+
+/// The bodies of every function defined in `module`, in index order.
+pub fn function_bodies(module: Module) -> Result<Vec<FunctionBody>, Error> {
+    let module = module
+        .parse_names()
+        .map_err(|(es, _)| format_err!("could not parse some of the name sections: {:?}", es))?;
+    let names = module_names(&module)?;
+    let index_base = module.import_count(ImportCountType::Function) as u32;
+    let bodies = module.code_section().map(|s| s.bodies()).unwrap_or(&[]);
+    Ok(bodies
+        .iter()
+        .enumerate()
+        .map(|(i, body)| {
+            let index = index_base + i as u32;
+            let instructions = body.code().elements();
+            FunctionBody {
+                index,
+                name: names.function_name(index),
+                locals: body.locals().to_vec(),
+                instructions: instructions.to_vec(),
+                noreturn: !can_return(instructions),
+            }
+        })
+        .collect())
+}
+
+/// A block, loop, or if whose end may still be reached.
+struct Frame {
+    is_loop: bool,
+    is_if: bool,
+    /// Whether the frame was entered on a reachable path.
+    entered: bool,
+    /// Whether an `if` has seen its `else`.
+    has_else: bool,
+    /// Whether some branch targets the end of the frame, or a nested arm fell through to it.
+    end_reached: bool,
+}
+
+/// Whether some path through a function body reaches a `return`, or the body's final `end`.
+///
+/// This follows reachability the way the translator does: code after `unreachable`, `br`,
+/// `br_table`, or `return` is dead until the end of the enclosing frame, and a frame's end is
+/// live if control can fall through to it or branch to it. Branches to a loop go back to its
+/// start, so a loop only ends if its body falls through.
+fn can_return(instructions: &[Instruction]) -> bool {
+    // The function body itself is the outermost frame, and branching to it returns.
+    let mut frames = vec![Frame {
+        is_loop: false,
+        is_if: false,
+        entered: true,
+        has_else: false,
+        end_reached: false,
+    }];
+    let mut reachable = true;
+    let mut returns = false;
+
+    fn branch(frames: &mut Vec<Frame>, depth: u32) {
+        let len = frames.len();
+        if let Some(frame) = (depth as usize)
+            .checked_add(1)
+            .and_then(|d| len.checked_sub(d))
+            .and_then(|i| frames.get_mut(i))
+        {
+            if !frame.is_loop {
+                frame.end_reached = true;
+            }
+        }
+    }
+
+    for instruction in instructions {
+        match instruction {
+            Instruction::Block(_) | Instruction::Loop(_) | Instruction::If(_) => {
+                frames.push(Frame {
+                    is_loop: match instruction {
+                        Instruction::Loop(_) => true,
+                        _ => false,
+                    },
+                    is_if: match instruction {
+                        Instruction::If(_) => true,
+                        _ => false,
+                    },
+                    entered: reachable,
+                    has_else: false,
+                    end_reached: false,
+                });
+            }
+            Instruction::Else => {
+                if let Some(frame) = frames.last_mut() {
+                    frame.end_reached |= reachable;
+                    frame.has_else = true;
+                    reachable = frame.entered;
+                }
+            }
+            Instruction::End => {
+                let frame = match frames.pop() {
+                    Some(frame) => frame,
+                    None => break,
+                };
+                // An `if` without an `else` falls through when its condition is false.
+                let implicit_else = frame.is_if && frame.entered && !frame.has_else;
+                reachable = reachable || frame.end_reached || implicit_else;
+                if frames.is_empty() {
+                    returns |= reachable;
+                    break;
+                }
+            }
+            Instruction::Br(depth) => {
+                if reachable {
+                    branch(&mut frames, *depth);
+                }
+                reachable = false;
+            }
+            Instruction::BrIf(depth) => {
+                if reachable {
+                    branch(&mut frames, *depth);
+                }
+            }
+            Instruction::BrTable(data) => {
+                if reachable {
+                    for depth in data.table.iter().chain(Some(&data.default)) {
+                        branch(&mut frames, *depth);
+                    }
+                }
+                reachable = false;
+            }
+            Instruction::Return => {
+                returns |= reachable;
+                reachable = false;
+            }
+            Instruction::Unreachable => reachable = false,
+            _ => {}
+        }
+    }
+    returns
+}
@@ -0,0 +1,115 @@
+//! A C header declaring the functions a module exports, for hosts that call them directly.
+
+use crate::program::Program;
+use cranelift_codegen::ir::{self, types, ArgumentPurpose};
+use cranelift_module::Linkage;
+use failure::{format_err, Error};
+use parity_wasm::elements::Internal;
+use std::fmt::Write;
+use std::iter;
+
+/// The text of a header declaring each function `prog` defines and exports, by its
+/// `guest_func_` symbol and by each alias in `aliases`, given as `(export, c_symbol)` pairs.
+/// Every function takes the instance's vmctx before its wasm parameters.
+///
+/// Re-exported imports are left out, since the host defines them. So are exports whose symbols
+/// aren't C identifiers, with a comment in their place.
+pub fn c_header(name: &str, prog: &Program, aliases: &[(String, String)]) -> Result<String, Error> {
+    let guard: String = format!("LUCET_MODULE_{}_H", name)
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let mut out = String::new();
+    writeln!(
+        out,
+        "// Exports of the `{}` module, generated by lucetc.",
+        name
+    )?;
+    writeln!(out, "#ifndef {}", guard)?;
+    writeln!(out, "#define {}\n", guard)?;
+    writeln!(out, "#include <stdint.h>\n")?;
+    writeln!(out, "struct lucet_vmctx;\n")?;
+    writeln!(out, "#ifdef __cplusplus\nextern \"C\" {{\n#endif\n")?;
+
+    let exports = prog
+        .module()
+        .export_section()
+        .map(|s| s.entries())
+        .unwrap_or(&[]);
+    for entry in exports {
+        let index = match entry.internal() {
+            Internal::Function(index) => *index,
+            _ => continue,
+        };
+        let func = prog.get_function(index)?;
+        if func.linkage() == Linkage::Import {
+            continue;
+        }
+        let sig = func.signature();
+        let symbols =
+            iter::once(func.symbol()).chain(aliases.iter().filter_map(|(export, alias)| {
+                if export == entry.field() {
+                    Some(alias.as_str())
+                } else {
+                    None
+                }
+            }));
+        for symbol in symbols {
+            if c_identifier(symbol) {
+                let prototype = prototype(symbol, &sig)
+                    .map_err(|e| format_err!("cannot declare export {}: {}", entry.field(), e))?;
+                writeln!(out, "extern {};", prototype)?;
+            } else {
+                writeln!(out, "// {:?} is not a C identifier.", symbol)?;
+            }
+        }
+    }
+
+    writeln!(out, "\n#ifdef __cplusplus\n}}\n#endif\n")?;
+    writeln!(out, "#endif // {}", guard)?;
+    Ok(out)
+}
+
+fn prototype(symbol: &str, sig: &ir::Signature) -> Result<String, Error> {
+    let ret = match sig.returns.as_slice() {
+        [] => "void",
+        [ret] => c_type(ret.value_type)?,
+        _ => return Err(format_err!("C functions can't return more than one value")),
+    };
+    let mut params = vec![];
+    let mut args = 0;
+    for param in sig.params.iter() {
+        if param.purpose == ArgumentPurpose::VMContext {
+            params.push("struct lucet_vmctx *vmctx".to_owned());
+        } else {
+            params.push(format!("{} arg{}", c_type(param.value_type)?, args));
+            args += 1;
+        }
+    }
+    Ok(format!("{} {}({})", ret, symbol, params.join(", ")))
+}
+
+fn c_type(ty: ir::Type) -> Result<&'static str, Error> {
+    match ty {
+        types::I32 => Ok("int32_t"),
+        types::I64 => Ok("int64_t"),
+        types::F32 => Ok("float"),
+        types::F64 => Ok("double"),
+        _ => Err(format_err!("no C type for {}", ty)),
+    }
+}
+
+fn c_identifier(symbol: &str) -> bool {
+    let mut chars = symbol.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {}
+        _ => return false,
+    }
+    chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
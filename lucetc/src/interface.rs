@@ -0,0 +1,192 @@
+//! The exports a module offers its host, and how they differ between two versions of a module.
+
+use crate::program::types::{signature_key, valuetype_name};
+use failure::{format_err, Error};
+use parity_wasm::elements::{
+    External, FunctionType, GlobalType, Internal, MemoryType, Module, ResizableLimits, TableType,
+    Type,
+};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// What the host sees of one export.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExportSignature {
+    Function(FunctionType),
+    Global(GlobalType),
+    Memory(MemoryType),
+    Table(TableType),
+}
+
+impl fmt::Display for ExportSignature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExportSignature::Function(ftype) => write!(f, "func {}", signature_key(ftype)),
+            ExportSignature::Global(gtype) => write!(
+                f,
+                "global {}{}",
+                if gtype.is_mutable() { "mut " } else { "" },
+                valuetype_name(&gtype.content_type())
+            ),
+            ExportSignature::Memory(mtype) => write!(f, "memory {}", limits(mtype.limits())),
+            ExportSignature::Table(ttype) => write!(f, "table {}", limits(ttype.limits())),
+        }
+    }
+}
+
+fn limits(limits: &ResizableLimits) -> String {
+    match limits.maximum() {
+        Some(max) => format!("{}..={}", limits.initial(), max),
+        None => format!("{}..", limits.initial()),
+    }
+}
+
+/// Every export of a module, by name.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ModuleSignature {
+    pub exports: BTreeMap<String, ExportSignature>,
+}
+
+/// An export present in both modules under the same name, but with another signature.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChangedExport {
+    pub name: String,
+    pub old: ExportSignature,
+    pub new: ExportSignature,
+}
+
+/// How the exports of a newer module differ from an older one's. Each list is sorted by name.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InterfaceDiff {
+    pub added: Vec<(String, ExportSignature)>,
+    pub removed: Vec<(String, ExportSignature)>,
+    pub changed: Vec<ChangedExport>,
+}
+
+impl InterfaceDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Whether a host written against the older module could fail with the newer one: an
+    /// export it may use was removed or changed. Added exports don't break anything.
+    pub fn is_breaking(&self) -> bool {
+        !self.removed.is_empty() || !self.changed.is_empty()
+    }
+}
+
+impl fmt::Display for InterfaceDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (name, sig) in self.added.iter() {
+            writeln!(f, "+ {}: {}", name, sig)?;
+        }
+        for (name, sig) in self.removed.iter() {
+            writeln!(f, "- {}: {}", name, sig)?;
+        }
+        for change in self.changed.iter() {
+            writeln!(f, "~ {}: {} -> {}", change.name, change.old, change.new)?;
+        }
+        Ok(())
+    }
+}
+
+impl ModuleSignature {
+    /// What changed from `self` to `newer`.
+    pub fn diff(&self, newer: &ModuleSignature) -> InterfaceDiff {
+        let mut diff = InterfaceDiff::default();
+        for (name, old) in self.exports.iter() {
+            match newer.exports.get(name) {
+                None => diff.removed.push((name.clone(), old.clone())),
+                Some(new) if new != old => diff.changed.push(ChangedExport {
+                    name: name.clone(),
+                    old: old.clone(),
+                    new: new.clone(),
+                }),
+                Some(_) => {}
+            }
+        }
+        for (name, new) in newer.exports.iter() {
+            if !self.exports.contains_key(name) {
+                diff.added.push((name.clone(), new.clone()));
+            }
+        }
+        diff
+    }
+}
+
+/// The signature of each export of `module`, whether it is defined by the module or re-exports
+/// an import.
+pub fn module_signature(module: &Module) -> Result<ModuleSignature, Error> {
+    let types = module.type_section().map(|s| s.types()).unwrap_or(&[]);
+    let imports = module.import_section().map(|s| s.entries()).unwrap_or(&[]);
+
+    let mut functions = vec![];
+    let mut globals = vec![];
+    let mut memories = vec![];
+    let mut tables = vec![];
+    for entry in imports {
+        match entry.external() {
+            External::Function(ty) => functions.push(*ty),
+            External::Global(gtype) => globals.push(gtype.clone()),
+            External::Memory(mtype) => memories.push(mtype.clone()),
+            External::Table(ttype) => tables.push(ttype.clone()),
+        }
+    }
+    if let Some(section) = module.function_section() {
+        functions.extend(section.entries().iter().map(|f| f.type_ref()));
+    }
+    if let Some(section) = module.global_section() {
+        globals.extend(section.entries().iter().map(|g| g.global_type().clone()));
+    }
+    if let Some(section) = module.memory_section() {
+        memories.extend(section.entries().iter().cloned());
+    }
+    if let Some(section) = module.table_section() {
+        tables.extend(section.entries().iter().cloned());
+    }
+
+    let mut exports = BTreeMap::new();
+    let entries = module.export_section().map(|s| s.entries()).unwrap_or(&[]);
+    for entry in entries {
+        let out_of_bounds = || {
+            format_err!(
+                "export {} refers to {:?}, which is out of bounds",
+                entry.field(),
+                entry.internal()
+            )
+        };
+        let sig = match *entry.internal() {
+            Internal::Function(index) => {
+                let ty = functions.get(index as usize).ok_or_else(out_of_bounds)?;
+                match types.get(*ty as usize) {
+                    Some(Type::Function(ftype)) => ExportSignature::Function(ftype.clone()),
+                    None => Err(format_err!(
+                        "export {} has type {}, which is out of bounds",
+                        entry.field(),
+                        ty
+                    ))?,
+                }
+            }
+            Internal::Global(index) => ExportSignature::Global(
+                globals
+                    .get(index as usize)
+                    .ok_or_else(out_of_bounds)?
+                    .clone(),
+            ),
+            Internal::Memory(index) => ExportSignature::Memory(
+                memories
+                    .get(index as usize)
+                    .ok_or_else(out_of_bounds)?
+                    .clone(),
+            ),
+            Internal::Table(index) => ExportSignature::Table(
+                tables
+                    .get(index as usize)
+                    .ok_or_else(out_of_bounds)?
+                    .clone(),
+            ),
+        };
+        exports.insert(entry.field().to_owned(), sig);
+    }
+    Ok(ModuleSignature { exports })
+}
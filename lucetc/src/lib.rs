@@ -1,38 +1,140 @@
+pub mod atomics;
 pub mod bindings;
+pub mod check;
 pub mod compiler;
+pub mod diagnostics;
 pub mod error;
+pub mod features;
+pub mod functions;
+pub mod header;
+pub mod interface;
 pub mod load;
 pub mod patch;
 pub mod program;
+pub mod report;
+pub mod tail_call;
+pub mod timings;
+#[cfg(feature = "jit")]
+pub mod vectors;
 
-use crate::compiler::data::{compile_data_initializers, compile_sparse_page_data};
+use crate::check::check_module;
+use crate::compiler::data::{
+    compile_data_initializers, compile_data_snapshot, compile_deferred_data, compile_rodata,
+    compile_sparse_page_data,
+};
 use crate::compiler::function::compile_function;
 use crate::compiler::globals::compile_global_specs;
+use crate::compiler::layout::{function_layout, function_layout_json};
 use crate::compiler::memory::compile_memory_specs;
 use crate::compiler::module_data::compile_module_data;
-use crate::compiler::table::compile_table;
+use crate::compiler::naming::Naming;
+use crate::compiler::relocations::rebind_symbols;
+use crate::compiler::table::{compile_elements, compile_table};
+use crate::compiler::user_metadata::{compile_user_metadata, serialize_user_metadata};
+use crate::compiler::vmctx::compile_vmctx_layout;
+use crate::compiler::{isa_builder, shared_flags};
 use crate::error::{LucetcError, LucetcErrorKind};
-use crate::load::read_module;
+use crate::features::detect_features;
+use crate::functions::function_bodies;
+use crate::header::c_header;
+use crate::interface::module_signature;
+use crate::load::{
+    check_custom_sections, check_features, decode_module, dylink_info, read_module_config,
+    read_module_with_features, read_to_u8s, to_binary, DylinkInfo, ModuleConfig,
+};
 use crate::patch::patch_module;
-use crate::program::Program;
+use crate::program::{Program, Runtime};
+use crate::report::module_report;
+use cranelift_codegen::ir;
+use cranelift_codegen::settings::Flags;
+use cranelift_module::{Backend, Linkage};
 use failure::{format_err, Error, ResultExt};
-use parity_wasm::elements::Module;
+use parity_wasm::elements::{ExportEntry, External, Internal, Module, Section};
+use sha2::{Digest, Sha256};
+use std::collections::{btree_map, hash_map, BTreeMap, HashMap, HashSet};
 use std::env;
+use std::io;
 use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use target_lexicon::Triple;
 use tempfile;
 
 pub use crate::{
-    bindings::Bindings,
-    compiler::{Compiler, OptLevel},
-    program::memory::HeapSettings,
+    bindings::{Bindings, UnboundImport, UnboundReason},
+    check::{CheckReport, Problem},
+    compiler::layout::FunctionLayout,
+    compiler::naming::{DefaultNaming, MetadataSymbol, SymbolNaming},
+    compiler::recompile::CompiledFunc,
+    compiler::relocations::{RelocKind, Relocation},
+    compiler::vmctx::VmctxLayout,
+    compiler::{
+        CodegenSettings, Compiler, DataInit, HeapBase, Hint, ObjectFile, OptLevel,
+        SegmentOffsetPolicy, UnsupportedFunctionPolicy, WasiAbi,
+    },
+    diagnostics::{Diagnostic, Diagnostics, Severity},
+    features::FeatureSet,
+    functions::FunctionBody,
+    interface::{ChangedExport, ExportSignature, InterfaceDiff, ModuleSignature},
+    load::{Atomics, CustomSectionPolicy, WasmFeatures},
+    program::memory::{GrowZeroing, GuardPlacement, HeapSettings, HeapSettingsBuilder},
+    program::types::TypeIds,
+    timings::CompileTimings,
 };
 
+#[cfg(feature = "jit")]
+pub use crate::compiler::jit::{JitModule, Value};
+#[cfg(feature = "jit")]
+pub use crate::vectors::{Mismatch, TestVector, VectorReport};
+
 pub struct Lucetc {
     input: PathBuf,
     bindings: Vec<Bindings>,
-    opt_level: OptLevel,
+    codegen: CodegenSettings,
     heap: HeapSettings,
     builtins_paths: Vec<PathBuf>,
+    extra_objects: Vec<PathBuf>,
+    linker: Option<PathBuf>,
+    relocation_hook: Option<Box<Fn(&mut Relocation)>>,
+    export_filter: Option<Box<Fn(&str) -> Option<String>>>,
+    absolute_base: Option<u64>,
+    export_aliases: Vec<(String, String)>,
+    custom_section_policy: CustomSectionPolicy,
+    type_ids: Option<TypeIds>,
+    features: WasmFeatures,
+    build_id: BuildId,
+}
+
+/// Whether the object carries a GNU build ID note, for matching core dumps and debuggers to the
+/// build that produced it.
+///
+/// lucetc writes the note into the objects written by `object_file`, from a hash of the object.
+/// The linker discards the notes of its inputs, so `shared_object_file` instead passes it
+/// `--build-id=sha1`, or `--build-id=none`, for a hash of the linked image. Only ELF objects get
+/// a note: a flat image written with `absolute_base` has none, and on macOS the linker records
+/// its own UUID instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuildId {
+    /// A SHA-1 hash of the object in a `.note.gnu.build-id` section.
+    Sha1,
+    /// No build ID note is written.
+    None,
+}
+
+impl Default for BuildId {
+    fn default() -> Self {
+        BuildId::Sha1
+    }
+}
+
+/// The settings a `Lucetc` will compile with, once defaults, the module's `lucet.config` section,
+/// and `LucetcOpts` calls have all been applied.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EffectiveSettings {
+    pub opt_level: OptLevel,
+    pub heap: HeapSettings,
+    /// The machine code is generated for. `None` is the host.
+    pub target: Option<Triple>,
+    pub features: WasmFeatures,
 }
 
 pub trait AsLucetc {
@@ -55,6 +157,9 @@ pub trait LucetcOpts {
     fn builtins<P: AsRef<Path>>(&mut self, builtins_path: P);
     fn with_builtins<P: AsRef<Path>>(self, builtins_path: P) -> Self;
 
+    fn heap_settings(&mut self, settings: HeapSettings);
+    fn with_heap_settings(self, settings: HeapSettings) -> Self;
+
     fn min_reserved_size(&mut self, min_reserved_size: u64);
     fn with_min_reserved_size(self, min_reserved_size: u64) -> Self;
 
@@ -63,6 +168,126 @@ pub trait LucetcOpts {
 
     fn guard_size(&mut self, guard_size: u64);
     fn with_guard_size(self, guard_size: u64) -> Self;
+
+    fn reservation_alignment(&mut self, alignment: u64);
+    fn with_reservation_alignment(self, alignment: u64) -> Self;
+
+    fn host_page_size(&mut self, page_size: u64);
+    fn with_host_page_size(self, page_size: u64) -> Self;
+
+    fn grow_zeroing(&mut self, zeroing: GrowZeroing);
+    fn with_grow_zeroing(self, zeroing: GrowZeroing) -> Self;
+
+    fn guard_placement(&mut self, placement: GuardPlacement);
+    fn with_guard_placement(self, placement: GuardPlacement) -> Self;
+
+    fn guard_pages(&mut self, pages: u32);
+    fn with_guard_pages(self, pages: u32) -> Self;
+
+    fn function_hints(&mut self, hints: HashMap<String, Hint>);
+    fn with_function_hints(self, hints: HashMap<String, Hint>) -> Self;
+
+    fn lazy_compile(&mut self, hot_functions: HashSet<String>);
+    fn with_lazy_compile(self, hot_functions: HashSet<String>) -> Self;
+
+    fn extra_objects(&mut self, paths: Vec<PathBuf>);
+    fn with_extra_objects(self, paths: Vec<PathBuf>) -> Self;
+
+    fn linker(&mut self, path: PathBuf);
+    fn with_linker(self, path: PathBuf) -> Self;
+
+    fn max_stack_per_function(&mut self, bytes: u32);
+    fn with_max_stack_per_function(self, bytes: u32) -> Self;
+
+    fn max_locals_per_function(&mut self, count: u32);
+    fn with_max_locals_per_function(self, count: u32) -> Self;
+
+    fn target(&mut self, target: Triple);
+    fn with_target(self, target: Triple) -> Self;
+
+    fn export_alias(&mut self, wasm_export: &str, c_symbol: &str);
+    fn with_export_alias(self, wasm_export: &str, c_symbol: &str) -> Self;
+
+    fn heap_base(&mut self, heap_base: HeapBase);
+    fn with_heap_base(self, heap_base: HeapBase) -> Self;
+
+    fn relocation_hook<F: Fn(&mut Relocation) + 'static>(&mut self, hook: F);
+    fn with_relocation_hook<F: Fn(&mut Relocation) + 'static>(self, hook: F) -> Self;
+
+    fn export_filter<F: Fn(&str) -> Option<String> + 'static>(&mut self, filter: F);
+    fn with_export_filter<F: Fn(&str) -> Option<String> + 'static>(self, filter: F) -> Self;
+
+    fn absolute_base(&mut self, base: Option<u64>);
+    fn with_absolute_base(self, base: Option<u64>) -> Self;
+
+    fn diagnostics<F: Fn(Diagnostic) + 'static>(&mut self, sink: F);
+    fn with_diagnostics<F: Fn(Diagnostic) + 'static>(self, sink: F) -> Self;
+
+    fn warn_unreachable_code(&mut self, warn: bool);
+    fn with_warn_unreachable_code(self, warn: bool) -> Self;
+
+    fn metadata(&mut self, key: &str, value: &[u8]);
+    fn with_metadata(self, key: &str, value: &[u8]) -> Self;
+
+    fn trap_messages(&mut self, messages: HashMap<String, String>);
+    fn with_trap_messages(self, messages: HashMap<String, String>) -> Self;
+
+    fn symbol_naming<N: SymbolNaming + 'static>(&mut self, policy: N);
+    fn with_symbol_naming<N: SymbolNaming + 'static>(self, policy: N) -> Self;
+
+    fn custom_section_policy(&mut self, policy: CustomSectionPolicy);
+    fn with_custom_section_policy(self, policy: CustomSectionPolicy) -> Self;
+
+    fn type_ids(&mut self, ids: TypeIds);
+    fn with_type_ids(self, ids: TypeIds) -> Self;
+
+    fn strip(&mut self, strip: bool);
+    fn with_strip(self, strip: bool) -> Self;
+
+    fn elide_bounds_checks(&mut self, elide: bool);
+    fn with_elide_bounds_checks(self, elide: bool) -> Self;
+
+    fn elide_stack_probes(&mut self, elide: bool);
+    fn with_elide_stack_probes(self, elide: bool) -> Self;
+
+    fn trap_on_signed_overflow(&mut self, trap: bool);
+    fn with_trap_on_signed_overflow(self, trap: bool) -> Self;
+
+    fn fp_traps(&mut self, enable: bool);
+    fn with_fp_traps(self, enable: bool) -> Self;
+
+    fn wasi_abi(&mut self, abi: WasiAbi);
+    fn with_wasi_abi(self, abi: WasiAbi) -> Self;
+
+    fn on_unsupported_function(&mut self, policy: UnsupportedFunctionPolicy);
+    fn with_on_unsupported_function(self, policy: UnsupportedFunctionPolicy) -> Self;
+
+    fn non_constant_segment_offset(&mut self, policy: SegmentOffsetPolicy);
+    fn with_non_constant_segment_offset(self, policy: SegmentOffsetPolicy) -> Self;
+
+    fn sort_imports(&mut self, sort: bool);
+    fn with_sort_imports(self, sort: bool) -> Self;
+
+    fn time_budget(&mut self, budget: Duration);
+    fn with_time_budget(self, budget: Duration) -> Self;
+
+    fn separate_rodata(&mut self, separate: bool);
+    fn with_separate_rodata(self, separate: bool) -> Self;
+
+    fn data_init(&mut self, init: DataInit);
+    fn with_data_init(self, init: DataInit) -> Self;
+
+    fn function_sections(&mut self, sections: bool);
+    fn with_function_sections(self, sections: bool) -> Self;
+
+    fn wasm_features(&mut self, features: WasmFeatures);
+    fn with_wasm_features(self, features: WasmFeatures) -> Self;
+
+    fn atomics(&mut self, atomics: Atomics);
+    fn with_atomics(self, atomics: Atomics) -> Self;
+
+    fn build_id(&mut self, build_id: BuildId);
+    fn with_build_id(self, build_id: BuildId) -> Self;
 }
 
 impl<T: AsLucetc> LucetcOpts for T {
@@ -76,7 +301,7 @@ impl<T: AsLucetc> LucetcOpts for T {
     }
 
     fn opt_level(&mut self, opt_level: OptLevel) {
-        self.as_lucetc().opt_level = opt_level;
+        self.as_lucetc().codegen.opt_level = opt_level;
     }
 
     fn with_opt_level(mut self, opt_level: OptLevel) -> Self {
@@ -95,6 +320,17 @@ impl<T: AsLucetc> LucetcOpts for T {
         self
     }
 
+    /// Replace every heap setting at once, such as with settings made by
+    /// `HeapSettings::builder`. Setters such as `min_reserved_size` change a single field.
+    fn heap_settings(&mut self, settings: HeapSettings) {
+        self.as_lucetc().heap = settings;
+    }
+
+    fn with_heap_settings(mut self, settings: HeapSettings) -> Self {
+        self.heap_settings(settings);
+        self
+    }
+
     fn min_reserved_size(&mut self, min_reserved_size: u64) {
         self.as_lucetc().heap.min_reserved_size = min_reserved_size;
     }
@@ -113,6 +349,8 @@ impl<T: AsLucetc> LucetcOpts for T {
         self
     }
 
+    /// A guard size of 0 leaves the heap without guard pages, so every access is bounds-checked
+    /// in full. The reserved size must then be below 4GiB.
     fn guard_size(&mut self, guard_size: u64) {
         self.as_lucetc().heap.guard_size = guard_size;
     }
@@ -121,21 +359,532 @@ impl<T: AsLucetc> LucetcOpts for T {
         self.guard_size(guard_size);
         self
     }
+
+    /// Round the reserved size up to a multiple of `alignment`, which must be a power of two,
+    /// for hosts that back the heap with huge pages.
+    fn reservation_alignment(&mut self, alignment: u64) {
+        self.as_lucetc().heap.reservation_alignment = Some(alignment);
+    }
+
+    fn with_reservation_alignment(mut self, alignment: u64) -> Self {
+        self.reservation_alignment(alignment);
+        self
+    }
+
+    fn host_page_size(&mut self, page_size: u64) {
+        self.as_lucetc().heap.host_page_size = page_size;
+    }
+
+    fn with_host_page_size(mut self, page_size: u64) -> Self {
+        self.host_page_size(page_size);
+        self
+    }
+
+    fn grow_zeroing(&mut self, zeroing: GrowZeroing) {
+        self.as_lucetc().heap.grow_zeroing = zeroing;
+    }
+
+    fn with_grow_zeroing(mut self, zeroing: GrowZeroing) -> Self {
+        self.grow_zeroing(zeroing);
+        self
+    }
+
+    /// Whether each instance has its own guard region. With `GuardPlacement::Shared`, every
+    /// heap access is bounds-checked in full rather than relying on the guard.
+    fn guard_placement(&mut self, placement: GuardPlacement) {
+        self.as_lucetc().heap.guard_placement = placement;
+    }
+
+    fn with_guard_placement(mut self, placement: GuardPlacement) -> Self {
+        self.guard_placement(placement);
+        self
+    }
+
+    /// Set the guard size to `pages` host pages. The page size is read when this is called, so
+    /// set `host_page_size` first if the default of 4 KiB is wrong for the host.
+    fn guard_pages(&mut self, pages: u32) {
+        let heap = &mut self.as_lucetc().heap;
+        heap.guard_size = pages as u64 * heap.host_page_size;
+    }
+
+    fn with_guard_pages(mut self, pages: u32) -> Self {
+        self.guard_pages(pages);
+        self
+    }
+
+    /// Order the defined functions named in `hints` for code layout: hot ones first, then
+    /// unhinted ones, then cold ones. This only reorders functions; each stays in its own
+    /// `.text.<symbol>` section, with no `.text.hot` or `.text.unlikely` grouping, so a linker
+    /// keeps the order by placing the sections as it finds them. Names that aren't defined
+    /// functions are ignored with a warning.
+    fn function_hints(&mut self, hints: HashMap<String, Hint>) {
+        self.as_lucetc().codegen.function_hints.extend(hints);
+    }
+
+    fn with_function_hints(mut self, hints: HashMap<String, Hint>) -> Self {
+        self.function_hints(hints);
+        self
+    }
+
+    /// Compile only `hot_functions`, named as for `function_hints`, ahead of time. Every other
+    /// defined function becomes a stub that calls the runtime's `lucet_compile_on_demand` with
+    /// its index, then calls the code that returns. Startup is faster, but each cold function is
+    /// slow the first time it is called.
+    fn lazy_compile(&mut self, hot_functions: HashSet<String>) {
+        self.as_lucetc().codegen.lazy_compile = Some(hot_functions);
+    }
+
+    fn with_lazy_compile(mut self, hot_functions: HashSet<String>) -> Self {
+        self.lazy_compile(hot_functions);
+        self
+    }
+
+    /// Objects to link into the shared object alongside the module, such as a host shim that
+    /// defines its imports.
+    fn extra_objects(&mut self, paths: Vec<PathBuf>) {
+        self.as_lucetc().extra_objects.extend(paths);
+    }
+
+    fn with_extra_objects(mut self, paths: Vec<PathBuf>) -> Self {
+        self.extra_objects(paths);
+        self
+    }
+
+    /// Link shared objects with `path` rather than the linker named by the `LD` environment
+    /// variable, or `ld` on the `PATH` if that isn't set.
+    fn linker(&mut self, path: PathBuf) {
+        self.as_lucetc().linker = Some(path);
+    }
+
+    fn with_linker(mut self, path: PathBuf) -> Self {
+        self.linker(path);
+        self
+    }
+
+    /// Fail compilation if any function's stack frame is larger than `bytes`.
+    fn max_stack_per_function(&mut self, bytes: u32) {
+        self.as_lucetc().codegen.max_stack_per_function = Some(bytes);
+    }
+
+    fn with_max_stack_per_function(mut self, bytes: u32) -> Self {
+        self.max_stack_per_function(bytes);
+        self
+    }
+
+    /// Fail compilation with `LucetcErrorKind::TooManyLocals` if any function declares more than
+    /// `count` locals, not counting its parameters. This is checked before the function is
+    /// translated, so a module with millions of locals is turned away cheaply.
+    fn max_locals_per_function(&mut self, count: u32) {
+        self.as_lucetc().codegen.max_locals_per_function = Some(count);
+    }
+
+    fn with_max_locals_per_function(mut self, count: u32) -> Self {
+        self.max_locals_per_function(count);
+        self
+    }
+
+    fn target(&mut self, target: Triple) {
+        self.as_lucetc().codegen.target = Some(target);
+    }
+
+    fn with_target(mut self, target: Triple) -> Self {
+        self.target(target);
+        self
+    }
+
+    /// Also define `c_symbol` at the address of the function exported as `wasm_export`, for hosts
+    /// that call it by a plain C name. An export may have any number of aliases.
+    fn export_alias(&mut self, wasm_export: &str, c_symbol: &str) {
+        self.as_lucetc()
+            .export_aliases
+            .push((wasm_export.to_owned(), c_symbol.to_owned()));
+    }
+
+    fn with_export_alias(mut self, wasm_export: &str, c_symbol: &str) -> Self {
+        self.export_alias(wasm_export, c_symbol);
+        self
+    }
+
+    /// Where generated code finds linear memory. See `HeapBase::FixedOffset` before changing this.
+    fn heap_base(&mut self, heap_base: HeapBase) {
+        self.as_lucetc().codegen.heap_base = heap_base;
+    }
+
+    fn with_heap_base(mut self, heap_base: HeapBase) -> Self {
+        self.heap_base(heap_base);
+        self
+    }
+
+    /// Rewrite each relocation of the object file before it is written out.
+    fn relocation_hook<F: Fn(&mut Relocation) + 'static>(&mut self, hook: F) {
+        self.as_lucetc().relocation_hook = Some(Box::new(hook));
+    }
+
+    fn with_relocation_hook<F: Fn(&mut Relocation) + 'static>(mut self, hook: F) -> Self {
+        self.relocation_hook(hook);
+        self
+    }
+
+    /// Pass the name of each function export through `filter` before compiling: `None` hides
+    /// the export, and `Some(name)` exports the function as `name` instead. A hidden function is
+    /// still compiled, under an internal symbol. Export aliases refer to the names the filter
+    /// leaves.
+    fn export_filter<F: Fn(&str) -> Option<String> + 'static>(&mut self, filter: F) {
+        self.as_lucetc().export_filter = Some(Box::new(filter));
+    }
+
+    fn with_export_filter<F: Fn(&str) -> Option<String> + 'static>(mut self, filter: F) -> Self {
+        self.export_filter(filter);
+        self
+    }
+
+    /// Instead of an object, write a flat image of the module's code and data linked to run at
+    /// address `base`, with every relocation already applied. The module can't import functions
+    /// or use the runtime's heap helpers, since the image has no way to refer to code outside
+    /// it. Shared objects can't be built this way.
+    fn absolute_base(&mut self, base: Option<u64>) {
+        self.as_lucetc().absolute_base = base;
+    }
+
+    fn with_absolute_base(mut self, base: Option<u64>) -> Self {
+        self.absolute_base(base);
+        self
+    }
+
+    /// Send warnings and notes raised while compiling to `sink`, as well as to the log.
+    fn diagnostics<F: Fn(Diagnostic) + 'static>(&mut self, sink: F) {
+        self.as_lucetc().codegen.diagnostics = Diagnostics::new(sink);
+    }
+
+    fn with_diagnostics<F: Fn(Diagnostic) + 'static>(mut self, sink: F) -> Self {
+        self.diagnostics(sink);
+        self
+    }
+
+    /// Warn about code that can never run, such as instructions after a `return`, `br`, or
+    /// `unreachable`, naming the function and the index of the first dead instruction in its
+    /// body. It is valid wasm, but can point to a bug in whatever produced the module.
+    fn warn_unreachable_code(&mut self, warn: bool) {
+        self.as_lucetc().codegen.warn_unreachable_code = warn;
+    }
+
+    fn with_warn_unreachable_code(mut self, warn: bool) -> Self {
+        self.warn_unreachable_code(warn);
+        self
+    }
+
+    /// Record `value` under `key` in `lucet_user_metadata`, for stamping the object with
+    /// details such as the source commit or build time. Pairs are kept in the order they were
+    /// first given; giving a key again replaces its value. Read them back with
+    /// `compiler::user_metadata::parse_user_metadata`.
+    fn metadata(&mut self, key: &str, value: &[u8]) {
+        let pairs = &mut self.as_lucetc().codegen.user_metadata;
+        match pairs.iter_mut().find(|(k, _)| k == key) {
+            Some(pair) => pair.1 = value.to_vec(),
+            None => pairs.push((key.to_owned(), value.to_vec())),
+        }
+    }
+
+    fn with_metadata(mut self, key: &str, value: &[u8]) -> Self {
+        self.metadata(key, value);
+        self
+    }
+
+    /// Write `lucet_trap_messages`, giving the `unreachable` traps in the function named by each
+    /// key its value as a message, such as "assertion failed in foo". Functions are named as in
+    /// the export or name section. The traps of other named functions get a message naming the
+    /// function.
+    fn trap_messages(&mut self, messages: HashMap<String, String>) {
+        self.as_lucetc().codegen.trap_messages = Some(messages.into_iter().collect());
+    }
+
+    fn with_trap_messages(mut self, messages: HashMap<String, String>) -> Self {
+        self.trap_messages(messages);
+        self
+    }
+
+    /// Name the heap spec, trap manifest, and other metadata symbols with `policy` instead of
+    /// the names lucet-runtime looks for.
+    fn symbol_naming<N: SymbolNaming + 'static>(&mut self, policy: N) {
+        self.as_lucetc().codegen.symbol_naming = Naming::new(policy);
+    }
+
+    fn with_symbol_naming<N: SymbolNaming + 'static>(mut self, policy: N) -> Self {
+        self.symbol_naming(policy);
+        self
+    }
+
+    /// Refuse to compile modules carrying custom sections the policy doesn't permit.
+    fn custom_section_policy(&mut self, policy: CustomSectionPolicy) {
+        self.as_lucetc().custom_section_policy = policy;
+    }
+
+    fn with_custom_section_policy(mut self, policy: CustomSectionPolicy) -> Self {
+        self.custom_section_policy(policy);
+        self
+    }
+
+    /// Use a fixed mapping of `call_indirect` signature IDs, so that separately compiled modules
+    /// sharing a table agree on them.
+    fn type_ids(&mut self, ids: TypeIds) {
+        self.as_lucetc().type_ids = Some(ids);
+    }
+
+    fn with_type_ids(mut self, ids: TypeIds) -> Self {
+        self.type_ids(ids);
+        self
+    }
+
+    /// Emit only the symbols needed to load and run the module:
+    ///
+    /// - `guest_func_*` for each exported function, and `guest_start` if there is a start function
+    /// - `lucet_heap_spec`, `lucet_globals_spec`, `lucet_module_data`, and `lucet_module_data_len`
+    /// - `wasm_data_segments`, `wasm_data_segments_len`, and `guest_sparse_page_data`
+    /// - `guest_table_*`, `lucet_elements`, and `lucet_elements_len`
+    /// - `lucet_trap_manifest` and `lucet_trap_manifest_len`
+    /// - `lucet_heap_base`, when compiling with `HeapBase::FixedOffset`
+    ///
+    /// Internal functions are named by index rather than by the name section, and the
+    /// `lucet_trap_table_*` of each function become local symbols. The module's custom sections,
+    /// including the name section, are ignored.
+    fn strip(&mut self, strip: bool) {
+        self.as_lucetc().codegen.strip = strip;
+    }
+
+    fn with_strip(mut self, strip: bool) -> Self {
+        self.strip(strip);
+        self
+    }
+
+    /// Skip the bounds check of loads and stores whose address is a constant, when the whole
+    /// access is known to be inside the heap's reserved region.
+    fn elide_bounds_checks(&mut self, elide: bool) {
+        self.as_lucetc().codegen.elide_bounds_checks = elide;
+    }
+
+    fn with_elide_bounds_checks(mut self, elide: bool) -> Self {
+        self.elide_bounds_checks(elide);
+        self
+    }
+
+    /// Leave stack probes out of function prologues. Compilation fails if a function's frame is
+    /// larger than the heap's guard region, since overflowing it could then go undetected.
+    fn elide_stack_probes(&mut self, elide: bool) {
+        self.as_lucetc().codegen.elide_stack_probes = elide;
+    }
+
+    fn with_elide_stack_probes(mut self, elide: bool) -> Self {
+        self.elide_stack_probes(elide);
+        self
+    }
+
+    /// Trap with `traps::SIGNED_OVERFLOW` when `i32` or `i64` `add`, `sub`, or `mul` overflows
+    /// as a signed operation, for embedders that want C's undefined signed overflow caught.
+    ///
+    /// This is not WebAssembly: the spec defines these operations to wrap, and a correct module
+    /// may rely on that, for example in hashing or when the values are unsigned. Off by default.
+    fn trap_on_signed_overflow(&mut self, trap: bool) {
+        self.as_lucetc().codegen.trap_on_signed_overflow = trap;
+    }
+
+    fn with_trap_on_signed_overflow(mut self, trap: bool) -> Self {
+        self.trap_on_signed_overflow(trap);
+        self
+    }
+
+    /// Mark the module as wanting hardware floating-point exceptions, so that the runtime
+    /// unmasks them while it runs. This breaks WebAssembly semantics, which never trap on
+    /// floating-point arithmetic, and is meant for debugging where NaNs come from. Off by default.
+    fn fp_traps(&mut self, enable: bool) {
+        self.as_lucetc().codegen.fp_traps = enable;
+    }
+
+    fn with_fp_traps(mut self, enable: bool) -> Self {
+        self.fp_traps(enable);
+        self
+    }
+
+    /// Record the module as a WASI command or reactor. Without this, `_start` marks a command and
+    /// `_initialize` a reactor; compilation fails if the module's exports don't fit the choice.
+    fn wasi_abi(&mut self, abi: WasiAbi) {
+        self.as_lucetc().codegen.wasi_abi = Some(abi);
+    }
+
+    fn with_wasi_abi(mut self, abi: WasiAbi) -> Self {
+        self.wasi_abi(abi);
+        self
+    }
+
+    /// Whether a function lucetc can't translate fails the compilation, or is replaced by a stub
+    /// that traps so the rest of the module can still be run.
+    fn on_unsupported_function(&mut self, policy: UnsupportedFunctionPolicy) {
+        self.as_lucetc().codegen.on_unsupported_function = policy;
+    }
+
+    fn with_on_unsupported_function(mut self, policy: UnsupportedFunctionPolicy) -> Self {
+        self.on_unsupported_function(policy);
+        self
+    }
+
+    /// Whether a data segment placed at an imported global fails the compilation, or is copied
+    /// into the heap by `guest_deferred_data_init`. The runtime has to call that function with
+    /// the instance's vmctx after setting its globals and before running any other code.
+    fn non_constant_segment_offset(&mut self, policy: SegmentOffsetPolicy) {
+        self.as_lucetc().codegen.non_constant_segment_offset = policy;
+    }
+
+    fn with_non_constant_segment_offset(mut self, policy: SegmentOffsetPolicy) -> Self {
+        self.non_constant_segment_offset(policy);
+        self
+    }
+
+    /// Declare imported functions sorted by module and field name, so that modules differing
+    /// only in the order of their import sections produce the same symbols and relocations.
+    fn sort_imports(&mut self, sort: bool) {
+        self.as_lucetc().codegen.sort_imports = sort;
+    }
+
+    fn with_sort_imports(mut self, sort: bool) -> Self {
+        self.sort_imports(sort);
+        self
+    }
+
+    /// Give up with `LucetcErrorKind::Cancelled` if translating and generating code takes longer
+    /// than `budget`. Checks happen between functions, so the budget can be overrun by however
+    /// long the slowest function takes. Reading the module is not counted.
+    fn time_budget(&mut self, budget: Duration) {
+        self.as_lucetc().codegen.time_budget = Some(budget);
+    }
+
+    fn with_time_budget(mut self, budget: Duration) -> Self {
+        self.time_budget(budget);
+        self
+    }
+
+    /// Also put the module's initialized pages in `lucet_rodata`, described by
+    /// `lucet_rodata_spec`, alongside the usual `wasm_data_segments` and
+    /// `guest_sparse_page_data`. The data is still the initial contents of the writable heap, so
+    /// a runtime sharing it between instances has to map it copy-on-write.
+    fn separate_rodata(&mut self, separate: bool) {
+        self.as_lucetc().codegen.separate_rodata = separate;
+    }
+
+    fn with_separate_rodata(mut self, separate: bool) -> Self {
+        self.separate_rodata(separate);
+        self
+    }
+
+    /// With `DataInit::Snapshot`, also write the initial heap as one contiguous image, so
+    /// instantiating the module can be a single copy rather than a walk over segments and pages.
+    /// Everything below the last initialized byte is in the image, zeroes included, so the
+    /// object can be much larger. This can't be combined with `separate_rodata`.
+    fn data_init(&mut self, init: DataInit) {
+        self.as_lucetc().codegen.data_init = init;
+    }
+
+    fn with_data_init(mut self, init: DataInit) -> Self {
+        self.data_init(init);
+        self
+    }
+
+    /// Let a linker run with `--gc-sections` drop the functions that can't be reached from an
+    /// export, a table, or the start function. Each function is always emitted in its own
+    /// `.text.<symbol>` section, but `lucet_trap_manifest` points at every one of them, so none
+    /// can be collected; with this set, the manifest leaves the unreachable ones out.
+    fn function_sections(&mut self, sections: bool) {
+        self.as_lucetc().codegen.function_sections = sections;
+    }
+
+    fn with_function_sections(mut self, sections: bool) -> Self {
+        self.function_sections(sections);
+        self
+    }
+
+    /// Proposals beyond the MVP that the module may use.
+    fn wasm_features(&mut self, features: WasmFeatures) {
+        self.as_lucetc().features = features;
+    }
+
+    fn with_wasm_features(mut self, features: WasmFeatures) -> Self {
+        self.wasm_features(features);
+        self
+    }
+
+    /// How to compile the module's atomic memory instructions. This is the `atomics` field of
+    /// the features set by `wasm_features`, so whichever is called last wins.
+    fn atomics(&mut self, atomics: Atomics) {
+        self.as_lucetc().features.atomics = atomics;
+    }
+
+    fn with_atomics(mut self, atomics: Atomics) -> Self {
+        self.atomics(atomics);
+        self
+    }
+
+    fn build_id(&mut self, build_id: BuildId) {
+        self.as_lucetc().build_id = build_id;
+    }
+
+    fn with_build_id(mut self, build_id: BuildId) -> Self {
+        self.build_id(build_id);
+        self
+    }
 }
 
 impl Lucetc {
+    /// Settings recommended by the module's `lucet.config` section are applied here, so any
+    /// `LucetcOpts` call made afterwards takes precedence over them.
     pub fn new<P: AsRef<Path>>(input: P) -> Self {
         let input = input.as_ref();
-        Self {
+        let mut lucetc = Self {
             input: input.to_owned(),
             bindings: vec![],
-            opt_level: OptLevel::default(),
+            codegen: CodegenSettings::default(),
             heap: HeapSettings::default(),
             builtins_paths: vec![],
+            extra_objects: vec![],
+            linker: None,
+            relocation_hook: None,
+            export_filter: None,
+            absolute_base: None,
+            export_aliases: vec![],
+            custom_section_policy: CustomSectionPolicy::default(),
+            type_ids: None,
+            features: WasmFeatures::default(),
+            build_id: BuildId::default(),
+        };
+        // Errors reading the config are reported by `build`, which reads it the same way.
+        if let Ok(Some(config)) = read_module_config(input) {
+            lucetc.apply_config(&config);
+        }
+        lucetc
+    }
+
+    fn apply_config(&mut self, config: &ModuleConfig) {
+        if let Some(min_reserved_size) = config.min_reserved_size {
+            self.min_reserved_size(min_reserved_size);
+        }
+        if let Some(max_reserved_size) = config.max_reserved_size {
+            self.max_reserved_size(max_reserved_size);
+        }
+        if let Some(guard_size) = config.guard_size {
+            self.guard_size(guard_size);
+        }
+        if let Some(opt_level) = config.opt_level {
+            self.opt_level(opt_level);
         }
     }
 
     fn build(&self) -> Result<(String, Module, Bindings), Error> {
+        self.build_with_bindings(&self.bindings)
+    }
+
+    /// Like `build`, but with `user_bindings` in place of those given with
+    /// `LucetcOpts::bindings`.
+    fn build_with_bindings(
+        &self,
+        user_bindings: &[Bindings],
+    ) -> Result<(String, Module, Bindings), Error> {
         let name = String::from(
             self.input
                 .file_stem()
@@ -147,40 +896,528 @@ impl Lucetc {
                 ))?,
         );
         let mut builtins_bindings = vec![];
-        let mut module = read_module(&self.input)?;
+        let mut module = read_module_with_features(&self.input, &self.features)?;
+        check_custom_sections(&module, &self.custom_section_policy)?;
+        read_module_config(&self.input)?;
 
         for builtins in self.builtins_paths.iter() {
-            let (newmodule, builtins_map) = patch_module(module, builtins)?;
+            let (newmodule, builtins_map) =
+                patch_module(module, builtins, &self.codegen.diagnostics)?;
             module = newmodule;
             builtins_bindings.push(Bindings::env(builtins_map));
         }
 
         let mut bindings = Bindings::empty();
 
-        for binding in builtins_bindings.iter().chain(self.bindings.iter()) {
+        for binding in builtins_bindings.iter().chain(user_bindings.iter()) {
             bindings.extend(binding)?;
         }
 
         Ok((name, module, bindings))
     }
 
-    pub fn object_file<P: AsRef<Path>>(self, output: P) -> Result<(), Error> {
-        let (name, module, bindings) = self.build()?;
+    fn program(&self) -> Result<(String, Program), Error> {
+        let (name, mut module, bindings) = self.build()?;
+        check_features(&module)?;
+        self.filter_exports(&mut module)?;
+        if self.codegen.strip {
+            module.sections_mut().retain(|s| match s {
+                Section::Custom(_) | Section::Name(_) => false,
+                _ => true,
+            });
+        }
+        let mut prog = Program::new(module, bindings, self.heap.clone())?;
+        if let Some(ref ids) = self.type_ids {
+            prog.assign_type_ids(ids)?;
+        }
+        Ok((name, prog))
+    }
+
+    fn filter_exports(&self, module: &mut Module) -> Result<(), Error> {
+        let filter = match self.export_filter {
+            Some(ref filter) => filter,
+            None => return Ok(()),
+        };
+        let entries = match module.export_section_mut() {
+            Some(section) => section.entries_mut(),
+            None => return Ok(()),
+        };
+        let mut kept = vec![];
+        for entry in entries.drain(..) {
+            match *entry.internal() {
+                Internal::Function(index) => {
+                    if let Some(name) = filter(entry.field()) {
+                        kept.push(ExportEntry::new(name, Internal::Function(index)));
+                    }
+                }
+                _ => kept.push(entry),
+            }
+        }
+        let mut names = HashSet::new();
+        for entry in kept.iter() {
+            if !names.insert(entry.field()) {
+                Err(format_err!(
+                    "the export filter leaves more than one export named {}",
+                    entry.field()
+                ))?;
+            }
+        }
+        *entries = kept;
+        Ok(())
+    }
 
-        let prog = Program::new(module, bindings, self.heap)?;
-        let comp = compile(&prog, &name, self.opt_level)?;
+    fn alias_exports(&self, prog: &Program, obj: &mut ObjectFile) -> Result<(), Error> {
+        let exports = prog
+            .module()
+            .export_section()
+            .map(|s| s.entries())
+            .unwrap_or(&[]);
+        for (export, alias) in self.export_aliases.iter() {
+            let index = exports
+                .iter()
+                .find_map(|e| match e.internal() {
+                    Internal::Function(index) if e.field() == export => Some(*index),
+                    _ => None,
+                })
+                .ok_or(format_err!(
+                    "cannot alias {}: no function is exported by that name",
+                    export
+                ))?;
+            let func = prog.get_function(index)?;
+            if func.linkage() == Linkage::Import {
+                Err(format_err!(
+                    "cannot alias {}: it re-exports an imported function",
+                    export
+                ))?;
+            }
+            obj.alias(func.symbol(), alias);
+        }
+        Ok(())
+    }
 
-        let obj = comp.codegen()?;
-        obj.write(output.as_ref()).context("writing object file")?;
+    fn write_object(&self, obj: &mut ObjectFile, output: &Path) -> Result<(), Error> {
+        obj.build_id(self.build_id == BuildId::Sha1);
+        match (self.absolute_base, &self.relocation_hook) {
+            (Some(base), Some(hook)) => obj.write_flat(output, base, |r| hook(r)),
+            (Some(base), None) => obj.write_flat(output, base, |_| ()),
+            (None, Some(hook)) => obj.write_rewriting_relocations(output, |r| hook(r)),
+            (None, None) => obj.write(output),
+        }
+        .context("writing object file")?;
+        Ok(())
+    }
 
+    fn check_linkable(&self) -> Result<(), Error> {
+        if self.absolute_base.is_some() {
+            Err(format_err!(
+                "a flat image at an absolute base can't be linked into a shared object"
+            )
+            .context(LucetcErrorKind::Unsupported("absolute base".to_owned())))?;
+        }
         Ok(())
     }
 
-    pub fn clif_ir<P: AsRef<Path>>(self, output: P) -> Result<(), Error> {
+    pub fn effective_settings(&self) -> EffectiveSettings {
+        EffectiveSettings {
+            opt_level: self.codegen.opt_level,
+            heap: self.heap.clone(),
+            target: self.codegen.target.clone(),
+            features: self.features.clone(),
+        }
+    }
+
+    /// Where the generated code will look for the heap and globals relative to the vmctx, as
+    /// also written to the `lucet_vmctx_layout` symbol.
+    pub fn vmctx_layout(&self) -> VmctxLayout {
+        VmctxLayout::new(&self.codegen.heap_base)
+    }
+
+    /// The proposals the module uses beyond the MVP, for deciding which `wasm_features` to
+    /// enable, or whether to take the module at all. Nothing is decoded or compiled, so this
+    /// works for modules using proposals lucetc can't compile. A module that can't be read uses
+    /// nothing.
+    pub fn detected_features(&self) -> FeatureSet {
+        read_to_u8s(&self.input)
+            .and_then(to_binary)
+            .map(|wasm| detect_features(&wasm))
+            .unwrap_or_else(|_| FeatureSet::empty())
+    }
+
+    /// The target and the Cranelift shared flags code would be generated with, in Cranelift's
+    /// `name = value` format. Nothing is compiled.
+    pub fn dump_flags(&self) -> Result<String, Error> {
+        let isa = isa_builder(&self.codegen)?.finish(shared_flags(&self.codegen)?);
+        Ok(format!("target = \"{}\"\n{}", isa.triple(), isa.flags()))
+    }
+
+    /// A hash of everything that determines the object `object_file` would write: the module
+    /// after builtins are substituted, the bindings, and the settings that affect code
+    /// generation, along with the version of lucetc. Nothing is compiled.
+    ///
+    /// A relocation hook or export filter is only accounted for by its presence, since what it
+    /// does can't be inspected.
+    pub fn output_hash(&self) -> Result<[u8; 32], Error> {
         let (name, module, bindings) = self.build()?;
+        let mut hasher = Sha256::new();
+        let mut field = |bytes: &[u8]| {
+            hasher.input(&(bytes.len() as u64).to_le_bytes());
+            hasher.input(bytes);
+        };
+        field(env!("CARGO_PKG_VERSION").as_bytes());
+        field(name.as_bytes());
+        field(&parity_wasm::serialize(module)?);
+        field(bindings.to_json().to_string().as_bytes());
+
+        let codegen = &self.codegen;
+        let mut hints: Vec<_> = codegen.function_hints.iter().collect();
+        hints.sort_by(|a, b| a.0.cmp(b.0));
+        let hot = codegen.lazy_compile.as_ref().map(|hot| {
+            let mut hot: Vec<_> = hot.iter().collect();
+            hot.sort();
+            hot
+        });
+        let target = codegen
+            .target
+            .as_ref()
+            .map(|t| t.to_string())
+            .unwrap_or_else(|| "host".to_owned());
+        let settings = format!(
+            "{} {:?} {:?} {} {:?} {} {} {} {} {} {:?} {:?} {:?} {} {} {:?} {}",
+            codegen.opt_level.to_flag(),
+            hints,
+            hot,
+            target,
+            codegen.heap_base,
+            codegen.strip,
+            codegen.elide_bounds_checks,
+            codegen.elide_stack_probes,
+            codegen.trap_on_signed_overflow,
+            codegen.fp_traps,
+            codegen.on_unsupported_function,
+            codegen.non_constant_segment_offset,
+            codegen.wasi_abi,
+            codegen.sort_imports,
+            codegen.separate_rodata,
+            codegen.data_init,
+            codegen.function_sections,
+        );
+        field(settings.as_bytes());
+        field(format!("{:?}", codegen.symbol_naming.names()).as_bytes());
+        field(&serialize_user_metadata(&codegen.user_metadata));
+        field(format!("{:?}", codegen.trap_messages).as_bytes());
+        let heap = format!(
+            "{} {} {} {:?} {:?} {:?}",
+            self.heap.min_reserved_size,
+            self.heap.max_reserved_size,
+            self.heap.guard_size,
+            self.heap.grow_zeroing,
+            self.heap.reservation_alignment,
+            self.heap.guard_placement,
+        );
+        field(heap.as_bytes());
+        field(format!("{:?}", self.features).as_bytes());
+        let type_ids = self.type_ids.as_ref().map(|ids| ids.to_json().to_string());
+        field(format!("{:?}", type_ids).as_bytes());
+        field(format!("{:?}", self.export_aliases).as_bytes());
+        field(&[self.relocation_hook.is_some() as u8]);
+        field(&[self.export_filter.is_some() as u8]);
+        field(format!("{:?}", self.absolute_base).as_bytes());
+        field(format!("{:?}", self.build_id).as_bytes());
+
+        let mut hash = [0; 32];
+        hash.copy_from_slice(&hasher.result());
+        Ok(hash)
+    }
+
+    /// A JSON summary of the module and the settings it would be compiled with. This only decodes
+    /// the module; nothing is compiled.
+    pub fn report_json(&self) -> Result<String, Error> {
+        let (_name, module, bindings) = self.build()?;
+        let report = module_report(
+            &module,
+            self.detected_features(),
+            &self.features,
+            &bindings,
+            &self.heap,
+            &self.codegen,
+        );
+        Ok(report.to_string())
+    }
+
+    /// The symbols the module's object leaves undefined for the host to provide, sorted: the
+    /// bound symbol of each imported function, and the runtime's heap helpers. The stack probe is
+    /// not among them, since every object defines its own.
+    pub fn required_host_symbols(&self) -> Result<Vec<String>, Error> {
+        let (_name, prog) = self.program()?;
+        let imports = prog.import_functions().iter().map(|f| f.symbol());
+        let runtime = prog.runtime_functions().iter().map(|f| f.symbol());
+        let on_demand = Runtime::compile_on_demand();
+        let lazy = self.codegen.lazy_compile.iter().map(|_| on_demand.symbol());
+        let mut symbols: Vec<String> = imports
+            .chain(runtime)
+            .chain(lazy)
+            .map(String::from)
+            .collect();
+        symbols.sort();
+        symbols.dedup();
+        Ok(symbols)
+    }
+
+    /// The signature the module calls each of `required_host_symbols` with, vmctx argument
+    /// included, for generating typed host stubs. Fails if a symbol is called with two different
+    /// signatures.
+    pub fn required_host_signatures(&self) -> Result<Vec<(String, ir::Signature)>, Error> {
+        let (_name, prog) = self.program()?;
+        let imports = prog
+            .import_functions()
+            .iter()
+            .map(|f| (f.symbol(), f.signature()));
+        let runtime = prog
+            .runtime_functions()
+            .iter()
+            .map(|f| (f.symbol(), f.signature()));
+        let on_demand = Runtime::compile_on_demand();
+        let lazy = self
+            .codegen
+            .lazy_compile
+            .iter()
+            .map(|_| (on_demand.symbol(), on_demand.signature()));
+        let mut signatures: BTreeMap<String, ir::Signature> = BTreeMap::new();
+        for (symbol, signature) in imports.chain(runtime).chain(lazy) {
+            match signatures.entry(symbol.to_owned()) {
+                btree_map::Entry::Vacant(vac) => {
+                    vac.insert(signature);
+                }
+                btree_map::Entry::Occupied(occ) => {
+                    if *occ.get() != signature {
+                        Err(format_err!(
+                            "`{}` is called both as `{}` and as `{}`",
+                            symbol,
+                            occ.get(),
+                            signature
+                        ))?;
+                    }
+                }
+            }
+        }
+        Ok(signatures.into_iter().collect())
+    }
+
+    /// Every problem that would stop the module from compiling and that can be found without
+    /// translating it: unsupported features, unbound imports, validation errors, and a memory
+    /// too large for the heap settings. Unlike the other entry points, this carries on past the
+    /// first problem. Only a module that can't be read at all yields a single
+    /// `Problem::Unreadable`.
+    pub fn check(&self) -> CheckReport {
+        match self.build() {
+            Ok((_name, module, bindings)) => check_module(&module, &bindings, &self.heap),
+            Err(e) => CheckReport {
+                problems: vec![Problem::Unreadable(e.to_string())],
+            },
+        }
+    }
+
+    /// The signature of each export the compiled module would have, after the export filter.
+    /// Nothing is compiled, and imports don't need to be bound.
+    pub fn module_signature(&self) -> Result<ModuleSignature, Error> {
+        let (_name, mut module, _bindings) = self.build()?;
+        self.filter_exports(&mut module)?;
+        module_signature(&module)
+    }
+
+    /// The exports added, removed, or changed in `newer` compared to this module, for checking
+    /// that a new version of a module still offers what its host relies on.
+    pub fn interface_diff(&self, newer: &Lucetc) -> Result<InterfaceDiff, Error> {
+        Ok(self.module_signature()?.diff(&newer.module_signature()?))
+    }
+
+    /// The function imports that the bindings, including those for builtins, leave unbound, and
+    /// why. Nothing is compiled.
+    pub fn verify_bindings(&self) -> Result<Vec<UnboundImport>, Error> {
+        let (_name, module, bindings) = self.build()?;
+        bindings.verify(&module)
+    }
+
+    /// The module's `dylink` section, if it was built as a side module for dynamic linking.
+    pub fn dylink_info(&self) -> Result<Option<DylinkInfo>, Error> {
+        let (_name, module, _bindings) = self.build()?;
+        dylink_info(&module)
+    }
+
+    /// The decoded body of each function the module defines, for analyses that don't need to
+    /// compile it.
+    pub fn functions(&self) -> Result<impl Iterator<Item = FunctionBody>, Error> {
+        let (_name, module, _bindings) = self.build()?;
+        Ok(function_bodies(module)?.into_iter())
+    }
+
+    /// The signature IDs `call_indirect` will check, keyed by signature. Pass these to
+    /// `LucetcOpts::type_ids` when compiling another module that shares a table with this one.
+    pub fn assigned_type_ids(&self) -> Result<TypeIds, Error> {
+        let (_name, prog) = self.program()?;
+        Ok(prog.type_ids())
+    }
+
+    pub fn object_file<P: AsRef<Path>>(self, output: P) -> Result<(), Error> {
+        let (name, prog) = self.program()?;
+        let comp = compile_with_settings(&prog, &name, &self.codegen)?;
+
+        let mut obj = comp.codegen()?;
+        self.alias_exports(&prog, &mut obj)?;
+        self.write_object(&mut obj, output.as_ref())?;
+
+        Ok(())
+    }
+
+    /// Rebind the imported functions of `object`, an object compiled from this module with its
+    /// current bindings, to the symbols `bindings` gives them, without recompiling. `bindings`
+    /// takes the place of every set given with `LucetcOpts::bindings`; builtins stay bound as
+    /// they were. The code doesn't depend on the host symbols, so only the symbols its
+    /// relocations refer to are changed, and the object is rewritten in place.
+    ///
+    /// Fails if an import's addend changes, if imports that shared a symbol are bound apart, or
+    /// if an imported global gains or loses a host symbol, since each needs different code.
+    pub fn relink_with_bindings<P: AsRef<Path>>(
+        &self,
+        object: P,
+        bindings: Bindings,
+    ) -> Result<(), Error> {
+        let (_name, module, old_bindings) = self.build()?;
+        let (_name, _module, new_bindings) = self.build_with_bindings(&[bindings])?;
+        let entries = module.import_section().map(|s| s.entries()).unwrap_or(&[]);
+        let mut renames: HashMap<String, String> = HashMap::new();
+        for entry in entries {
+            let (module, field) = (entry.module(), entry.field());
+            let (old, new) = match entry.external() {
+                External::Function(_) => {
+                    if old_bindings.addend(module, field) != new_bindings.addend(module, field) {
+                        Err(format_err!(
+                            "cannot relink {}::{}: its addend changed, so it must be recompiled",
+                            module,
+                            field
+                        ))?;
+                    }
+                    (
+                        old_bindings.translate(module, field)?,
+                        new_bindings.translate(module, field)?,
+                    )
+                }
+                External::Global(_) => match (
+                    old_bindings.translate(module, field),
+                    new_bindings.translate(module, field),
+                ) {
+                    (Ok(old), Ok(new)) => (old, new),
+                    (Err(_), Err(_)) => continue,
+                    _ => Err(format_err!(
+                        "cannot relink global {}::{}: whether it is bound to a host symbol changed, so it must be recompiled",
+                        module,
+                        field
+                    ))?,
+                },
+                _ => continue,
+            };
+            match renames.entry(old.clone()) {
+                hash_map::Entry::Vacant(vac) => {
+                    vac.insert(new);
+                }
+                hash_map::Entry::Occupied(occ) => {
+                    if *occ.get() != new {
+                        Err(format_err!(
+                            "cannot relink {}::{} to {}: other imports bound to {} are bound to {}, so it must be recompiled",
+                            module,
+                            field,
+                            new,
+                            old,
+                            occ.get()
+                        ))?;
+                    }
+                }
+            }
+        }
+        renames.retain(|old, new| old != new);
+
+        let obj = std::fs::read(object.as_ref()).context("reading object file")?;
+        let obj = rebind_symbols(&obj, &renames)?;
+        std::fs::write(object.as_ref(), obj).context("writing relinked object file")?;
+        Ok(())
+    }
+
+    /// Like `object_file`, but also write a JSON map of the functions in the object to `map`:
+    /// an object with a `functions` array giving the `symbol`, `section`, `offset` within the
+    /// section, `file_offset` within the object, and `size` of each. The map is read back from
+    /// the object as written, so it reflects any relocation hook and export aliases.
+    pub fn object_file_with_map<P: AsRef<Path>, Q: AsRef<Path>>(
+        self,
+        output: P,
+        map: Q,
+    ) -> Result<(), Error> {
+        if self.absolute_base.is_some() {
+            Err(format_err!("a flat image has no symbols to map")
+                .context(LucetcErrorKind::Unsupported("absolute base".to_owned())))?;
+        }
+        self.object_file(output.as_ref())?;
+        let obj = std::fs::read(output.as_ref()).context("reading back object file")?;
+        let layout = function_layout_json(&function_layout(&obj)?);
+        std::fs::write(map.as_ref(), serde_json::to_string_pretty(&layout)?)
+            .context("writing symbol map")?;
+        Ok(())
+    }
+
+    /// Like `object_file`, but also reports how long each phase took.
+    pub fn object_file_timed<P: AsRef<Path>>(self, output: P) -> Result<CompileTimings, Error> {
+        let mut timings = CompileTimings::default();
+
+        let start = Instant::now();
+        let (name, prog) = self.program()?;
+        timings.parse = start.elapsed();
+
+        let start = Instant::now();
+        let mut comp = Compiler::new(name, &prog, &self.codegen)?;
+        timings.declare = start.elapsed();
+
+        let start = Instant::now();
+        translate(&mut comp)?;
+        let mut obj = comp.codegen()?;
+        timings.compile = start.elapsed();
 
-        let prog = Program::new(module, bindings, self.heap.clone())?;
-        let comp = compile(&prog, &name, self.opt_level)?;
+        let start = Instant::now();
+        self.alias_exports(&prog, &mut obj)?;
+        self.write_object(&mut obj, output.as_ref())?;
+        timings.emit = start.elapsed();
+
+        Ok(timings)
+    }
+
+    /// Compile into executable memory in this process instead of writing an object file.
+    #[cfg(feature = "jit")]
+    pub fn jit(self) -> Result<JitModule, Error> {
+        let (_name, prog) = self.program()?;
+        let mut comp = Compiler::new_jit(&prog, &self.codegen)?;
+        translate(&mut comp)?;
+
+        Ok(comp.jit()?)
+    }
+
+    /// Compile into executable memory and run `vectors` against the module, reporting each one
+    /// whose result isn't the expected one. See `vectors::run_vectors`.
+    #[cfg(feature = "jit")]
+    pub fn run_test_vectors(self, vectors: &[TestVector]) -> Result<VectorReport, Error> {
+        let mut jit = self.jit()?;
+        Ok(vectors::run_vectors(&mut jit, vectors))
+    }
+
+    /// Write a C header declaring the functions the module exports, as `header::c_header`
+    /// describes. Nothing is compiled.
+    pub fn emit_c_header<P: AsRef<Path>>(self, output: P) -> Result<(), Error> {
+        let (name, prog) = self.program()?;
+        let header = c_header(&name, &prog, &self.export_aliases)?;
+        std::fs::write(output.as_ref(), header).context("writing C header")?;
+        Ok(())
+    }
+
+    pub fn clif_ir<P: AsRef<Path>>(self, output: P) -> Result<(), Error> {
+        let (name, prog) = self.program()?;
+        let comp = compile_with_settings(&prog, &name, &self.codegen)?;
 
         comp.cranelift_funcs()
             .write(&output)
@@ -189,13 +1426,51 @@ impl Lucetc {
         Ok(())
     }
 
+    /// Write the control flow graph of each function, or only of the one with symbol `func`,
+    /// in Graphviz `.dot` format. The graphs are of the Cranelift IR as translated, before it is
+    /// optimized or legalized.
+    pub fn emit_cfg<P: AsRef<Path>>(self, func: Option<&str>, output: P) -> Result<(), Error> {
+        let (name, prog) = self.program()?;
+        let comp = compile_with_settings(&prog, &name, &self.codegen)?;
+
+        comp.cranelift_funcs()
+            .write_cfg(func, &output)
+            .context("writing cfg file")?;
+
+        Ok(())
+    }
+
     pub fn shared_object_file<P: AsRef<Path>>(self, output: P) -> Result<(), Error> {
+        self.check_linkable()?;
         let dir = tempfile::Builder::new().prefix("lucetc").tempdir()?;
         let objpath = dir.path().join("tmp.o");
+        let extra_objects = self.extra_objects.clone();
+        let linker = self.linker.clone();
+        let build_id = self.build_id;
         self.object_file(objpath.clone())?;
-        link_so(objpath, output)?;
+        link_so(objpath, &extra_objects, linker, build_id, output)?;
         Ok(())
     }
+
+    /// Like `shared_object_file`, but also reports how long each phase took.
+    pub fn shared_object_file_timed<P: AsRef<Path>>(
+        self,
+        output: P,
+    ) -> Result<CompileTimings, Error> {
+        self.check_linkable()?;
+        let dir = tempfile::Builder::new().prefix("lucetc").tempdir()?;
+        let objpath = dir.path().join("tmp.o");
+        let extra_objects = self.extra_objects.clone();
+        let linker = self.linker.clone();
+        let build_id = self.build_id;
+        let mut timings = self.object_file_timed(objpath.clone())?;
+
+        let start = Instant::now();
+        link_so(objpath, &extra_objects, linker, build_id, output)?;
+        timings.link = start.elapsed();
+
+        Ok(timings)
+    }
 }
 
 const LD_DEFAULT: &str = "ld";
@@ -206,24 +1481,51 @@ const LDFLAGS_DEFAULT: &str = "-shared";
 #[cfg(target_os = "macos")]
 const LDFLAGS_DEFAULT: &str = "-dylib -dead_strip -export_dynamic -undefined dynamic_lookup";
 
-fn link_so<P, Q>(objpath: P, sopath: Q) -> Result<(), Error>
+fn link_so<P, Q>(
+    objpath: P,
+    extra_objects: &[PathBuf],
+    linker: Option<PathBuf>,
+    build_id: BuildId,
+    sopath: Q,
+) -> Result<(), Error>
 where
     P: AsRef<Path>,
     Q: AsRef<Path>,
 {
     use std::process::Command;
-    let mut cmd_ld = Command::new(env::var("LD").unwrap_or(LD_DEFAULT.into()));
+    // ld's own message for a missing input is easy to lose among the rest of its output.
+    for extra in extra_objects {
+        if !extra.is_file() {
+            Err(format_err!("extra object {:?} does not exist", extra))?;
+        }
+    }
+
+    let linker = linker.unwrap_or_else(|| env::var("LD").unwrap_or(LD_DEFAULT.into()).into());
+    let mut cmd_ld = Command::new(&linker);
     cmd_ld.arg(objpath.as_ref());
+    cmd_ld.args(extra_objects);
     let env_ldflags = env::var("LDFLAGS").unwrap_or(LDFLAGS_DEFAULT.into());
     for flag in env_ldflags.split_whitespace() {
         cmd_ld.arg(flag);
     }
+    if cfg!(not(target_os = "macos")) {
+        cmd_ld.arg(match build_id {
+            BuildId::Sha1 => "--build-id=sha1",
+            BuildId::None => "--build-id=none",
+        });
+    }
     cmd_ld.arg("-o");
     cmd_ld.arg(sopath.as_ref());
 
-    let run_ld = cmd_ld
-        .output()
-        .context(format_err!("running ld on {:?}", objpath.as_ref()))?;
+    let run_ld = match cmd_ld.output() {
+        Ok(output) => output,
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => {
+            Err(LucetcError::from(LucetcErrorKind::LinkerNotFound {
+                path: linker.display().to_string(),
+            }))?
+        }
+        Err(e) => Err(e).context(format_err!("running ld on {:?}", objpath.as_ref()))?,
+    };
 
     if !run_ld.status.success() {
         Err(format_err!(
@@ -240,23 +1542,84 @@ pub fn compile<'p>(
     name: &str,
     opt_level: OptLevel,
 ) -> Result<Compiler<'p>, LucetcError> {
-    let mut compiler = Compiler::new(name.to_owned(), &program, opt_level)?;
+    compile_with_settings(program, name, &CodegenSettings::new(opt_level))
+}
+
+pub fn compile_with_settings<'p>(
+    program: &'p Program,
+    name: &str,
+    settings: &CodegenSettings,
+) -> Result<Compiler<'p>, LucetcError> {
+    let mut compiler = Compiler::new(name.to_owned(), &program, settings)?;
+    translate(&mut compiler)?;
+    Ok(compiler)
+}
 
-    compile_data_initializers(&mut compiler).context(LucetcErrorKind::DataInitializers)?;
-    compile_sparse_page_data(&mut compiler).context(LucetcErrorKind::DataInitializers)?;
-    compile_memory_specs(&mut compiler).context(LucetcErrorKind::MemorySpecs)?;
-    compile_global_specs(&mut compiler).context(LucetcErrorKind::GlobalSpecs)?;
-    compile_module_data(&mut compiler).context(LucetcErrorKind::ModuleData)?;
+/// Code generation settings prepared once, for compiling many modules with them.
+///
+/// The target is checked and the shared Cranelift flags are built when the context is created,
+/// rather than for every module. A context can't be sent to another thread, since a diagnostics
+/// sink can't be; give each worker thread a context of its own.
+pub struct CompilerContext {
+    settings: CodegenSettings,
+    flags: Flags,
+}
+
+impl CompilerContext {
+    pub fn new(settings: CodegenSettings) -> Result<Self, Error> {
+        isa_builder(&settings)?;
+        let flags = shared_flags(&settings)?;
+        Ok(Self { settings, flags })
+    }
+
+    pub fn settings(&self) -> &CodegenSettings {
+        &self.settings
+    }
+
+    /// Compile a module given in binary or text form. Builtins, export aliases, and the other
+    /// options `Lucetc` offers beyond `CodegenSettings` aren't available here.
+    pub fn compile(
+        &self,
+        module_bytes: &[u8],
+        bindings: &Bindings,
+        heap: &HeapSettings,
+    ) -> Result<ObjectFile, Error> {
+        let module = decode_module(module_bytes.to_vec(), &WasmFeatures::default())?;
+        check_features(&module)?;
+        let prog = Program::new(module, bindings.clone(), heap.clone())?;
+        let isa = isa_builder(&self.settings)?.finish(self.flags.clone());
+        let mut compiler = Compiler::with_isa("guest".to_owned(), &prog, &self.settings, isa)?;
+        translate(&mut compiler)?;
+        compiler.codegen()
+    }
+}
+
+fn translate<B: Backend>(compiler: &mut Compiler<B>) -> Result<(), LucetcError> {
+    let program = compiler.prog;
+
+    compile_data_initializers(compiler).context(LucetcErrorKind::DataInitializers)?;
+    compile_sparse_page_data(compiler).context(LucetcErrorKind::DataInitializers)?;
+    compile_rodata(compiler).context(LucetcErrorKind::DataInitializers)?;
+    compile_data_snapshot(compiler).context(LucetcErrorKind::DataInitializers)?;
+    compile_deferred_data(compiler).context(LucetcErrorKind::DataInitializers)?;
+    compile_memory_specs(compiler).context(LucetcErrorKind::MemorySpecs)?;
+    compile_global_specs(compiler).context(LucetcErrorKind::GlobalSpecs)?;
+    compile_vmctx_layout(compiler).context(LucetcErrorKind::VmctxLayout)?;
+    compile_user_metadata(compiler).context(LucetcErrorKind::UserMetadata)?;
+    compile_module_data(compiler).context(LucetcErrorKind::ModuleData)?;
 
     for function in program.defined_functions() {
+        compiler.check_deadline()?;
         let body = program.function_body(&function);
-        compile_function(&mut compiler, &function, body)
+        compile_function(compiler, &function, body)
             .context(LucetcErrorKind::Function(function.symbol().to_owned()))?;
     }
     for table in program.tables() {
-        compile_table(&mut compiler, &table)
+        compile_table(compiler, &table)
             .context(LucetcErrorKind::Table(table.symbol().to_owned()))?;
     }
+    let elements = compiler.metadata_symbol(MetadataSymbol::Elements);
+    compile_elements(compiler).context(LucetcErrorKind::Table(elements))?;
 
-    Ok(compiler)
+    Ok(())
 }
@@ -1,20 +1,559 @@
+use crate::atomics::{lower_single_threaded, rejected};
+use crate::compiler::OptLevel;
+use crate::error::{LucetcError, LucetcErrorKind};
+use crate::features::FeatureSet;
+use crate::program::init_expr::{const_init_expr, is_extended_const};
+use crate::tail_call::lower_tail_calls;
 use failure::*;
 use parity_wasm::deserialize_buffer;
 pub use parity_wasm::elements::Module;
+use parity_wasm::elements::{
+    Deserialize, Error as DeserializeError, External, InitExpr, Instruction, Section, VarUint32,
+};
+use serde_json::Value;
+use std::collections::HashSet;
 use std::fs::File;
-use std::io::Read;
+use std::io::{Cursor, Read};
 use std::path::Path;
 use wabt::wat2wasm;
 
+/// Opcodes of the `tail-call` proposal: `return_call` and `return_call_indirect`.
+const RETURN_CALL_OPCODE: u8 = 0x12;
+const RETURN_CALL_INDIRECT_OPCODE: u8 = 0x13;
+
+/// The prefix of the threads proposal's atomic instructions.
+const ATOMIC_PREFIX: u8 = 0xfe;
+
+const CUSTOM_SECTION_ID: u8 = 0;
+const DATA_SECTION_ID: u8 = 11;
+/// The data count section, from the bulk-memory proposal.
+const DATA_COUNT_SECTION_ID: u8 = 12;
+
+/// The value type byte of `externref`, from the reference-types proposal, read as a signed
+/// LEB128 byte the way the decoder reports it.
+const EXTERNREF_TYPE: i8 = -0x11;
+
 pub fn read_module<P: AsRef<Path>>(path: P) -> Result<Module, Error> {
-    let contents = read_to_u8s(path)?;
-    let wasm = if wasm_preamble(&contents) {
-        contents
+    read_module_with_features(path, &WasmFeatures::default())
+}
+
+/// Like `read_module`, but a module that fails to decode because it uses a proposal is reported
+/// according to whether that proposal is enabled in `features`.
+pub fn read_module_with_features<P: AsRef<Path>>(
+    path: P,
+    features: &WasmFeatures,
+) -> Result<Module, Error> {
+    decode_module(read_to_u8s(path)?, features)
+}
+
+/// Decode a module from its binary or text form, as read by `read_module_with_features`.
+pub fn decode_module(contents: Vec<u8>, features: &WasmFeatures) -> Result<Module, Error> {
+    let wasm = to_binary(contents)?;
+    let wasm = if features.tail_call {
+        lower_tail_calls(wasm)?
     } else {
-        wat2wasm(contents)?
+        wasm
+    };
+    let wasm = match features.atomics {
+        Atomics::SingleThreaded => lower_single_threaded(wasm)?,
+        Atomics::Disallow | Atomics::Full => wasm,
     };
+    let wasm = check_data_count(wasm)?;
     let module_res = deserialize_buffer(&wasm);
-    module_res.map_err(|e| format_err!("deserializing wasm module: {}", e))
+    let mut module = module_res.map_err(|e| match e {
+        // The wasm decoder does not know the tail-call opcodes, so a module using them would
+        // otherwise be reported as malformed. They are left in place when the proposal is
+        // disabled, or in a function the lowering doesn't understand.
+        DeserializeError::UnknownOpcode(op)
+            if op == RETURN_CALL_OPCODE || op == RETURN_CALL_INDIRECT_OPCODE =>
+        {
+            if !features.tail_call {
+                return format_err!(
+                    "module uses `return_call`, but the tail-call proposal is not enabled"
+                );
+            }
+            format_err!("deserializing wasm module: {}", e)
+                .context(LucetcErrorKind::Unsupported(
+                    "tail-call proposal (`return_call`, `return_call_indirect`)".to_owned(),
+                ))
+                .into()
+        }
+        DeserializeError::UnknownOpcode(ATOMIC_PREFIX) => rejected(features.atomics),
+        DeserializeError::UnknownValueType(EXTERNREF_TYPE)
+        | DeserializeError::UnknownTableElementType(EXTERNREF_TYPE) => {
+            // The decoder has no value type for `externref`, nor the `table.get` and
+            // `table.set` instructions, so there is nothing to translate it from yet, and no
+            // flag to enable it with.
+            format_err!("deserializing wasm module: {}", e)
+                .context(LucetcErrorKind::Unsupported(
+                    "reference-types proposal (`externref`)".to_owned(),
+                ))
+                .into()
+        }
+        _ => format_err!("deserializing wasm module: {}", e),
+    })?;
+    fold_extended_const(&mut module, features)?;
+    Ok(module)
+}
+
+/// Replace each extended-const init expr in `module` with the single const it evaluates to,
+/// which is the only form the validator and the rest of lucetc accept.
+fn fold_extended_const(module: &mut Module, features: &WasmFeatures) -> Result<(), Error> {
+    let mut exprs: Vec<&mut InitExpr> = Vec::new();
+    for section in module.sections_mut() {
+        match section {
+            Section::Global(s) => {
+                exprs.extend(s.entries_mut().iter_mut().map(|g| g.init_expr_mut()))
+            }
+            Section::Data(s) => exprs.extend(
+                s.entries_mut()
+                    .iter_mut()
+                    .filter_map(|d| d.offset_mut().as_mut()),
+            ),
+            Section::Element(s) => exprs.extend(
+                s.entries_mut()
+                    .iter_mut()
+                    .filter_map(|e| e.offset_mut().as_mut()),
+            ),
+            _ => (),
+        }
+    }
+    for expr in exprs {
+        if !is_extended_const(expr.code()) {
+            continue;
+        }
+        if !features.extended_const {
+            bail!(
+                "init expr {:?} uses arithmetic, but the extended-const proposal is not enabled",
+                expr.code()
+            );
+        }
+        let value = const_init_expr(expr.code())?;
+        let folded = match expr.code().iter().rev().nth(1) {
+            Some(Instruction::I64Add) | Some(Instruction::I64Sub) | Some(Instruction::I64Mul) => {
+                Instruction::I64Const(value)
+            }
+            _ => Instruction::I32Const(value as i32),
+        };
+        *expr.code_mut() = vec![folded, Instruction::End];
+    }
+    Ok(())
+}
+
+/// The binary form of a module given in binary or text form.
+pub(crate) fn to_binary(contents: Vec<u8>) -> Result<Vec<u8>, Error> {
+    if wasm_preamble(&contents) {
+        Ok(contents)
+    } else {
+        Ok(wat2wasm(contents)?)
+    }
+}
+
+/// A section of an undecoded module, as byte offsets into it.
+pub(crate) struct RawSection {
+    pub id: u8,
+    pub start: usize,
+    pub payload: usize,
+    pub end: usize,
+}
+
+/// Delimit the sections of `wasm`, or `None` if they run past the end of it.
+pub(crate) fn raw_sections(wasm: &[u8]) -> Option<Vec<RawSection>> {
+    let mut sections = vec![];
+    let mut start = 8;
+    while start < wasm.len() {
+        let mut cursor = Cursor::new(&wasm[start + 1..]);
+        let size = read_varuint32(&mut cursor).ok()? as usize;
+        let payload = start + 1 + cursor.position() as usize;
+        let end = payload.checked_add(size).filter(|end| *end <= wasm.len())?;
+        sections.push(RawSection {
+            id: wasm[start],
+            start,
+            payload,
+            end,
+        });
+        start = end;
+    }
+    Some(sections)
+}
+
+/// Reads the parts of an undecoded section, for scans the decoder can't do.
+pub(crate) struct Reader<'a> {
+    bytes: &'a [u8],
+    pub pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    pub fn byte(&mut self) -> Result<u8, Error> {
+        let b = *self
+            .bytes
+            .get(self.pos)
+            .ok_or(format_err!("unexpected end of section"))?;
+        self.pos += 1;
+        Ok(b)
+    }
+
+    pub fn bytes(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        let bytes = self
+            .bytes
+            .get(self.pos..self.pos + len)
+            .ok_or(format_err!("unexpected end of section"))?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    /// Skip a LEB128 number of either signedness.
+    pub fn leb(&mut self) -> Result<(), Error> {
+        while self.byte()? & 0x80 != 0 {}
+        Ok(())
+    }
+
+    pub fn u32(&mut self) -> Result<u32, Error> {
+        let mut value: u64 = 0;
+        let mut shift = 0;
+        loop {
+            let b = self.byte()?;
+            value |= ((b & 0x7f) as u64) << shift;
+            if b & 0x80 == 0 {
+                break;
+            }
+            shift += 7;
+            if shift > 28 {
+                bail!("malformed LEB128 number");
+            }
+        }
+        if value > u32::max_value() as u64 {
+            bail!("malformed LEB128 number");
+        }
+        Ok(value as u32)
+    }
+
+    pub fn limits(&mut self) -> Result<(), Error> {
+        let flags = self.byte()?;
+        self.leb()?;
+        if flags & 0x1 != 0 {
+            self.leb()?;
+        }
+        Ok(())
+    }
+}
+
+/// Append `value` as an unsigned LEB128 number.
+pub(crate) fn write_u32(out: &mut Vec<u8>, mut value: u32) {
+    loop {
+        let b = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(b);
+            return;
+        }
+        out.push(b | 0x80);
+    }
+}
+
+/// Check that the number of data segments declared by a data count section matches the data
+/// section, and remove the data count section, which the decoder does not know.
+///
+/// The data count only exists so `memory.init` and `data.drop` can be validated in one pass,
+/// and neither can be decoded either, so nothing after this needs it.
+fn check_data_count(mut wasm: Vec<u8>) -> Result<Vec<u8>, Error> {
+    // Sections that can't be delimited are left for the decoder to report.
+    let sections = match raw_sections(&wasm) {
+        Some(sections) => sections,
+        None => return Ok(wasm),
+    };
+    let data_count = match sections.iter().find(|s| s.id == DATA_COUNT_SECTION_ID) {
+        Some(section) => section,
+        None => return Ok(wasm),
+    };
+    let count = |section: &RawSection| {
+        read_varuint32(&mut Cursor::new(&wasm[section.payload..section.end]))
+    };
+    let declared = count(data_count).context("reading data count section")?;
+    let segments = match sections.iter().find(|s| s.id == DATA_SECTION_ID) {
+        Some(data) => count(data).context("reading data section")?,
+        None => 0,
+    };
+    if declared != segments {
+        Err(LucetcError::from(
+            format_err!(
+                "data count section declares {} segments, but the data section has {}",
+                declared,
+                segments
+            )
+            .context(LucetcErrorKind::Validation),
+        ))?;
+    }
+    wasm.drain(data_count.start..data_count.end);
+    Ok(wasm)
+}
+
+/// Name of the custom section in which a module can carry its recommended compilation settings.
+///
+/// The payload is a JSON object with any of the keys `min_reserved_size`, `max_reserved_size`,
+/// `guard_size` (all in bytes), and `opt_level` (`"default"`, `"best"`, or `"fastest"`).
+pub const CONFIG_SECTION: &str = "lucet.config";
+
+/// Compilation settings recommended by the module itself.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ModuleConfig {
+    pub min_reserved_size: Option<u64>,
+    pub max_reserved_size: Option<u64>,
+    pub guard_size: Option<u64>,
+    pub opt_level: Option<OptLevel>,
+}
+
+/// Read the module's `lucet.config` section, if it has one.
+pub fn module_config(module: &Module) -> Result<Option<ModuleConfig>, Error> {
+    let payload = module.sections().iter().find_map(|s| match s {
+        Section::Custom(custom) if custom.name() == CONFIG_SECTION => Some(custom.payload()),
+        _ => None,
+    });
+    match payload {
+        Some(payload) => parse_module_config(payload)
+            .context(format!("parsing {} section", CONFIG_SECTION))
+            .map(Some)
+            .map_err(Error::from),
+        None => Ok(None),
+    }
+}
+
+/// Like `module_config`, but read straight from the module's binary or text form without
+/// decoding it, so the section is found whichever proposals the module needs to decode.
+pub fn read_module_config<P: AsRef<Path>>(path: P) -> Result<Option<ModuleConfig>, Error> {
+    let wasm = to_binary(read_to_u8s(path)?)?;
+    let sections = raw_sections(&wasm).ok_or(format_err!("module sections are malformed"))?;
+    for section in sections.iter().filter(|s| s.id == CUSTOM_SECTION_ID) {
+        let mut r = Reader::new(&wasm[section.payload..section.end]);
+        let name_len = r.u32()? as usize;
+        if r.bytes(name_len)? == CONFIG_SECTION.as_bytes() {
+            return parse_module_config(&wasm[section.payload + r.pos..section.end])
+                .context(format!("parsing {} section", CONFIG_SECTION))
+                .map(Some)
+                .map_err(Error::from);
+        }
+    }
+    Ok(None)
+}
+
+fn parse_module_config(payload: &[u8]) -> Result<ModuleConfig, Error> {
+    let v: Value = serde_json::from_slice(payload)?;
+    let fields = v
+        .as_object()
+        .ok_or(format_err!("top level json expected to be object"))?;
+    let size = |key: &str, v: &Value| {
+        v.as_u64()
+            .ok_or(format_err!("{} must be a non-negative integer", key))
+    };
+    let mut config = ModuleConfig::default();
+    for (key, v) in fields {
+        match key.as_str() {
+            "min_reserved_size" => config.min_reserved_size = Some(size(key, v)?),
+            "max_reserved_size" => config.max_reserved_size = Some(size(key, v)?),
+            "guard_size" => config.guard_size = Some(size(key, v)?),
+            "opt_level" => {
+                config.opt_level = Some(match v.as_str() {
+                    Some("default") => OptLevel::Default,
+                    Some("best") => OptLevel::Best,
+                    Some("fastest") => OptLevel::Fastest,
+                    _ => bail!("opt_level must be one of \"default\", \"best\", \"fastest\""),
+                })
+            }
+            _ => bail!("unknown setting {}", key),
+        }
+    }
+    Ok(config)
+}
+
+/// Name of the custom section describing a module built for dynamic linking, such as an
+/// emscripten `SIDE_MODULE`.
+pub const DYLINK_SECTION: &str = "dylink";
+
+/// The contents of a `dylink` section: what a side module needs from the module loading it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DylinkInfo {
+    /// Bytes of linear memory the module's data needs.
+    pub mem_size: u32,
+    /// Required alignment of the module's memory, as a power of two.
+    pub mem_align: u32,
+    /// Table slots the module's elements need.
+    pub table_size: u32,
+    /// Required alignment of the module's table slots, as a power of two.
+    pub table_align: u32,
+    /// Dynamic libraries that must be loaded first.
+    pub needed: Vec<String>,
+}
+
+/// Read the module's `dylink` section, if it has one.
+pub fn dylink_info(module: &Module) -> Result<Option<DylinkInfo>, Error> {
+    let payload = module.sections().iter().find_map(|s| match s {
+        Section::Custom(custom) if custom.name() == DYLINK_SECTION => Some(custom.payload()),
+        _ => None,
+    });
+    match payload {
+        Some(payload) => parse_dylink(payload)
+            .context(format!("parsing {} section", DYLINK_SECTION))
+            .map(Some)
+            .map_err(Error::from),
+        None => Ok(None),
+    }
+}
+
+fn parse_dylink(payload: &[u8]) -> Result<DylinkInfo, Error> {
+    let mut reader = Cursor::new(payload);
+    let mem_size = read_varuint32(&mut reader)?;
+    let mem_align = read_varuint32(&mut reader)?;
+    let table_size = read_varuint32(&mut reader)?;
+    let table_align = read_varuint32(&mut reader)?;
+    let needed_count = read_varuint32(&mut reader)?;
+    let mut needed = vec![];
+    for _ in 0..needed_count {
+        let mut name = vec![0; read_varuint32(&mut reader)? as usize];
+        reader
+            .read_exact(&mut name)
+            .context("reading needed library name")?;
+        needed.push(String::from_utf8(name)?);
+    }
+    if reader.position() != payload.len() as u64 {
+        bail!("unexpected bytes after the needed libraries");
+    }
+    Ok(DylinkInfo {
+        mem_size,
+        mem_align,
+        table_size,
+        table_align,
+        needed,
+    })
+}
+
+fn read_varuint32(reader: &mut Cursor<&[u8]>) -> Result<u32, Error> {
+    Ok(VarUint32::deserialize(reader)?.into())
+}
+
+/// WebAssembly proposals a module may use beyond the MVP.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WasmFeatures {
+    /// What to do with the atomic instructions of the threads proposal.
+    pub atomics: Atomics,
+    /// Integer `add`, `sub` and `mul` in segment offsets and global initializers, from the
+    /// extended-const proposal.
+    pub extended_const: bool,
+    /// `return_call` and `return_call_indirect`, from the tail-call proposal. They are compiled
+    /// as a call followed by a return, so each still takes a stack frame until the callee
+    /// returns: deep tail recursion can overflow the stack where a real tail call would not.
+    pub tail_call: bool,
+}
+
+impl WasmFeatures {
+    /// The detected proposals these settings let a module use. Extended-const isn't detected,
+    /// so it isn't among them.
+    pub fn enabled(&self) -> FeatureSet {
+        let mut enabled = FeatureSet::empty();
+        if self.atomics == Atomics::SingleThreaded {
+            enabled.insert(FeatureSet::ATOMICS);
+        }
+        if self.tail_call {
+            enabled.insert(FeatureSet::TAIL_CALL);
+        }
+        enabled
+    }
+}
+
+/// How a module's atomic memory instructions are handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Atomics {
+    /// Reject modules that use them.
+    Disallow,
+    /// Lower them to plain loads and stores, which is only correct if no other thread can
+    /// access the heap. Shared memories are compiled as unshared ones.
+    SingleThreaded,
+    /// Compile them as atomic operations on a shared memory. This isn't supported yet.
+    Full,
+}
+
+impl Default for Atomics {
+    fn default() -> Self {
+        Atomics::Disallow
+    }
+}
+
+/// Fail if `module` uses a proposal lucetc can't compile yet. The proposals that need
+/// `WasmFeatures` are checked while decoding.
+pub fn check_features(module: &Module) -> Result<(), Error> {
+    let imported = module
+        .import_section()
+        .map(|s| {
+            s.entries()
+                .iter()
+                .filter(|e| match e.external() {
+                    External::Memory(_) => true,
+                    _ => false,
+                })
+                .count()
+        })
+        .unwrap_or(0);
+    let defined = module
+        .memory_section()
+        .map(|s| s.entries().len())
+        .unwrap_or(0);
+    let memories = imported + defined;
+    if memories > 1 {
+        // There is no flag for the multi-memory proposal: memory instructions can only name
+        // memory 0 in the decoder's representation, and the runtime lays out a single heap per
+        // instance.
+        Err(format_err!("module has {} memories", memories).context(
+            LucetcErrorKind::Unsupported("multi-memory proposal".to_owned()),
+        ))?;
+    }
+    Ok(())
+}
+
+/// Custom sections lucetc reads itself, which every `CustomSectionPolicy` accepts.
+const CONSUMED_SECTIONS: [&str; 3] = ["name", CONFIG_SECTION, DYLINK_SECTION];
+
+/// What to do with custom sections other than the ones lucetc reads itself: `name`,
+/// `lucet.config`, and `dylink`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CustomSectionPolicy {
+    /// Accept any custom section.
+    Allow,
+    /// Reject custom sections whose names are not in the set.
+    AllowList(HashSet<String>),
+    /// Reject every custom section.
+    Deny,
+}
+
+impl Default for CustomSectionPolicy {
+    fn default() -> Self {
+        CustomSectionPolicy::Allow
+    }
+}
+
+/// Fail if `module` carries a custom section that `policy` does not permit.
+pub fn check_custom_sections(module: &Module, policy: &CustomSectionPolicy) -> Result<(), Error> {
+    for section in module.sections() {
+        let name = match section {
+            Section::Custom(custom) => custom.name(),
+            Section::Name(_) => "name",
+            _ => continue,
+        };
+        if CONSUMED_SECTIONS.contains(&name) {
+            continue;
+        }
+        let allowed = match policy {
+            CustomSectionPolicy::Allow => true,
+            CustomSectionPolicy::AllowList(names) => names.contains(name),
+            CustomSectionPolicy::Deny => false,
+        };
+        if !allowed {
+            bail!("custom section {:?} is not allowed", name);
+        }
+    }
+    Ok(())
 }
 
 pub fn read_to_u8s<P: AsRef<Path>>(path: P) -> Result<Vec<u8>, Error> {
@@ -42,9 +42,11 @@ pub fn run(opts: &Options) -> Result<(), Error> {
             .context(format!("adding bindings from {:?}", file))?;
     }
 
-    let mut c = Lucetc::new(PathBuf::from(input))
-        .with_bindings(bindings)
-        .with_opt_level(opts.opt_level);
+    let mut c = Lucetc::new(PathBuf::from(input)).with_bindings(bindings);
+
+    if let Some(opt_level) = opts.opt_level {
+        c.opt_level(opt_level);
+    }
 
     if let Some(ref builtins) = opts.builtins_path {
         c.builtins(builtins);
@@ -39,7 +39,7 @@ pub struct Options {
     pub min_reserved_size: Option<u64>,
     pub max_reserved_size: Option<u64>,
     pub guard_size: Option<u64>,
-    pub opt_level: OptLevel,
+    pub opt_level: Option<OptLevel>,
 }
 
 impl Options {
@@ -87,10 +87,10 @@ impl Options {
         };
 
         let opt_level = match m.value_of("opt_level") {
-            None => OptLevel::Default,
-            Some("default") => OptLevel::Default,
-            Some("best") => OptLevel::Best,
-            Some("fastest") => OptLevel::Fastest,
+            None => None,
+            Some("default") => Some(OptLevel::Default),
+            Some("best") => Some(OptLevel::Best),
+            Some("fastest") => Some(OptLevel::Fastest),
             Some(_) => panic!("unknown value for opt-level"),
         };
 
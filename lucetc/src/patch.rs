@@ -1,13 +1,31 @@
-use failure::Error;
-use parity_wasm::elements::Module;
+use crate::diagnostics::Diagnostics;
+use crate::error::{LucetcError, LucetcErrorKind};
+use failure::{format_err, Error};
+use parity_wasm::elements::{External, Internal, Module};
 use std::collections::HashMap;
 use std::path::Path;
-use wasmonkey::{Patcher, PatcherConfig};
+use wasmonkey::{extract_symbols, Patcher, PatcherConfig, BUILTIN_PREFIX};
 
 pub fn patch_module<P: AsRef<Path>>(
     module: Module,
     builtins_path: P,
+    diagnostics: &Diagnostics,
 ) -> Result<(Module, HashMap<String, String>), Error> {
+    let symbols = extract_symbols(builtins_path.as_ref())?;
+    let builtins = symbols.builtins_names();
+    check_substitutions(&module, &builtins)?;
+
+    let exports = module.export_section().map(|s| s.entries()).unwrap_or(&[]);
+    for builtin in builtins.iter() {
+        if !exports.iter().any(|e| e.field() == *builtin) {
+            diagnostics.note(format!(
+                "builtin {} from {:?} matches no function the module exports",
+                builtin,
+                builtins_path.as_ref()
+            ));
+        }
+    }
+
     let mut patcher_config = PatcherConfig::default();
     patcher_config.builtins_map_original_names = false;
     patcher_config.builtins_path = Some(builtins_path.as_ref().into());
@@ -16,3 +34,49 @@ pub fn patch_module<P: AsRef<Path>>(
     let patched_module = patcher.patched_module();
     Ok((patched_module, patched_builtins_map))
 }
+
+/// The patcher replaces the body of each export named by a builtin, so every such export must
+/// be a function the module defines.
+///
+/// An export that is already the builtin's import, as after patching with the same builtins
+/// twice, would be substituted with itself.
+fn check_substitutions(module: &Module, builtins: &[&str]) -> Result<(), Error> {
+    let imports: Vec<_> = module
+        .import_section()
+        .map(|s| s.entries())
+        .unwrap_or(&[])
+        .iter()
+        .filter(|e| match e.external() {
+            External::Function(_) => true,
+            _ => false,
+        })
+        .collect();
+    let exports = module.export_section().map(|s| s.entries()).unwrap_or(&[]);
+    for export in exports {
+        let import = match export.internal() {
+            Internal::Function(index) => imports.get(*index as usize),
+            _ => None,
+        };
+        match import {
+            Some(import) if builtins.contains(&export.field()) => {
+                let builtin = format!("{}{}", BUILTIN_PREFIX, export.field());
+                if import.module() == "env" && import.field() == builtin {
+                    Err(LucetcError::from(LucetcErrorKind::BuiltinCycle(format!(
+                        "{} -> {} -> {}",
+                        export.field(),
+                        builtin,
+                        export.field()
+                    ))))?;
+                }
+                Err(format_err!(
+                    "cannot substitute builtin for {}: it re-exports the import {}::{}",
+                    export.field(),
+                    import.module(),
+                    import.field()
+                ))?;
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
@@ -2,7 +2,7 @@ pub mod sparse;
 
 use super::init_expr::const_init_expr;
 use failure::{format_err, Error, ResultExt};
-use parity_wasm::elements::Module;
+use parity_wasm::elements::{DataSegment, Instruction, Module};
 
 #[derive(Debug)]
 pub struct DataInit<'m> {
@@ -22,6 +22,40 @@ impl<'m> DataInit<'m> {
     }
 }
 
+/// An active segment placed at the value of an imported global, which isn't known until the
+/// module is instantiated.
+#[derive(Debug)]
+pub struct DeferredDataInit<'m> {
+    pub segment: u32,
+    pub global: u32,
+    pub data: &'m [u8],
+}
+
+/// The global a segment's offset is read from, if it isn't a constant.
+fn offset_global(segment: &DataSegment) -> Option<u32> {
+    match segment.offset().as_ref().map(|o| o.code()) {
+        Some([Instruction::GetGlobal(global), Instruction::End]) => Some(*global),
+        _ => None,
+    }
+}
+
+/// The segments `module_data` leaves out, because their offsets are imported globals.
+pub fn deferred_module_data<'m>(module: &'m Module) -> Vec<DeferredDataInit<'m>> {
+    let segments = module.data_section().map(|s| s.entries()).unwrap_or(&[]);
+    segments
+        .iter()
+        .enumerate()
+        .filter_map(|(ix, segment)| {
+            offset_global(segment).map(|global| DeferredDataInit {
+                segment: ix as u32,
+                global,
+                data: segment.value(),
+            })
+        })
+        .collect()
+}
+
+/// The segments with constant offsets. See `deferred_module_data` for the others.
 pub fn module_data<'m>(module: &'m Module) -> Result<Vec<DataInit<'m>>, Error> {
     let mut initializers = Vec::new();
     // XXX check the location of these init sections against the size of the memory imported or
@@ -37,6 +71,9 @@ pub fn module_data<'m>(module: &'m Module) -> Result<Vec<DataInit<'m>>, Error> {
                     segment.index(),
                 ));
             }
+            if offset_global(segment).is_some() {
+                continue;
+            }
 
             // Take the offset, and treat it as an unsigned 32 bit number.
             // XXX need a type checked const_init_expr - this should always be a u32.
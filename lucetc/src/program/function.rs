@@ -63,18 +63,34 @@ pub struct FunctionDef {
     sig: FunctionSig,
     exported: bool,
     symbol: String,
+    export_aliases: Vec<String>,
+    name: Option<String>,
 }
 
 impl FunctionDef {
-    pub fn new(wasmidx: u32, sig: FunctionSig, exported: bool, symbol: String) -> Self {
+    pub fn new(
+        wasmidx: u32,
+        sig: FunctionSig,
+        exported: bool,
+        symbol: String,
+        export_aliases: Vec<String>,
+        name: Option<String>,
+    ) -> Self {
         Self {
             wasmidx,
             sig,
             exported,
             symbol,
+            export_aliases,
+            name,
         }
     }
 
+    /// The function's name from the export or name section, if it has one.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_ref().map(|n| n.as_str())
+    }
+
     pub fn signature(&self) -> ir::Signature {
         self.sig.cton_signature()
     }
@@ -87,6 +103,12 @@ impl FunctionDef {
         &self.symbol
     }
 
+    /// The other `guest_func_` symbols of a function exported under more than one name. They
+    /// are added to the object as aliases of `symbol`.
+    pub fn export_aliases(&self) -> &[String] {
+        &self.export_aliases
+    }
+
     pub fn linkage(&self) -> Linkage {
         if self.exported {
             Linkage::Export
@@ -103,6 +125,7 @@ pub struct FunctionImport {
     field: String,
     sig: FunctionSig,
     symbol: String,
+    addend: i64,
 }
 
 impl FunctionImport {
@@ -115,12 +138,14 @@ impl FunctionImport {
         let module = String::from(importentry.module());
         let field = String::from(importentry.field());
         let symbol = bindings.translate(&module, &field)?;
+        let addend = bindings.addend(&module, &field);
         Ok(Self {
             wasmidx,
             module,
             field,
             sig,
             symbol,
+            addend,
         })
     }
 
@@ -144,6 +169,11 @@ impl FunctionImport {
         &self.symbol
     }
 
+    /// How far past `symbol` the function is. See `Bindings::bind_with_addend`.
+    pub fn addend(&self) -> i64 {
+        self.addend
+    }
+
     pub fn linkage(&self) -> Linkage {
         Linkage::Import
     }
@@ -10,18 +10,31 @@ pub struct GlobalImport {
     field: String,
     pub global_type: GlobalType,
     export: Option<String>,
+    symbol: Option<String>,
 }
 
 impl GlobalImport {
-    pub fn new(importentry: &ImportEntry, global_type: GlobalType, export: Option<String>) -> Self {
+    pub fn new(
+        importentry: &ImportEntry,
+        global_type: GlobalType,
+        export: Option<String>,
+        symbol: Option<String>,
+    ) -> Self {
         Self {
             module: String::from(importentry.module()),
             field: String::from(importentry.field()),
             global_type,
             export,
+            symbol,
         }
     }
 
+    /// The host symbol the bindings give the global, if any. Code reads and writes the global's
+    /// value at that symbol, rather than in the instance's slot for it.
+    pub fn symbol(&self) -> Option<&str> {
+        self.symbol.as_ref().map(|s| s.as_str())
+    }
+
     pub fn cton_type(&self) -> ir::Type {
         cton_valuetype(&self.global_type.content_type())
     }
@@ -114,8 +127,14 @@ impl Global {
 use lucet_module_data as data;
 
 impl Global {
+    /// The global as the runtime sees it. An import bound to a host symbol is a definition
+    /// with a placeholder value, since code reaches it through the symbol and nothing is left
+    /// for the runtime to provide.
     pub fn to_spec(&self) -> data::GlobalSpec {
         let global = match self {
+            Global::Import(i) if i.symbol().is_some() => data::Global::Def {
+                def: data::GlobalDef::new(0),
+            },
             Global::Import(i) => data::Global::Import {
                 module: i.module(),
                 field: i.field(),
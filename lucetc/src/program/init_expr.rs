@@ -1,7 +1,9 @@
 use crate::error::{LucetcError, LucetcErrorKind};
 use failure::{format_err, ResultExt};
-use parity_wasm::elements::Instruction;
+use parity_wasm::elements::{Instruction, ValueType};
 
+/// The value of an init expr: a single integer const, or, from the extended-const proposal,
+/// `add`, `sub` and `mul` of integer consts.
 pub fn const_init_expr(opcodes: &[Instruction]) -> Result<i64, LucetcError> {
     let len = opcodes.len();
     if !(len >= 1 && opcodes[len - 1] == Instruction::End) {
@@ -11,23 +13,68 @@ pub fn const_init_expr(opcodes: &[Instruction]) -> Result<i64, LucetcError> {
         ))?;
     }
 
-    if len > 2 {
-        Err(format_err!(
-            "init expr is too long to be a single const, got {:?}",
-            opcodes
-        ))
-        .context(LucetcErrorKind::Unsupported(
-            "non-const init expr".to_owned(),
-        ))?;
+    let mut stack: Vec<(ValueType, i64)> = Vec::new();
+    for opcode in &opcodes[..len - 1] {
+        let ty = match opcode {
+            Instruction::I32Const(i32_const) => {
+                stack.push((ValueType::I32, *i32_const as i64));
+                continue;
+            }
+            Instruction::I64Const(i64_const) => {
+                stack.push((ValueType::I64, *i64_const));
+                continue;
+            }
+            Instruction::I32Add | Instruction::I32Sub | Instruction::I32Mul => ValueType::I32,
+            Instruction::I64Add | Instruction::I64Sub | Instruction::I64Mul => ValueType::I64,
+            Instruction::GetGlobal(_) => {
+                Err(format_err!("init expr reads a global, got {:?}", opcodes)).context(
+                    LucetcErrorKind::Unsupported("non-const init expr".to_owned()),
+                )?
+            }
+            _ => Err(format_err!(
+                "init expr is not a const integer expr, got {:?}",
+                opcodes
+            ))
+            .context(LucetcErrorKind::Unsupported("non-int init expr".to_owned()))?,
+        };
+        let (a, b) = match (stack.pop(), stack.pop()) {
+            (Some((b_ty, b)), Some((a_ty, a))) if a_ty == ty && b_ty == ty => (a, b),
+            _ => Err(format_err!(
+                "invalid init expr: {:?} needs two {:?} operands, got {:?}",
+                opcode,
+                ty,
+                opcodes
+            ))?,
+        };
+        let value = match opcode {
+            Instruction::I32Add => (a as i32).wrapping_add(b as i32) as i64,
+            Instruction::I32Sub => (a as i32).wrapping_sub(b as i32) as i64,
+            Instruction::I32Mul => (a as i32).wrapping_mul(b as i32) as i64,
+            Instruction::I64Add => a.wrapping_add(b),
+            Instruction::I64Sub => a.wrapping_sub(b),
+            _ => a.wrapping_mul(b),
+        };
+        stack.push((ty, value));
     }
 
-    match opcodes[0] {
-        Instruction::I32Const(i32_const) => Ok(i32_const as i64),
-        Instruction::I64Const(i64_const) => Ok(i64_const),
+    match stack.as_slice() {
+        [(_, value)] => Ok(*value),
         _ => Err(format_err!(
-            "init expr is not a const integer expr, got {:?}",
+            "invalid init expr: must produce a single value, got {:?}",
             opcodes
-        ))
-        .context(LucetcErrorKind::Unsupported("non-int init expr".to_owned()))?,
+        ))?,
     }
 }
+
+/// Whether `opcodes` uses the arithmetic of the extended-const proposal.
+pub fn is_extended_const(opcodes: &[Instruction]) -> bool {
+    opcodes.iter().any(|op| match op {
+        Instruction::I32Add
+        | Instruction::I32Sub
+        | Instruction::I32Mul
+        | Instruction::I64Add
+        | Instruction::I64Sub
+        | Instruction::I64Mul => true,
+        _ => false,
+    })
+}
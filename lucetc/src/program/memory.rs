@@ -11,6 +11,55 @@ pub struct HeapSettings {
     pub min_reserved_size: u64,
     pub max_reserved_size: u64,
     pub guard_size: u64,
+    /// Page size of the host the module will run on, used to convert page counts to bytes.
+    pub host_page_size: u64,
+    /// Which runtime helper `memory.grow` calls.
+    pub grow_zeroing: GrowZeroing,
+    /// A power of two the reserved size is rounded up to, so the host can map the reservation
+    /// with pages that large. The rounded size can exceed `max_reserved_size`.
+    pub reservation_alignment: Option<u64>,
+    pub guard_placement: GuardPlacement,
+}
+
+/// Whether the runtime must zero the pages `memory.grow` adds, or may assume they are zero
+/// already. They must read as zero either way.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GrowZeroing {
+    /// Call `lucet_vmctx_grow_memory`, which relies on the pages past the current heap being
+    /// zero, as fresh anonymous mappings are.
+    Lazy,
+    /// Call `lucet_vmctx_grow_memory_zeroing`, which clears the new pages before returning.
+    /// Needed when the reservation can hold stale contents, for example when it is reused
+    /// between instances without being unmapped.
+    Eager,
+}
+
+impl Default for GrowZeroing {
+    fn default() -> Self {
+        GrowZeroing::Lazy
+    }
+}
+
+/// Whether the guard region after the heap belongs to the instance, which decides whether
+/// generated code may rely on it to catch accesses that run past the reserved region. The
+/// placement compiled for is recorded in `lucet_heap_spec`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GuardPlacement {
+    /// Each instance's reservation is `reserved_size` bytes of heap followed by `guard_size`
+    /// bytes that are never mapped accessible. Accesses are only checked against the
+    /// reservation in steps of the guard size, since any byte of the guard faults.
+    PerInstance,
+    /// Instances are packed so that one guard region serves a group of them, and the bytes
+    /// after one heap's reservation may be another instance's heap. Generated code assumes no
+    /// guard at all and checks every byte of each access against the reserved size, so the
+    /// reservation must be smaller than 4GiB.
+    Shared,
+}
+
+impl Default for GuardPlacement {
+    fn default() -> Self {
+        GuardPlacement::PerInstance
+    }
 }
 
 impl Default for HeapSettings {
@@ -19,25 +68,148 @@ impl Default for HeapSettings {
             min_reserved_size: 4 * 1024 * 1024,
             max_reserved_size: 6 * 1024 * 1024 * 1024,
             guard_size: 4 * 1024 * 1024,
+            host_page_size: 4096,
+            grow_zeroing: GrowZeroing::default(),
+            reservation_alignment: None,
+            guard_placement: GuardPlacement::default(),
         }
     }
 }
 
+impl HeapSettings {
+    /// A builder starting from the default settings, which checks the settings it is given
+    /// against each other.
+    pub fn builder() -> HeapSettingsBuilder {
+        HeapSettingsBuilder::default()
+    }
+
+    /// Fail if the settings can't describe a heap: the minimum reserved size is larger than the
+    /// maximum, the host page size or reservation alignment isn't a power of two, or a size
+    /// isn't a whole number of host pages.
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.min_reserved_size > self.max_reserved_size {
+            bail!(
+                "min reserved size ({}) exceeds max reserved size ({})",
+                self.min_reserved_size,
+                self.max_reserved_size
+            );
+        }
+        if !self.host_page_size.is_power_of_two() {
+            bail!(
+                "host page size {} is not a power of two",
+                self.host_page_size
+            );
+        }
+        let sizes = [
+            ("min reserved size", self.min_reserved_size),
+            ("max reserved size", self.max_reserved_size),
+            ("guard size", self.guard_size),
+        ];
+        for (what, size) in sizes.iter() {
+            if size % self.host_page_size != 0 {
+                bail!(
+                    "{} ({}) is not a multiple of the {}-byte host page size",
+                    what,
+                    size,
+                    self.host_page_size
+                );
+            }
+        }
+        if let Some(alignment) = self.reservation_alignment {
+            if !alignment.is_power_of_two() {
+                bail!("reservation alignment {} is not a power of two", alignment);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Builds `HeapSettings` from the defaults, for setting several fields at once. `build` checks
+/// the result with `HeapSettings::validate`.
+#[derive(Clone, Debug, Default)]
+pub struct HeapSettingsBuilder {
+    settings: HeapSettings,
+}
+
+impl HeapSettingsBuilder {
+    pub fn min_reserved_size(mut self, size: u64) -> Self {
+        self.settings.min_reserved_size = size;
+        self
+    }
+
+    pub fn max_reserved_size(mut self, size: u64) -> Self {
+        self.settings.max_reserved_size = size;
+        self
+    }
+
+    pub fn guard_size(mut self, size: u64) -> Self {
+        self.settings.guard_size = size;
+        self
+    }
+
+    pub fn host_page_size(mut self, page_size: u64) -> Self {
+        self.settings.host_page_size = page_size;
+        self
+    }
+
+    pub fn grow_zeroing(mut self, zeroing: GrowZeroing) -> Self {
+        self.settings.grow_zeroing = zeroing;
+        self
+    }
+
+    pub fn reservation_alignment(mut self, alignment: u64) -> Self {
+        self.settings.reservation_alignment = Some(alignment);
+        self
+    }
+
+    pub fn guard_placement(mut self, placement: GuardPlacement) -> Self {
+        self.settings.guard_placement = placement;
+        self
+    }
+
+    pub fn build(self) -> Result<HeapSettings, Error> {
+        self.settings.validate()?;
+        Ok(self.settings)
+    }
+}
+
 pub use lucet_module_data::HeapSpec;
 pub fn create_heap_spec(mem: &MemorySpec, heap: &HeapSettings) -> Result<HeapSpec, Error> {
     let wasm_page: u64 = 64 * 1024;
 
     let initial_size = mem.initial_pages as u64 * wasm_page;
 
-    let reserved_size = std::cmp::max(initial_size, heap.min_reserved_size);
-
-    if reserved_size > heap.max_reserved_size {
+    // The initial memory must always fit in the reservation, so it's what can make a module
+    // impossible to compile. The settings themselves are checked as `HeapSettings::validate`
+    // does, for settings that weren't built with `HeapSettingsBuilder`.
+    if heap.min_reserved_size > heap.max_reserved_size {
         bail!(
-            "module reserved size ({}) exceeds max reserved size ({})",
+            "min reserved size ({}) exceeds max reserved size ({})",
+            heap.min_reserved_size,
+            heap.max_reserved_size
+        );
+    }
+    if initial_size > heap.max_reserved_size {
+        bail!(
+            "module's initial memory ({} bytes) exceeds max reserved size ({})",
             initial_size,
             heap.max_reserved_size,
         );
     }
+    let mut reserved_size = std::cmp::max(initial_size, heap.min_reserved_size);
+    if let Some(alignment) = heap.reservation_alignment {
+        if !alignment.is_power_of_two() {
+            bail!("reservation alignment {} is not a power of two", alignment);
+        }
+        reserved_size = match reserved_size.checked_add(alignment - 1) {
+            Some(size) => size & !(alignment - 1),
+            None => bail!(
+                "reserved size {} can't be aligned to {}",
+                reserved_size,
+                alignment
+            ),
+        };
+    }
 
     // Find the max size permitted by the heap and the memory spec
     let max_size = mem.max_pages.map(|pages| pages as u64 * wasm_page);
@@ -8,18 +8,20 @@ pub mod runtime;
 pub mod table;
 pub mod types;
 
-pub use self::data::{module_data, DataInit};
+pub use self::data::{deferred_module_data, module_data, DataInit, DeferredDataInit};
 pub use self::function::{Function, FunctionDef, FunctionImport, FunctionRuntime};
 pub use self::globals::{Global, GlobalDef, GlobalImport};
-pub use self::memory::{create_heap_spec, empty_heap_spec, HeapSettings, HeapSpec, MemorySpec};
+pub use self::memory::{
+    create_heap_spec, empty_heap_spec, GrowZeroing, GuardPlacement, HeapSettings,
+    HeapSettingsBuilder, HeapSpec, MemorySpec,
+};
 pub use self::names::{module_names, ModuleNames};
 pub use self::runtime::Runtime;
-pub use self::table::{TableBuilder, TableDef};
-pub use self::types::{CtonSignature, FunctionSig};
+pub use self::table::{module_elements, ElementInit, TableBuilder, TableDef};
+pub use self::types::{CtonSignature, FunctionSig, TypeIds};
 
 use crate::bindings::Bindings;
 use crate::error::{LucetcError, LucetcErrorKind};
-use crate::program::init_expr::const_init_expr;
 use failure::{format_err, ResultExt};
 use parity_wasm::elements::{External, FuncBody, MemoryType, Module, TableElementType, Type};
 use pwasm_validation::validate_module;
@@ -38,6 +40,9 @@ pub struct Program {
 
     import_functions: Vec<FunctionImport>,
     import_memory: Option<MemorySpec>,
+
+    /// The `call_indirect` signature ID of each entry of the type section.
+    type_ids: Vec<u32>,
 }
 
 impl Program {
@@ -50,13 +55,15 @@ impl Program {
             format_err!("could not parse some of the name sections: {:?}", es)
         })?;
 
+        check_type_indices(&module)?;
         let module = validate_module(module)?.unwrap();
         let names = module_names(&module)?;
         let imports = module_imports(&module, bindings, &names)?;
         let defs = module_definitions(&module, &imports, &names)?;
         let tables = module_tables(&module, imports.tables)?;
         let globals = module_globals(imports.globals, defs.globals);
-        let runtime = Runtime::liblucet_runtime_c();
+        let runtime = Runtime::liblucet_runtime_c(heap_settings.grow_zeroing);
+        let type_ids = (0..module_types(&module).len() as u32).collect();
         Ok(Self {
             module,
             globals,
@@ -68,6 +75,7 @@ impl Program {
 
             import_functions: imports.functions,
             import_memory: imports.memory,
+            type_ids,
         })
     }
 
@@ -114,10 +122,19 @@ impl Program {
         self.runtime.functions()
     }
 
+    /// The runtime helper `memory.grow` calls, which depends on `HeapSettings::grow_zeroing`.
+    pub fn grow_memory_function(&self) -> &FunctionRuntime {
+        self.runtime.grow_memory()
+    }
+
     pub fn get_runtime_function(&self, name: &str) -> Result<&FunctionRuntime, LucetcError> {
         self.runtime.get_symbol(name)
     }
 
+    pub fn guard_placement(&self) -> GuardPlacement {
+        self.heap_settings.guard_placement
+    }
+
     pub fn heap_spec(&self) -> Result<HeapSpec, LucetcError> {
         if let Some(ref mem_spec) = self.import_memory {
             Ok(create_heap_spec(mem_spec, &self.heap_settings)
@@ -130,11 +147,61 @@ impl Program {
         }
     }
 
+    /// Use the IDs from `ids` for `call_indirect` signature checks instead of the module's type
+    /// indices. Every signature in the module's type section must be in `ids`.
+    pub fn assign_type_ids(&mut self, ids: &TypeIds) -> Result<(), LucetcError> {
+        let mut type_ids = Vec::with_capacity(self.type_ids.len());
+        for ty in module_types(&self.module) {
+            match ty {
+                Type::Function(ftype) => type_ids.push(ids.get(ftype).ok_or_else(|| {
+                    LucetcErrorKind::Other(format!(
+                        "signature {} is not in the type ID mapping",
+                        types::signature_key(ftype)
+                    ))
+                })?),
+            }
+        }
+        self.type_ids = type_ids;
+        Ok(())
+    }
+
+    /// The ID `call_indirect` checks for functions of type `type_index`.
+    pub fn type_id(&self, type_index: u32) -> Result<u32, LucetcError> {
+        Ok(*self
+            .type_ids
+            .get(type_index as usize)
+            .ok_or_else(|| LucetcErrorKind::Other(format!("no signature for {}", type_index)))?)
+    }
+
+    /// The signature IDs in use, keyed by signature. If identical signatures were given
+    /// different IDs, which the default assignment does, the first one's ID is reported.
+    pub fn type_ids(&self) -> TypeIds {
+        let mut ids = TypeIds::new();
+        for (ty, id) in module_types(&self.module).iter().zip(self.type_ids.iter()) {
+            match ty {
+                Type::Function(ftype) => {
+                    if ids.get(ftype).is_none() {
+                        ids.insert(ftype, *id);
+                    }
+                }
+            }
+        }
+        ids
+    }
+
     pub fn data_initializers(&self) -> Result<Vec<DataInit>, LucetcError> {
         let v = module_data(&self.module)?;
         Ok(v)
     }
 
+    pub fn deferred_data_initializers(&self) -> Vec<DeferredDataInit> {
+        deferred_module_data(&self.module)
+    }
+
+    pub fn element_initializers(&self) -> Result<Vec<ElementInit>, LucetcError> {
+        module_elements(&self.module)
+    }
+
     pub fn function_body(&self, def: &FunctionDef) -> &FuncBody {
         let bodies = self
             .module
@@ -165,7 +232,7 @@ enum TableDecl {
     Def(TableBuilder),
 }
 
-fn memory_spec(mem: &MemoryType) -> MemorySpec {
+pub(crate) fn memory_spec(mem: &MemoryType) -> MemorySpec {
     let limits = mem.limits();
     MemorySpec {
         initial_pages: limits.initial(),
@@ -173,6 +240,40 @@ fn memory_spec(mem: &MemoryType) -> MemorySpec {
     }
 }
 
+fn module_types(module: &Module) -> &[Type] {
+    module.type_section().map(|s| s.types()).unwrap_or(&[])
+}
+
+/// Validation would reject a function whose type is out of range too, but without saying which
+/// function it is.
+pub(crate) fn check_type_indices(module: &Module) -> Result<(), LucetcError> {
+    let types = module_types(module).len() as u32;
+    let imported = module
+        .import_section()
+        .map(|s| s.entries())
+        .unwrap_or(&[])
+        .iter()
+        .filter_map(|e| match e.external() {
+            External::Function(index) => Some(*index),
+            _ => None,
+        });
+    let defined = module
+        .function_section()
+        .map(|s| s.entries())
+        .unwrap_or(&[])
+        .iter()
+        .map(|f| f.type_ref());
+    for (func, index) in imported.chain(defined).enumerate() {
+        if index >= types {
+            Err(LucetcErrorKind::InvalidTypeIndex {
+                func: func as u32,
+                index,
+            })?;
+        }
+    }
+    Ok(())
+}
+
 fn module_get_signature(module: &Module, index: u32) -> Result<FunctionSig, LucetcError> {
     let type_entry = module
         .type_section()
@@ -224,6 +325,7 @@ fn module_imports(
                         entry,
                         gty.clone(),
                         names.global_symbol(globalix),
+                        bindings.translate(entry.module(), entry.field()).ok(),
                     ))
                 }
 
@@ -237,6 +339,7 @@ fn module_imports(
             }
         }
     }
+    check_import_symbols(&functions)?;
     Ok(ModuleImports {
         memory,
         functions,
@@ -245,6 +348,33 @@ fn module_imports(
     })
 }
 
+/// Several imports may be bound to the same host symbol, in which case they all refer to a single
+/// declaration in the object. That is only sound if they agree on the signature.
+fn check_import_symbols(functions: &[FunctionImport]) -> Result<(), LucetcError> {
+    let mut by_symbol: HashMap<&str, &FunctionImport> = HashMap::new();
+    for f in functions {
+        match by_symbol.entry(f.symbol()) {
+            Entry::Vacant(vac) => {
+                vac.insert(f);
+            }
+            Entry::Occupied(occ) => {
+                let first = occ.get();
+                if first.signature() != f.signature() {
+                    Err(format_err!(
+                        "imports `{}::{}` and `{}::{}` are both bound to `{}` with different signatures",
+                        first.module(),
+                        first.field(),
+                        f.module(),
+                        f.field(),
+                        f.symbol()
+                    ))?
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
 struct ModuleDefs {
     funcs: Vec<FunctionDef>,
     globals: Vec<GlobalDef>,
@@ -268,9 +398,11 @@ fn module_definitions(
 
         funcs.push(FunctionDef::new(
             funcindex,
-            module_get_signature(&module, decl.type_ref()).expect("signature for func must exist"),
+            module_get_signature(&module, decl.type_ref())?,
             names.function_exported(funcindex),
             names.function_symbol(funcindex),
+            names.function_export_aliases(funcindex),
+            names.function_name(funcindex),
         ))
     }
 
@@ -336,38 +468,28 @@ fn module_tables(
                 }
             }
         }
-        if let Some(element_section) = module.elements_section() {
-            for (segment_ix, element_segment) in element_section.entries().iter().enumerate() {
-                let table_ix = element_segment.index();
-                let offs: i64 = const_init_expr(
-                    element_segment
-                        .offset()
-                        .as_ref()
-                        .ok_or(format_err!("Offset not found"))?
-                        .code(),
-                )
-                .context(LucetcErrorKind::Other(format!(
-                    "in element segment offset for table {}, segment {}",
-                    table_ix, segment_ix
-                )))?;
-                // Ensure its safe to make into an i32:
-                assert!(offs >= <i32>::min_value() as i64 && offs <= <i32>::max_value() as i64);
-                match tables.get_mut(&table_ix) {
-                    Some(TableDecl::Def(ref mut builder)) => builder
-                        .push_elements(offs as i32, element_segment.members().to_vec())
-                        .context(LucetcErrorKind::Other(format!(
-                            "in elements for table {}, segment {}",
-                            table_ix, segment_ix
-                        )))?,
-                    Some(TableDecl::Import(_)) => Err(format_err!(
-                        "Cannot define element for imported table {}",
-                        table_ix
-                    ))?,
-                    None => Err(format_err!(
-                        "Cannot define element for undeclared table {}",
-                        table_ix
-                    ))?,
-                }
+        for (segment_ix, element) in module_elements(module)?.iter().enumerate() {
+            let table_ix = element.table_index;
+            // Passive segments are left for `table.init`.
+            let offs = match element.offset {
+                Some(offs) => offs,
+                None => continue,
+            };
+            match tables.get_mut(&table_ix) {
+                Some(TableDecl::Def(ref mut builder)) => builder
+                    .push_elements(offs, element.functions.to_vec())
+                    .context(LucetcErrorKind::Other(format!(
+                        "in elements for table {}, segment {}",
+                        table_ix, segment_ix
+                    )))?,
+                Some(TableDecl::Import(_)) => Err(format_err!(
+                    "Cannot define element for imported table {}",
+                    table_ix
+                ))?,
+                None => Err(format_err!(
+                    "Cannot define element for undeclared table {}",
+                    table_ix
+                ))?,
             }
         }
     }
@@ -6,6 +6,8 @@ use std::collections::HashMap;
 pub struct ModuleNames {
     func_exports: Vec<u32>,
     func_names: BiMap<u32, String>,
+    /// Export names of functions exported more than once, other than the first.
+    func_extra_exports: HashMap<u32, Vec<String>>,
     glob_exports: HashMap<usize, String>,
 }
 
@@ -22,6 +24,17 @@ impl ModuleNames {
             }
         }
     }
+    pub fn function_name(&self, ix: u32) -> Option<String> {
+        self.func_names.get_by_left(&ix).cloned()
+    }
+    /// The `guest_func_` symbols a function exported under several names needs besides
+    /// `function_symbol`, one for each export name after the first.
+    pub fn function_export_aliases(&self, ix: u32) -> Vec<String> {
+        self.func_extra_exports
+            .get(&ix)
+            .map(|names| names.iter().map(|n| format!("guest_func_{}", n)).collect())
+            .unwrap_or_default()
+    }
     pub fn function_exported(&self, ix: u32) -> bool {
         self.func_exports.contains(&ix)
     }
@@ -51,14 +64,23 @@ fn define_unique_name(func_names: &mut BiMap<u32, String>, ix: u32, n: String) {
 pub fn module_names(module: &Module) -> Result<ModuleNames, LucetcError> {
     let mut func_exports = Vec::new();
     let mut func_names = BiMap::new();
+    let mut func_extra_exports: HashMap<u32, Vec<String>> = HashMap::new();
     let mut glob_exports = HashMap::new();
 
     if let Some(export_entries) = module.export_section().map(|s| s.entries()) {
         for entry in export_entries.iter() {
             match *entry.internal() {
                 Internal::Function(idx) => {
-                    func_exports.push(idx);
-                    func_names.insert(idx, entry.field().to_owned());
+                    // The first export names the function; later ones get aliases of its symbol.
+                    if func_names.contains_left(&idx) {
+                        func_extra_exports
+                            .entry(idx)
+                            .or_default()
+                            .push(entry.field().to_owned());
+                    } else {
+                        func_exports.push(idx);
+                        func_names.insert(idx, entry.field().to_owned());
+                    }
                 }
                 Internal::Global(idx) => {
                     glob_exports.insert(idx as usize, String::from(entry.field()));
@@ -74,9 +96,11 @@ pub fn module_names(module: &Module) -> Result<ModuleNames, LucetcError> {
             Section::Name(ref name_section) => match *name_section {
                 NameSection::Function(ref func_sect) => {
                     for (idx, name) in func_sect.names() {
+                        // Export names take precedence, and any other name that is already
+                        // taken, by an export or an earlier entry, gets a suffix. Imports are
+                        // named here too, though their symbols come from the bindings.
                         if !func_names.contains_left(&idx) {
                             define_unique_name(&mut func_names, idx, name.clone());
-                            func_names.insert(idx, name.to_owned());
                         }
                     }
                 }
@@ -90,6 +114,7 @@ pub fn module_names(module: &Module) -> Result<ModuleNames, LucetcError> {
         func_exports,
         glob_exports,
         func_names,
+        func_extra_exports,
     })
 }
 
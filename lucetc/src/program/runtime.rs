@@ -1,25 +1,45 @@
 use super::function::FunctionRuntime;
+use super::memory::GrowZeroing;
 use crate::error::LucetcError;
 use failure::format_err;
 use parity_wasm::elements::{FunctionType, ValueType};
 
+/// The helper the stub of a lazily compiled function calls with the function's index. It compiles
+/// the function if that hasn't happened yet, and returns the address of its code.
+pub const COMPILE_ON_DEMAND_SYM: &str = "lucet_compile_on_demand";
+
 #[derive(Debug, Clone)]
 pub struct Runtime {
     funcs: Vec<FunctionRuntime>,
 }
 
 impl Runtime {
-    pub fn liblucet_runtime_c() -> Self {
+    pub fn liblucet_runtime_c(grow_zeroing: GrowZeroing) -> Self {
         let current_memory_type = FunctionType::new(Vec::new(), Some(ValueType::I32));
         let grow_memory_type = FunctionType::new(vec![ValueType::I32], Some(ValueType::I32));
+        let grow_memory = match grow_zeroing {
+            GrowZeroing::Lazy => "lucet_vmctx_grow_memory",
+            GrowZeroing::Eager => "lucet_vmctx_grow_memory_zeroing",
+        };
         Self {
             funcs: vec![
                 FunctionRuntime::new(0, "lucet_vmctx_current_memory", current_memory_type),
-                FunctionRuntime::new(1, "lucet_vmctx_grow_memory", grow_memory_type),
+                FunctionRuntime::new(1, grow_memory, grow_memory_type),
             ],
         }
     }
 
+    /// `COMPILE_ON_DEMAND_SYM`, which is only called when compiling lazily, so it isn't one of
+    /// `functions`.
+    pub fn compile_on_demand() -> FunctionRuntime {
+        let ty = FunctionType::new(vec![ValueType::I32], Some(ValueType::I64));
+        FunctionRuntime::new(2, COMPILE_ON_DEMAND_SYM, ty)
+    }
+
+    pub fn grow_memory(&self) -> &FunctionRuntime {
+        &self.funcs[1]
+    }
+
     pub fn functions(&self) -> &[FunctionRuntime] {
         self.funcs.as_ref()
     }
@@ -1,4 +1,7 @@
-use failure::{format_err, Error};
+use crate::error::{LucetcError, LucetcErrorKind};
+use crate::program::init_expr::const_init_expr;
+use failure::{format_err, Error, ResultExt};
+use parity_wasm::elements::Module;
 use std::cmp;
 use std::collections::HashMap;
 
@@ -112,3 +115,48 @@ impl TableDef {
         format!("{}_len", self.symbol())
     }
 }
+
+/// An element segment of the module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ElementInit<'m> {
+    pub table_index: u32,
+    /// Where the segment is written into the table at instantiation. Passive segments, from the
+    /// bulk-memory proposal, are only written by `table.init` and have no offset.
+    pub offset: Option<i32>,
+    /// Indices of the functions the segment holds.
+    pub functions: &'m [u32],
+}
+
+pub fn module_elements<'m>(module: &'m Module) -> Result<Vec<ElementInit<'m>>, LucetcError> {
+    let mut elements = Vec::new();
+    if let Some(element_section) = module.elements_section() {
+        for (segment_ix, element_segment) in element_section.entries().iter().enumerate() {
+            let table_ix = element_segment.index();
+            let offset = match element_segment.offset() {
+                Some(offset) => {
+                    let offs: i64 =
+                        const_init_expr(offset.code()).context(LucetcErrorKind::Other(format!(
+                            "in element segment offset for table {}, segment {}",
+                            table_ix, segment_ix
+                        )))?;
+                    if offs < <i32>::min_value() as i64 || offs > <i32>::max_value() as i64 {
+                        Err(format_err!(
+                            "element segment offset {} for table {}, segment {} is out of range",
+                            offs,
+                            table_ix,
+                            segment_ix
+                        ))?;
+                    }
+                    Some(offs as i32)
+                }
+                None => None,
+            };
+            elements.push(ElementInit {
+                table_index: table_ix,
+                offset,
+                functions: element_segment.members(),
+            });
+        }
+    }
+    Ok(elements)
+}
@@ -1,5 +1,8 @@
 use cranelift_codegen::{ir, isa};
+use failure::{format_err, Error};
 use parity_wasm::elements::{FunctionType, ValueType};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
 
 #[derive(Debug, Clone)]
 pub struct FunctionSig {
@@ -16,6 +19,79 @@ impl FunctionSig {
     }
 }
 
+/// The IDs that `call_indirect` compares to check a callee's signature, keyed by signature.
+///
+/// By default a module uses its own type indices as IDs, so two modules only agree on them by
+/// accident. Modules that share a table must be compiled with the same mapping.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TypeIds {
+    ids: BTreeMap<String, u32>,
+}
+
+impl TypeIds {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Assign `id` to `ftype`, returning the ID it had before.
+    pub fn insert(&mut self, ftype: &FunctionType, id: u32) -> Option<u32> {
+        self.ids.insert(signature_key(ftype), id)
+    }
+
+    pub fn get(&self, ftype: &FunctionType) -> Option<u32> {
+        self.ids.get(&signature_key(ftype)).cloned()
+    }
+
+    /// Each signature, written like `(i32, i64) -> f32`, with its ID.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, u32)> {
+        self.ids.iter().map(|(k, v)| (k.as_str(), *v))
+    }
+
+    /// Read a mapping from a JSON object of signatures to IDs, as written by `to_json`.
+    pub fn from_json(v: &Value) -> Result<Self, Error> {
+        let entries = v
+            .as_object()
+            .ok_or(format_err!("top level json expected to be object"))?;
+        let mut ids = BTreeMap::new();
+        for (sig, id) in entries {
+            let id = id
+                .as_u64()
+                .filter(|id| *id <= u32::max_value() as u64)
+                .ok_or(format_err!("type ID for {} must be a 32-bit integer", sig))?;
+            ids.insert(sig.to_owned(), id as u32);
+        }
+        Ok(Self { ids })
+    }
+
+    pub fn to_json(&self) -> Value {
+        let entries: Map<String, Value> = self
+            .ids
+            .iter()
+            .map(|(k, v)| (k.clone(), Value::from(*v)))
+            .collect();
+        Value::Object(entries)
+    }
+}
+
+pub(crate) fn valuetype_name(t: &ValueType) -> &'static str {
+    match t {
+        &ValueType::I32 => "i32",
+        &ValueType::I64 => "i64",
+        &ValueType::F32 => "f32",
+        &ValueType::F64 => "f64",
+        &ValueType::V128 => "v128",
+    }
+}
+
+pub(crate) fn signature_key(ftype: &FunctionType) -> String {
+    let params: Vec<&str> = ftype.params().iter().map(valuetype_name).collect();
+    let ret = ftype
+        .return_type()
+        .map(|t| valuetype_name(&t))
+        .unwrap_or("()");
+    format!("({}) -> {}", params.join(", "), ret)
+}
+
 pub fn cton_valuetype(t: &ValueType) -> ir::Type {
     match t {
         &ValueType::I32 => ir::types::I32,
@@ -0,0 +1,149 @@
+//! A machine-readable summary of a module and the settings it would be compiled with.
+
+use crate::bindings::Bindings;
+use crate::compiler::CodegenSettings;
+use crate::features::FeatureSet;
+use crate::load::WasmFeatures;
+use crate::program::HeapSettings;
+use parity_wasm::elements::{External, Internal, Module, ResizableLimits};
+use serde_json::{json, Value};
+
+/// Summarize `module` without translating or compiling it.
+///
+/// Anything that would stop the module from compiling later is listed under
+/// `unsupported_features` rather than reported as an error, so a report can be produced for any
+/// module that decodes: the proposals in `detected` that `features` doesn't enable, and more
+/// than one memory or table.
+pub fn module_report(
+    module: &Module,
+    detected: FeatureSet,
+    features: &WasmFeatures,
+    bindings: &Bindings,
+    heap: &HeapSettings,
+    codegen: &CodegenSettings,
+) -> Value {
+    let mut imports = vec![];
+    let mut imported_funcs = 0;
+    let mut memories = vec![];
+    let mut tables = vec![];
+    let mut unsupported = detected.difference(features.enabled()).names();
+
+    if let Some(section) = module.import_section() {
+        for entry in section.entries() {
+            let (kind, symbol) = match entry.external() {
+                External::Function(_) => {
+                    imported_funcs += 1;
+                    (
+                        "function",
+                        bindings.translate(entry.module(), entry.field()).ok(),
+                    )
+                }
+                External::Table(t) => {
+                    tables.push(limits(t.limits(), true));
+                    ("table", None)
+                }
+                External::Memory(m) => {
+                    memories.push(limits(m.limits(), true));
+                    ("memory", None)
+                }
+                External::Global(_) => ("global", None),
+            };
+            imports.push(json!({
+                "module": entry.module(),
+                "field": entry.field(),
+                "kind": kind,
+                "symbol": symbol,
+            }));
+        }
+    }
+    if let Some(section) = module.memory_section() {
+        memories.extend(section.entries().iter().map(|m| limits(m.limits(), false)));
+    }
+    if let Some(section) = module.table_section() {
+        tables.extend(section.entries().iter().map(|t| limits(t.limits(), false)));
+    }
+
+    let exports: Vec<Value> = module
+        .export_section()
+        .map(|section| {
+            section
+                .entries()
+                .iter()
+                .map(|entry| {
+                    let kind = match entry.internal() {
+                        Internal::Function(_) => "function",
+                        Internal::Table(_) => "table",
+                        Internal::Memory(_) => "memory",
+                        Internal::Global(_) => "global",
+                    };
+                    json!({ "name": entry.field(), "kind": kind })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let (segment_count, segment_bytes) = module
+        .data_section()
+        .map(|section| {
+            let entries = section.entries();
+            (
+                entries.len(),
+                entries.iter().map(|s| s.value().len()).sum::<usize>(),
+            )
+        })
+        .unwrap_or((0, 0));
+
+    if memories.len() > 1 {
+        unsupported.push("multiple memories");
+    }
+    if tables.len() > 1 {
+        unsupported.push("multiple tables");
+    }
+
+    let defined_funcs = module
+        .function_section()
+        .map(|section| section.entries().len())
+        .unwrap_or(0);
+    // Declared locals of each defined function, in index order, not counting parameters.
+    let locals: Vec<u64> = module
+        .code_section()
+        .map(|section| {
+            section
+                .bodies()
+                .iter()
+                .map(|body| body.locals().iter().map(|l| l.count() as u64).sum())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    json!({
+        "functions": {
+            "defined": defined_funcs,
+            "imported": imported_funcs,
+            "locals": locals,
+        },
+        "imports": imports,
+        "exports": exports,
+        "memories": memories,
+        "tables": tables,
+        "data_segments": {
+            "count": segment_count,
+            "total_size": segment_bytes,
+        },
+        "unsupported_features": unsupported,
+        "settings": {
+            "opt_level": codegen.opt_level.to_flag(),
+            "min_reserved_size": heap.min_reserved_size,
+            "max_reserved_size": heap.max_reserved_size,
+            "guard_size": heap.guard_size,
+        },
+    })
+}
+
+fn limits(limits: &ResizableLimits, imported: bool) -> Value {
+    json!({
+        "initial": limits.initial(),
+        "maximum": limits.maximum(),
+        "imported": imported,
+    })
+}
@@ -0,0 +1,141 @@
+//! Lowering the `return_call` and `return_call_indirect` instructions of the tail-call proposal,
+//! for `WasmFeatures::tail_call`.
+//!
+//! The decoder doesn't know these opcodes, so this works on the module binary before it is
+//! decoded. Each one is replaced by the matching call followed by a `return`, which returns the
+//! same values. Unlike a real tail call, the caller's frame stays on the stack until the callee
+//! returns, so deep tail recursion still grows the stack and can overflow it.
+
+use crate::load::{raw_sections, write_u32, Reader};
+use failure::Error;
+
+const CODE_SECTION_ID: u8 = 10;
+
+const RETURN: u8 = 0x0f;
+const CALL: u8 = 0x10;
+const CALL_INDIRECT: u8 = 0x11;
+const RETURN_CALL: u8 = 0x12;
+const RETURN_CALL_INDIRECT: u8 = 0x13;
+
+const MISC_PREFIX: u8 = 0xfc;
+const ATOMIC_PREFIX: u8 = 0xfe;
+
+/// Rewrite `wasm` so it uses no tail-call instructions. A function the rewriting doesn't
+/// understand is left alone for the decoder to report.
+pub fn lower_tail_calls(wasm: Vec<u8>) -> Result<Vec<u8>, Error> {
+    let sections = match raw_sections(&wasm) {
+        Some(sections) => sections,
+        None => return Ok(wasm),
+    };
+    let code = match sections.iter().find(|s| s.id == CODE_SECTION_ID) {
+        Some(code) => code,
+        None => return Ok(wasm),
+    };
+
+    let mut r = Reader::new(&wasm[code.payload..code.end]);
+    let count = r.u32()?;
+    let mut payload = vec![];
+    write_u32(&mut payload, count);
+    let mut lowered = false;
+    for _ in 0..count {
+        let size = r.u32()? as usize;
+        let body = r.bytes(size)?;
+        let body = match lower_body(body)? {
+            Some(body) => {
+                lowered = true;
+                body
+            }
+            None => body.to_vec(),
+        };
+        write_u32(&mut payload, body.len() as u32);
+        payload.extend(body);
+    }
+    if !lowered {
+        return Ok(wasm);
+    }
+
+    let mut section = vec![CODE_SECTION_ID];
+    write_u32(&mut section, payload.len() as u32);
+    section.extend(payload);
+    let mut out = wasm.clone();
+    out.splice(code.start..code.end, section);
+    Ok(out)
+}
+
+/// The lowered body, or `None` if it has no tail calls or uses an instruction that isn't
+/// understood here.
+fn lower_body(body: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+    let mut r = Reader::new(body);
+    for _ in 0..r.u32()? {
+        r.leb()?;
+        r.byte()?;
+    }
+    let mut code = body[..r.pos].to_vec();
+    let mut lowered = false;
+
+    while r.pos < body.len() {
+        let start = r.pos;
+        let op = r.byte()?;
+        match op {
+            0x00 | 0x01 | 0x05 | 0x0b | 0x0f | 0x1a | 0x1b | 0x45..=0xc4 => {}
+            0x02 | 0x03 | 0x04 | 0x0c | 0x0d | 0x10 | 0x20..=0x24 | 0x3f | 0x40 | 0x41 | 0x42 => {
+                r.leb()?
+            }
+            0x0e => {
+                for _ in 0..=r.u32()? {
+                    r.leb()?;
+                }
+            }
+            0x11 | 0x28..=0x3e => {
+                r.leb()?;
+                r.leb()?;
+            }
+            0x43 => {
+                r.bytes(4)?;
+            }
+            0x44 => {
+                r.bytes(8)?;
+            }
+            RETURN_CALL => {
+                r.leb()?;
+                code.push(CALL);
+                code.extend(&body[start + 1..r.pos]);
+                code.push(RETURN);
+                lowered = true;
+                continue;
+            }
+            RETURN_CALL_INDIRECT => {
+                r.leb()?;
+                r.leb()?;
+                code.push(CALL_INDIRECT);
+                code.extend(&body[start + 1..r.pos]);
+                code.push(RETURN);
+                lowered = true;
+                continue;
+            }
+            MISC_PREFIX => {
+                // Only the saturating truncations have no immediates.
+                if r.u32()? > 7 {
+                    return Ok(None);
+                }
+            }
+            ATOMIC_PREFIX => {
+                // Kept for the atomics lowering, which runs after this one.
+                if r.u32()? == 0x03 {
+                    r.byte()?;
+                } else {
+                    r.leb()?;
+                    r.leb()?;
+                }
+            }
+            _ => return Ok(None),
+        }
+        code.extend(&body[start..r.pos]);
+    }
+
+    if lowered {
+        Ok(Some(code))
+    } else {
+        Ok(None)
+    }
+}
@@ -0,0 +1,22 @@
+use std::time::Duration;
+
+/// Wall-clock time spent in each phase of producing a compiled module.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CompileTimings {
+    /// Reading, patching, and validating the wasm module.
+    pub parse: Duration,
+    /// Declaring functions, tables, and data symbols.
+    pub declare: Duration,
+    /// Translating function bodies and generating machine code.
+    pub compile: Duration,
+    /// Writing the object file.
+    pub emit: Duration,
+    /// Linking the shared object. Zero when only an object file is produced.
+    pub link: Duration,
+}
+
+impl CompileTimings {
+    pub fn total(&self) -> Duration {
+        self.parse + self.declare + self.compile + self.emit + self.link
+    }
+}
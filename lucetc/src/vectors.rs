@@ -0,0 +1,93 @@
+//! Checking compiled code against expected results, by running it in the JIT.
+//!
+//! The expected values come from somewhere trusted, such as a reference interpreter, so a
+//! mismatch points at a code generation bug. A vector whose call traps takes the process down
+//! with it, since the JIT doesn't handle signals.
+
+use crate::compiler::jit::{JitModule, Value};
+use std::fmt;
+
+/// A call to an exported function and the result it should produce.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestVector {
+    pub export: String,
+    pub args: Vec<Value>,
+    /// `None` for a function without a result.
+    pub expected: Option<Value>,
+}
+
+impl TestVector {
+    pub fn new(export: &str, args: Vec<Value>, expected: Option<Value>) -> Self {
+        Self {
+            export: export.to_owned(),
+            args,
+            expected,
+        }
+    }
+}
+
+/// A vector whose call didn't produce the expected result.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mismatch {
+    pub vector: TestVector,
+    /// What the call returned, or why it couldn't be made.
+    pub actual: Result<Option<Value>, String>,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let show = |v: &Option<Value>| match v {
+            Some(v) => v.to_string(),
+            None => "nothing".to_owned(),
+        };
+        let args: Vec<String> = self.vector.args.iter().map(|a| a.to_string()).collect();
+        write!(
+            f,
+            "{}({}): expected {}, ",
+            self.vector.export,
+            args.join(", "),
+            show(&self.vector.expected)
+        )?;
+        match self.actual {
+            Ok(ref actual) => write!(f, "got {}", show(actual)),
+            Err(ref e) => write!(f, "could not call it: {}", e),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VectorReport {
+    pub passed: usize,
+    pub mismatches: Vec<Mismatch>,
+}
+
+impl VectorReport {
+    pub fn all_passed(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+/// Run each of `vectors` against `jit` in order. They share one instance, so a vector can see
+/// changes to the heap or globals made by the ones before it.
+pub fn run_vectors(jit: &mut JitModule, vectors: &[TestVector]) -> VectorReport {
+    let mut report = VectorReport::default();
+    for vector in vectors {
+        let actual = jit
+            .call(&vector.export, &vector.args)
+            .map_err(|e| e.to_string());
+        let same = match (&actual, &vector.expected) {
+            (Ok(Some(a)), Some(e)) => a.same_as(e),
+            (Ok(None), None) => true,
+            _ => false,
+        };
+        if same {
+            report.passed += 1;
+        } else {
+            report.mismatches.push(Mismatch {
+                vector: vector.clone(),
+                actual,
+            });
+        }
+    }
+    report
+}
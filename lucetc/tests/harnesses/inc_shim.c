@@ -0,0 +1,7 @@
+#include "vm.h"
+
+uint32_t inc(struct vmctx *ctx)
+{
+    (void) ctx;
+    return 1;
+}
@@ -25,6 +25,16 @@ fn test_bindings() -> Bindings {
     Bindings::env(imports)
 }
 
+fn aliased_bindings() -> Bindings {
+    let mut bindings = HashMap::new();
+    for module in &["env", "other"] {
+        let mut fields = HashMap::new();
+        fields.insert("malloc".to_owned(), "malloc".to_owned());
+        bindings.insert(module.to_string(), fields);
+    }
+    Bindings::new(bindings)
+}
+
 mod programs {
     /// Tests of the `Program` datastructure.
     use super::load;
@@ -159,6 +169,24 @@ mod programs {
         assert_eq!(p.get_table(0).unwrap().elements().get(6), None);
     }
 
+    #[test]
+    fn element_initializers() {
+        use lucetc::program::ElementInit;
+        let m = load("icall_sparse");
+        let b = Bindings::empty();
+        let h = HeapSettings::default();
+        let p = Program::new(m, b, h).expect(&format!("instantiating program"));
+
+        assert_eq!(
+            p.element_initializers().unwrap(),
+            vec![ElementInit {
+                table_index: 0,
+                offset: Some(1),
+                functions: &[1, 2, 3],
+            }]
+        );
+    }
+
     #[test]
     fn globals_import() {
         let m = load("globals_import");
@@ -260,6 +288,60 @@ mod programs {
         assert_eq!(*p.err().unwrap().get_context(), LucetcErrorKind::Validation);
     }
 
+    #[test]
+    fn import_aliased_mismatch() {
+        let m = load("import_aliased_mismatch");
+        let b = super::aliased_bindings();
+        let h = HeapSettings::default();
+        assert!(
+            Program::new(m, b, h).is_err(),
+            "imports bound to the same symbol must agree on the signature"
+        );
+    }
+
+    #[test]
+    fn data_count() {
+        // Both files are hand-assembled: a memory, a data count section, and a data section
+        // holding one segment. `data_count.wasm` declares one segment.
+        let wasmfile = PathBuf::from("tests/wasm/data_count.wasm");
+        let m = load::read_module(&wasmfile).expect("a matching data count is accepted");
+        assert_eq!(m.data_section().map(|s| s.entries().len()), Some(1));
+
+        // `data_count_mismatch.wasm` declares two.
+        let wasmfile = PathBuf::from("tests/wasm/data_count_mismatch.wasm");
+        let err = load::read_module(&wasmfile).expect_err("the data count disagrees");
+        let err = err
+            .downcast::<lucetc::error::LucetcError>()
+            .expect("a lucetc error");
+        assert_eq!(*err.get_context(), LucetcErrorKind::Validation);
+    }
+
+    #[test]
+    fn extended_const() {
+        use lucetc::WasmFeatures;
+        // `extended_const.wasm` is hand-assembled: a memory, an i32 global initialized to
+        // `(i32.mul (i32.const 6) (i32.const 7))`, and a data segment "hi" at
+        // `(i32.add (i32.const 16) (i32.const 16))`.
+        let wasmfile = PathBuf::from("tests/wasm/extended_const.wasm");
+        let err = load::read_module(&wasmfile).expect_err("extended-const is off by default");
+        assert!(format!("{}", err).contains("extended-const"), "{}", err);
+
+        let features = WasmFeatures {
+            extended_const: true,
+            ..WasmFeatures::default()
+        };
+        let m = load::read_module_with_features(&wasmfile, &features)
+            .expect("extended-const exprs are folded");
+        let p = Program::new(m, Bindings::empty(), HeapSettings::default())
+            .expect("instantiating program");
+        let data = p.data_initializers().expect("data initializers");
+        assert_eq!(data.len(), 1);
+        assert_eq!(data[0].offset, 32);
+        assert_eq!(data[0].data, b"hi");
+        let global = p.globals()[0].as_def().expect("global is defined");
+        assert_eq!(global.value(), 42);
+    }
+
     #[test]
     fn start_section() {
         let m = load("start_section");
@@ -296,6 +378,7 @@ mod compile {
     }
 
     compile_test!(arith);
+    compile_test!(bitcount);
     compile_test!(call);
     compile_test!(data_segment);
     compile_test!(fibonacci);
@@ -314,6 +397,247 @@ mod compile {
     compile_test!(grow_memory);
     compile_test!(unreachable_code);
     compile_test!(start_section);
+
+    #[test]
+    fn compiler_context() {
+        use lucetc::{CodegenSettings, CompilerContext};
+        let context = CompilerContext::new(CodegenSettings::new(OptLevel::Best))
+            .expect("create compiler context");
+        let b = super::test_bindings();
+        let h = HeapSettings::default();
+        for file in &["fibonacci", "import"] {
+            let wat = std::fs::read(format!("tests/wasm/{}.wat", file)).unwrap();
+            let obj = context
+                .compile(&wat, &b, &h)
+                .expect(&format!("compile {} with a shared context", file));
+            let symbols: Vec<_> = obj.stack_sizes().iter().map(|(s, _)| s.clone()).collect();
+            assert_eq!(symbols, vec!["guest_func_main".to_owned()], "{}", file);
+            let calls_inc = obj.relocations().unwrap().iter().any(|r| r.symbol == "inc");
+            assert_eq!(calls_inc, *file == "import", "{}", file);
+        }
+    }
+
+    #[test]
+    fn recompile_function() {
+        let m = load("fibonacci");
+        let p = Program::new(m, super::test_bindings(), HeapSettings::default())
+            .expect("make program for fibonacci");
+        let compiler = compile(&p, "fibonacci", OptLevel::Default).expect("compile fibonacci");
+        let fastest = compiler
+            .recompile_function(0, OptLevel::Fastest)
+            .expect("recompile main at fastest");
+        let best = compiler
+            .recompile_function(0, OptLevel::Best)
+            .expect("recompile main at best");
+        assert_eq!(fastest.symbol, "guest_func_main");
+        assert_eq!(best.opt_level, OptLevel::Best);
+        assert!(fastest.code_size() > 0 && best.code_size() > 0);
+        let again = compiler
+            .recompile_function(0, OptLevel::Best)
+            .expect("recompile main at best again");
+        assert_eq!(again.code, best.code, "recompiling is deterministic");
+        compiler
+            .codegen()
+            .expect("the module still compiles after recompiling main");
+
+        let m = load("import");
+        let p = Program::new(m, super::test_bindings(), HeapSettings::default())
+            .expect("make program for import");
+        let compiler = compile(&p, "import", OptLevel::Default).expect("compile import");
+        let err = compiler
+            .recompile_function(0, OptLevel::Best)
+            .expect_err("inc is imported");
+        assert!(format!("{}", err).contains("imported"));
+    }
+
+    #[test]
+    fn invalid_type_index() {
+        // `invalid_type_index.wasm` is hand-assembled: one type, and one function declared with
+        // type 5.
+        let wasmfile = PathBuf::from("tests/wasm/invalid_type_index.wasm");
+        let m = load::read_module(&wasmfile).expect("the module decodes");
+        let p = Program::new(m, Bindings::empty(), HeapSettings::default());
+        assert_eq!(
+            *p.err().expect("the type index is checked").get_context(),
+            LucetcErrorKind::InvalidTypeIndex { func: 0, index: 5 }
+        );
+    }
+}
+
+mod traps {
+    // Tests for the trap sites recorded in the trap manifest
+    use super::load;
+    use cranelift_codegen::ir::TrapCode;
+    use lucetc::compile;
+    use lucetc::compiler::OptLevel;
+    use lucetc::program::{HeapSettings, Program};
+
+    fn trap_codes(file: &str, func: &str) -> Vec<TrapCode> {
+        let m = load(file);
+        let b = super::test_bindings();
+        let h = HeapSettings::default();
+        let p = Program::new(m, b, h).expect(&format!("make program for {}", file));
+        let comp = compile(&p, file, OptLevel::Best).expect(&format!("compile {}", file));
+        let obj = comp.codegen().expect("generate code");
+        let sink = obj
+            .trap_manifest()
+            .sinks
+            .iter()
+            .find(|sink| sink.name == func)
+            .expect(&format!("trap sink for {}", func));
+        sink.sites.iter().map(|site| site.code).collect()
+    }
+
+    #[test]
+    fn trunc_float() {
+        use lucetc::compiler::layout::function_layout;
+        let codes = trap_codes("trunc_float", "guest_func_trunc");
+        assert!(codes.contains(&TrapCode::BadConversionToInteger));
+        assert!(codes.contains(&TrapCode::IntegerOverflow));
+
+        // Each site is a `ud2` in the checks following a truncating conversion.
+        let m = load("trunc_float");
+        let p = Program::new(m, super::test_bindings(), HeapSettings::default())
+            .expect("make program for trunc_float");
+        let obj = compile(&p, "trunc_float", OptLevel::Best)
+            .expect("compile trunc_float")
+            .codegen()
+            .expect("generate code");
+        let tmp = tempfile::Builder::new()
+            .prefix("trunc_float")
+            .tempdir()
+            .unwrap();
+        let obj_path = tmp.path().join("trunc_float.o");
+        obj.write(&obj_path).expect("write trunc_float");
+        let bytes = std::fs::read(&obj_path).unwrap();
+        let trunc = function_layout(&bytes)
+            .expect("read function layout")
+            .into_iter()
+            .find(|f| f.symbol == "guest_func_trunc")
+            .expect("trunc is laid out");
+        let start = trunc.file_offset as usize;
+        let code = &bytes[start..start + trunc.size as usize];
+        // `cvttss2si` and `cvttsd2si` are `f3 0f 2c` and `f2 0f 2c`, with a REX prefix
+        // before the `0f` for a 64-bit result or a high register.
+        let conversion = code
+            .windows(4)
+            .position(|w| {
+                (w[0] == 0xf3 || w[0] == 0xf2)
+                    && (w[1..3] == [0x0f, 0x2c] || (w[1] & 0xf0 == 0x40 && w[2..4] == [0x0f, 0x2c]))
+            })
+            .expect("trunc converts a float");
+        let sink = obj
+            .trap_manifest()
+            .sinks
+            .iter()
+            .find(|sink| sink.name == "guest_func_trunc")
+            .expect("trap sink for trunc");
+        for site in sink.sites.iter().filter(|site| {
+            site.code == TrapCode::BadConversionToInteger || site.code == TrapCode::IntegerOverflow
+        }) {
+            let offset = site.offset as usize;
+            assert!(
+                offset > conversion,
+                "{:?} precedes the conversion",
+                site.code
+            );
+            assert_eq!(
+                code.get(offset..offset + 2),
+                Some(&[0x0f, 0x0b][..]),
+                "{:?} at {:#x} is not a ud2",
+                site.code,
+                offset
+            );
+        }
+    }
+
+    #[test]
+    fn call_indirect() {
+        let codes = trap_codes("icall", "guest_func_foo");
+        let count = |code| codes.iter().filter(|c| **c == code).count();
+        assert_eq!(count(TrapCode::TableOutOfBounds), 1);
+        assert_eq!(count(TrapCode::BadSignature), 1);
+    }
+
+    #[test]
+    fn unsupported_function_stub() {
+        use lucetc::compile_with_settings;
+        use lucetc::compiler::traps::UNSUPPORTED_FUNCTION;
+        use lucetc::compiler::{CodegenSettings, UnsupportedFunctionPolicy};
+        // `simd_local.wasm` is hand-assembled: `supported` returns a constant, and `simd`
+        // declares a v128 local.
+        let m = lucetc::load::read_module("tests/wasm/simd_local.wasm").expect("load module");
+        let p =
+            Program::new(m, super::test_bindings(), HeapSettings::default()).expect("make program");
+        let settings = CodegenSettings {
+            on_unsupported_function: UnsupportedFunctionPolicy::Stub,
+            ..CodegenSettings::new(OptLevel::Default)
+        };
+        let obj = compile_with_settings(&p, "simd_local", &settings)
+            .expect("compile with stubs")
+            .codegen()
+            .expect("generate code");
+        let codes = |func: &str| -> Vec<TrapCode> {
+            obj.trap_manifest()
+                .sinks
+                .iter()
+                .find(|sink| sink.name == func)
+                .map(|sink| sink.sites.iter().map(|site| site.code).collect())
+                .unwrap_or_default()
+        };
+        assert_eq!(codes("guest_func_simd"), vec![UNSUPPORTED_FUNCTION]);
+        assert!(!codes("guest_func_supported").contains(&UNSUPPORTED_FUNCTION));
+    }
+
+    #[test]
+    fn traps_for() {
+        let m = load("traps_for");
+        let b = super::test_bindings();
+        let p = Program::new(m, b, HeapSettings::default()).expect("make program for traps_for");
+        let comp = compile(&p, "traps_for", OptLevel::Best).expect("compile traps_for");
+        let obj = comp.codegen().expect("generate code");
+
+        // Index 0 is the import.
+        assert!(obj.traps_for(0).is_empty());
+        assert!(obj.traps_for(1).is_empty(), "`add` can't trap");
+        let codes: Vec<TrapCode> = obj.traps_for(2).iter().map(|site| site.code).collect();
+        assert!(codes.contains(&TrapCode::IntegerDivisionByZero));
+        assert!(codes.contains(&TrapCode::IntegerOverflow));
+        assert!(obj.traps_for(3).is_empty());
+    }
+
+    #[test]
+    fn trap_on_signed_overflow() {
+        use lucetc::compile_with_settings;
+        use lucetc::compiler::traps::SIGNED_OVERFLOW;
+        use lucetc::compiler::CodegenSettings;
+        let m = load("signed_overflow");
+        let p = Program::new(m, super::test_bindings(), HeapSettings::default())
+            .expect("make program for signed_overflow");
+        let codes = |trap: bool| {
+            let settings = CodegenSettings {
+                trap_on_signed_overflow: trap,
+                ..CodegenSettings::new(OptLevel::Default)
+            };
+            let obj = compile_with_settings(&p, "signed_overflow", &settings)
+                .expect("compile signed_overflow")
+                .codegen()
+                .expect("generate code");
+            (0..7)
+                .map(|i| obj.traps_for(i).iter().map(|site| site.code).collect())
+                .collect::<Vec<Vec<TrapCode>>>()
+        };
+
+        for (i, codes) in codes(true).into_iter().enumerate() {
+            if i < 6 {
+                assert_eq!(codes, vec![SIGNED_OVERFLOW], "function {}", i);
+            } else {
+                assert!(codes.is_empty(), "`and` can't overflow");
+            }
+        }
+        // Arithmetic wraps by default, as the spec says.
+        assert!(codes(false).iter().all(|codes| codes.is_empty()));
+    }
 }
 
 mod execute {
@@ -436,3 +760,3068 @@ mod execute {
     execute_test!(heap_spec_definition);
     execute_test!(globals_definition);
 }
+
+mod options {
+    // Tests for settings applied through `LucetcOpts`
+    use lucetc::{Hint, Lucetc, LucetcOpts};
+    use std::collections::HashMap;
+    use std::path::{Path, PathBuf};
+    use std::process::Command;
+    use std::str;
+    use tempfile::TempDir;
+
+    fn test_file(name: &str) -> PathBuf {
+        PathBuf::from(format!("tests/wasm/{}.wat", name))
+    }
+
+    /// A new temporary directory, named starting with `prefix`.
+    fn temp_dir(prefix: &str) -> TempDir {
+        tempfile::Builder::new()
+            .prefix(prefix)
+            .tempdir()
+            .expect("Failed to create temp dir")
+    }
+
+    /// A new temporary directory, and the path of `file` in it. The directory is removed when
+    /// the `TempDir` is dropped, so it has to be kept for as long as the path is used.
+    fn out_path(file: &str) -> (TempDir, PathBuf) {
+        let dir = temp_dir(file);
+        let path = dir.path().join(file);
+        (dir, path)
+    }
+
+    /// Defined symbols of an object, in increasing address order.
+    fn symbols_by_address(path: &Path) -> Vec<String> {
+        let nm = Command::new("nm")
+            .arg("--defined-only")
+            .arg("--numeric-sort")
+            .arg(path)
+            .output()
+            .expect("failed to execute nm");
+        assert!(nm.status.success(), "nm failed!");
+        str::from_utf8(&nm.stdout)
+            .unwrap()
+            .lines()
+            .filter_map(|line| line.split_whitespace().nth(2).map(String::from))
+            .collect()
+    }
+
+    #[test]
+    fn function_hints() {
+        let (_tmp_dir, so_path) = out_path("function_hints.so");
+
+        let hints: HashMap<String, Hint> = [
+            ("handle_error".into(), Hint::Cold),
+            ("hot_path".into(), Hint::Hot),
+            ("nonexistent".into(), Hint::Hot),
+        ]
+        .iter()
+        .cloned()
+        .collect();
+        Lucetc::new(test_file("function_hints"))
+            .with_function_hints(hints)
+            .shared_object_file(&so_path)
+            .expect("compile function_hints");
+
+        let symbols = symbols_by_address(&so_path);
+        let position = |sym: &str| {
+            symbols
+                .iter()
+                .position(|s| s == sym)
+                .expect(&format!("{} is defined", sym))
+        };
+        assert!(position("guest_func_hot_path") < position("guest_func_middle"));
+        assert!(position("guest_func_middle") < position("guest_func_handle_error"));
+    }
+
+    #[test]
+    fn compile_timings() {
+        use std::time::Instant;
+        let tmp_dir = temp_dir("compile_timings");
+
+        let start = Instant::now();
+        let timings = Lucetc::new(test_file("fibonacci"))
+            .shared_object_file_timed(tmp_dir.path().join("fibonacci.so"))
+            .expect("compile fibonacci");
+        let elapsed = start.elapsed();
+
+        // Every phase of a shared object build does some work.
+        assert!(timings.parse > Default::default());
+        assert!(timings.declare > Default::default());
+        assert!(timings.compile > Default::default());
+        assert!(timings.emit > Default::default());
+        assert!(timings.link > Default::default());
+        assert!(timings.total() <= elapsed);
+    }
+
+    #[test]
+    fn import_aliased() {
+        let (_tmp_dir, obj_path) = out_path("import_aliased.o");
+        Lucetc::new(test_file("import_aliased"))
+            .with_bindings(super::aliased_bindings())
+            .object_file(&obj_path)
+            .expect("compile import_aliased");
+
+        let nm = Command::new("nm")
+            .arg("--undefined-only")
+            .arg(&obj_path)
+            .output()
+            .expect("failed to execute nm");
+        assert!(nm.status.success(), "nm failed!");
+        let mallocs = str::from_utf8(&nm.stdout)
+            .unwrap()
+            .lines()
+            .filter(|line| line.split_whitespace().last() == Some("malloc"))
+            .count();
+        assert_eq!(mallocs, 1, "both imports resolve to a single `malloc`");
+    }
+
+    /// Write `wat` out as a binary module with a custom section `name` appended.
+    fn with_custom_section(dir: &Path, wat: &str, name: &str, contents: &str) -> PathBuf {
+        let mut wasm = wabt::wat2wasm(std::fs::read(test_file(wat)).unwrap()).unwrap();
+        let mut payload = vec![name.len() as u8];
+        payload.extend_from_slice(name.as_bytes());
+        payload.extend_from_slice(contents.as_bytes());
+        assert!(
+            payload.len() < 0x80,
+            "section size must fit in one LEB128 byte"
+        );
+        wasm.push(0); // custom section id
+        wasm.push(payload.len() as u8);
+        wasm.extend(payload);
+
+        let path = dir.join(format!("{}.wasm", wat));
+        std::fs::write(&path, wasm).unwrap();
+        path
+    }
+
+    /// Write `wat` out as a binary module carrying `config` in a `lucet.config` section.
+    fn with_config_section(dir: &Path, wat: &str, config: &str) -> PathBuf {
+        with_custom_section(dir, wat, lucetc::load::CONFIG_SECTION, config)
+    }
+
+    #[test]
+    fn module_config() {
+        let tmp = temp_dir("module_config");
+        // The module's memory starts at two pages, more than the section allows.
+        let input = with_config_section(
+            tmp.path(),
+            "module_config",
+            r#"{"min_reserved_size": 65536, "max_reserved_size": 65536, "opt_level": "fastest"}"#,
+        );
+        let obj = tmp.path().join("module_config.o");
+
+        let err = Lucetc::new(&input)
+            .object_file(&obj)
+            .expect_err("config section limits the heap");
+        assert!(err
+            .iter_chain()
+            .any(|c| c.to_string().contains("exceeds max reserved size")));
+
+        Lucetc::new(&input)
+            .with_max_reserved_size(1024 * 1024)
+            .object_file(&obj)
+            .expect("explicit setting overrides the config section");
+    }
+
+    #[test]
+    fn module_config_malformed() {
+        let tmp = temp_dir("module_config");
+        let input = with_config_section(tmp.path(), "module_config", r#"{"heap": 1}"#);
+        let err = Lucetc::new(&input)
+            .object_file(tmp.path().join("module_config.o"))
+            .expect_err("unknown setting in config section");
+        assert!(err
+            .iter_chain()
+            .any(|c| c.to_string().contains("unknown setting heap")));
+    }
+
+    #[test]
+    fn report_json() {
+        let report = Lucetc::new(test_file("arith"))
+            .report_json()
+            .expect("report for arith");
+        let report: serde_json::Value = serde_json::from_str(&report).expect("report is json");
+        for key in &[
+            "functions",
+            "imports",
+            "exports",
+            "memories",
+            "tables",
+            "data_segments",
+            "unsupported_features",
+            "settings",
+        ] {
+            assert!(report.get(key).is_some(), "report has a `{}` key", key);
+        }
+        assert_eq!(report["functions"]["defined"], 1);
+        assert_eq!(report["exports"][0]["name"], "main");
+        assert_eq!(report["memories"][0]["initial"], 1);
+        assert_eq!(report["data_segments"]["count"], 1);
+        assert_eq!(report["data_segments"]["total_size"], 8);
+        assert_eq!(report["settings"]["opt_level"], "default");
+        assert_eq!(report["unsupported_features"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn report_unsupported_features() {
+        use lucetc::{Atomics, WasmFeatures};
+        let unsupported = |lucetc: Lucetc| -> serde_json::Value {
+            let report = lucetc.report_json().expect("report");
+            let report: serde_json::Value = serde_json::from_str(&report).unwrap();
+            report["unsupported_features"].clone()
+        };
+        // `simd_local.wasm` declares a v128 local, which decodes but can't be compiled.
+        assert_eq!(
+            unsupported(Lucetc::new("tests/wasm/simd_local.wasm")),
+            serde_json::json!(["SIMD"])
+        );
+        // Proposals that are enabled aren't listed.
+        assert_eq!(
+            unsupported(
+                Lucetc::new("tests/wasm/atomics.wasm").with_atomics(Atomics::SingleThreaded)
+            ),
+            serde_json::json!([])
+        );
+        assert_eq!(
+            unsupported(Lucetc::new("tests/wasm/tail_call.wasm").with_wasm_features(
+                WasmFeatures {
+                    tail_call: true,
+                    ..WasmFeatures::default()
+                }
+            )),
+            serde_json::json!([])
+        );
+    }
+
+    #[test]
+    fn extra_objects() {
+        let (tmp_dir, shim_path) = out_path("inc_shim.o");
+        let gcc = Command::new("gcc")
+            .args(&[
+                "--std=c99",
+                "-fPIC",
+                "-c",
+                "tests/harnesses/inc_shim.c",
+                "-o",
+            ])
+            .arg(&shim_path)
+            .output()
+            .expect("failed to execute GCC");
+        assert!(gcc.status.success(), "GCC failed!");
+
+        let so_path = tmp_dir.path().join("import.so");
+        Lucetc::new(test_file("import"))
+            .with_bindings(super::test_bindings())
+            .with_extra_objects(vec![shim_path])
+            .shared_object_file(&so_path)
+            .expect("compile import with shim");
+
+        // The shim satisfies the import, so nothing is left for the loader to resolve.
+        assert!(symbols_by_address(&so_path).contains(&"inc".to_owned()));
+        let nm = Command::new("nm")
+            .arg("--undefined-only")
+            .arg(&so_path)
+            .output()
+            .expect("failed to execute nm");
+        assert!(nm.status.success(), "nm failed!");
+        assert!(!str::from_utf8(&nm.stdout)
+            .unwrap()
+            .lines()
+            .any(|line| line.split_whitespace().last() == Some("inc")));
+    }
+
+    #[test]
+    fn extra_objects_missing() {
+        let tmp_dir = temp_dir("extra_objects");
+        let err = Lucetc::new(test_file("import"))
+            .with_bindings(super::test_bindings())
+            .with_extra_objects(vec![tmp_dir.path().join("missing.o")])
+            .shared_object_file(tmp_dir.path().join("import.so"))
+            .expect_err("missing extra object");
+        assert!(format!("{}", err).contains("missing.o"));
+    }
+
+    #[test]
+    fn stack_sizes() {
+        use lucetc::compile;
+        use lucetc::program::{HeapSettings, Program};
+        let m = super::load("stack_usage");
+        let p = Program::new(m, super::test_bindings(), HeapSettings::default())
+            .expect("make program for stack_usage");
+        let obj = compile(&p, "stack_usage", lucetc::OptLevel::Best)
+            .expect("compile stack_usage")
+            .codegen()
+            .expect("codegen stack_usage");
+        let size = |sym: &str| {
+            obj.stack_sizes()
+                .iter()
+                .find(|(s, _)| s == sym)
+                .map(|(_, size)| *size)
+                .expect(&format!("{} has a stack size", sym))
+        };
+        // 64 i64 locals are spilled across the call.
+        assert!(size("guest_func_spills") >= 64 * 8);
+        assert!(size("guest_func_leaf") < 64);
+    }
+
+    #[test]
+    fn max_stack_per_function() {
+        let (_tmp_dir, obj_path) = out_path("stack_usage.o");
+        let err = Lucetc::new(test_file("stack_usage"))
+            .with_max_stack_per_function(256)
+            .object_file(&obj_path)
+            .expect_err("spills exceeds the stack limit");
+        assert!(format!("{}", err).contains("guest_func_spills"));
+
+        Lucetc::new(test_file("stack_usage"))
+            .with_max_stack_per_function(64 * 1024)
+            .object_file(&obj_path)
+            .expect("stack_usage fits in 64KiB of stack");
+    }
+
+    #[test]
+    fn big_endian_target() {
+        let tmp_dir = temp_dir("big_endian_target");
+        let target = "powerpc64-unknown-linux-gnu".parse().unwrap();
+        let err = Lucetc::new(test_file("data_segment"))
+            .with_target(target)
+            .object_file(tmp_dir.path().join("data_segment.o"))
+            .expect_err("big-endian targets are unsupported");
+        assert!(err
+            .iter_chain()
+            .any(|c| c.to_string().contains("big-endian")));
+    }
+
+    #[test]
+    fn relocations() {
+        use lucetc::compile;
+        use lucetc::program::{HeapSettings, Program};
+        use lucetc::RelocKind;
+        let m = super::load("import");
+        let p = Program::new(m, super::test_bindings(), HeapSettings::default())
+            .expect("make program for import");
+        let obj = compile(&p, "import", lucetc::OptLevel::Best)
+            .expect("compile import")
+            .codegen()
+            .expect("codegen import");
+        let relocs = obj.relocations().expect("read relocations");
+        let calls: Vec<_> = relocs
+            .iter()
+            .filter(|r| r.symbol == "inc" && r.section.contains("guest_func_main"))
+            .collect();
+        assert_eq!(calls.len(), 1, "main calls the import once: {:?}", relocs);
+        assert_eq!(calls[0].kind, RelocKind::PltRel4);
+    }
+
+    #[test]
+    fn relocation_hook() {
+        use lucetc::compiler::relocations::relocations;
+        use lucetc::RelocKind;
+        let (_tmp_dir, obj_path) = out_path("import.o");
+        Lucetc::new(test_file("import"))
+            .with_bindings(super::test_bindings())
+            .with_relocation_hook(|r| {
+                if r.symbol == "inc" {
+                    r.kind = RelocKind::PcRel4;
+                }
+            })
+            .object_file(&obj_path)
+            .expect("compile import");
+
+        let relocs = relocations(&std::fs::read(&obj_path).unwrap()).expect("read relocations");
+        assert!(relocs
+            .iter()
+            .filter(|r| r.symbol == "inc")
+            .all(|r| r.kind == RelocKind::PcRel4));
+    }
+
+    fn clif_for(lucetc: Lucetc, dir: &Path) -> String {
+        let clif_path = dir.join("out.clif");
+        lucetc.clif_ir(&clif_path).expect("write clif");
+        std::fs::read_to_string(&clif_path).unwrap()
+    }
+
+    #[test]
+    fn heap_base() {
+        use lucetc::HeapBase;
+        let tmp_dir = temp_dir("heap_base");
+
+        // By default the heap is the vmctx itself.
+        let clif = clif_for(Lucetc::new(test_file("memory")), tmp_dir.path());
+        assert!(clif.contains("= vmctx"));
+        assert!(!clif.contains("symbol colocated"));
+
+        // With a fixed offset the base is loaded from `lucet_heap_base` instead.
+        let clif = clif_for(
+            Lucetc::new(test_file("memory")).with_heap_base(HeapBase::FixedOffset(0x1000_0000)),
+            tmp_dir.path(),
+        );
+        assert!(clif.contains("symbol colocated"));
+        assert!(clif.contains("readonly"));
+
+        let obj_path = tmp_dir.path().join("memory.o");
+        Lucetc::new(test_file("memory"))
+            .with_heap_base(HeapBase::FixedOffset(0x1000_0000))
+            .object_file(&obj_path)
+            .expect("compile memory with a fixed heap base");
+        assert!(symbols_by_address(&obj_path).contains(&"lucet_heap_base".to_owned()));
+    }
+
+    #[test]
+    fn export_alias() {
+        let (_tmp_dir, so_path) = out_path("fibonacci.so");
+        Lucetc::new(test_file("fibonacci"))
+            .with_export_alias("main", "guest_main")
+            .with_export_alias("main", "fib_main")
+            .shared_object_file(&so_path)
+            .expect("compile fibonacci with aliases");
+
+        let nm = Command::new("nm")
+            .arg("--defined-only")
+            .arg(&so_path)
+            .output()
+            .expect("failed to execute nm");
+        assert!(nm.status.success(), "nm failed!");
+        let nm = String::from_utf8(nm.stdout).unwrap();
+        let address = |sym: &str| {
+            nm.lines()
+                .find(|line| line.split_whitespace().nth(2) == Some(sym))
+                .and_then(|line| line.split_whitespace().next())
+                .expect(&format!("{} is defined", sym))
+                .to_owned()
+        };
+        assert_eq!(address("guest_func_main"), address("guest_main"));
+        assert_eq!(address("guest_func_main"), address("fib_main"));
+    }
+
+    #[test]
+    fn export_alias_missing() {
+        let tmp_dir = temp_dir("export_alias");
+        let err = Lucetc::new(test_file("fibonacci"))
+            .with_export_alias("start", "guest_start_alias")
+            .object_file(tmp_dir.path().join("fibonacci.o"))
+            .expect_err("fibonacci has no `start` export");
+        assert!(format!("{}", err).contains("start"));
+    }
+
+    #[test]
+    fn custom_section_policy() {
+        use lucetc::CustomSectionPolicy;
+        let tmp = temp_dir("custom_section_policy");
+        let input = with_custom_section(tmp.path(), "arith", "producers", "lucet tests");
+        let obj = tmp.path().join("arith.o");
+        let allow_list = |names: &[&str]| {
+            CustomSectionPolicy::AllowList(names.iter().map(|n| n.to_string()).collect())
+        };
+
+        Lucetc::new(&input)
+            .object_file(&obj)
+            .expect("custom sections are allowed by default");
+        Lucetc::new(&input)
+            .with_custom_section_policy(CustomSectionPolicy::Allow)
+            .object_file(&obj)
+            .expect("allow any custom section");
+        Lucetc::new(&input)
+            .with_custom_section_policy(allow_list(&["producers"]))
+            .object_file(&obj)
+            .expect("section is on the allow list");
+
+        let err = Lucetc::new(&input)
+            .with_custom_section_policy(allow_list(&["lucet.config"]))
+            .object_file(&obj)
+            .expect_err("section is not on the allow list");
+        assert!(err.iter_chain().any(|c| c
+            .to_string()
+            .contains("custom section \"producers\" is not allowed")));
+
+        let err = Lucetc::new(&input)
+            .with_custom_section_policy(CustomSectionPolicy::Deny)
+            .object_file(&obj)
+            .expect_err("no custom sections allowed");
+        assert!(err.iter_chain().any(|c| c
+            .to_string()
+            .contains("custom section \"producers\" is not allowed")));
+
+        Lucetc::new(test_file("arith"))
+            .with_custom_section_policy(CustomSectionPolicy::Deny)
+            .object_file(&obj)
+            .expect("module without custom sections");
+    }
+
+    #[test]
+    fn custom_section_policy_name_section() {
+        use lucetc::CustomSectionPolicy;
+        let tmp = temp_dir("custom_section_policy");
+        let input = with_custom_section(tmp.path(), "arith", "name", "");
+        let obj = tmp.path().join("arith.o");
+
+        Lucetc::new(&input)
+            .with_custom_section_policy(CustomSectionPolicy::AllowList(Default::default()))
+            .object_file(&obj)
+            .expect("name section is always on the allow list");
+        Lucetc::new(&input)
+            .with_custom_section_policy(CustomSectionPolicy::Deny)
+            .object_file(&obj)
+            .expect("deny accepts the name section, which lucetc reads");
+
+        let input = with_config_section(tmp.path(), "arith", "{}");
+        Lucetc::new(&input)
+            .with_custom_section_policy(CustomSectionPolicy::Deny)
+            .object_file(&obj)
+            .expect("deny accepts the lucet.config section, which lucetc reads");
+    }
+
+    #[test]
+    fn functions() {
+        use parity_wasm::elements::Instruction;
+        let functions: Vec<_> = Lucetc::new(test_file("add"))
+            .functions()
+            .expect("decode add")
+            .collect();
+        assert_eq!(functions.len(), 2);
+
+        let add = &functions[0];
+        assert_eq!(add.index, 0);
+        assert_eq!(add.name.as_ref().map(|n| n.as_str()), Some("add"));
+        assert_eq!(add.instructions.len(), 4);
+        let get_locals = add
+            .instructions
+            .iter()
+            .filter(|i| match i {
+                Instruction::GetLocal(_) => true,
+                _ => false,
+            })
+            .count();
+        assert_eq!(get_locals, 2);
+        assert!(add.instructions.contains(&Instruction::I32Add));
+
+        let grow = &functions[1];
+        assert_eq!(grow.index, 1);
+        assert_eq!(grow.name.as_ref().map(|n| n.as_str()), Some("grow"));
+        assert_eq!(grow.instructions.len(), 3);
+        assert!(grow.instructions.contains(&Instruction::GrowMemory(0)));
+        assert_eq!(grow.instructions.last(), Some(&Instruction::End));
+    }
+
+    #[test]
+    fn type_ids() {
+        use lucetc::TypeIds;
+        let a = Lucetc::new(test_file("type_ids_a"));
+        let b = Lucetc::new(test_file("type_ids_b"));
+
+        // Each module numbers signatures by its own type section.
+        let a_ids = a.assigned_type_ids().expect("type IDs for a");
+        let b_ids = b.assigned_type_ids().expect("type IDs for b");
+        assert_ne!(a_ids, b_ids);
+
+        let mut ids = TypeIds::from_json(&serde_json::json!({
+            "(i32) -> i32": 100,
+            "(i32, i32) -> i32": 200,
+        }))
+        .expect("valid type IDs");
+        ids.insert(&parity_wasm::elements::FunctionType::new(vec![], None), 300);
+        assert_eq!(
+            ids.iter().collect::<Vec<_>>(),
+            vec![
+                ("() -> ()", 300),
+                ("(i32) -> i32", 100),
+                ("(i32, i32) -> i32", 200)
+            ]
+        );
+
+        let a = a.with_type_ids(ids.clone());
+        let b = b.with_type_ids(ids.clone());
+        let a_ids = a.assigned_type_ids().expect("type IDs for a");
+        let b_ids = b.assigned_type_ids().expect("type IDs for b");
+        assert_eq!(a_ids, b_ids);
+        assert_eq!(
+            a_ids.to_json(),
+            serde_json::json!({"(i32) -> i32": 100, "(i32, i32) -> i32": 200})
+        );
+
+        let tmp = temp_dir("type_ids");
+        a.object_file(tmp.path().join("a.o"))
+            .expect("compile a with supplied type IDs");
+        b.object_file(tmp.path().join("b.o"))
+            .expect("compile b with supplied type IDs");
+    }
+
+    #[test]
+    fn type_ids_missing_signature() {
+        use lucetc::TypeIds;
+        let ids = TypeIds::from_json(&serde_json::json!({ "(i32) -> i32": 1 })).unwrap();
+        let err = Lucetc::new(test_file("type_ids_a"))
+            .with_type_ids(ids)
+            .assigned_type_ids()
+            .expect_err("binary signature has no ID");
+        assert!(err.iter_chain().any(|c| c
+            .to_string()
+            .contains("signature (i32, i32) -> i32 is not in the type ID mapping")));
+    }
+
+    #[test]
+    fn strip() {
+        let tmp = temp_dir("strip");
+        let wat = std::fs::read(test_file("strip")).unwrap();
+        let wasm = wabt::Wat2Wasm::new()
+            .write_debug_names(true)
+            .convert(wat)
+            .expect("assemble strip.wat");
+        let input = tmp.path().join("strip.wasm");
+        std::fs::write(&input, wasm.as_ref()).unwrap();
+
+        let full = tmp.path().join("full.o");
+        Lucetc::new(&input)
+            .object_file(&full)
+            .expect("compile without strip");
+        let stripped = tmp.path().join("stripped.o");
+        Lucetc::new(&input)
+            .with_strip(true)
+            .object_file(&stripped)
+            .expect("compile with strip");
+
+        let size = |p: &Path| std::fs::metadata(p).unwrap().len();
+        assert!(size(&stripped) < size(&full));
+
+        let full_syms = symbols_by_address(&full);
+        assert!(full_syms
+            .iter()
+            .any(|s| s.contains("a_rather_long_internal_function_name")));
+
+        let syms = symbols_by_address(&stripped);
+        for essential in &[
+            "guest_func_main",
+            "lucet_heap_spec",
+            "lucet_globals_spec",
+            "lucet_module_data",
+            "lucet_module_data_len",
+            "wasm_data_segments",
+            "wasm_data_segments_len",
+            "guest_sparse_page_data",
+            "lucet_trap_manifest",
+            "lucet_trap_manifest_len",
+        ] {
+            assert!(
+                syms.iter().any(|s| s == essential),
+                "{} is missing from the stripped object",
+                essential
+            );
+        }
+        assert!(!syms
+            .iter()
+            .any(|s| s.contains("a_rather_long_internal_function_name")));
+    }
+
+    #[test]
+    fn export_symbols_checked() {
+        let (_tmp, obj) = out_path("function_hints.o");
+        Lucetc::new(test_file("function_hints"))
+            .object_file(&obj)
+            .expect("every export has a symbol");
+        let syms = symbols_by_address(&obj);
+        for export in &["handle_error", "middle", "hot_path"] {
+            assert!(syms.contains(&format!("guest_func_{}", export)));
+        }
+    }
+
+    #[test]
+    fn export_twice() {
+        // A function exported under two names gets a symbol for each, at the same address.
+        let (_tmp, obj) = out_path("export_twice.o");
+        Lucetc::new(test_file("export_twice"))
+            .object_file(&obj)
+            .expect("compile export_twice");
+        let nm = Command::new("nm").arg(&obj).output().expect("run nm");
+        let syms = str::from_utf8(&nm.stdout).unwrap();
+        let address = |symbol: &str| {
+            syms.lines()
+                .find(|l| l.split_whitespace().last() == Some(symbol))
+                .expect(&format!("{} is in the symbol table: {}", symbol, syms))
+                .split_whitespace()
+                .next()
+                .unwrap()
+                .to_owned()
+        };
+        assert_eq!(address("guest_func_first"), address("guest_func_second"));
+    }
+
+    #[test]
+    fn guard_pages() {
+        let guard_size = |lucetc: Lucetc| {
+            let report = lucetc.report_json().expect("report for arith");
+            let report: serde_json::Value = serde_json::from_str(&report).unwrap();
+            report["settings"]["guard_size"].as_u64().unwrap()
+        };
+        assert_eq!(
+            guard_size(
+                Lucetc::new(test_file("arith"))
+                    .with_host_page_size(4096)
+                    .with_guard_pages(2)
+            ),
+            8192
+        );
+        assert_eq!(
+            guard_size(
+                Lucetc::new(test_file("arith"))
+                    .with_host_page_size(16 * 1024)
+                    .with_guard_pages(2)
+            ),
+            32 * 1024
+        );
+        assert_eq!(
+            guard_size(Lucetc::new(test_file("arith")).with_guard_pages(3)),
+            3 * 4096,
+            "4 KiB pages by default"
+        );
+    }
+
+    #[test]
+    fn dylink_info() {
+        use lucetc::load::DylinkInfo;
+        let tmp = temp_dir("dylink");
+        // mem_size 16, mem_align 2, table_size 3, table_align 0, and one needed library.
+        let input = with_custom_section(
+            tmp.path(),
+            "arith",
+            lucetc::load::DYLINK_SECTION,
+            "\x10\x02\x03\x00\x01\x09libfoo.so",
+        );
+        let info = Lucetc::new(&input).dylink_info().expect("parse dylink");
+        assert_eq!(
+            info,
+            Some(DylinkInfo {
+                mem_size: 16,
+                mem_align: 2,
+                table_size: 3,
+                table_align: 0,
+                needed: vec!["libfoo.so".to_owned()],
+            })
+        );
+
+        let info = Lucetc::new(test_file("arith"))
+            .dylink_info()
+            .expect("no dylink section");
+        assert_eq!(info, None);
+
+        let input = with_custom_section(
+            tmp.path(),
+            "arith",
+            lucetc::load::DYLINK_SECTION,
+            "\x10\x02\x03\x00\x01\x09libfoo",
+        );
+        let err = Lucetc::new(&input)
+            .dylink_info()
+            .expect_err("needed library name is truncated");
+        assert!(err
+            .iter_chain()
+            .any(|c| c.to_string().contains("parsing dylink section")));
+    }
+
+    #[test]
+    fn required_host_symbols() {
+        let lucetc = Lucetc::new(test_file("import_many")).with_bindings(super::test_bindings());
+        let required = lucetc
+            .required_host_symbols()
+            .expect("required symbols for import_many");
+        for symbol in &["imp_0", "imp_3", "lucet_vmctx_grow_memory"] {
+            assert!(
+                required.iter().any(|s| s == symbol),
+                "{} is required",
+                symbol
+            );
+        }
+        assert!(!required.iter().any(|s| s == "lucet_probestack"));
+
+        let (_tmp, obj) = out_path("import_many.o");
+        lucetc.object_file(&obj).expect("compile import_many");
+        let nm = Command::new("nm")
+            .arg("--undefined-only")
+            .arg(&obj)
+            .output()
+            .expect("failed to execute nm");
+        assert!(nm.status.success(), "nm failed!");
+        for line in str::from_utf8(&nm.stdout).unwrap().lines() {
+            let symbol = line.split_whitespace().last().unwrap();
+            assert!(
+                required.iter().any(|s| s == symbol),
+                "undefined symbol {} is not in the list",
+                symbol
+            );
+        }
+    }
+
+    #[test]
+    fn elide_bounds_checks() {
+        let tmp_dir = temp_dir("elide_bounds_checks");
+        /// The CLIF of `func`, from a file holding every function.
+        fn function<'a>(clif: &'a str, func: &str) -> &'a str {
+            clif.split("; guest_func_")
+                .find(|f| f.starts_with(func))
+                .expect("function is in the clif")
+        }
+
+        let clif = clif_for(Lucetc::new(test_file("bounds_check")), tmp_dir.path());
+        for func in &["constant", "dynamic", "far"] {
+            assert!(function(&clif, func).contains("heap_addr"));
+        }
+
+        let clif = clif_for(
+            Lucetc::new(test_file("bounds_check")).with_elide_bounds_checks(true),
+            tmp_dir.path(),
+        );
+        assert!(!function(&clif, "constant").contains("heap_addr"));
+        assert!(function(&clif, "constant").contains("iadd_imm"));
+        // The index isn't known, or is past the reserved region, so these are still checked.
+        assert!(function(&clif, "dynamic").contains("heap_addr"));
+        assert!(function(&clif, "far").contains("heap_addr"));
+    }
+
+    #[test]
+    fn multi_memory() {
+        let tmp = temp_dir("multi_memory");
+        // The wat assembler only accepts one memory, so write the binary by hand: a memory
+        // section holding two one-page memories.
+        let input = tmp.path().join("two_memories.wasm");
+        std::fs::write(
+            &input,
+            b"\0asm\x01\0\0\0\x05\x05\x02\x00\x01\x00\x01".to_vec(),
+        )
+        .unwrap();
+        let obj = tmp.path().join("two_memories.o");
+
+        let err = Lucetc::new(&input)
+            .object_file(&obj)
+            .expect_err("multi-memory cannot be compiled");
+        assert!(err
+            .iter_chain()
+            .any(|c| c.to_string().contains("Unsupported: multi-memory proposal")));
+
+        // The report still describes both memories.
+        let report = Lucetc::new(&input).report_json().expect("report");
+        let report: serde_json::Value = serde_json::from_str(&report).unwrap();
+        assert_eq!(report["memories"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn builtin_cycle() {
+        let (tmp, source) = out_path("builtins.c");
+        std::fs::write(&source, "int builtin_inc(int x) { return x + 1; }\n").unwrap();
+        let builtins = tmp.path().join("libbuiltins.so");
+        let gcc = Command::new("gcc")
+            .arg("-shared")
+            .arg("-fPIC")
+            .arg(&source)
+            .arg("-o")
+            .arg(&builtins)
+            .output()
+            .expect("failed to execute gcc");
+        assert!(gcc.status.success(), "GCC failed!");
+
+        // The patcher needs a name section to record the builtin imports in.
+        let wasm = wabt::Wat2Wasm::new()
+            .write_debug_names(true)
+            .convert(std::fs::read(test_file("builtin_inc")).unwrap())
+            .expect("assemble builtin_inc.wat");
+        let input = tmp.path().join("builtin_inc.wasm");
+        std::fs::write(&input, wasm.as_ref()).unwrap();
+        let obj = tmp.path().join("builtin_inc.o");
+
+        Lucetc::new(&input)
+            .with_builtins(&builtins)
+            .object_file(&obj)
+            .expect("substitute builtin_inc once");
+
+        // After the first substitution `inc` is the `builtin_inc` import, so the second would
+        // replace it with itself.
+        let err = Lucetc::new(&input)
+            .with_builtins(&builtins)
+            .with_builtins(&builtins)
+            .object_file(&obj)
+            .expect_err("substituting the same builtin twice");
+        assert!(err.iter_chain().any(|c| c
+            .to_string()
+            .contains("Builtin substitution cycle: inc -> builtin_inc -> inc")));
+    }
+
+    #[test]
+    fn effective_settings() {
+        use lucetc::{HeapSettings, OptLevel};
+        let tmp = temp_dir("effective_settings");
+        let input = with_config_section(
+            tmp.path(),
+            "module_config",
+            r#"{"max_reserved_size": 65536, "opt_level": "fastest"}"#,
+        );
+
+        let settings = Lucetc::new(&input).effective_settings();
+        assert_eq!(settings.heap.max_reserved_size, 65536);
+        assert_eq!(settings.opt_level, OptLevel::Fastest);
+
+        let settings = Lucetc::new(&input)
+            .with_max_reserved_size(1024 * 1024)
+            .effective_settings();
+        assert_eq!(settings.heap.max_reserved_size, 1024 * 1024);
+        assert_eq!(settings.opt_level, OptLevel::Fastest);
+        assert_eq!(settings.heap.guard_size, HeapSettings::default().guard_size);
+        assert_eq!(settings.target, None);
+    }
+
+    #[test]
+    fn module_config_with_features() {
+        use lucetc::OptLevel;
+        let tmp = temp_dir("module_config_with_features");
+        // `tail_call.wasm` only decodes with the tail-call proposal enabled.
+        let mut wasm = std::fs::read("tests/wasm/tail_call.wasm").unwrap();
+        let name = lucetc::load::CONFIG_SECTION;
+        let config = r#"{"opt_level": "fastest"}"#;
+        wasm.push(0); // custom section id
+        wasm.push((1 + name.len() + config.len()) as u8);
+        wasm.push(name.len() as u8);
+        wasm.extend(name.as_bytes());
+        wasm.extend(config.as_bytes());
+        let input = tmp.path().join("tail_call.wasm");
+        std::fs::write(&input, wasm).unwrap();
+
+        let settings = Lucetc::new(&input).effective_settings();
+        assert_eq!(settings.opt_level, OptLevel::Fastest);
+    }
+
+    #[test]
+    fn build_id() {
+        use lucetc::BuildId;
+        let tmp = temp_dir("build_id");
+        let notes = |path: &Path| {
+            let readelf = Command::new("readelf")
+                .arg("--notes")
+                .arg(path)
+                .output()
+                .expect("failed to execute readelf");
+            assert!(readelf.status.success(), "readelf failed!");
+            String::from_utf8(readelf.stdout).unwrap()
+        };
+
+        let obj = tmp.path().join("default.o");
+        Lucetc::new(test_file("add"))
+            .object_file(&obj)
+            .expect("compile with the default build ID");
+        assert!(notes(&obj).contains("NT_GNU_BUILD_ID"));
+
+        let obj = tmp.path().join("none.o");
+        Lucetc::new(test_file("add"))
+            .with_build_id(BuildId::None)
+            .object_file(&obj)
+            .expect("compile without a build ID");
+        assert!(!notes(&obj).contains("NT_GNU_BUILD_ID"));
+
+        // The linker writes its own note, for the linked image.
+        let so = tmp.path().join("default.so");
+        Lucetc::new(test_file("add"))
+            .shared_object_file(&so)
+            .expect("link with the default build ID");
+        assert!(notes(&so).contains("NT_GNU_BUILD_ID"));
+
+        let so = tmp.path().join("sha1.so");
+        Lucetc::new(test_file("add"))
+            .with_build_id(BuildId::Sha1)
+            .shared_object_file(&so)
+            .expect("link with a build ID");
+        assert!(notes(&so).contains("NT_GNU_BUILD_ID"));
+
+        let so = tmp.path().join("none.so");
+        Lucetc::new(test_file("add"))
+            .with_build_id(BuildId::None)
+            .shared_object_file(&so)
+            .expect("link without a build ID");
+        assert!(!notes(&so).contains("NT_GNU_BUILD_ID"));
+    }
+
+    #[test]
+    fn output_hash() {
+        use lucetc::{BuildId, OptLevel};
+        let hash = |lucetc: Lucetc| lucetc.output_hash().expect("hash module");
+        let base = hash(Lucetc::new(test_file("fibonacci")));
+        assert_eq!(base, hash(Lucetc::new(test_file("fibonacci"))));
+        // Only the shared object is affected by these.
+        assert_eq!(
+            base,
+            hash(
+                Lucetc::new(test_file("fibonacci"))
+                    .with_extra_objects(vec![PathBuf::from("extra.o")])
+            )
+        );
+        assert_ne!(
+            base,
+            hash(Lucetc::new(test_file("fibonacci")).with_opt_level(OptLevel::Best))
+        );
+        assert_ne!(
+            base,
+            hash(Lucetc::new(test_file("fibonacci")).with_build_id(BuildId::None))
+        );
+    }
+
+    #[test]
+    fn externref() {
+        let tmp = temp_dir("externref");
+        // The wat assembler doesn't know `externref`, so write the binary by hand: a function
+        // of type `(externref) -> ()` that stores its argument into slot 0 of an `externref`
+        // table with `i32.const 0; local.get 0; table.set 0`.
+        let input = tmp.path().join("externref.wasm");
+        std::fs::write(
+            &input,
+            b"\0asm\x01\0\0\0\
+              \x01\x05\x01\x60\x01\x6f\x00\
+              \x03\x02\x01\x00\
+              \x04\x04\x01\x6f\x00\x01\
+              \x0a\x0a\x01\x08\x00\x41\x00\x20\x00\x26\x00\x0b"
+                .to_vec(),
+        )
+        .unwrap();
+        let obj = tmp.path().join("externref.o");
+
+        let err = Lucetc::new(&input)
+            .object_file(&obj)
+            .expect_err("externref cannot be compiled");
+        assert!(err.iter_chain().any(|c| c
+            .to_string()
+            .contains("Unsupported: reference-types proposal")));
+    }
+
+    #[test]
+    fn diagnostics() {
+        use lucetc::{Diagnostic, Severity};
+        use std::cell::RefCell;
+        use std::rc::Rc;
+        let tmp = temp_dir("diagnostics");
+        let diagnostics = Rc::new(RefCell::new(vec![]));
+        let sink = diagnostics.clone();
+        let hints: HashMap<String, Hint> = [("nonexistent".into(), Hint::Hot)]
+            .iter()
+            .cloned()
+            .collect();
+        Lucetc::new(test_file("fibonacci"))
+            .with_function_hints(hints)
+            .with_diagnostics(move |d| sink.borrow_mut().push(d))
+            .object_file(tmp.path().join("fibonacci.o"))
+            .expect("an unknown hint is not an error");
+        assert_eq!(
+            *diagnostics.borrow(),
+            vec![Diagnostic {
+                severity: Severity::Warning,
+                message: "ignoring layout hint for unknown function `nonexistent`".to_owned(),
+            }]
+        );
+    }
+
+    /// The bytes of `symbol`, which must start its own section, as objects from lucetc do.
+    fn symbol_contents(path: &Path, symbol: &str) -> Vec<u8> {
+        let objdump = Command::new("objdump")
+            .arg("-t")
+            .arg(path)
+            .output()
+            .expect("run objdump");
+        let table = str::from_utf8(&objdump.stdout).unwrap();
+        let line = table
+            .lines()
+            .find(|l| l.split_whitespace().last() == Some(symbol))
+            .expect(&format!("{} is in the symbol table", symbol));
+        let mut fields = line.split('\t');
+        let section = fields.next().unwrap().split_whitespace().last().unwrap();
+        let size = u64::from_str_radix(
+            fields.next().unwrap().split_whitespace().next().unwrap(),
+            16,
+        )
+        .unwrap();
+
+        let out = path.with_extension(format!("{}.bin", symbol));
+        let objcopy = Command::new("objcopy")
+            .args(&["-O", "binary", "--only-section"])
+            .arg(section)
+            .arg(path)
+            .arg(&out)
+            .status()
+            .expect("run objcopy");
+        assert!(objcopy.success());
+        let mut contents = std::fs::read(&out).unwrap();
+        contents.truncate(size as usize);
+        contents
+    }
+
+    #[test]
+    fn elements() {
+        let (_tmp, obj) = out_path("icall_sparse.o");
+        Lucetc::new(test_file("icall_sparse"))
+            .object_file(&obj)
+            .expect("compile icall_sparse");
+
+        let words = |bytes: Vec<u8>| {
+            bytes
+                .chunks(4)
+                .map(|w| u32::from_le_bytes([w[0], w[1], w[2], w[3]]))
+                .collect::<Vec<_>>()
+        };
+        // Table 0, active, at offset 1: `$righttype1`, `$righttype2`, and `$wrongtype`, padded
+        // to 8 bytes.
+        let expected = vec![0, 0, 1, 3, 1, 2, 3, 0];
+        assert_eq!(words(symbol_contents(&obj, "lucet_elements")), expected);
+        assert_eq!(
+            words(symbol_contents(&obj, "lucet_elements_len")),
+            vec![expected.len() as u32 * 4]
+        );
+    }
+
+    #[test]
+    fn on_unsupported_function() {
+        use lucetc::UnsupportedFunctionPolicy;
+        let tmp = temp_dir("on_unsupported_function");
+        let input = PathBuf::from("tests/wasm/simd_local.wasm");
+        let obj = tmp.path().join("simd_local.o");
+
+        let err = Lucetc::new(&input)
+            .object_file(&obj)
+            .expect_err("the v128 local can't be compiled");
+        assert!(err
+            .iter_chain()
+            .any(|c| c.to_string().contains("Unsupported: SIMD proposal")));
+
+        Lucetc::new(&input)
+            .with_on_unsupported_function(UnsupportedFunctionPolicy::Stub)
+            .object_file(&obj)
+            .expect("the rest of the module compiles");
+        let symbols = symbols_by_address(&obj);
+        assert!(symbols.iter().any(|s| s == "guest_func_supported"));
+        assert!(symbols.iter().any(|s| s == "guest_func_simd"));
+    }
+
+    #[test]
+    fn object_file_with_map() {
+        let (tmp, obj) = out_path("function_hints.o");
+        let map = tmp.path().join("function_hints.json");
+        Lucetc::new(test_file("function_hints"))
+            .object_file_with_map(&obj, &map)
+            .expect("compile function_hints");
+
+        let map: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(&map).expect("map is written")).unwrap();
+        let functions = map["functions"].as_array().unwrap();
+
+        let nm = Command::new("nm")
+            .arg("--defined-only")
+            .arg(&obj)
+            .output()
+            .expect("failed to execute nm");
+        let text_symbols: Vec<(u64, String)> = str::from_utf8(&nm.stdout)
+            .unwrap()
+            .lines()
+            .filter_map(|line| {
+                let fields: Vec<_> = line.split_whitespace().collect();
+                match fields.as_slice() {
+                    [address, "T", name] | [address, "t", name] => {
+                        Some((u64::from_str_radix(address, 16).unwrap(), name.to_string()))
+                    }
+                    _ => None,
+                }
+            })
+            .collect();
+        assert_eq!(functions.len(), text_symbols.len());
+        for (address, name) in text_symbols {
+            let entry = functions
+                .iter()
+                .find(|f| f["symbol"] == name.as_str())
+                .expect(&format!("{} is in the map", name));
+            assert_eq!(entry["offset"].as_u64(), Some(address));
+        }
+    }
+
+    #[test]
+    fn sort_imports() {
+        use lucetc::compile_with_settings;
+        use lucetc::program::{HeapSettings, Program};
+        use lucetc::CodegenSettings;
+        // `import_shuffled` is `import_many` with its imports declared in a different order.
+        let relocations = |file: &str| {
+            let m = super::load(file);
+            let p = Program::new(m, super::test_bindings(), HeapSettings::default())
+                .expect(&format!("make program for {}", file));
+            let settings = CodegenSettings {
+                sort_imports: true,
+                ..CodegenSettings::default()
+            };
+            compile_with_settings(&p, "imports", &settings)
+                .expect(&format!("compile {}", file))
+                .codegen()
+                .expect(&format!("generate code for {}", file))
+                .relocations()
+                .expect("read relocations")
+        };
+        assert_eq!(relocations("import_many"), relocations("import_shuffled"));
+    }
+
+    #[test]
+    fn noreturn() {
+        let noreturn: HashMap<String, bool> = Lucetc::new(test_file("noreturn"))
+            .functions()
+            .expect("decode noreturn")
+            .map(|f| (f.name.unwrap(), f.noreturn))
+            .collect();
+        assert_eq!(noreturn["trap"], true);
+        assert_eq!(noreturn["spin"], true);
+        assert_eq!(noreturn["early_return"], false);
+        assert_eq!(noreturn["loop_exit"], false);
+
+        let mut returns = Lucetc::new(test_file("add")).functions().unwrap();
+        assert!(returns.all(|f| !f.noreturn));
+    }
+
+    #[test]
+    fn time_budget() {
+        use lucetc::error::{LucetcError, LucetcErrorKind};
+        use std::time::Duration;
+        let tmp = temp_dir("time_budget");
+        let mut wat = String::from("(module\n");
+        for i in 0..500 {
+            wat.push_str(&format!(
+                "  (func $f{} (export \"f{}\") (param i32) (result i32)\n    \
+                 (i32.mul (i32.add (get_local 0) (i32.const {})) (get_local 0)))\n",
+                i, i, i
+            ));
+        }
+        wat.push_str(")\n");
+        let input = tmp.path().join("many_functions.wat");
+        std::fs::write(&input, wat).unwrap();
+        let obj = tmp.path().join("many_functions.o");
+
+        let err = Lucetc::new(&input)
+            .with_time_budget(Duration::from_secs(0))
+            .object_file(&obj)
+            .expect_err("no time to compile anything");
+        let cancelled = err
+            .iter_chain()
+            .any(|c| match c.downcast_ref::<LucetcError>() {
+                Some(e) => *e.get_context() == LucetcErrorKind::Cancelled,
+                None => false,
+            });
+        assert!(cancelled, "compilation is cancelled: {}", err);
+
+        Lucetc::new(&input)
+            .with_time_budget(Duration::from_secs(600))
+            .object_file(&obj)
+            .expect("plenty of time to compile");
+    }
+
+    #[test]
+    fn absolute_base() {
+        let tmp = temp_dir("absolute_base");
+        let image = |base: u64| {
+            let out = tmp.path().join(format!("fibonacci_{:x}.bin", base));
+            Lucetc::new(test_file("fibonacci"))
+                .with_absolute_base(Some(base))
+                .object_file(&out)
+                .expect("fibonacci defines everything it refers to");
+            std::fs::read(out).unwrap()
+        };
+        let low = image(0x1000_0000);
+        let high = image(0x2000_0000);
+        assert_ne!(&low[0..4], b"\x7fELF", "a flat image is not an object");
+        assert_eq!(
+            low.len(),
+            high.len(),
+            "the layout doesn't depend on the base"
+        );
+        assert_ne!(low, high, "addresses are resolved against the base");
+
+        let err = Lucetc::new(test_file("import"))
+            .with_bindings(super::test_bindings())
+            .with_absolute_base(Some(0x1000_0000))
+            .object_file(tmp.path().join("import.bin"))
+            .expect_err("imports can't be resolved in a flat image");
+        assert!(
+            err.iter_chain()
+                .any(|c| c.to_string().contains("doesn't define: inc")),
+            "the unresolved import is named: {}",
+            err
+        );
+    }
+
+    #[test]
+    fn bitcount_baseline_target() {
+        // An explicit target gets none of the host's optional instructions, so this needs the
+        // fallback lowering of counts.
+        let tmp = temp_dir("bitcount_baseline_target");
+        let target = "x86_64-unknown-linux-gnu".parse().unwrap();
+        Lucetc::new(test_file("bitcount"))
+            .with_target(target)
+            .object_file(tmp.path().join("bitcount.o"))
+            .expect("compile bitcount without lzcnt, tzcnt, or popcnt");
+    }
+
+    #[test]
+    fn separate_rodata() {
+        let (tmp, obj) = out_path("data_segment.o");
+        Lucetc::new(test_file("data_segment"))
+            .with_separate_rodata(true)
+            .object_file(&obj)
+            .expect("compile data_segment");
+
+        // The three segments overlap on the first page.
+        let rodata = symbol_contents(&obj, "lucet_rodata");
+        assert_eq!(rodata.len(), 4096);
+        assert_eq!(&rodata[0..6], b"\xaa\xcc\xdd99\x00");
+        let spec = symbol_contents(&obj, "lucet_rodata_spec")
+            .chunks(8)
+            .map(|w| u64::from_le_bytes([w[0], w[1], w[2], w[3], w[4], w[5], w[6], w[7]]))
+            .collect::<Vec<_>>();
+        assert_eq!(spec, vec![1, 0, 0, 4096]);
+        // The usual segments and sparse page table are still there, pointing at real pages.
+        assert_ne!(symbol_contents(&obj, "wasm_data_segments_len"), vec![0; 4]);
+        assert!(symbols_by_address(&obj).contains(&"guest_sparse_page_data_0".to_owned()));
+
+        let plain = tmp.path().join("data_segment_plain.o");
+        Lucetc::new(test_file("data_segment"))
+            .object_file(&plain)
+            .expect("compile data_segment");
+        assert!(!symbols_by_address(&plain).contains(&"lucet_rodata".to_owned()));
+    }
+
+    #[test]
+    fn atomics() {
+        use lucetc::Atomics;
+        // `atomics.wasm` is hand-assembled: a shared memory, `load` doing an
+        // `i32.atomic.load`, `add` an `i32.atomic.rmw.add`, `cmpxchg8` an
+        // `i32.atomic.rmw8.cmpxchg_u`, and `fence` an `atomic.fence`.
+        let tmp = temp_dir("atomics");
+        let input = PathBuf::from("tests/wasm/atomics.wasm");
+
+        let err = Lucetc::new(&input)
+            .clif_ir(tmp.path().join("disallowed.clif"))
+            .expect_err("atomics are disallowed by default");
+        assert!(err
+            .iter_chain()
+            .any(|c| c.to_string().contains("Atomics::Disallow")));
+
+        let err = Lucetc::new(&input)
+            .with_atomics(Atomics::Full)
+            .clif_ir(tmp.path().join("full.clif"))
+            .expect_err("real atomics are unsupported");
+        assert!(err
+            .iter_chain()
+            .any(|c| c.to_string().contains("Unsupported")));
+
+        let clif = clif_for(
+            Lucetc::new(&input).with_atomics(Atomics::SingleThreaded),
+            tmp.path(),
+        );
+        // Plain loads and stores, including the narrow ones `cmpxchg8` is lowered to.
+        assert!(clif.contains("load.i32"), "{}", clif);
+        assert!(clif.contains("uload8.i32"), "{}", clif);
+        assert!(clif.contains("istore8"), "{}", clif);
+    }
+
+    #[test]
+    fn verify_bindings_missing_module() {
+        use lucetc::{UnboundImport, UnboundReason};
+        let unbound = Lucetc::new(test_file("import"))
+            .verify_bindings()
+            .expect("verify bindings");
+        assert_eq!(
+            unbound,
+            vec![UnboundImport {
+                module: "env".to_owned(),
+                field: "inc".to_owned(),
+                reason: UnboundReason::MissingModule,
+            }]
+        );
+    }
+
+    #[test]
+    fn verify_bindings_missing_field() {
+        use lucetc::{Bindings, UnboundReason};
+        let mut env = HashMap::new();
+        env.insert("dec".to_owned(), "dec".to_owned());
+        let unbound = Lucetc::new(test_file("import"))
+            .with_bindings(Bindings::env(env))
+            .verify_bindings()
+            .expect("verify bindings");
+        assert_eq!(unbound.len(), 1);
+        assert_eq!(unbound[0].field, "inc");
+        assert_eq!(unbound[0].reason, UnboundReason::MissingField);
+
+        let unbound = Lucetc::new(test_file("import"))
+            .with_bindings(super::test_bindings())
+            .verify_bindings()
+            .expect("verify bindings");
+        assert!(unbound.is_empty());
+    }
+
+    #[test]
+    fn verify_bindings_signature_mismatch() {
+        use lucetc::UnboundReason;
+        let unbound = Lucetc::new(test_file("import_aliased_mismatch"))
+            .with_bindings(super::aliased_bindings())
+            .verify_bindings()
+            .expect("verify bindings");
+        assert_eq!(unbound.len(), 1);
+        assert_eq!(
+            (unbound[0].module.as_str(), unbound[0].field.as_str()),
+            ("other", "malloc")
+        );
+        assert_eq!(
+            unbound[0].reason,
+            UnboundReason::SignatureMismatch {
+                symbol: "malloc".to_owned(),
+                other: ("env".to_owned(), "malloc".to_owned()),
+            }
+        );
+    }
+
+    #[test]
+    fn emit_cfg() {
+        let (tmp, dot) = out_path("choose.dot");
+        Lucetc::new(test_file("if_else"))
+            .emit_cfg(Some("guest_func_choose"), &dot)
+            .expect("write cfg");
+        let dot = std::fs::read_to_string(&dot).unwrap();
+        let count = |pattern: &str| dot.lines().filter(|l| l.contains(pattern)).count();
+        assert_eq!(dot.matches("digraph").count(), 1, "{}", dot);
+        // The entry block branches to `else`, and both arms jump to the block after the `if`,
+        // which jumps to the exit block.
+        assert_eq!(count("[shape=record"), 4, "{}", dot);
+        assert_eq!(count(" -> "), 4, "{}", dot);
+
+        let all = tmp.path().join("all.dot");
+        Lucetc::new(test_file("if_else"))
+            .emit_cfg(None, &all)
+            .expect("write cfg");
+        let all = std::fs::read_to_string(&all).unwrap();
+        assert_eq!(all.matches("digraph").count(), 2, "{}", all);
+
+        assert!(Lucetc::new(test_file("if_else"))
+            .emit_cfg(Some("guest_func_missing"), tmp.path().join("missing.dot"))
+            .is_err());
+    }
+
+    #[test]
+    fn grow_zeroing() {
+        use lucetc::GrowZeroing;
+        let tmp = temp_dir("grow_zeroing");
+        let undefined = |zeroing: GrowZeroing| {
+            let obj = tmp.path().join(format!("grow_memory_{:?}.o", zeroing));
+            Lucetc::new(test_file("grow_memory"))
+                .with_grow_zeroing(zeroing)
+                .object_file(&obj)
+                .expect("compile grow_memory");
+            let nm = Command::new("nm")
+                .arg("--undefined-only")
+                .arg(&obj)
+                .output()
+                .expect("run nm");
+            str::from_utf8(&nm.stdout)
+                .unwrap()
+                .lines()
+                .filter_map(|l| l.split_whitespace().last().map(String::from))
+                .collect::<Vec<_>>()
+        };
+
+        let lazy = undefined(GrowZeroing::Lazy);
+        assert!(lazy.contains(&"lucet_vmctx_grow_memory".to_owned()));
+        assert!(!lazy.contains(&"lucet_vmctx_grow_memory_zeroing".to_owned()));
+        let eager = undefined(GrowZeroing::Eager);
+        assert!(eager.contains(&"lucet_vmctx_grow_memory_zeroing".to_owned()));
+        assert!(!eager.contains(&"lucet_vmctx_grow_memory".to_owned()));
+    }
+
+    #[test]
+    fn elide_stack_probes() {
+        let (_tmp_dir, obj_path) = out_path("large_frame.o");
+        let err = Lucetc::new(test_file("large_frame"))
+            .with_elide_stack_probes(true)
+            .with_guard_size(4096)
+            .object_file(&obj_path)
+            .expect_err("large_frame overruns a one-page guard without probes");
+        let message = format!("{}", err);
+        assert!(message.contains("guest_func_large_frame"), "{}", message);
+        assert!(message.contains("4096-byte guard"), "{}", message);
+
+        Lucetc::new(test_file("large_frame"))
+            .with_guard_size(4096)
+            .object_file(&obj_path)
+            .expect("probes keep large_frame from skipping the guard");
+
+        Lucetc::new(test_file("large_frame"))
+            .with_elide_stack_probes(true)
+            .object_file(&obj_path)
+            .expect("large_frame fits in the default guard");
+    }
+
+    /// The symbol `lucet_wasi_entry` points at in the object at `path`.
+    fn wasi_entry(path: &Path) -> String {
+        use lucetc::compiler::relocations::relocations;
+        let relocs = relocations(&std::fs::read(path).unwrap()).expect("read relocations");
+        let entry: Vec<_> = relocs
+            .iter()
+            .filter(|r| r.section.ends_with("lucet_wasi_entry"))
+            .collect();
+        assert_eq!(entry.len(), 1, "entry is a single pointer: {:?}", relocs);
+        entry[0].symbol.clone()
+    }
+
+    #[test]
+    fn wasi_command() {
+        use lucetc::WasiAbi;
+        let (_tmp_dir, obj_path) = out_path("wasi_command.o");
+        Lucetc::new(test_file("wasi_command"))
+            .object_file(&obj_path)
+            .expect("compile wasi_command");
+        assert_eq!(
+            symbol_contents(&obj_path, "lucet_wasi_abi"),
+            vec![1, 0, 0, 0]
+        );
+        assert_eq!(wasi_entry(&obj_path), "guest_func__start");
+
+        Lucetc::new(test_file("wasi_command"))
+            .with_wasi_abi(WasiAbi::Command)
+            .object_file(&obj_path)
+            .expect("compile wasi_command as a command");
+        assert_eq!(
+            symbol_contents(&obj_path, "lucet_wasi_abi"),
+            vec![1, 0, 0, 0]
+        );
+
+        let err = Lucetc::new(test_file("wasi_command"))
+            .with_wasi_abi(WasiAbi::Reactor)
+            .object_file(&obj_path)
+            .expect_err("a reactor can't export _start");
+        assert!(format!("{}", err).contains("_start"));
+    }
+
+    #[test]
+    fn wasi_reactor() {
+        use lucetc::WasiAbi;
+        let (tmp_dir, obj_path) = out_path("wasi_reactor.o");
+        Lucetc::new(test_file("wasi_reactor"))
+            .object_file(&obj_path)
+            .expect("compile wasi_reactor");
+        assert_eq!(
+            symbol_contents(&obj_path, "lucet_wasi_abi"),
+            vec![2, 0, 0, 0]
+        );
+        assert_eq!(wasi_entry(&obj_path), "guest_func__initialize");
+
+        let err = Lucetc::new(test_file("wasi_reactor"))
+            .with_wasi_abi(WasiAbi::Command)
+            .object_file(&obj_path)
+            .expect_err("a command must export _start");
+        assert!(format!("{}", err).contains("_start"));
+
+        let obj_path = tmp_dir.path().join("stack_usage.o");
+        Lucetc::new(test_file("stack_usage"))
+            .object_file(&obj_path)
+            .expect("compile stack_usage");
+        let nm = Command::new("nm").arg(&obj_path).output().expect("run nm");
+        let symbols = str::from_utf8(&nm.stdout).unwrap();
+        assert!(
+            !symbols.contains("lucet_wasi"),
+            "not a WASI module: {}",
+            symbols
+        );
+    }
+
+    #[test]
+    fn symbol_naming() {
+        use lucetc::{DataInit, HeapBase, MetadataSymbol, SymbolNaming};
+        struct Prefixed;
+        impl SymbolNaming for Prefixed {
+            fn name(&self, symbol: MetadataSymbol) -> String {
+                format!("myrt_{}", symbol.default_name())
+            }
+        }
+        struct Constant;
+        impl SymbolNaming for Constant {
+            fn name(&self, _symbol: MetadataSymbol) -> String {
+                "metadata".to_owned()
+            }
+        }
+
+        let (tmp_dir, obj_path) = out_path("fibonacci.o");
+        Lucetc::new(test_file("fibonacci"))
+            .with_symbol_naming(Prefixed)
+            .with_heap_base(HeapBase::FixedOffset(0x1000_0000))
+            .with_separate_rodata(true)
+            .object_file(&obj_path)
+            .expect("compile fibonacci with prefixed names");
+        let mut symbols = symbols_by_address(&obj_path);
+        let wasi_path = tmp_dir.path().join("wasi_command.o");
+        Lucetc::new(test_file("wasi_command"))
+            .with_symbol_naming(Prefixed)
+            .with_data_init(DataInit::Snapshot)
+            .object_file(&wasi_path)
+            .expect("compile wasi_command with prefixed names");
+        symbols.extend(symbols_by_address(&wasi_path));
+        for symbol in MetadataSymbol::ALL {
+            let name = symbol.default_name();
+            assert!(
+                symbols.contains(&format!("myrt_{}", name)),
+                "{:?} follows the policy: {:?}",
+                symbol,
+                symbols
+            );
+            assert!(
+                !symbols.iter().any(|s| s == name),
+                "{} is not emitted",
+                name
+            );
+        }
+
+        let err = Lucetc::new(test_file("fibonacci"))
+            .with_symbol_naming(Constant)
+            .object_file(&obj_path)
+            .expect_err("metadata symbols can't share a name");
+        assert!(format!("{}", err).contains("more than one metadata symbol"));
+    }
+
+    #[test]
+    fn import_and_export_share_a_name() {
+        let tmp = temp_dir("name_collision");
+        let wat = std::fs::read(test_file("name_collision")).unwrap();
+        let wasm = wabt::Wat2Wasm::new()
+            .write_debug_names(true)
+            .convert(wat)
+            .expect("assemble name_collision.wat");
+        let input = tmp.path().join("name_collision.wasm");
+        std::fs::write(&input, wasm.as_ref()).unwrap();
+
+        let mut imports = HashMap::new();
+        imports.insert("foo".to_owned(), "host_foo".to_owned());
+        let obj_path = tmp.path().join("name_collision.o");
+        Lucetc::new(&input)
+            .with_bindings(lucetc::Bindings::env(imports))
+            .object_file(&obj_path)
+            .expect("compile name_collision");
+
+        let defined = symbols_by_address(&obj_path);
+        assert!(
+            defined.contains(&"guest_func_bar".to_owned()),
+            "{:?}",
+            defined
+        );
+        assert!(
+            defined.contains(&"guest_internalfunc_bar_1".to_owned()),
+            "{:?}",
+            defined
+        );
+        assert!(!defined.iter().any(|s| s.ends_with("foo")), "{:?}", defined);
+
+        let relocs = lucetc::compiler::relocations::relocations(&std::fs::read(&obj_path).unwrap())
+            .expect("read relocations");
+        let calls = |from: &str| -> Vec<String> {
+            relocs
+                .iter()
+                .filter(|r| r.section.ends_with(from))
+                .map(|r| r.symbol.clone())
+                .collect()
+        };
+        assert_eq!(calls("guest_func_bar"), vec!["guest_internalfunc_bar_1"]);
+        assert_eq!(calls("guest_internalfunc_bar_1"), vec!["host_foo"]);
+    }
+
+    #[test]
+    fn sparse_page_data_is_position_independent() {
+        use lucetc::RelocKind;
+        let (tmp_dir, obj_path) = out_path("sparse_data.o");
+        Lucetc::new(test_file("sparse_data"))
+            .object_file(&obj_path)
+            .expect("compile sparse_data");
+        let relocs = lucetc::compiler::relocations::relocations(&std::fs::read(&obj_path).unwrap())
+            .expect("read relocations");
+        let mut table: Vec<_> = relocs
+            .iter()
+            .filter(|r| r.section.ends_with("guest_sparse_page_data"))
+            .collect();
+        table.sort_by_key(|r| r.offset);
+        let offsets: Vec<u64> = table.iter().map(|r| r.offset).collect();
+        assert_eq!(offsets, vec![8, 24, 48], "one entry per initialized page");
+        for (r, page) in table.iter().zip(&[0, 2, 5]) {
+            assert_eq!(r.kind, RelocKind::Abs8);
+            assert_eq!(r.symbol, format!("guest_sparse_page_data_{}", page));
+        }
+
+        let so_path = tmp_dir.path().join("sparse_data.so");
+        Lucetc::new(test_file("sparse_data"))
+            .shared_object_file(&so_path)
+            .expect("link sparse_data as a shared object");
+        let nm = Command::new("nm")
+            .arg("--defined-only")
+            .arg(&so_path)
+            .output()
+            .expect("failed to execute nm");
+        let table_address = str::from_utf8(&nm.stdout)
+            .unwrap()
+            .lines()
+            .find(|l| l.ends_with(" guest_sparse_page_data"))
+            .and_then(|l| u64::from_str_radix(l.split_whitespace().next().unwrap(), 16).ok())
+            .expect("the shared object defines guest_sparse_page_data");
+        let readelf = Command::new("readelf")
+            .args(&["--relocs", "--wide"])
+            .arg(&so_path)
+            .output()
+            .expect("failed to execute readelf");
+        assert!(readelf.status.success(), "readelf failed!");
+        let relative: Vec<u64> = str::from_utf8(&readelf.stdout)
+            .unwrap()
+            .lines()
+            .filter(|l| l.contains("R_X86_64_RELATIVE"))
+            .filter_map(|l| u64::from_str_radix(l.split_whitespace().next()?, 16).ok())
+            .collect();
+        for offset in &[8, 24, 48] {
+            assert!(
+                relative.contains(&(table_address + offset)),
+                "the loader relocates the entry at +{}: {:?}",
+                offset,
+                relative
+            );
+        }
+    }
+
+    #[test]
+    fn dump_flags() {
+        use lucetc::OptLevel;
+        let flags = Lucetc::new(test_file("add"))
+            .with_opt_level(OptLevel::Best)
+            .with_target("x86_64-unknown-linux-gnu".parse().unwrap())
+            .dump_flags()
+            .expect("dump flags");
+        let line = |name: &str| {
+            flags
+                .lines()
+                .find(|l| l.split_whitespace().next() == Some(name))
+                .expect(&format!("{} is in the dump:\n{}", name, flags))
+                .to_owned()
+        };
+        assert!(line("target").contains("x86_64-unknown-linux-gnu"));
+        assert!(line("opt_level").contains("best"));
+        assert!(line("is_pic").ends_with("true"));
+        assert!(line("probestack_enabled").ends_with("true"));
+
+        let flags = Lucetc::new(test_file("add"))
+            .with_elide_stack_probes(true)
+            .dump_flags()
+            .expect("dump flags");
+        assert!(flags
+            .lines()
+            .any(|l| l.starts_with("probestack_enabled") && l.ends_with("false")));
+    }
+
+    #[test]
+    fn initial_memory_larger_than_min_reserved() {
+        let (_tmp_dir, obj_path) = out_path("initial_memory.o");
+        Lucetc::new(test_file("initial_memory"))
+            .with_min_reserved_size(4096)
+            .object_file(&obj_path)
+            .expect("compile initial_memory");
+        let spec = symbol_contents(&obj_path, "lucet_heap_spec");
+        let field = |i: usize| {
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(&spec[i * 8..(i + 1) * 8]);
+            u64::from_le_bytes(bytes)
+        };
+        let (reserved_size, initial_size) = (field(0), field(2));
+        assert_eq!(initial_size, 4 * 65536);
+        assert_eq!(
+            reserved_size, initial_size,
+            "the reservation holds the initial memory"
+        );
+
+        let err = Lucetc::new(test_file("initial_memory"))
+            .with_min_reserved_size(4096)
+            .with_max_reserved_size(2 * 65536)
+            .object_file(&obj_path)
+            .expect_err("four pages don't fit in a two-page reservation");
+        assert!(err
+            .iter_chain()
+            .any(|c| c.to_string().contains("initial memory (262144 bytes)")));
+
+        let err = Lucetc::new(test_file("initial_memory"))
+            .with_min_reserved_size(8 * 65536)
+            .with_max_reserved_size(4 * 65536)
+            .object_file(&obj_path)
+            .expect_err("the minimum reservation is larger than the maximum");
+        assert!(err
+            .iter_chain()
+            .any(|c| c.to_string().contains("min reserved size (524288)")));
+    }
+
+    #[test]
+    fn required_host_signatures() {
+        use cranelift_codegen::ir::{types, ArgumentPurpose};
+        let signatures = Lucetc::new(test_file("import_many"))
+            .with_bindings(super::test_bindings())
+            .required_host_signatures()
+            .expect("required signatures for import_many");
+        let symbols: Vec<&str> = signatures.iter().map(|(s, _)| s.as_str()).collect();
+        assert_eq!(
+            symbols,
+            Lucetc::new(test_file("import_many"))
+                .with_bindings(super::test_bindings())
+                .required_host_symbols()
+                .unwrap()
+        );
+        for (symbol, signature) in signatures.iter() {
+            assert_eq!(
+                signature.params[0].purpose,
+                ArgumentPurpose::VMContext,
+                "{} is passed the vmctx",
+                symbol
+            );
+            if symbol.starts_with("imp_") {
+                assert_eq!(signature.params.len(), 1, "{}: {}", symbol, signature);
+                assert_eq!(signature.returns.len(), 1, "{}: {}", symbol, signature);
+                assert_eq!(signature.returns[0].value_type, types::I32);
+            }
+        }
+
+        let err = Lucetc::new(test_file("import_aliased_mismatch"))
+            .with_bindings(super::aliased_bindings())
+            .required_host_signatures()
+            .expect_err("malloc is imported with two signatures");
+        assert!(err.iter_chain().any(|c| c.to_string().contains("malloc")));
+    }
+
+    /// The size each `heap_addr` in `func` checks.
+    fn check_sizes(clif: &str, func: &str) -> Vec<u32> {
+        clif.split("; guest_func_")
+            .find(|f| f.starts_with(func))
+            .expect("function is in the clif")
+            .lines()
+            .filter(|l| l.contains("heap_addr"))
+            .map(|l| l.rsplit(", ").next().unwrap().trim().parse().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn zero_guard_checks_every_access() {
+        let tmp_dir = temp_dir("zero_guard");
+
+        // With guard pages, only the first byte is checked and the guard catches the rest.
+        let clif = clif_for(Lucetc::new(test_file("bounds_check")), tmp_dir.path());
+        assert_eq!(check_sizes(&clif, "constant"), vec![1]);
+        assert_eq!(check_sizes(&clif, "dynamic"), vec![1]);
+
+        let clif = clif_for(
+            Lucetc::new(test_file("bounds_check")).with_guard_size(0),
+            tmp_dir.path(),
+        );
+        assert!(clif.contains("offset_guard 0"), "{}", clif);
+        // offset=8 plus a four-byte load.
+        assert_eq!(check_sizes(&clif, "constant"), vec![12]);
+        assert_eq!(check_sizes(&clif, "dynamic"), vec![4]);
+        assert_eq!(check_sizes(&clif, "far"), vec![4]);
+
+        let err = Lucetc::new(test_file("bounds_check"))
+            .with_guard_size(0)
+            .with_min_reserved_size(4 * 1024 * 1024 * 1024)
+            .object_file(tmp_dir.path().join("bounds_check.o"))
+            .expect_err("a 4GiB heap needs guard pages");
+        assert!(err
+            .iter_chain()
+            .any(|c| c.to_string().contains("without guard pages")));
+    }
+
+    #[test]
+    fn detected_features() {
+        use lucetc::FeatureSet;
+        // `bulk_sign_ext.wasm` is hand-assembled: one function doing a `memory.copy`, then an
+        // `i32.extend8_s`.
+        let features = Lucetc::new("tests/wasm/bulk_sign_ext.wasm").detected_features();
+        assert_eq!(
+            features,
+            FeatureSet::BULK_MEMORY | FeatureSet::SIGN_EXTENSION
+        );
+
+        assert_eq!(
+            Lucetc::new("tests/wasm/atomics.wasm").detected_features(),
+            FeatureSet::ATOMICS
+        );
+        assert_eq!(
+            Lucetc::new("tests/wasm/data_count.wasm").detected_features(),
+            FeatureSet::BULK_MEMORY
+        );
+        assert_eq!(
+            Lucetc::new("tests/wasm/tail_call.wasm").detected_features(),
+            FeatureSet::TAIL_CALL
+        );
+        assert!(Lucetc::new(test_file("arith"))
+            .detected_features()
+            .is_empty());
+    }
+
+    #[test]
+    fn reservation_alignment() {
+        let (_tmp_dir, obj_path) = out_path("initial_memory.o");
+        Lucetc::new(test_file("initial_memory"))
+            .with_min_reserved_size(3 * 1024 * 1024)
+            .with_reservation_alignment(2 * 1024 * 1024)
+            .object_file(&obj_path)
+            .expect("compile initial_memory");
+        let spec = symbol_contents(&obj_path, "lucet_heap_spec");
+        let mut reserved_size = [0u8; 8];
+        reserved_size.copy_from_slice(&spec[0..8]);
+        assert_eq!(u64::from_le_bytes(reserved_size), 4 * 1024 * 1024);
+
+        let err = Lucetc::new(test_file("initial_memory"))
+            .with_reservation_alignment(3 * 1024 * 1024)
+            .object_file(&obj_path)
+            .expect_err("alignment must be a power of two");
+        assert!(err
+            .iter_chain()
+            .any(|c| c.to_string().contains("not a power of two")));
+    }
+
+    #[test]
+    fn export_filter() {
+        let (_tmp_dir, obj_path) = out_path("bounds_check.o");
+        Lucetc::new(test_file("bounds_check"))
+            .with_export_filter(|name| match name {
+                "far" => None,
+                "dynamic" => Some("load_at".to_owned()),
+                _ => Some(name.to_owned()),
+            })
+            .object_file(&obj_path)
+            .expect("compile bounds_check");
+        let symbols = symbols_by_address(&obj_path);
+        assert!(symbols.contains(&"guest_func_constant".to_owned()));
+        assert!(symbols.contains(&"guest_func_load_at".to_owned()));
+        assert!(!symbols.contains(&"guest_func_dynamic".to_owned()));
+        assert!(!symbols.contains(&"guest_func_far".to_owned()));
+        // The hidden function is still compiled.
+        assert!(
+            symbols.iter().any(|s| s.starts_with("guest_internalfunc_")),
+            "{:?}",
+            symbols
+        );
+
+        let err = Lucetc::new(test_file("bounds_check"))
+            .with_export_filter(|_| Some("same".to_owned()))
+            .object_file(&obj_path)
+            .expect_err("every export renamed to the same name");
+        assert!(err.to_string().contains("more than one export named same"));
+    }
+
+    #[test]
+    fn emit_c_header() {
+        let (_tmp_dir, header_path) = out_path("vectors.h");
+        Lucetc::new(test_file("vectors"))
+            .with_export_alias("sqrt", "vectors_sqrt")
+            .emit_c_header(&header_path)
+            .expect("emit header");
+        let header = std::fs::read_to_string(&header_path).unwrap();
+        assert!(
+            header.contains("#ifndef LUCET_MODULE_VECTORS_H"),
+            "{}",
+            header
+        );
+        assert!(header.contains(
+            "extern double guest_func_scale(struct lucet_vmctx *vmctx, int32_t arg0, double arg1, int64_t arg2, double arg3);"
+        ), "{}", header);
+        assert!(header.contains(
+            "extern float guest_func_mul_add(struct lucet_vmctx *vmctx, float arg0, float arg1, float arg2);"
+        ), "{}", header);
+        assert!(
+            header
+                .contains("extern void guest_func_store(struct lucet_vmctx *vmctx, int32_t arg0);"),
+            "{}",
+            header
+        );
+        assert!(
+            header.contains("extern int32_t guest_func_load(struct lucet_vmctx *vmctx);"),
+            "{}",
+            header
+        );
+        assert!(
+            header.contains("extern double vectors_sqrt(struct lucet_vmctx *vmctx, double arg0);"),
+            "{}",
+            header
+        );
+    }
+
+    #[test]
+    fn lazy_compile() {
+        use lucetc::compiler::relocations::relocations;
+        use std::collections::HashSet;
+        let tmp_dir = temp_dir("lazy_compile");
+        let hot: HashSet<String> = ["add".to_owned()].iter().cloned().collect();
+
+        let obj_path = tmp_dir.path().join("add.o");
+        Lucetc::new(test_file("add"))
+            .with_lazy_compile(hot.clone())
+            .object_file(&obj_path)
+            .expect("compile add");
+        let relocs = relocations(&std::fs::read(&obj_path).unwrap()).expect("read relocations");
+        let calls_helper = |section: &str| {
+            relocs
+                .iter()
+                .any(|r| r.section == section && r.symbol == "lucet_compile_on_demand")
+        };
+        assert!(calls_helper(".text.guest_func_grow"), "{:?}", relocs);
+        assert!(!calls_helper(".text.guest_func_add"), "{:?}", relocs);
+
+        let clif = clif_for(
+            Lucetc::new(test_file("add")).with_lazy_compile(hot),
+            tmp_dir.path(),
+        );
+        let function = |name: &str| {
+            clif.split("; guest_func_")
+                .find(|f| f.starts_with(name))
+                .expect("function is in the clif")
+                .to_owned()
+        };
+        // The stub calls whatever the helper returns instead of growing memory itself.
+        assert!(function("grow").contains("call_indirect"));
+        assert!(function("add").contains("iadd"));
+        assert!(!function("add").contains("call_indirect"));
+    }
+
+    #[test]
+    fn non_constant_segment_offset() {
+        use lucetc::compiler::relocations::relocations;
+        use lucetc::SegmentOffsetPolicy;
+        let (_tmp_dir, obj_path) = out_path("data_global_offset.o");
+
+        let err = Lucetc::new(test_file("data_global_offset"))
+            .object_file(&obj_path)
+            .expect_err("segment offsets must be constant by default");
+        assert!(
+            err.iter_chain()
+                .any(|c| c.to_string().contains("placed at imported global 0")),
+            "{}",
+            err
+        );
+
+        Lucetc::new(test_file("data_global_offset"))
+            .with_non_constant_segment_offset(SegmentOffsetPolicy::InitAtRuntime)
+            .object_file(&obj_path)
+            .expect("compile data_global_offset");
+        let symbols = symbols_by_address(&obj_path);
+        assert!(symbols
+            .iter()
+            .any(|s| s.ends_with("guest_deferred_data_init")));
+        // Only the segment at a constant offset is left to the data initializers.
+        let segments = symbol_contents(&obj_path, "wasm_data_segments");
+        assert_eq!(&segments[12..17], b"fixed");
+        assert_eq!(segments.len(), 24);
+
+        let relocs = relocations(&std::fs::read(&obj_path).unwrap()).expect("read relocations");
+        let init = |symbol: &str| {
+            relocs
+                .iter()
+                .any(|r| r.section == ".text.guest_deferred_data_init" && r.symbol == symbol)
+        };
+        assert!(init("guest_deferred_data_1"), "{:?}", relocs);
+        assert!(init("memcpy"), "{:?}", relocs);
+        assert!(init("lucet_vmctx_current_memory"), "{:?}", relocs);
+    }
+
+    #[test]
+    fn check_reports_every_problem() {
+        use lucetc::{Problem, UnboundImport, UnboundReason};
+        // `many_problems.wasm` is hand-assembled: an unbound import `env::missing`, an imported
+        // two-page memory alongside a defined one, and a function declared to return an i32
+        // whose body leaves an i64.
+        let report = Lucetc::new("tests/wasm/many_problems.wasm")
+            .with_min_reserved_size(64 * 1024)
+            .with_max_reserved_size(64 * 1024)
+            .check();
+        assert!(!report.passed());
+        let problems = &report.problems;
+        assert!(
+            problems.iter().any(|p| match p {
+                Problem::Feature(e) => e.contains("multi-memory"),
+                _ => false,
+            }),
+            "{:?}",
+            problems
+        );
+        assert!(problems.contains(&Problem::UnboundImport(UnboundImport {
+            module: "env".to_owned(),
+            field: "missing".to_owned(),
+            reason: UnboundReason::MissingModule,
+        })));
+        assert!(
+            problems.iter().any(|p| match p {
+                Problem::Invalid(_) => true,
+                _ => false,
+            }),
+            "{:?}",
+            problems
+        );
+        assert!(
+            problems.iter().any(|p| match p {
+                Problem::Limit(e) => e.contains("exceeds max reserved size"),
+                _ => false,
+            }),
+            "{:?}",
+            problems
+        );
+
+        assert!(Lucetc::new(test_file("arith")).check().passed());
+        let report = Lucetc::new("tests/wasm/does_not_exist.wat").check();
+        match report.problems.as_slice() {
+            [Problem::Unreadable(_)] => (),
+            problems => panic!("expected one unreadable problem, got {:?}", problems),
+        }
+    }
+
+    #[test]
+    fn binding_addend() {
+        use lucetc::compiler::relocations::relocations;
+        use lucetc::{Bindings, RelocKind};
+        let (_tmp_dir, obj_path) = out_path("import.o");
+        let mut bindings = Bindings::empty();
+        bindings.bind_with_addend("env", "inc", "host_table", 16);
+        Lucetc::new(test_file("import"))
+            .with_bindings(bindings)
+            .object_file(&obj_path)
+            .expect("compile import");
+
+        let relocs = relocations(&std::fs::read(&obj_path).unwrap()).expect("read relocations");
+        // The call loads its callee from a pointer to 16 bytes past `host_table`.
+        let pointer = relocs
+            .iter()
+            .find(|r| r.symbol == "host_table")
+            .expect("host_table is referenced");
+        assert_eq!(pointer.kind, RelocKind::Abs8);
+        assert_eq!(pointer.addend, 16);
+        assert!(
+            pointer.section.ends_with("guest_import_host_table_16"),
+            "{:?}",
+            pointer
+        );
+        assert!(
+            relocs.iter().any(|r| r.section == ".text.guest_func_main"
+                && r.symbol == "guest_import_host_table_16"),
+            "{:?}",
+            relocs
+        );
+        assert!(!relocs.iter().any(|r| r.symbol == "inc"));
+    }
+
+    #[test]
+    fn vmctx_layout() {
+        use lucetc::{HeapBase, VmctxLayout};
+        let (tmp_dir, obj_path) = out_path("globals_definition.o");
+        let lucetc = Lucetc::new(test_file("globals_definition"));
+        let layout = lucetc.vmctx_layout();
+        assert_eq!(
+            layout,
+            VmctxLayout {
+                heap: Some(0),
+                globals_pointer: -8,
+                global_stride: 8,
+            }
+        );
+        lucetc
+            .object_file(&obj_path)
+            .expect("compile globals_definition");
+        assert_eq!(
+            symbol_contents(&obj_path, "lucet_vmctx_layout"),
+            layout.serialize()
+        );
+
+        // The globals pointer is loaded from the offset the layout gives, and the second global
+        // is a stride past the first.
+        let clif = clif_for(Lucetc::new(test_file("globals_definition")), tmp_dir.path());
+        assert!(
+            clif.lines().any(|l| l.contains("= load.i64")
+                && l.trim_end()
+                    .ends_with(&format!("{}", layout.globals_pointer))),
+            "{}",
+            clif
+        );
+        assert!(
+            clif.lines().any(|l| l.contains("= iadd_imm")
+                && l.trim_end()
+                    .ends_with(&format!(", {}", layout.global_stride))),
+            "{}",
+            clif
+        );
+        assert!(clif.contains("= vmctx"));
+
+        let fixed = Lucetc::new(test_file("globals_definition"))
+            .with_heap_base(HeapBase::FixedOffset(0x1000_0000))
+            .vmctx_layout();
+        assert_eq!(fixed.heap, None);
+    }
+
+    #[test]
+    fn function_sections() {
+        let tmp = temp_dir("function_sections");
+        let link = |sections: bool| {
+            let obj = tmp.path().join(format!("sections_{}.o", sections));
+            Lucetc::new(test_file("function_sections"))
+                .with_function_sections(sections)
+                .object_file(&obj)
+                .expect("compile function_sections");
+            let so = tmp.path().join(format!("sections_{}.so", sections));
+            let ld = Command::new("ld")
+                .arg(&obj)
+                .args(&["-shared", "--gc-sections", "-o"])
+                .arg(&so)
+                .output()
+                .expect("failed to execute ld");
+            assert!(
+                ld.status.success(),
+                "ld failed: {}",
+                String::from_utf8_lossy(&ld.stderr)
+            );
+            symbols_by_address(&so)
+        };
+
+        let kept = link(true);
+        let has = |syms: &[String], sym: &str| syms.iter().any(|s| s == sym);
+        assert!(has(&kept, "guest_func_main"));
+        assert!(has(&kept, "guest_internalfunc_2"), "called by an export");
+        assert!(has(&kept, "guest_internalfunc_3"), "in the table");
+        assert!(!has(&kept, "guest_internalfunc_0"), "unreferenced");
+        assert!(
+            !has(&kept, "guest_internalfunc_1"),
+            "only called by unreferenced code"
+        );
+        assert!(has(&kept, "lucet_trap_manifest"));
+
+        // Without it, the trap manifest points at every function.
+        let all = link(false);
+        assert!(has(&all, "guest_internalfunc_0"));
+        assert!(has(&all, "guest_internalfunc_1"));
+    }
+
+    #[test]
+    fn data_snapshot() {
+        use lucetc::DataInit;
+        let (_tmp, obj_path) = out_path("data_snapshot.o");
+        Lucetc::new(test_file("data_snapshot"))
+            .with_data_init(DataInit::Snapshot)
+            .object_file(&obj_path)
+            .expect("compile data_snapshot");
+
+        let mut expected = vec![0u8; 4098];
+        expected[..6].copy_from_slice(&[1, 2, 0xaa, 0xbb, 0xcc, 0]);
+        expected[4094..].copy_from_slice(&[0x11, 0x22, 0x33, 0x44]);
+        assert_eq!(symbol_contents(&obj_path, "lucet_data_snapshot"), expected);
+        assert_eq!(
+            symbol_contents(&obj_path, "lucet_data_snapshot_len"),
+            vec![0x02, 0x10, 0, 0, 0, 0, 0, 0]
+        );
+        // The usual segments and sparse page table are still there, pointing at real pages.
+        assert_ne!(
+            symbol_contents(&obj_path, "wasm_data_segments_len"),
+            vec![0, 0, 0, 0]
+        );
+        assert!(symbols_by_address(&obj_path)
+            .iter()
+            .any(|s| s.starts_with("guest_sparse_page_data_")));
+
+        let err = Lucetc::new(test_file("data_snapshot"))
+            .with_data_init(DataInit::Snapshot)
+            .with_separate_rodata(true)
+            .object_file(&obj_path)
+            .expect_err("a snapshot can't also be separate rodata");
+        assert!(err
+            .iter_chain()
+            .any(|c| c.to_string().contains("separate rodata")));
+    }
+
+    #[test]
+    fn max_locals_per_function() {
+        use lucetc::error::{LucetcError, LucetcErrorKind};
+        let (_tmp, obj) = out_path("many_locals.o");
+        let too_many = |err: &failure::Error| {
+            err.iter_chain()
+                .filter_map(|c| c.downcast_ref::<LucetcError>())
+                .find_map(|e| match e.get_context() {
+                    LucetcErrorKind::TooManyLocals { func, count } => Some((func.clone(), *count)),
+                    _ => None,
+                })
+        };
+
+        let err = Lucetc::new("tests/wasm/many_locals.wasm")
+            .with_max_locals_per_function(1000)
+            .object_file(&obj)
+            .expect_err("50000 locals is over the limit");
+        assert_eq!(too_many(&err), Some(("guest_func_f".to_owned(), 50000)));
+
+        let err = Lucetc::new(test_file("locals"))
+            .with_max_locals_per_function(32)
+            .object_file(&obj)
+            .expect_err("localpalooza declares 64 locals");
+        assert_eq!(
+            too_many(&err),
+            Some(("guest_func_localpalooza".to_owned(), 64))
+        );
+        Lucetc::new(test_file("locals"))
+            .with_max_locals_per_function(64)
+            .object_file(&obj)
+            .expect("64 locals is within the limit");
+
+        let report = Lucetc::new(test_file("locals"))
+            .report_json()
+            .expect("report for locals");
+        let report: serde_json::Value = serde_json::from_str(&report).unwrap();
+        assert_eq!(report["functions"]["locals"], serde_json::json!([1, 64]));
+    }
+
+    #[test]
+    fn warn_unreachable_code() {
+        use lucetc::{Diagnostic, Severity};
+        use std::cell::RefCell;
+        use std::rc::Rc;
+        let tmp = temp_dir("warn_unreachable_code");
+        let compile = |warn: bool| {
+            let diagnostics = Rc::new(RefCell::new(vec![]));
+            let sink = diagnostics.clone();
+            Lucetc::new(test_file("dead_code"))
+                .with_warn_unreachable_code(warn)
+                .with_diagnostics(move |d| sink.borrow_mut().push(d))
+                .object_file(tmp.path().join("dead_code.o"))
+                .expect("dead code is valid");
+            let diagnostics = diagnostics.borrow().clone();
+            diagnostics
+        };
+
+        // The nested block is part of the same stretch of dead code as the `drop` before it.
+        assert_eq!(
+            compile(true),
+            vec![Diagnostic {
+                severity: Severity::Warning,
+                message: "guest_func_after_br has unreachable code starting at instruction 2"
+                    .to_owned(),
+            }]
+        );
+        assert_eq!(compile(false), vec![]);
+    }
+
+    #[test]
+    fn many_functions() {
+        let tmp = temp_dir("many_functions");
+        let count = 10_000;
+        let mut wat = String::from("(module\n");
+        for i in 0..count {
+            // Each function calls the one before it, so every one is in use.
+            let body = if i == 0 {
+                "(get_local 0)".to_owned()
+            } else {
+                format!(
+                    "(i32.add (call $f{} (get_local 0)) (i32.const {}))",
+                    i - 1,
+                    i
+                )
+            };
+            wat.push_str(&format!(
+                "  (func $f{} (export \"f{}\") (param i32) (result i32)\n    {})\n",
+                i, i, body
+            ));
+        }
+        wat.push_str(")\n");
+        let input = tmp.path().join("many_functions.wat");
+        std::fs::write(&input, wat).unwrap();
+        let obj = tmp.path().join("many_functions.o");
+
+        Lucetc::new(&input)
+            .object_file(&obj)
+            .expect("compile many_functions");
+        let symbols = symbols_by_address(&obj);
+        let functions = symbols.iter().filter(|s| s.starts_with("guest_func_f"));
+        assert_eq!(functions.count(), count);
+    }
+
+    #[test]
+    fn guard_placement() {
+        use lucetc::GuardPlacement;
+        let tmp_dir = temp_dir("guard_placement");
+        let placement_flag = |lucetc: Lucetc| {
+            let obj_path = tmp_dir.path().join("bounds_check.o");
+            lucetc.object_file(&obj_path).expect("compile bounds_check");
+            let spec = symbol_contents(&obj_path, "lucet_heap_spec");
+            assert_eq!(spec.len(), 6 * 8);
+            spec[5 * 8]
+        };
+
+        let clif = clif_for(Lucetc::new(test_file("bounds_check")), tmp_dir.path());
+        assert_eq!(check_sizes(&clif, "constant"), vec![1]);
+        assert_eq!(placement_flag(Lucetc::new(test_file("bounds_check"))), 0);
+
+        // A shared guard may be followed by another instance's heap, so the whole access is
+        // checked, as if there were no guard.
+        let shared =
+            || Lucetc::new(test_file("bounds_check")).with_guard_placement(GuardPlacement::Shared);
+        let clif = clif_for(shared(), tmp_dir.path());
+        assert!(clif.contains("offset_guard 0"), "{}", clif);
+        assert_eq!(check_sizes(&clif, "constant"), vec![12]);
+        assert_eq!(check_sizes(&clif, "dynamic"), vec![4]);
+        assert_eq!(placement_flag(shared()), 1);
+
+        let err = shared()
+            .with_min_reserved_size(4 * 1024 * 1024 * 1024)
+            .object_file(tmp_dir.path().join("bounds_check.o"))
+            .expect_err("a 4GiB heap needs a guard of its own");
+        assert!(err
+            .iter_chain()
+            .any(|c| c.to_string().contains("without guard pages of its own")));
+    }
+
+    #[test]
+    fn user_metadata() {
+        use lucetc::compiler::user_metadata::parse_user_metadata;
+        let (_tmp_dir, obj_path) = out_path("fibonacci.o");
+        Lucetc::new(test_file("fibonacci"))
+            .with_metadata("commit", b"0123abcd")
+            .with_metadata("build_time", &[0xe8, 0x03, 0, 0])
+            .with_metadata("version", b"1.0")
+            .with_metadata("version", b"1.1")
+            .object_file(&obj_path)
+            .expect("compile fibonacci");
+        let pairs = parse_user_metadata(&symbol_contents(&obj_path, "lucet_user_metadata"))
+            .expect("metadata parses");
+        assert_eq!(
+            pairs,
+            vec![
+                ("commit".to_owned(), b"0123abcd".to_vec()),
+                ("build_time".to_owned(), vec![0xe8, 0x03, 0, 0]),
+                ("version".to_owned(), b"1.1".to_vec()),
+            ]
+        );
+
+        Lucetc::new(test_file("fibonacci"))
+            .object_file(&obj_path)
+            .expect("compile fibonacci");
+        let pairs = parse_user_metadata(&symbol_contents(&obj_path, "lucet_user_metadata"))
+            .expect("metadata parses");
+        assert!(pairs.is_empty());
+    }
+
+    #[test]
+    fn linker_not_found() {
+        use lucetc::error::{LucetcError, LucetcErrorKind};
+        let (tmp_dir, linker) = out_path("no-such-ld");
+        let err = Lucetc::new(test_file("fibonacci"))
+            .with_linker(linker.clone())
+            .shared_object_file(tmp_dir.path().join("fibonacci.so"))
+            .expect_err("the linker doesn't exist");
+        let kind = err
+            .iter_chain()
+            .filter_map(|c| c.downcast_ref::<LucetcError>())
+            .map(|e| e.get_context())
+            .next();
+        assert_eq!(
+            kind,
+            Some(&LucetcErrorKind::LinkerNotFound {
+                path: linker.display().to_string()
+            })
+        );
+        assert!(err.to_string().contains("install binutils"), "{}", err);
+
+        Lucetc::new(test_file("fibonacci"))
+            .with_linker("ld".into())
+            .shared_object_file(tmp_dir.path().join("fibonacci.so"))
+            .expect("link with ld from the PATH");
+    }
+
+    #[test]
+    fn interface_diff() {
+        use lucetc::ExportSignature;
+        use parity_wasm::elements::{FunctionType, ValueType};
+        let v1 = Lucetc::new(test_file("interface_v1"));
+        let v2 = Lucetc::new(test_file("interface_v2"));
+        let sig = v1.module_signature().expect("signature of interface_v1");
+        let exports: Vec<&str> = sig.exports.keys().map(|k| k.as_str()).collect();
+        assert_eq!(exports, vec!["add", "memory", "scale"]);
+
+        let diff = v1
+            .interface_diff(&v2)
+            .expect("diff interface_v1 and interface_v2");
+        let added: Vec<String> = diff
+            .added
+            .iter()
+            .map(|(name, sig)| format!("{}: {}", name, sig))
+            .collect();
+        assert_eq!(
+            added,
+            vec!["negate: func (i32) -> i32", "version: global i32"]
+        );
+        assert!(diff.removed.is_empty(), "{}", diff);
+        assert_eq!(diff.changed.len(), 1, "{}", diff);
+        let changed = &diff.changed[0];
+        assert_eq!(changed.name, "scale");
+        assert_eq!(
+            changed.old,
+            ExportSignature::Function(FunctionType::new(
+                vec![ValueType::I32],
+                Some(ValueType::I32)
+            ))
+        );
+        assert_eq!(
+            changed.new,
+            ExportSignature::Function(FunctionType::new(
+                vec![ValueType::I64],
+                Some(ValueType::I64)
+            ))
+        );
+        assert!(diff.is_breaking());
+
+        let backwards = v2.interface_diff(&v1).unwrap();
+        assert_eq!(backwards.removed.len(), 2);
+        assert!(backwards.added.is_empty());
+        assert!(v1.interface_diff(&v1).unwrap().is_empty());
+    }
+
+    #[test]
+    fn trap_messages() {
+        use byteorder::{ByteOrder, LittleEndian};
+        let (_tmp_dir, obj_path) = out_path("trap_messages.o");
+        let mut messages = HashMap::new();
+        messages.insert("check".to_owned(), "assertion failed in check".to_owned());
+        Lucetc::new(test_file("trap_messages"))
+            .with_trap_messages(messages)
+            .object_file(&obj_path)
+            .expect("compile trap_messages");
+
+        let len = symbol_contents(&obj_path, "lucet_trap_messages_len");
+        let table = symbol_contents(&obj_path, "lucet_trap_messages");
+        let mut rows = vec![];
+        let mut at = 0;
+        while at < table.len() {
+            let offset = LittleEndian::read_u32(&table[at + 8..]);
+            let message_len = LittleEndian::read_u32(&table[at + 12..]) as usize;
+            let message = str::from_utf8(&table[at + 16..at + 16 + message_len]).unwrap();
+            rows.push((offset, message.to_owned()));
+            at += 16 + (message_len + 7) / 8 * 8;
+        }
+        assert_eq!(at, table.len());
+        assert_eq!(LittleEndian::read_u32(&len) as usize, rows.len());
+
+        // Each function's unreachable is the only trap in its trap table.
+        let trap_offset = |func: &str| {
+            let traps = symbol_contents(&obj_path, &format!("lucet_trap_table_{}", func));
+            assert_eq!(traps.len(), 8, "{} has one trap site", func);
+            LittleEndian::read_u32(&traps)
+        };
+        assert_eq!(
+            rows,
+            vec![
+                (
+                    trap_offset("guest_func_check"),
+                    "assertion failed in check".to_owned()
+                ),
+                (
+                    trap_offset("guest_func_other"),
+                    "unreachable executed in other".to_owned()
+                ),
+            ]
+        );
+
+        let err = Lucetc::new(test_file("trap_messages"))
+            .with_trap_messages(
+                vec![("nothing".to_owned(), "x".to_owned())]
+                    .into_iter()
+                    .collect(),
+            )
+            .object_file(&obj_path)
+            .expect_err("no function is named nothing");
+        assert!(err.iter_chain().any(|c| c.to_string().contains("nothing")));
+
+        Lucetc::new(test_file("trap_messages"))
+            .object_file(&obj_path)
+            .expect("compile trap_messages");
+        let len = symbol_contents(&obj_path, "lucet_trap_messages_len");
+        assert_eq!(LittleEndian::read_u32(&len), 0);
+    }
+
+    #[test]
+    fn fp_traps() {
+        let (_tmp_dir, obj_path) = out_path("arith.o");
+        let fp_traps = |lucetc: Lucetc| {
+            lucetc.object_file(&obj_path).expect("compile arith");
+            symbol_contents(&obj_path, "lucet_fp_traps")
+        };
+        // Exceptions stay masked unless asked for, as WebAssembly requires.
+        assert_eq!(fp_traps(Lucetc::new(test_file("arith"))), vec![0, 0, 0, 0]);
+        assert_eq!(
+            fp_traps(Lucetc::new(test_file("arith")).with_fp_traps(true)),
+            vec![1, 0, 0, 0]
+        );
+        assert_eq!(
+            fp_traps(Lucetc::new(test_file("arith")).with_fp_traps(false)),
+            vec![0, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn relink_with_bindings() {
+        use lucetc::compiler::relocations::relocations;
+        use lucetc::Bindings;
+        let (tmp_dir, obj_path) = out_path("import.o");
+        let lucetc = Lucetc::new(test_file("import")).with_bindings(super::test_bindings());
+        let mut fields = HashMap::new();
+        fields.insert("inc".to_owned(), "inc_v2".to_owned());
+        Lucetc::new(test_file("import"))
+            .with_bindings(super::test_bindings())
+            .object_file(&obj_path)
+            .expect("compile import");
+        let before = relocations(&std::fs::read(&obj_path).unwrap()).unwrap();
+
+        lucetc
+            .relink_with_bindings(&obj_path, Bindings::env(fields))
+            .expect("relink import");
+        let after = relocations(&std::fs::read(&obj_path).unwrap()).expect("read relocations");
+        assert!(before.iter().any(|r| r.symbol == "inc"));
+        assert!(after.iter().all(|r| r.symbol != "inc"));
+        assert_eq!(after.len(), before.len());
+        for (before, after) in before.iter().zip(after.iter()) {
+            if before.symbol == "inc" {
+                assert_eq!(after.symbol, "inc_v2");
+                assert_eq!(
+                    (&after.section, after.offset),
+                    (&before.section, before.offset)
+                );
+            } else {
+                assert_eq!(after, before);
+            }
+        }
+        let undefined = Command::new("nm")
+            .arg("--undefined-only")
+            .arg(&obj_path)
+            .output()
+            .expect("run nm");
+        let undefined = String::from_utf8(undefined.stdout).unwrap();
+        assert!(
+            undefined.lines().any(|l| l.ends_with(" inc_v2")),
+            "{}",
+            undefined
+        );
+        assert!(
+            !undefined.lines().any(|l| l.ends_with(" inc")),
+            "{}",
+            undefined
+        );
+
+        // Imports sharing a symbol can't be split without recompiling.
+        let obj_path = tmp_dir.path().join("import_aliased.o");
+        let lucetc =
+            Lucetc::new(test_file("import_aliased")).with_bindings(super::aliased_bindings());
+        Lucetc::new(test_file("import_aliased"))
+            .with_bindings(super::aliased_bindings())
+            .object_file(&obj_path)
+            .expect("compile import_aliased");
+        let mut modules = HashMap::new();
+        for (module, symbol) in &[("env", "env_malloc"), ("other", "malloc")] {
+            let mut fields = HashMap::new();
+            fields.insert("malloc".to_owned(), symbol.to_string());
+            modules.insert(module.to_string(), fields);
+        }
+        let err = lucetc
+            .relink_with_bindings(&obj_path, Bindings::new(modules))
+            .expect_err("the imports share a symbol");
+        assert!(err
+            .iter_chain()
+            .any(|c| c.to_string().contains("must be recompiled")));
+    }
+
+    #[test]
+    fn heap_settings() {
+        use byteorder::{ByteOrder, LittleEndian};
+        use lucetc::{GrowZeroing, HeapSettings};
+        let reject = |builder: lucetc::HeapSettingsBuilder, expected: &str| {
+            let err = builder.build().expect_err(expected);
+            assert!(err.to_string().contains(expected), "{}", err);
+        };
+        reject(
+            HeapSettings::builder()
+                .min_reserved_size(8 * 1024 * 1024)
+                .max_reserved_size(4 * 1024 * 1024),
+            "exceeds max reserved size",
+        );
+        reject(
+            HeapSettings::builder().reservation_alignment(3 * 1024 * 1024),
+            "reservation alignment 3145728 is not a power of two",
+        );
+        reject(
+            HeapSettings::builder().host_page_size(3000),
+            "host page size 3000 is not a power of two",
+        );
+        reject(
+            HeapSettings::builder().guard_size(4096 + 1),
+            "guard size (4097) is not a multiple of the 4096-byte host page size",
+        );
+        reject(
+            HeapSettings::builder()
+                .host_page_size(16 * 1024)
+                .guard_size(8 * 1024),
+            "guard size (8192) is not a multiple of the 16384-byte host page size",
+        );
+        assert_eq!(
+            HeapSettings::builder().build().unwrap(),
+            HeapSettings::default()
+        );
+
+        let settings = HeapSettings::builder()
+            .min_reserved_size(8 * 1024 * 1024)
+            .max_reserved_size(16 * 1024 * 1024)
+            .guard_size(64 * 1024)
+            .grow_zeroing(GrowZeroing::Eager)
+            .build()
+            .expect("valid heap settings");
+        assert_eq!(settings.guard_size, 64 * 1024);
+        let (_tmp_dir, obj_path) = out_path("bounds_check.o");
+        Lucetc::new(test_file("bounds_check"))
+            .with_guard_size(0)
+            .with_heap_settings(settings)
+            .object_file(&obj_path)
+            .expect("compile bounds_check");
+        let spec = symbol_contents(&obj_path, "lucet_heap_spec");
+        assert_eq!(LittleEndian::read_u64(&spec[0..]), 8 * 1024 * 1024);
+        assert_eq!(LittleEndian::read_u64(&spec[8..]), 64 * 1024);
+    }
+
+    #[test]
+    fn imported_global_symbol() {
+        use lucetc::compiler::relocations::relocations;
+        use lucetc::{Bindings, RelocKind};
+        let (_tmp_dir, obj_path) = out_path("globals_import.o");
+        let mut fields = HashMap::new();
+        fields.insert("x".to_owned(), "host_config_x".to_owned());
+        Lucetc::new(test_file("globals_import"))
+            .with_bindings(Bindings::env(fields))
+            .object_file(&obj_path)
+            .expect("compile globals_import");
+        let relocs = relocations(&std::fs::read(&obj_path).unwrap()).expect("read relocations");
+        let reads: Vec<_> = relocs
+            .iter()
+            .filter(|r| r.symbol == "host_config_x")
+            .collect();
+        assert_eq!(reads.len(), 1, "main reads the global once: {:?}", relocs);
+        assert!(reads[0].section.contains("guest_func_main"));
+        assert_eq!(reads[0].kind, RelocKind::GotPcRel4);
+
+        // Without a binding, the global is read from the instance's globals.
+        Lucetc::new(test_file("globals_import"))
+            .object_file(&obj_path)
+            .expect("compile globals_import");
+        let relocs = relocations(&std::fs::read(&obj_path).unwrap()).expect("read relocations");
+        assert!(
+            relocs
+                .iter()
+                .all(|r| !r.section.contains("guest_func_main")),
+            "{:?}",
+            relocs
+        );
+    }
+
+    #[test]
+    fn tail_call() {
+        use lucetc::WasmFeatures;
+        // `tail_call.wasm` is hand-assembled: a `countdown` function whose body is
+        // `(return_call 0 (local.get 0))`.
+        let tmp = temp_dir("tail_call");
+        let input = PathBuf::from("tests/wasm/tail_call.wasm");
+
+        let err = Lucetc::new(&input)
+            .clif_ir(tmp.path().join("disabled.clif"))
+            .expect_err("tail calls are disabled by default");
+        assert!(err
+            .iter_chain()
+            .any(|c| c.to_string().contains("tail-call proposal is not enabled")));
+
+        // The tail call is compiled as a call followed by a return.
+        let clif = clif_for(
+            Lucetc::new(&input).with_wasm_features(WasmFeatures {
+                tail_call: true,
+                ..WasmFeatures::default()
+            }),
+            tmp.path(),
+        );
+        assert!(clif.contains("call fn0("), "{}", clif);
+        assert!(clif.contains("return"), "{}", clif);
+    }
+
+    #[test]
+    fn wasi_ambiguous() {
+        use lucetc::{Diagnostic, Severity, WasiAbi};
+        use std::cell::RefCell;
+        use std::rc::Rc;
+        let (_tmp_dir, obj_path) = out_path("wasi_ambiguous.o");
+
+        // Without a requested ABI, a module exporting both entry points is compiled as neither.
+        let diagnostics = Rc::new(RefCell::new(vec![]));
+        let sink = diagnostics.clone();
+        Lucetc::new(test_file("wasi_ambiguous"))
+            .with_diagnostics(move |d| sink.borrow_mut().push(d))
+            .object_file(&obj_path)
+            .expect("compile wasi_ambiguous");
+        assert_eq!(
+            *diagnostics.borrow(),
+            vec![Diagnostic {
+                severity: Severity::Warning,
+                message: "module exports both `_start` and `_initialize`, so it is neither a WASI command nor a reactor".to_owned(),
+            }]
+        );
+        let nm = Command::new("nm").arg(&obj_path).output().expect("run nm");
+        let symbols = str::from_utf8(&nm.stdout).unwrap();
+        assert!(!symbols.contains("lucet_wasi"), "{}", symbols);
+
+        // A requested ABI must agree with both exports.
+        for abi in [WasiAbi::Command, WasiAbi::Reactor].iter() {
+            Lucetc::new(test_file("wasi_ambiguous"))
+                .with_wasi_abi(*abi)
+                .object_file(&obj_path)
+                .expect_err("the exports contradict the requested ABI");
+        }
+    }
+
+    #[test]
+    fn malformed_object() {
+        use byteorder::{ByteOrder, LittleEndian};
+        use lucetc::compiler::relocations::{rebind_symbols, relocations};
+        let (_tmp_dir, obj_path) = out_path("import.o");
+        Lucetc::new(test_file("import"))
+            .with_bindings(super::test_bindings())
+            .object_file(&obj_path)
+            .expect("compile import");
+        let obj = std::fs::read(&obj_path).unwrap();
+        let mut renames = HashMap::new();
+        renames.insert("inc".to_owned(), "renamed".to_owned());
+        rebind_symbols(&obj, &renames).expect("the object is well formed");
+
+        for len in [obj.len() / 2, 64, 16].iter() {
+            let truncated = &obj[..*len];
+            assert!(relocations(truncated).is_err(), "truncated to {}", len);
+            assert!(
+                rebind_symbols(truncated, &renames).is_err(),
+                "truncated to {}",
+                len
+            );
+        }
+
+        // A symbol table whose end overflows the address space.
+        let shoff = LittleEndian::read_u64(&obj[0x28..]) as usize;
+        let shnum = LittleEndian::read_u16(&obj[0x3c..]) as usize;
+        let symtab = (0..shnum)
+            .map(|i| shoff + i * 64)
+            .find(|h| LittleEndian::read_u32(&obj[h + 4..]) == 2)
+            .expect("object has a symbol table");
+        let mut overflowing = obj.clone();
+        LittleEndian::write_u64(&mut overflowing[symtab + 0x20..], u64::max_value());
+        assert!(relocations(&overflowing).is_err());
+        assert!(rebind_symbols(&overflowing, &renames).is_err());
+    }
+}
+
+#[cfg(feature = "jit")]
+mod jit {
+    // Tests for code compiled into the test process
+    use lucetc::{JitModule, Lucetc};
+    use std::mem;
+
+    #[test]
+    fn add() {
+        let mut jit = Lucetc::new("tests/wasm/add.wat").jit().expect("jit add");
+        let add = jit.export("add").expect("add is exported");
+        let add: extern "C" fn(*mut u8, i32, i32) -> i32 = unsafe { mem::transmute(add) };
+        assert_eq!(add(jit.vmctx(), 2, 40), 42);
+        assert_eq!(add(jit.vmctx(), -1, 1), 0);
+    }
+
+    #[test]
+    fn grow() {
+        let mut jit = Lucetc::new("tests/wasm/add.wat").jit().expect("jit add");
+        let grow = jit.export("grow").expect("grow is exported");
+        let grow: extern "C" fn(*mut u8, i32) -> i32 = unsafe { mem::transmute(grow) };
+        assert_eq!(grow(jit.vmctx(), 1), 1);
+        assert_eq!(grow(jit.vmctx(), 0), 2);
+        // The default heap settings reserve 4MiB, or 64 pages.
+        assert_eq!(grow(jit.vmctx(), 1000), -1);
+    }
+
+    #[test]
+    fn missing_export() {
+        let mut jit = Lucetc::new("tests/wasm/add.wat").jit().expect("jit add");
+        assert!(jit.export("sub").is_err());
+    }
+
+    #[test]
+    fn bitcount() {
+        let mut jit = Lucetc::new("tests/wasm/bitcount.wat")
+            .jit()
+            .expect("jit bitcount");
+        let vmctx = jit.vmctx();
+        // Each count is checked on zero, where Wasm defines `clz` and `ctz` as the bit width.
+        let unary32 = |jit: &mut JitModule, name: &str| -> extern "C" fn(*mut u8, i32) -> i32 {
+            unsafe { mem::transmute(jit.export(name).expect("export")) }
+        };
+        let unary64 = |jit: &mut JitModule, name: &str| -> extern "C" fn(*mut u8, i64) -> i64 {
+            unsafe { mem::transmute(jit.export(name).expect("export")) }
+        };
+        let binary32 =
+            |jit: &mut JitModule, name: &str| -> extern "C" fn(*mut u8, i32, i32) -> i32 {
+                unsafe { mem::transmute(jit.export(name).expect("export")) }
+            };
+        let binary64 =
+            |jit: &mut JitModule, name: &str| -> extern "C" fn(*mut u8, i64, i64) -> i64 {
+                unsafe { mem::transmute(jit.export(name).expect("export")) }
+            };
+
+        let clz = unary32(&mut jit, "i32_clz");
+        assert_eq!(clz(vmctx, 0), 32);
+        assert_eq!(clz(vmctx, 1), 31);
+        assert_eq!(clz(vmctx, -1), 0);
+        let ctz = unary32(&mut jit, "i32_ctz");
+        assert_eq!(ctz(vmctx, 0), 32);
+        assert_eq!(ctz(vmctx, 0x100), 8);
+        assert_eq!(ctz(vmctx, i32::min_value()), 31);
+        let popcnt = unary32(&mut jit, "i32_popcnt");
+        assert_eq!(popcnt(vmctx, 0), 0);
+        assert_eq!(popcnt(vmctx, -1), 32);
+        assert_eq!(popcnt(vmctx, 0x5555), 8);
+        let rotl = binary32(&mut jit, "i32_rotl");
+        assert_eq!(rotl(vmctx, i32::min_value(), 1), 1);
+        assert_eq!(rotl(vmctx, 0x1234_5678, 36), 0x2345_6781);
+        let rotr = binary32(&mut jit, "i32_rotr");
+        assert_eq!(rotr(vmctx, 1, 1), i32::min_value());
+        assert_eq!(rotr(vmctx, 0x1234_5678, 32), 0x1234_5678);
+
+        let clz = unary64(&mut jit, "i64_clz");
+        assert_eq!(clz(vmctx, 0), 64);
+        assert_eq!(clz(vmctx, 1), 63);
+        assert_eq!(clz(vmctx, -1), 0);
+        let ctz = unary64(&mut jit, "i64_ctz");
+        assert_eq!(ctz(vmctx, 0), 64);
+        assert_eq!(ctz(vmctx, 1 << 40), 40);
+        let popcnt = unary64(&mut jit, "i64_popcnt");
+        assert_eq!(popcnt(vmctx, 0), 0);
+        assert_eq!(popcnt(vmctx, -1), 64);
+        let rotl = binary64(&mut jit, "i64_rotl");
+        assert_eq!(rotl(vmctx, i64::min_value(), 1), 1);
+        assert_eq!(rotl(vmctx, 1, 65), 2);
+        let rotr = binary64(&mut jit, "i64_rotr");
+        assert_eq!(rotr(vmctx, 1, 1), i64::min_value());
+        assert_eq!(rotr(vmctx, 2, 64), 2);
+    }
+
+    #[test]
+    fn atomics_single_threaded() {
+        use lucetc::{Atomics, LucetcOpts};
+        let mut jit = Lucetc::new("tests/wasm/atomics.wasm")
+            .with_atomics(Atomics::SingleThreaded)
+            .jit()
+            .expect("jit atomics");
+        let vmctx = jit.vmctx();
+        let load: extern "C" fn(*mut u8, i32) -> i32 =
+            unsafe { mem::transmute(jit.export("load").expect("load is exported")) };
+        let add: extern "C" fn(*mut u8, i32, i32) -> i32 =
+            unsafe { mem::transmute(jit.export("add").expect("add is exported")) };
+        let cmpxchg8: extern "C" fn(*mut u8, i32, i32, i32) -> i32 =
+            unsafe { mem::transmute(jit.export("cmpxchg8").expect("cmpxchg8 is exported")) };
+        let fence: extern "C" fn(*mut u8) =
+            unsafe { mem::transmute(jit.export("fence").expect("fence is exported")) };
+
+        // Read-modify-write operations return the old value.
+        assert_eq!(add(vmctx, 0, 5), 0);
+        assert_eq!(add(vmctx, 0, 3), 5);
+        assert_eq!(load(vmctx, 0), 8);
+        fence(vmctx);
+        // A narrow cmpxchg compares and stores only the low byte.
+        assert_eq!(cmpxchg8(vmctx, 0, 8, 0x1ff), 8);
+        assert_eq!(load(vmctx, 0), 0xff);
+        assert_eq!(cmpxchg8(vmctx, 0, 0x1ff, 7), 0xff);
+        assert_eq!(load(vmctx, 0), 7);
+        assert_eq!(cmpxchg8(vmctx, 0, 0x2ff, 9), 7);
+        assert_eq!(load(vmctx, 0), 7);
+    }
+
+    #[test]
+    fn tail_call_recursion() {
+        use lucetc::{LucetcOpts, WasmFeatures};
+        // `tail_call_sum.wasm` is hand-assembled: `sum(n, acc)` returns `acc` once `n` is zero,
+        // and otherwise ends in `(return_call 0 (n - 1) (acc + n))`.
+        let mut jit = Lucetc::new("tests/wasm/tail_call_sum.wasm")
+            .with_wasm_features(WasmFeatures {
+                tail_call: true,
+                ..WasmFeatures::default()
+            })
+            .jit()
+            .expect("jit tail_call_sum");
+        let sum: extern "C" fn(*mut u8, i32, i32) -> i32 =
+            unsafe { mem::transmute(jit.export("sum").expect("sum is exported")) };
+        assert_eq!(sum(jit.vmctx(), 0, 7), 7);
+        // Each lowered tail call still takes a frame, but this many fit on a test thread's stack.
+        assert_eq!(sum(jit.vmctx(), 10_000, 0), 50_005_000);
+    }
+
+    #[test]
+    fn test_vectors() {
+        use lucetc::{TestVector, Value};
+        let vectors = vec![
+            TestVector::new(
+                "add",
+                vec![Value::I32(2), Value::I32(40)],
+                Some(Value::I32(42)),
+            ),
+            TestVector::new(
+                "add",
+                vec![Value::I32(i32::max_value()), Value::I32(1)],
+                Some(Value::I32(i32::min_value())),
+            ),
+            TestVector::new(
+                "div_s",
+                vec![Value::I64(-7), Value::I64(2)],
+                Some(Value::I64(-3)),
+            ),
+            TestVector::new(
+                "mul_add",
+                vec![Value::F32(1.5), Value::F32(4.0), Value::F32(-0.5)],
+                Some(Value::F32(5.5)),
+            ),
+            TestVector::new(
+                "scale",
+                vec![
+                    Value::I32(1),
+                    Value::F64(0.5),
+                    Value::I64(3),
+                    Value::F64(1.0),
+                ],
+                Some(Value::F64(6.0)),
+            ),
+            TestVector::new(
+                "sqrt",
+                vec![Value::F64(-1.0)],
+                Some(Value::F64(std::f64::NAN)),
+            ),
+            TestVector::new("store", vec![Value::I32(1234)], None),
+            TestVector::new("load", vec![], Some(Value::I32(1234))),
+        ];
+        let report = Lucetc::new("tests/wasm/vectors.wat")
+            .run_test_vectors(&vectors)
+            .expect("run vectors");
+        assert!(report.all_passed(), "{:?}", report.mismatches);
+        assert_eq!(report.passed, vectors.len());
+
+        let wrong = vec![
+            TestVector::new(
+                "add",
+                vec![Value::I32(2), Value::I32(2)],
+                Some(Value::I32(5)),
+            ),
+            TestVector::new(
+                "add",
+                vec![Value::I64(2), Value::I64(2)],
+                Some(Value::I64(4)),
+            ),
+        ];
+        let report = Lucetc::new("tests/wasm/vectors.wat")
+            .run_test_vectors(&wrong)
+            .expect("run vectors");
+        assert_eq!(report.passed, 0);
+        assert_eq!(report.mismatches[0].actual, Ok(Some(Value::I32(4))));
+        assert!(report.mismatches[0]
+            .to_string()
+            .contains("expected 5:i32, got 4:i32"));
+        assert!(report.mismatches[1].actual.is_err(), "add takes i32s");
+    }
+}
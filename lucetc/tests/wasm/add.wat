@@ -0,0 +1,9 @@
+(module
+  (memory 1)
+  (func $add (export "add") (param i32 i32) (result i32)
+    (i32.add (get_local 0) (get_local 1))
+  )
+  (func $grow (export "grow") (param i32) (result i32)
+    (grow_memory (get_local 0))
+  )
+)
@@ -0,0 +1,12 @@
+(module
+  (func (export "i32_clz") (param i32) (result i32) (i32.clz (get_local 0)))
+  (func (export "i32_ctz") (param i32) (result i32) (i32.ctz (get_local 0)))
+  (func (export "i32_popcnt") (param i32) (result i32) (i32.popcnt (get_local 0)))
+  (func (export "i32_rotl") (param i32 i32) (result i32) (i32.rotl (get_local 0) (get_local 1)))
+  (func (export "i32_rotr") (param i32 i32) (result i32) (i32.rotr (get_local 0) (get_local 1)))
+  (func (export "i64_clz") (param i64) (result i64) (i64.clz (get_local 0)))
+  (func (export "i64_ctz") (param i64) (result i64) (i64.ctz (get_local 0)))
+  (func (export "i64_popcnt") (param i64) (result i64) (i64.popcnt (get_local 0)))
+  (func (export "i64_rotl") (param i64 i64) (result i64) (i64.rotl (get_local 0) (get_local 1)))
+  (func (export "i64_rotr") (param i64 i64) (result i64) (i64.rotr (get_local 0) (get_local 1)))
+)
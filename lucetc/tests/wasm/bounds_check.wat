@@ -0,0 +1,12 @@
+(module
+  (memory 1)
+  (func $constant (export "constant") (result i32)
+    (i32.load offset=8 (i32.const 16))
+  )
+  (func $dynamic (export "dynamic") (param i32) (result i32)
+    (i32.load (get_local 0))
+  )
+  (func $far (export "far") (result i32)
+    (i32.load (i32.const 0x7fff0000))
+  )
+)
@@ -0,0 +1,8 @@
+(module
+  (func $inc (export "inc") (param i32) (result i32)
+    (i32.add (get_local 0) (i32.const 1))
+  )
+  (func $main (export "main") (result i32)
+    (call $inc (i32.const 1))
+  )
+)
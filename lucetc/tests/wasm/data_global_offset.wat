@@ -0,0 +1,12 @@
+;; A data segment placed at an imported global, so its offset isn't known until the module is
+;; instantiated.
+
+(module
+  (global $base (import "env" "base") i32)
+  (memory 1)
+  (func $first (export "first") (result i32)
+    (i32.load8_u (get_global $base))
+  )
+  (data (i32.const 0) "fixed")
+  (data (get_global $base) "hello")
+)
@@ -0,0 +1,6 @@
+(module
+  (memory 1)
+  (data (i32.const 0) "\01\02\03\04")
+  (data (i32.const 2) "\aa\bb\cc")
+  (data (i32.const 4094) "\11\22\33\44")
+)
@@ -0,0 +1,11 @@
+(module
+  (func $after_br (export "after_br") (result i32)
+    (block
+      (br 0)
+      (drop (i32.const 1))
+      (block
+        (nop)))
+    (i32.const 2))
+  (func $clean (export "clean") (result i32)
+    (i32.const 3))
+)
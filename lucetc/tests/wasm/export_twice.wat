@@ -0,0 +1,4 @@
+(module
+  (func $f (export "first") (export "second") (result i32)
+    (i32.const 1))
+)
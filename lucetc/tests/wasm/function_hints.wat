@@ -0,0 +1,8 @@
+(module
+  (func $handle_error (export "handle_error") (result i32)
+    (i32.const -1))
+  (func $middle (export "middle") (result i32)
+    (i32.const 0))
+  (func $hot_path (export "hot_path") (result i32)
+    (i32.const 1))
+)
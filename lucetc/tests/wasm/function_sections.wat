@@ -0,0 +1,13 @@
+(module
+  (func $dead (result i32)
+    (call $only_called_by_dead))
+  (func $only_called_by_dead (result i32)
+    (i32.const 7))
+  (func $helper (result i32)
+    (i32.const 1))
+  (func $in_table (result i32)
+    (i32.const 2))
+  (table anyfunc (elem $in_table))
+  (func $main (export "main") (result i32)
+    (call $helper))
+)
@@ -0,0 +1,8 @@
+(module
+  (func $choose (export "choose") (param i32) (result i32)
+    (if (result i32) (get_local 0)
+      (then (i32.const 1))
+      (else (i32.const 2))))
+  (func $identity (export "identity") (param i32) (result i32)
+    (get_local 0))
+)
@@ -0,0 +1,8 @@
+(module
+  (import "env" "malloc" (func $env_malloc (param i32) (result i32)))
+  (import "other" "malloc" (func $other_malloc (param i32) (result i32)))
+  (func $main (export "main") (result i32)
+    (i32.add
+      (call $env_malloc (i32.const 1))
+      (call $other_malloc (i32.const 2))))
+)
@@ -0,0 +1,4 @@
+(module
+  (import "env" "malloc" (func $env_malloc (param i32) (result i32)))
+  (import "other" "malloc" (func $other_malloc (param i64) (result i32)))
+)
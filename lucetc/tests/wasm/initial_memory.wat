@@ -0,0 +1,6 @@
+(module
+  (memory 4)
+  (func $main (export "main") (result i32)
+    (i32.load (i32.const 262140))
+  )
+)
@@ -0,0 +1,9 @@
+(module
+  (func $add (export "add") (param i32 i32) (result i32)
+    (i32.add (get_local 0) (get_local 1))
+  )
+  (func $scale (export "scale") (param i32) (result i32)
+    (i32.mul (get_local 0) (i32.const 2))
+  )
+  (memory (export "memory") 1)
+)
@@ -0,0 +1,13 @@
+(module
+  (func $add (export "add") (param i32 i32) (result i32)
+    (i32.add (get_local 0) (get_local 1))
+  )
+  (func $scale (export "scale") (param i64) (result i64)
+    (i64.mul (get_local 0) (i64.const 2))
+  )
+  (func $negate (export "negate") (param i32) (result i32)
+    (i32.sub (i32.const 0) (get_local 0))
+  )
+  (global $version (export "version") i32 (i32.const 2))
+  (memory (export "memory") 1)
+)
@@ -0,0 +1,1211 @@
+(module
+  (memory 1)
+  (func $leaf (result i64)
+    (i64.const 1)
+  )
+  ;; Every local is live across the call to $leaf, so the frame ends up larger than a page.
+  (func $large_frame (export "large_frame") (param i64) (result i64)
+    (local i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64)
+    (set_local 1 (i64.add (get_local 0) (i64.const 0)))
+    (set_local 2 (i64.add (get_local 0) (i64.const 1)))
+    (set_local 3 (i64.add (get_local 0) (i64.const 2)))
+    (set_local 4 (i64.add (get_local 0) (i64.const 3)))
+    (set_local 5 (i64.add (get_local 0) (i64.const 4)))
+    (set_local 6 (i64.add (get_local 0) (i64.const 5)))
+    (set_local 7 (i64.add (get_local 0) (i64.const 6)))
+    (set_local 8 (i64.add (get_local 0) (i64.const 7)))
+    (set_local 9 (i64.add (get_local 0) (i64.const 8)))
+    (set_local 10 (i64.add (get_local 0) (i64.const 9)))
+    (set_local 11 (i64.add (get_local 0) (i64.const 10)))
+    (set_local 12 (i64.add (get_local 0) (i64.const 11)))
+    (set_local 13 (i64.add (get_local 0) (i64.const 12)))
+    (set_local 14 (i64.add (get_local 0) (i64.const 13)))
+    (set_local 15 (i64.add (get_local 0) (i64.const 14)))
+    (set_local 16 (i64.add (get_local 0) (i64.const 15)))
+    (set_local 17 (i64.add (get_local 0) (i64.const 16)))
+    (set_local 18 (i64.add (get_local 0) (i64.const 17)))
+    (set_local 19 (i64.add (get_local 0) (i64.const 18)))
+    (set_local 20 (i64.add (get_local 0) (i64.const 19)))
+    (set_local 21 (i64.add (get_local 0) (i64.const 20)))
+    (set_local 22 (i64.add (get_local 0) (i64.const 21)))
+    (set_local 23 (i64.add (get_local 0) (i64.const 22)))
+    (set_local 24 (i64.add (get_local 0) (i64.const 23)))
+    (set_local 25 (i64.add (get_local 0) (i64.const 24)))
+    (set_local 26 (i64.add (get_local 0) (i64.const 25)))
+    (set_local 27 (i64.add (get_local 0) (i64.const 26)))
+    (set_local 28 (i64.add (get_local 0) (i64.const 27)))
+    (set_local 29 (i64.add (get_local 0) (i64.const 28)))
+    (set_local 30 (i64.add (get_local 0) (i64.const 29)))
+    (set_local 31 (i64.add (get_local 0) (i64.const 30)))
+    (set_local 32 (i64.add (get_local 0) (i64.const 31)))
+    (set_local 33 (i64.add (get_local 0) (i64.const 32)))
+    (set_local 34 (i64.add (get_local 0) (i64.const 33)))
+    (set_local 35 (i64.add (get_local 0) (i64.const 34)))
+    (set_local 36 (i64.add (get_local 0) (i64.const 35)))
+    (set_local 37 (i64.add (get_local 0) (i64.const 36)))
+    (set_local 38 (i64.add (get_local 0) (i64.const 37)))
+    (set_local 39 (i64.add (get_local 0) (i64.const 38)))
+    (set_local 40 (i64.add (get_local 0) (i64.const 39)))
+    (set_local 41 (i64.add (get_local 0) (i64.const 40)))
+    (set_local 42 (i64.add (get_local 0) (i64.const 41)))
+    (set_local 43 (i64.add (get_local 0) (i64.const 42)))
+    (set_local 44 (i64.add (get_local 0) (i64.const 43)))
+    (set_local 45 (i64.add (get_local 0) (i64.const 44)))
+    (set_local 46 (i64.add (get_local 0) (i64.const 45)))
+    (set_local 47 (i64.add (get_local 0) (i64.const 46)))
+    (set_local 48 (i64.add (get_local 0) (i64.const 47)))
+    (set_local 49 (i64.add (get_local 0) (i64.const 48)))
+    (set_local 50 (i64.add (get_local 0) (i64.const 49)))
+    (set_local 51 (i64.add (get_local 0) (i64.const 50)))
+    (set_local 52 (i64.add (get_local 0) (i64.const 51)))
+    (set_local 53 (i64.add (get_local 0) (i64.const 52)))
+    (set_local 54 (i64.add (get_local 0) (i64.const 53)))
+    (set_local 55 (i64.add (get_local 0) (i64.const 54)))
+    (set_local 56 (i64.add (get_local 0) (i64.const 55)))
+    (set_local 57 (i64.add (get_local 0) (i64.const 56)))
+    (set_local 58 (i64.add (get_local 0) (i64.const 57)))
+    (set_local 59 (i64.add (get_local 0) (i64.const 58)))
+    (set_local 60 (i64.add (get_local 0) (i64.const 59)))
+    (set_local 61 (i64.add (get_local 0) (i64.const 60)))
+    (set_local 62 (i64.add (get_local 0) (i64.const 61)))
+    (set_local 63 (i64.add (get_local 0) (i64.const 62)))
+    (set_local 64 (i64.add (get_local 0) (i64.const 63)))
+    (set_local 65 (i64.add (get_local 0) (i64.const 64)))
+    (set_local 66 (i64.add (get_local 0) (i64.const 65)))
+    (set_local 67 (i64.add (get_local 0) (i64.const 66)))
+    (set_local 68 (i64.add (get_local 0) (i64.const 67)))
+    (set_local 69 (i64.add (get_local 0) (i64.const 68)))
+    (set_local 70 (i64.add (get_local 0) (i64.const 69)))
+    (set_local 71 (i64.add (get_local 0) (i64.const 70)))
+    (set_local 72 (i64.add (get_local 0) (i64.const 71)))
+    (set_local 73 (i64.add (get_local 0) (i64.const 72)))
+    (set_local 74 (i64.add (get_local 0) (i64.const 73)))
+    (set_local 75 (i64.add (get_local 0) (i64.const 74)))
+    (set_local 76 (i64.add (get_local 0) (i64.const 75)))
+    (set_local 77 (i64.add (get_local 0) (i64.const 76)))
+    (set_local 78 (i64.add (get_local 0) (i64.const 77)))
+    (set_local 79 (i64.add (get_local 0) (i64.const 78)))
+    (set_local 80 (i64.add (get_local 0) (i64.const 79)))
+    (set_local 81 (i64.add (get_local 0) (i64.const 80)))
+    (set_local 82 (i64.add (get_local 0) (i64.const 81)))
+    (set_local 83 (i64.add (get_local 0) (i64.const 82)))
+    (set_local 84 (i64.add (get_local 0) (i64.const 83)))
+    (set_local 85 (i64.add (get_local 0) (i64.const 84)))
+    (set_local 86 (i64.add (get_local 0) (i64.const 85)))
+    (set_local 87 (i64.add (get_local 0) (i64.const 86)))
+    (set_local 88 (i64.add (get_local 0) (i64.const 87)))
+    (set_local 89 (i64.add (get_local 0) (i64.const 88)))
+    (set_local 90 (i64.add (get_local 0) (i64.const 89)))
+    (set_local 91 (i64.add (get_local 0) (i64.const 90)))
+    (set_local 92 (i64.add (get_local 0) (i64.const 91)))
+    (set_local 93 (i64.add (get_local 0) (i64.const 92)))
+    (set_local 94 (i64.add (get_local 0) (i64.const 93)))
+    (set_local 95 (i64.add (get_local 0) (i64.const 94)))
+    (set_local 96 (i64.add (get_local 0) (i64.const 95)))
+    (set_local 97 (i64.add (get_local 0) (i64.const 96)))
+    (set_local 98 (i64.add (get_local 0) (i64.const 97)))
+    (set_local 99 (i64.add (get_local 0) (i64.const 98)))
+    (set_local 100 (i64.add (get_local 0) (i64.const 99)))
+    (set_local 101 (i64.add (get_local 0) (i64.const 100)))
+    (set_local 102 (i64.add (get_local 0) (i64.const 101)))
+    (set_local 103 (i64.add (get_local 0) (i64.const 102)))
+    (set_local 104 (i64.add (get_local 0) (i64.const 103)))
+    (set_local 105 (i64.add (get_local 0) (i64.const 104)))
+    (set_local 106 (i64.add (get_local 0) (i64.const 105)))
+    (set_local 107 (i64.add (get_local 0) (i64.const 106)))
+    (set_local 108 (i64.add (get_local 0) (i64.const 107)))
+    (set_local 109 (i64.add (get_local 0) (i64.const 108)))
+    (set_local 110 (i64.add (get_local 0) (i64.const 109)))
+    (set_local 111 (i64.add (get_local 0) (i64.const 110)))
+    (set_local 112 (i64.add (get_local 0) (i64.const 111)))
+    (set_local 113 (i64.add (get_local 0) (i64.const 112)))
+    (set_local 114 (i64.add (get_local 0) (i64.const 113)))
+    (set_local 115 (i64.add (get_local 0) (i64.const 114)))
+    (set_local 116 (i64.add (get_local 0) (i64.const 115)))
+    (set_local 117 (i64.add (get_local 0) (i64.const 116)))
+    (set_local 118 (i64.add (get_local 0) (i64.const 117)))
+    (set_local 119 (i64.add (get_local 0) (i64.const 118)))
+    (set_local 120 (i64.add (get_local 0) (i64.const 119)))
+    (set_local 121 (i64.add (get_local 0) (i64.const 120)))
+    (set_local 122 (i64.add (get_local 0) (i64.const 121)))
+    (set_local 123 (i64.add (get_local 0) (i64.const 122)))
+    (set_local 124 (i64.add (get_local 0) (i64.const 123)))
+    (set_local 125 (i64.add (get_local 0) (i64.const 124)))
+    (set_local 126 (i64.add (get_local 0) (i64.const 125)))
+    (set_local 127 (i64.add (get_local 0) (i64.const 126)))
+    (set_local 128 (i64.add (get_local 0) (i64.const 127)))
+    (set_local 129 (i64.add (get_local 0) (i64.const 128)))
+    (set_local 130 (i64.add (get_local 0) (i64.const 129)))
+    (set_local 131 (i64.add (get_local 0) (i64.const 130)))
+    (set_local 132 (i64.add (get_local 0) (i64.const 131)))
+    (set_local 133 (i64.add (get_local 0) (i64.const 132)))
+    (set_local 134 (i64.add (get_local 0) (i64.const 133)))
+    (set_local 135 (i64.add (get_local 0) (i64.const 134)))
+    (set_local 136 (i64.add (get_local 0) (i64.const 135)))
+    (set_local 137 (i64.add (get_local 0) (i64.const 136)))
+    (set_local 138 (i64.add (get_local 0) (i64.const 137)))
+    (set_local 139 (i64.add (get_local 0) (i64.const 138)))
+    (set_local 140 (i64.add (get_local 0) (i64.const 139)))
+    (set_local 141 (i64.add (get_local 0) (i64.const 140)))
+    (set_local 142 (i64.add (get_local 0) (i64.const 141)))
+    (set_local 143 (i64.add (get_local 0) (i64.const 142)))
+    (set_local 144 (i64.add (get_local 0) (i64.const 143)))
+    (set_local 145 (i64.add (get_local 0) (i64.const 144)))
+    (set_local 146 (i64.add (get_local 0) (i64.const 145)))
+    (set_local 147 (i64.add (get_local 0) (i64.const 146)))
+    (set_local 148 (i64.add (get_local 0) (i64.const 147)))
+    (set_local 149 (i64.add (get_local 0) (i64.const 148)))
+    (set_local 150 (i64.add (get_local 0) (i64.const 149)))
+    (set_local 151 (i64.add (get_local 0) (i64.const 150)))
+    (set_local 152 (i64.add (get_local 0) (i64.const 151)))
+    (set_local 153 (i64.add (get_local 0) (i64.const 152)))
+    (set_local 154 (i64.add (get_local 0) (i64.const 153)))
+    (set_local 155 (i64.add (get_local 0) (i64.const 154)))
+    (set_local 156 (i64.add (get_local 0) (i64.const 155)))
+    (set_local 157 (i64.add (get_local 0) (i64.const 156)))
+    (set_local 158 (i64.add (get_local 0) (i64.const 157)))
+    (set_local 159 (i64.add (get_local 0) (i64.const 158)))
+    (set_local 160 (i64.add (get_local 0) (i64.const 159)))
+    (set_local 161 (i64.add (get_local 0) (i64.const 160)))
+    (set_local 162 (i64.add (get_local 0) (i64.const 161)))
+    (set_local 163 (i64.add (get_local 0) (i64.const 162)))
+    (set_local 164 (i64.add (get_local 0) (i64.const 163)))
+    (set_local 165 (i64.add (get_local 0) (i64.const 164)))
+    (set_local 166 (i64.add (get_local 0) (i64.const 165)))
+    (set_local 167 (i64.add (get_local 0) (i64.const 166)))
+    (set_local 168 (i64.add (get_local 0) (i64.const 167)))
+    (set_local 169 (i64.add (get_local 0) (i64.const 168)))
+    (set_local 170 (i64.add (get_local 0) (i64.const 169)))
+    (set_local 171 (i64.add (get_local 0) (i64.const 170)))
+    (set_local 172 (i64.add (get_local 0) (i64.const 171)))
+    (set_local 173 (i64.add (get_local 0) (i64.const 172)))
+    (set_local 174 (i64.add (get_local 0) (i64.const 173)))
+    (set_local 175 (i64.add (get_local 0) (i64.const 174)))
+    (set_local 176 (i64.add (get_local 0) (i64.const 175)))
+    (set_local 177 (i64.add (get_local 0) (i64.const 176)))
+    (set_local 178 (i64.add (get_local 0) (i64.const 177)))
+    (set_local 179 (i64.add (get_local 0) (i64.const 178)))
+    (set_local 180 (i64.add (get_local 0) (i64.const 179)))
+    (set_local 181 (i64.add (get_local 0) (i64.const 180)))
+    (set_local 182 (i64.add (get_local 0) (i64.const 181)))
+    (set_local 183 (i64.add (get_local 0) (i64.const 182)))
+    (set_local 184 (i64.add (get_local 0) (i64.const 183)))
+    (set_local 185 (i64.add (get_local 0) (i64.const 184)))
+    (set_local 186 (i64.add (get_local 0) (i64.const 185)))
+    (set_local 187 (i64.add (get_local 0) (i64.const 186)))
+    (set_local 188 (i64.add (get_local 0) (i64.const 187)))
+    (set_local 189 (i64.add (get_local 0) (i64.const 188)))
+    (set_local 190 (i64.add (get_local 0) (i64.const 189)))
+    (set_local 191 (i64.add (get_local 0) (i64.const 190)))
+    (set_local 192 (i64.add (get_local 0) (i64.const 191)))
+    (set_local 193 (i64.add (get_local 0) (i64.const 192)))
+    (set_local 194 (i64.add (get_local 0) (i64.const 193)))
+    (set_local 195 (i64.add (get_local 0) (i64.const 194)))
+    (set_local 196 (i64.add (get_local 0) (i64.const 195)))
+    (set_local 197 (i64.add (get_local 0) (i64.const 196)))
+    (set_local 198 (i64.add (get_local 0) (i64.const 197)))
+    (set_local 199 (i64.add (get_local 0) (i64.const 198)))
+    (set_local 200 (i64.add (get_local 0) (i64.const 199)))
+    (set_local 201 (i64.add (get_local 0) (i64.const 200)))
+    (set_local 202 (i64.add (get_local 0) (i64.const 201)))
+    (set_local 203 (i64.add (get_local 0) (i64.const 202)))
+    (set_local 204 (i64.add (get_local 0) (i64.const 203)))
+    (set_local 205 (i64.add (get_local 0) (i64.const 204)))
+    (set_local 206 (i64.add (get_local 0) (i64.const 205)))
+    (set_local 207 (i64.add (get_local 0) (i64.const 206)))
+    (set_local 208 (i64.add (get_local 0) (i64.const 207)))
+    (set_local 209 (i64.add (get_local 0) (i64.const 208)))
+    (set_local 210 (i64.add (get_local 0) (i64.const 209)))
+    (set_local 211 (i64.add (get_local 0) (i64.const 210)))
+    (set_local 212 (i64.add (get_local 0) (i64.const 211)))
+    (set_local 213 (i64.add (get_local 0) (i64.const 212)))
+    (set_local 214 (i64.add (get_local 0) (i64.const 213)))
+    (set_local 215 (i64.add (get_local 0) (i64.const 214)))
+    (set_local 216 (i64.add (get_local 0) (i64.const 215)))
+    (set_local 217 (i64.add (get_local 0) (i64.const 216)))
+    (set_local 218 (i64.add (get_local 0) (i64.const 217)))
+    (set_local 219 (i64.add (get_local 0) (i64.const 218)))
+    (set_local 220 (i64.add (get_local 0) (i64.const 219)))
+    (set_local 221 (i64.add (get_local 0) (i64.const 220)))
+    (set_local 222 (i64.add (get_local 0) (i64.const 221)))
+    (set_local 223 (i64.add (get_local 0) (i64.const 222)))
+    (set_local 224 (i64.add (get_local 0) (i64.const 223)))
+    (set_local 225 (i64.add (get_local 0) (i64.const 224)))
+    (set_local 226 (i64.add (get_local 0) (i64.const 225)))
+    (set_local 227 (i64.add (get_local 0) (i64.const 226)))
+    (set_local 228 (i64.add (get_local 0) (i64.const 227)))
+    (set_local 229 (i64.add (get_local 0) (i64.const 228)))
+    (set_local 230 (i64.add (get_local 0) (i64.const 229)))
+    (set_local 231 (i64.add (get_local 0) (i64.const 230)))
+    (set_local 232 (i64.add (get_local 0) (i64.const 231)))
+    (set_local 233 (i64.add (get_local 0) (i64.const 232)))
+    (set_local 234 (i64.add (get_local 0) (i64.const 233)))
+    (set_local 235 (i64.add (get_local 0) (i64.const 234)))
+    (set_local 236 (i64.add (get_local 0) (i64.const 235)))
+    (set_local 237 (i64.add (get_local 0) (i64.const 236)))
+    (set_local 238 (i64.add (get_local 0) (i64.const 237)))
+    (set_local 239 (i64.add (get_local 0) (i64.const 238)))
+    (set_local 240 (i64.add (get_local 0) (i64.const 239)))
+    (set_local 241 (i64.add (get_local 0) (i64.const 240)))
+    (set_local 242 (i64.add (get_local 0) (i64.const 241)))
+    (set_local 243 (i64.add (get_local 0) (i64.const 242)))
+    (set_local 244 (i64.add (get_local 0) (i64.const 243)))
+    (set_local 245 (i64.add (get_local 0) (i64.const 244)))
+    (set_local 246 (i64.add (get_local 0) (i64.const 245)))
+    (set_local 247 (i64.add (get_local 0) (i64.const 246)))
+    (set_local 248 (i64.add (get_local 0) (i64.const 247)))
+    (set_local 249 (i64.add (get_local 0) (i64.const 248)))
+    (set_local 250 (i64.add (get_local 0) (i64.const 249)))
+    (set_local 251 (i64.add (get_local 0) (i64.const 250)))
+    (set_local 252 (i64.add (get_local 0) (i64.const 251)))
+    (set_local 253 (i64.add (get_local 0) (i64.const 252)))
+    (set_local 254 (i64.add (get_local 0) (i64.const 253)))
+    (set_local 255 (i64.add (get_local 0) (i64.const 254)))
+    (set_local 256 (i64.add (get_local 0) (i64.const 255)))
+    (set_local 257 (i64.add (get_local 0) (i64.const 256)))
+    (set_local 258 (i64.add (get_local 0) (i64.const 257)))
+    (set_local 259 (i64.add (get_local 0) (i64.const 258)))
+    (set_local 260 (i64.add (get_local 0) (i64.const 259)))
+    (set_local 261 (i64.add (get_local 0) (i64.const 260)))
+    (set_local 262 (i64.add (get_local 0) (i64.const 261)))
+    (set_local 263 (i64.add (get_local 0) (i64.const 262)))
+    (set_local 264 (i64.add (get_local 0) (i64.const 263)))
+    (set_local 265 (i64.add (get_local 0) (i64.const 264)))
+    (set_local 266 (i64.add (get_local 0) (i64.const 265)))
+    (set_local 267 (i64.add (get_local 0) (i64.const 266)))
+    (set_local 268 (i64.add (get_local 0) (i64.const 267)))
+    (set_local 269 (i64.add (get_local 0) (i64.const 268)))
+    (set_local 270 (i64.add (get_local 0) (i64.const 269)))
+    (set_local 271 (i64.add (get_local 0) (i64.const 270)))
+    (set_local 272 (i64.add (get_local 0) (i64.const 271)))
+    (set_local 273 (i64.add (get_local 0) (i64.const 272)))
+    (set_local 274 (i64.add (get_local 0) (i64.const 273)))
+    (set_local 275 (i64.add (get_local 0) (i64.const 274)))
+    (set_local 276 (i64.add (get_local 0) (i64.const 275)))
+    (set_local 277 (i64.add (get_local 0) (i64.const 276)))
+    (set_local 278 (i64.add (get_local 0) (i64.const 277)))
+    (set_local 279 (i64.add (get_local 0) (i64.const 278)))
+    (set_local 280 (i64.add (get_local 0) (i64.const 279)))
+    (set_local 281 (i64.add (get_local 0) (i64.const 280)))
+    (set_local 282 (i64.add (get_local 0) (i64.const 281)))
+    (set_local 283 (i64.add (get_local 0) (i64.const 282)))
+    (set_local 284 (i64.add (get_local 0) (i64.const 283)))
+    (set_local 285 (i64.add (get_local 0) (i64.const 284)))
+    (set_local 286 (i64.add (get_local 0) (i64.const 285)))
+    (set_local 287 (i64.add (get_local 0) (i64.const 286)))
+    (set_local 288 (i64.add (get_local 0) (i64.const 287)))
+    (set_local 289 (i64.add (get_local 0) (i64.const 288)))
+    (set_local 290 (i64.add (get_local 0) (i64.const 289)))
+    (set_local 291 (i64.add (get_local 0) (i64.const 290)))
+    (set_local 292 (i64.add (get_local 0) (i64.const 291)))
+    (set_local 293 (i64.add (get_local 0) (i64.const 292)))
+    (set_local 294 (i64.add (get_local 0) (i64.const 293)))
+    (set_local 295 (i64.add (get_local 0) (i64.const 294)))
+    (set_local 296 (i64.add (get_local 0) (i64.const 295)))
+    (set_local 297 (i64.add (get_local 0) (i64.const 296)))
+    (set_local 298 (i64.add (get_local 0) (i64.const 297)))
+    (set_local 299 (i64.add (get_local 0) (i64.const 298)))
+    (set_local 300 (i64.add (get_local 0) (i64.const 299)))
+    (set_local 301 (i64.add (get_local 0) (i64.const 300)))
+    (set_local 302 (i64.add (get_local 0) (i64.const 301)))
+    (set_local 303 (i64.add (get_local 0) (i64.const 302)))
+    (set_local 304 (i64.add (get_local 0) (i64.const 303)))
+    (set_local 305 (i64.add (get_local 0) (i64.const 304)))
+    (set_local 306 (i64.add (get_local 0) (i64.const 305)))
+    (set_local 307 (i64.add (get_local 0) (i64.const 306)))
+    (set_local 308 (i64.add (get_local 0) (i64.const 307)))
+    (set_local 309 (i64.add (get_local 0) (i64.const 308)))
+    (set_local 310 (i64.add (get_local 0) (i64.const 309)))
+    (set_local 311 (i64.add (get_local 0) (i64.const 310)))
+    (set_local 312 (i64.add (get_local 0) (i64.const 311)))
+    (set_local 313 (i64.add (get_local 0) (i64.const 312)))
+    (set_local 314 (i64.add (get_local 0) (i64.const 313)))
+    (set_local 315 (i64.add (get_local 0) (i64.const 314)))
+    (set_local 316 (i64.add (get_local 0) (i64.const 315)))
+    (set_local 317 (i64.add (get_local 0) (i64.const 316)))
+    (set_local 318 (i64.add (get_local 0) (i64.const 317)))
+    (set_local 319 (i64.add (get_local 0) (i64.const 318)))
+    (set_local 320 (i64.add (get_local 0) (i64.const 319)))
+    (set_local 321 (i64.add (get_local 0) (i64.const 320)))
+    (set_local 322 (i64.add (get_local 0) (i64.const 321)))
+    (set_local 323 (i64.add (get_local 0) (i64.const 322)))
+    (set_local 324 (i64.add (get_local 0) (i64.const 323)))
+    (set_local 325 (i64.add (get_local 0) (i64.const 324)))
+    (set_local 326 (i64.add (get_local 0) (i64.const 325)))
+    (set_local 327 (i64.add (get_local 0) (i64.const 326)))
+    (set_local 328 (i64.add (get_local 0) (i64.const 327)))
+    (set_local 329 (i64.add (get_local 0) (i64.const 328)))
+    (set_local 330 (i64.add (get_local 0) (i64.const 329)))
+    (set_local 331 (i64.add (get_local 0) (i64.const 330)))
+    (set_local 332 (i64.add (get_local 0) (i64.const 331)))
+    (set_local 333 (i64.add (get_local 0) (i64.const 332)))
+    (set_local 334 (i64.add (get_local 0) (i64.const 333)))
+    (set_local 335 (i64.add (get_local 0) (i64.const 334)))
+    (set_local 336 (i64.add (get_local 0) (i64.const 335)))
+    (set_local 337 (i64.add (get_local 0) (i64.const 336)))
+    (set_local 338 (i64.add (get_local 0) (i64.const 337)))
+    (set_local 339 (i64.add (get_local 0) (i64.const 338)))
+    (set_local 340 (i64.add (get_local 0) (i64.const 339)))
+    (set_local 341 (i64.add (get_local 0) (i64.const 340)))
+    (set_local 342 (i64.add (get_local 0) (i64.const 341)))
+    (set_local 343 (i64.add (get_local 0) (i64.const 342)))
+    (set_local 344 (i64.add (get_local 0) (i64.const 343)))
+    (set_local 345 (i64.add (get_local 0) (i64.const 344)))
+    (set_local 346 (i64.add (get_local 0) (i64.const 345)))
+    (set_local 347 (i64.add (get_local 0) (i64.const 346)))
+    (set_local 348 (i64.add (get_local 0) (i64.const 347)))
+    (set_local 349 (i64.add (get_local 0) (i64.const 348)))
+    (set_local 350 (i64.add (get_local 0) (i64.const 349)))
+    (set_local 351 (i64.add (get_local 0) (i64.const 350)))
+    (set_local 352 (i64.add (get_local 0) (i64.const 351)))
+    (set_local 353 (i64.add (get_local 0) (i64.const 352)))
+    (set_local 354 (i64.add (get_local 0) (i64.const 353)))
+    (set_local 355 (i64.add (get_local 0) (i64.const 354)))
+    (set_local 356 (i64.add (get_local 0) (i64.const 355)))
+    (set_local 357 (i64.add (get_local 0) (i64.const 356)))
+    (set_local 358 (i64.add (get_local 0) (i64.const 357)))
+    (set_local 359 (i64.add (get_local 0) (i64.const 358)))
+    (set_local 360 (i64.add (get_local 0) (i64.const 359)))
+    (set_local 361 (i64.add (get_local 0) (i64.const 360)))
+    (set_local 362 (i64.add (get_local 0) (i64.const 361)))
+    (set_local 363 (i64.add (get_local 0) (i64.const 362)))
+    (set_local 364 (i64.add (get_local 0) (i64.const 363)))
+    (set_local 365 (i64.add (get_local 0) (i64.const 364)))
+    (set_local 366 (i64.add (get_local 0) (i64.const 365)))
+    (set_local 367 (i64.add (get_local 0) (i64.const 366)))
+    (set_local 368 (i64.add (get_local 0) (i64.const 367)))
+    (set_local 369 (i64.add (get_local 0) (i64.const 368)))
+    (set_local 370 (i64.add (get_local 0) (i64.const 369)))
+    (set_local 371 (i64.add (get_local 0) (i64.const 370)))
+    (set_local 372 (i64.add (get_local 0) (i64.const 371)))
+    (set_local 373 (i64.add (get_local 0) (i64.const 372)))
+    (set_local 374 (i64.add (get_local 0) (i64.const 373)))
+    (set_local 375 (i64.add (get_local 0) (i64.const 374)))
+    (set_local 376 (i64.add (get_local 0) (i64.const 375)))
+    (set_local 377 (i64.add (get_local 0) (i64.const 376)))
+    (set_local 378 (i64.add (get_local 0) (i64.const 377)))
+    (set_local 379 (i64.add (get_local 0) (i64.const 378)))
+    (set_local 380 (i64.add (get_local 0) (i64.const 379)))
+    (set_local 381 (i64.add (get_local 0) (i64.const 380)))
+    (set_local 382 (i64.add (get_local 0) (i64.const 381)))
+    (set_local 383 (i64.add (get_local 0) (i64.const 382)))
+    (set_local 384 (i64.add (get_local 0) (i64.const 383)))
+    (set_local 385 (i64.add (get_local 0) (i64.const 384)))
+    (set_local 386 (i64.add (get_local 0) (i64.const 385)))
+    (set_local 387 (i64.add (get_local 0) (i64.const 386)))
+    (set_local 388 (i64.add (get_local 0) (i64.const 387)))
+    (set_local 389 (i64.add (get_local 0) (i64.const 388)))
+    (set_local 390 (i64.add (get_local 0) (i64.const 389)))
+    (set_local 391 (i64.add (get_local 0) (i64.const 390)))
+    (set_local 392 (i64.add (get_local 0) (i64.const 391)))
+    (set_local 393 (i64.add (get_local 0) (i64.const 392)))
+    (set_local 394 (i64.add (get_local 0) (i64.const 393)))
+    (set_local 395 (i64.add (get_local 0) (i64.const 394)))
+    (set_local 396 (i64.add (get_local 0) (i64.const 395)))
+    (set_local 397 (i64.add (get_local 0) (i64.const 396)))
+    (set_local 398 (i64.add (get_local 0) (i64.const 397)))
+    (set_local 399 (i64.add (get_local 0) (i64.const 398)))
+    (set_local 400 (i64.add (get_local 0) (i64.const 399)))
+    (set_local 401 (i64.add (get_local 0) (i64.const 400)))
+    (set_local 402 (i64.add (get_local 0) (i64.const 401)))
+    (set_local 403 (i64.add (get_local 0) (i64.const 402)))
+    (set_local 404 (i64.add (get_local 0) (i64.const 403)))
+    (set_local 405 (i64.add (get_local 0) (i64.const 404)))
+    (set_local 406 (i64.add (get_local 0) (i64.const 405)))
+    (set_local 407 (i64.add (get_local 0) (i64.const 406)))
+    (set_local 408 (i64.add (get_local 0) (i64.const 407)))
+    (set_local 409 (i64.add (get_local 0) (i64.const 408)))
+    (set_local 410 (i64.add (get_local 0) (i64.const 409)))
+    (set_local 411 (i64.add (get_local 0) (i64.const 410)))
+    (set_local 412 (i64.add (get_local 0) (i64.const 411)))
+    (set_local 413 (i64.add (get_local 0) (i64.const 412)))
+    (set_local 414 (i64.add (get_local 0) (i64.const 413)))
+    (set_local 415 (i64.add (get_local 0) (i64.const 414)))
+    (set_local 416 (i64.add (get_local 0) (i64.const 415)))
+    (set_local 417 (i64.add (get_local 0) (i64.const 416)))
+    (set_local 418 (i64.add (get_local 0) (i64.const 417)))
+    (set_local 419 (i64.add (get_local 0) (i64.const 418)))
+    (set_local 420 (i64.add (get_local 0) (i64.const 419)))
+    (set_local 421 (i64.add (get_local 0) (i64.const 420)))
+    (set_local 422 (i64.add (get_local 0) (i64.const 421)))
+    (set_local 423 (i64.add (get_local 0) (i64.const 422)))
+    (set_local 424 (i64.add (get_local 0) (i64.const 423)))
+    (set_local 425 (i64.add (get_local 0) (i64.const 424)))
+    (set_local 426 (i64.add (get_local 0) (i64.const 425)))
+    (set_local 427 (i64.add (get_local 0) (i64.const 426)))
+    (set_local 428 (i64.add (get_local 0) (i64.const 427)))
+    (set_local 429 (i64.add (get_local 0) (i64.const 428)))
+    (set_local 430 (i64.add (get_local 0) (i64.const 429)))
+    (set_local 431 (i64.add (get_local 0) (i64.const 430)))
+    (set_local 432 (i64.add (get_local 0) (i64.const 431)))
+    (set_local 433 (i64.add (get_local 0) (i64.const 432)))
+    (set_local 434 (i64.add (get_local 0) (i64.const 433)))
+    (set_local 435 (i64.add (get_local 0) (i64.const 434)))
+    (set_local 436 (i64.add (get_local 0) (i64.const 435)))
+    (set_local 437 (i64.add (get_local 0) (i64.const 436)))
+    (set_local 438 (i64.add (get_local 0) (i64.const 437)))
+    (set_local 439 (i64.add (get_local 0) (i64.const 438)))
+    (set_local 440 (i64.add (get_local 0) (i64.const 439)))
+    (set_local 441 (i64.add (get_local 0) (i64.const 440)))
+    (set_local 442 (i64.add (get_local 0) (i64.const 441)))
+    (set_local 443 (i64.add (get_local 0) (i64.const 442)))
+    (set_local 444 (i64.add (get_local 0) (i64.const 443)))
+    (set_local 445 (i64.add (get_local 0) (i64.const 444)))
+    (set_local 446 (i64.add (get_local 0) (i64.const 445)))
+    (set_local 447 (i64.add (get_local 0) (i64.const 446)))
+    (set_local 448 (i64.add (get_local 0) (i64.const 447)))
+    (set_local 449 (i64.add (get_local 0) (i64.const 448)))
+    (set_local 450 (i64.add (get_local 0) (i64.const 449)))
+    (set_local 451 (i64.add (get_local 0) (i64.const 450)))
+    (set_local 452 (i64.add (get_local 0) (i64.const 451)))
+    (set_local 453 (i64.add (get_local 0) (i64.const 452)))
+    (set_local 454 (i64.add (get_local 0) (i64.const 453)))
+    (set_local 455 (i64.add (get_local 0) (i64.const 454)))
+    (set_local 456 (i64.add (get_local 0) (i64.const 455)))
+    (set_local 457 (i64.add (get_local 0) (i64.const 456)))
+    (set_local 458 (i64.add (get_local 0) (i64.const 457)))
+    (set_local 459 (i64.add (get_local 0) (i64.const 458)))
+    (set_local 460 (i64.add (get_local 0) (i64.const 459)))
+    (set_local 461 (i64.add (get_local 0) (i64.const 460)))
+    (set_local 462 (i64.add (get_local 0) (i64.const 461)))
+    (set_local 463 (i64.add (get_local 0) (i64.const 462)))
+    (set_local 464 (i64.add (get_local 0) (i64.const 463)))
+    (set_local 465 (i64.add (get_local 0) (i64.const 464)))
+    (set_local 466 (i64.add (get_local 0) (i64.const 465)))
+    (set_local 467 (i64.add (get_local 0) (i64.const 466)))
+    (set_local 468 (i64.add (get_local 0) (i64.const 467)))
+    (set_local 469 (i64.add (get_local 0) (i64.const 468)))
+    (set_local 470 (i64.add (get_local 0) (i64.const 469)))
+    (set_local 471 (i64.add (get_local 0) (i64.const 470)))
+    (set_local 472 (i64.add (get_local 0) (i64.const 471)))
+    (set_local 473 (i64.add (get_local 0) (i64.const 472)))
+    (set_local 474 (i64.add (get_local 0) (i64.const 473)))
+    (set_local 475 (i64.add (get_local 0) (i64.const 474)))
+    (set_local 476 (i64.add (get_local 0) (i64.const 475)))
+    (set_local 477 (i64.add (get_local 0) (i64.const 476)))
+    (set_local 478 (i64.add (get_local 0) (i64.const 477)))
+    (set_local 479 (i64.add (get_local 0) (i64.const 478)))
+    (set_local 480 (i64.add (get_local 0) (i64.const 479)))
+    (set_local 481 (i64.add (get_local 0) (i64.const 480)))
+    (set_local 482 (i64.add (get_local 0) (i64.const 481)))
+    (set_local 483 (i64.add (get_local 0) (i64.const 482)))
+    (set_local 484 (i64.add (get_local 0) (i64.const 483)))
+    (set_local 485 (i64.add (get_local 0) (i64.const 484)))
+    (set_local 486 (i64.add (get_local 0) (i64.const 485)))
+    (set_local 487 (i64.add (get_local 0) (i64.const 486)))
+    (set_local 488 (i64.add (get_local 0) (i64.const 487)))
+    (set_local 489 (i64.add (get_local 0) (i64.const 488)))
+    (set_local 490 (i64.add (get_local 0) (i64.const 489)))
+    (set_local 491 (i64.add (get_local 0) (i64.const 490)))
+    (set_local 492 (i64.add (get_local 0) (i64.const 491)))
+    (set_local 493 (i64.add (get_local 0) (i64.const 492)))
+    (set_local 494 (i64.add (get_local 0) (i64.const 493)))
+    (set_local 495 (i64.add (get_local 0) (i64.const 494)))
+    (set_local 496 (i64.add (get_local 0) (i64.const 495)))
+    (set_local 497 (i64.add (get_local 0) (i64.const 496)))
+    (set_local 498 (i64.add (get_local 0) (i64.const 497)))
+    (set_local 499 (i64.add (get_local 0) (i64.const 498)))
+    (set_local 500 (i64.add (get_local 0) (i64.const 499)))
+    (set_local 501 (i64.add (get_local 0) (i64.const 500)))
+    (set_local 502 (i64.add (get_local 0) (i64.const 501)))
+    (set_local 503 (i64.add (get_local 0) (i64.const 502)))
+    (set_local 504 (i64.add (get_local 0) (i64.const 503)))
+    (set_local 505 (i64.add (get_local 0) (i64.const 504)))
+    (set_local 506 (i64.add (get_local 0) (i64.const 505)))
+    (set_local 507 (i64.add (get_local 0) (i64.const 506)))
+    (set_local 508 (i64.add (get_local 0) (i64.const 507)))
+    (set_local 509 (i64.add (get_local 0) (i64.const 508)))
+    (set_local 510 (i64.add (get_local 0) (i64.const 509)))
+    (set_local 511 (i64.add (get_local 0) (i64.const 510)))
+    (set_local 512 (i64.add (get_local 0) (i64.const 511)))
+    (set_local 513 (i64.add (get_local 0) (i64.const 512)))
+    (set_local 514 (i64.add (get_local 0) (i64.const 513)))
+    (set_local 515 (i64.add (get_local 0) (i64.const 514)))
+    (set_local 516 (i64.add (get_local 0) (i64.const 515)))
+    (set_local 517 (i64.add (get_local 0) (i64.const 516)))
+    (set_local 518 (i64.add (get_local 0) (i64.const 517)))
+    (set_local 519 (i64.add (get_local 0) (i64.const 518)))
+    (set_local 520 (i64.add (get_local 0) (i64.const 519)))
+    (set_local 521 (i64.add (get_local 0) (i64.const 520)))
+    (set_local 522 (i64.add (get_local 0) (i64.const 521)))
+    (set_local 523 (i64.add (get_local 0) (i64.const 522)))
+    (set_local 524 (i64.add (get_local 0) (i64.const 523)))
+    (set_local 525 (i64.add (get_local 0) (i64.const 524)))
+    (set_local 526 (i64.add (get_local 0) (i64.const 525)))
+    (set_local 527 (i64.add (get_local 0) (i64.const 526)))
+    (set_local 528 (i64.add (get_local 0) (i64.const 527)))
+    (set_local 529 (i64.add (get_local 0) (i64.const 528)))
+    (set_local 530 (i64.add (get_local 0) (i64.const 529)))
+    (set_local 531 (i64.add (get_local 0) (i64.const 530)))
+    (set_local 532 (i64.add (get_local 0) (i64.const 531)))
+    (set_local 533 (i64.add (get_local 0) (i64.const 532)))
+    (set_local 534 (i64.add (get_local 0) (i64.const 533)))
+    (set_local 535 (i64.add (get_local 0) (i64.const 534)))
+    (set_local 536 (i64.add (get_local 0) (i64.const 535)))
+    (set_local 537 (i64.add (get_local 0) (i64.const 536)))
+    (set_local 538 (i64.add (get_local 0) (i64.const 537)))
+    (set_local 539 (i64.add (get_local 0) (i64.const 538)))
+    (set_local 540 (i64.add (get_local 0) (i64.const 539)))
+    (set_local 541 (i64.add (get_local 0) (i64.const 540)))
+    (set_local 542 (i64.add (get_local 0) (i64.const 541)))
+    (set_local 543 (i64.add (get_local 0) (i64.const 542)))
+    (set_local 544 (i64.add (get_local 0) (i64.const 543)))
+    (set_local 545 (i64.add (get_local 0) (i64.const 544)))
+    (set_local 546 (i64.add (get_local 0) (i64.const 545)))
+    (set_local 547 (i64.add (get_local 0) (i64.const 546)))
+    (set_local 548 (i64.add (get_local 0) (i64.const 547)))
+    (set_local 549 (i64.add (get_local 0) (i64.const 548)))
+    (set_local 550 (i64.add (get_local 0) (i64.const 549)))
+    (set_local 551 (i64.add (get_local 0) (i64.const 550)))
+    (set_local 552 (i64.add (get_local 0) (i64.const 551)))
+    (set_local 553 (i64.add (get_local 0) (i64.const 552)))
+    (set_local 554 (i64.add (get_local 0) (i64.const 553)))
+    (set_local 555 (i64.add (get_local 0) (i64.const 554)))
+    (set_local 556 (i64.add (get_local 0) (i64.const 555)))
+    (set_local 557 (i64.add (get_local 0) (i64.const 556)))
+    (set_local 558 (i64.add (get_local 0) (i64.const 557)))
+    (set_local 559 (i64.add (get_local 0) (i64.const 558)))
+    (set_local 560 (i64.add (get_local 0) (i64.const 559)))
+    (set_local 561 (i64.add (get_local 0) (i64.const 560)))
+    (set_local 562 (i64.add (get_local 0) (i64.const 561)))
+    (set_local 563 (i64.add (get_local 0) (i64.const 562)))
+    (set_local 564 (i64.add (get_local 0) (i64.const 563)))
+    (set_local 565 (i64.add (get_local 0) (i64.const 564)))
+    (set_local 566 (i64.add (get_local 0) (i64.const 565)))
+    (set_local 567 (i64.add (get_local 0) (i64.const 566)))
+    (set_local 568 (i64.add (get_local 0) (i64.const 567)))
+    (set_local 569 (i64.add (get_local 0) (i64.const 568)))
+    (set_local 570 (i64.add (get_local 0) (i64.const 569)))
+    (set_local 571 (i64.add (get_local 0) (i64.const 570)))
+    (set_local 572 (i64.add (get_local 0) (i64.const 571)))
+    (set_local 573 (i64.add (get_local 0) (i64.const 572)))
+    (set_local 574 (i64.add (get_local 0) (i64.const 573)))
+    (set_local 575 (i64.add (get_local 0) (i64.const 574)))
+    (set_local 576 (i64.add (get_local 0) (i64.const 575)))
+    (set_local 577 (i64.add (get_local 0) (i64.const 576)))
+    (set_local 578 (i64.add (get_local 0) (i64.const 577)))
+    (set_local 579 (i64.add (get_local 0) (i64.const 578)))
+    (set_local 580 (i64.add (get_local 0) (i64.const 579)))
+    (set_local 581 (i64.add (get_local 0) (i64.const 580)))
+    (set_local 582 (i64.add (get_local 0) (i64.const 581)))
+    (set_local 583 (i64.add (get_local 0) (i64.const 582)))
+    (set_local 584 (i64.add (get_local 0) (i64.const 583)))
+    (set_local 585 (i64.add (get_local 0) (i64.const 584)))
+    (set_local 586 (i64.add (get_local 0) (i64.const 585)))
+    (set_local 587 (i64.add (get_local 0) (i64.const 586)))
+    (set_local 588 (i64.add (get_local 0) (i64.const 587)))
+    (set_local 589 (i64.add (get_local 0) (i64.const 588)))
+    (set_local 590 (i64.add (get_local 0) (i64.const 589)))
+    (set_local 591 (i64.add (get_local 0) (i64.const 590)))
+    (set_local 592 (i64.add (get_local 0) (i64.const 591)))
+    (set_local 593 (i64.add (get_local 0) (i64.const 592)))
+    (set_local 594 (i64.add (get_local 0) (i64.const 593)))
+    (set_local 595 (i64.add (get_local 0) (i64.const 594)))
+    (set_local 596 (i64.add (get_local 0) (i64.const 595)))
+    (set_local 597 (i64.add (get_local 0) (i64.const 596)))
+    (set_local 598 (i64.add (get_local 0) (i64.const 597)))
+    (set_local 599 (i64.add (get_local 0) (i64.const 598)))
+    (set_local 600 (i64.add (get_local 0) (i64.const 599)))
+    (drop (call $leaf))
+    (get_local 1)
+    (i64.add (get_local 2))
+    (i64.add (get_local 3))
+    (i64.add (get_local 4))
+    (i64.add (get_local 5))
+    (i64.add (get_local 6))
+    (i64.add (get_local 7))
+    (i64.add (get_local 8))
+    (i64.add (get_local 9))
+    (i64.add (get_local 10))
+    (i64.add (get_local 11))
+    (i64.add (get_local 12))
+    (i64.add (get_local 13))
+    (i64.add (get_local 14))
+    (i64.add (get_local 15))
+    (i64.add (get_local 16))
+    (i64.add (get_local 17))
+    (i64.add (get_local 18))
+    (i64.add (get_local 19))
+    (i64.add (get_local 20))
+    (i64.add (get_local 21))
+    (i64.add (get_local 22))
+    (i64.add (get_local 23))
+    (i64.add (get_local 24))
+    (i64.add (get_local 25))
+    (i64.add (get_local 26))
+    (i64.add (get_local 27))
+    (i64.add (get_local 28))
+    (i64.add (get_local 29))
+    (i64.add (get_local 30))
+    (i64.add (get_local 31))
+    (i64.add (get_local 32))
+    (i64.add (get_local 33))
+    (i64.add (get_local 34))
+    (i64.add (get_local 35))
+    (i64.add (get_local 36))
+    (i64.add (get_local 37))
+    (i64.add (get_local 38))
+    (i64.add (get_local 39))
+    (i64.add (get_local 40))
+    (i64.add (get_local 41))
+    (i64.add (get_local 42))
+    (i64.add (get_local 43))
+    (i64.add (get_local 44))
+    (i64.add (get_local 45))
+    (i64.add (get_local 46))
+    (i64.add (get_local 47))
+    (i64.add (get_local 48))
+    (i64.add (get_local 49))
+    (i64.add (get_local 50))
+    (i64.add (get_local 51))
+    (i64.add (get_local 52))
+    (i64.add (get_local 53))
+    (i64.add (get_local 54))
+    (i64.add (get_local 55))
+    (i64.add (get_local 56))
+    (i64.add (get_local 57))
+    (i64.add (get_local 58))
+    (i64.add (get_local 59))
+    (i64.add (get_local 60))
+    (i64.add (get_local 61))
+    (i64.add (get_local 62))
+    (i64.add (get_local 63))
+    (i64.add (get_local 64))
+    (i64.add (get_local 65))
+    (i64.add (get_local 66))
+    (i64.add (get_local 67))
+    (i64.add (get_local 68))
+    (i64.add (get_local 69))
+    (i64.add (get_local 70))
+    (i64.add (get_local 71))
+    (i64.add (get_local 72))
+    (i64.add (get_local 73))
+    (i64.add (get_local 74))
+    (i64.add (get_local 75))
+    (i64.add (get_local 76))
+    (i64.add (get_local 77))
+    (i64.add (get_local 78))
+    (i64.add (get_local 79))
+    (i64.add (get_local 80))
+    (i64.add (get_local 81))
+    (i64.add (get_local 82))
+    (i64.add (get_local 83))
+    (i64.add (get_local 84))
+    (i64.add (get_local 85))
+    (i64.add (get_local 86))
+    (i64.add (get_local 87))
+    (i64.add (get_local 88))
+    (i64.add (get_local 89))
+    (i64.add (get_local 90))
+    (i64.add (get_local 91))
+    (i64.add (get_local 92))
+    (i64.add (get_local 93))
+    (i64.add (get_local 94))
+    (i64.add (get_local 95))
+    (i64.add (get_local 96))
+    (i64.add (get_local 97))
+    (i64.add (get_local 98))
+    (i64.add (get_local 99))
+    (i64.add (get_local 100))
+    (i64.add (get_local 101))
+    (i64.add (get_local 102))
+    (i64.add (get_local 103))
+    (i64.add (get_local 104))
+    (i64.add (get_local 105))
+    (i64.add (get_local 106))
+    (i64.add (get_local 107))
+    (i64.add (get_local 108))
+    (i64.add (get_local 109))
+    (i64.add (get_local 110))
+    (i64.add (get_local 111))
+    (i64.add (get_local 112))
+    (i64.add (get_local 113))
+    (i64.add (get_local 114))
+    (i64.add (get_local 115))
+    (i64.add (get_local 116))
+    (i64.add (get_local 117))
+    (i64.add (get_local 118))
+    (i64.add (get_local 119))
+    (i64.add (get_local 120))
+    (i64.add (get_local 121))
+    (i64.add (get_local 122))
+    (i64.add (get_local 123))
+    (i64.add (get_local 124))
+    (i64.add (get_local 125))
+    (i64.add (get_local 126))
+    (i64.add (get_local 127))
+    (i64.add (get_local 128))
+    (i64.add (get_local 129))
+    (i64.add (get_local 130))
+    (i64.add (get_local 131))
+    (i64.add (get_local 132))
+    (i64.add (get_local 133))
+    (i64.add (get_local 134))
+    (i64.add (get_local 135))
+    (i64.add (get_local 136))
+    (i64.add (get_local 137))
+    (i64.add (get_local 138))
+    (i64.add (get_local 139))
+    (i64.add (get_local 140))
+    (i64.add (get_local 141))
+    (i64.add (get_local 142))
+    (i64.add (get_local 143))
+    (i64.add (get_local 144))
+    (i64.add (get_local 145))
+    (i64.add (get_local 146))
+    (i64.add (get_local 147))
+    (i64.add (get_local 148))
+    (i64.add (get_local 149))
+    (i64.add (get_local 150))
+    (i64.add (get_local 151))
+    (i64.add (get_local 152))
+    (i64.add (get_local 153))
+    (i64.add (get_local 154))
+    (i64.add (get_local 155))
+    (i64.add (get_local 156))
+    (i64.add (get_local 157))
+    (i64.add (get_local 158))
+    (i64.add (get_local 159))
+    (i64.add (get_local 160))
+    (i64.add (get_local 161))
+    (i64.add (get_local 162))
+    (i64.add (get_local 163))
+    (i64.add (get_local 164))
+    (i64.add (get_local 165))
+    (i64.add (get_local 166))
+    (i64.add (get_local 167))
+    (i64.add (get_local 168))
+    (i64.add (get_local 169))
+    (i64.add (get_local 170))
+    (i64.add (get_local 171))
+    (i64.add (get_local 172))
+    (i64.add (get_local 173))
+    (i64.add (get_local 174))
+    (i64.add (get_local 175))
+    (i64.add (get_local 176))
+    (i64.add (get_local 177))
+    (i64.add (get_local 178))
+    (i64.add (get_local 179))
+    (i64.add (get_local 180))
+    (i64.add (get_local 181))
+    (i64.add (get_local 182))
+    (i64.add (get_local 183))
+    (i64.add (get_local 184))
+    (i64.add (get_local 185))
+    (i64.add (get_local 186))
+    (i64.add (get_local 187))
+    (i64.add (get_local 188))
+    (i64.add (get_local 189))
+    (i64.add (get_local 190))
+    (i64.add (get_local 191))
+    (i64.add (get_local 192))
+    (i64.add (get_local 193))
+    (i64.add (get_local 194))
+    (i64.add (get_local 195))
+    (i64.add (get_local 196))
+    (i64.add (get_local 197))
+    (i64.add (get_local 198))
+    (i64.add (get_local 199))
+    (i64.add (get_local 200))
+    (i64.add (get_local 201))
+    (i64.add (get_local 202))
+    (i64.add (get_local 203))
+    (i64.add (get_local 204))
+    (i64.add (get_local 205))
+    (i64.add (get_local 206))
+    (i64.add (get_local 207))
+    (i64.add (get_local 208))
+    (i64.add (get_local 209))
+    (i64.add (get_local 210))
+    (i64.add (get_local 211))
+    (i64.add (get_local 212))
+    (i64.add (get_local 213))
+    (i64.add (get_local 214))
+    (i64.add (get_local 215))
+    (i64.add (get_local 216))
+    (i64.add (get_local 217))
+    (i64.add (get_local 218))
+    (i64.add (get_local 219))
+    (i64.add (get_local 220))
+    (i64.add (get_local 221))
+    (i64.add (get_local 222))
+    (i64.add (get_local 223))
+    (i64.add (get_local 224))
+    (i64.add (get_local 225))
+    (i64.add (get_local 226))
+    (i64.add (get_local 227))
+    (i64.add (get_local 228))
+    (i64.add (get_local 229))
+    (i64.add (get_local 230))
+    (i64.add (get_local 231))
+    (i64.add (get_local 232))
+    (i64.add (get_local 233))
+    (i64.add (get_local 234))
+    (i64.add (get_local 235))
+    (i64.add (get_local 236))
+    (i64.add (get_local 237))
+    (i64.add (get_local 238))
+    (i64.add (get_local 239))
+    (i64.add (get_local 240))
+    (i64.add (get_local 241))
+    (i64.add (get_local 242))
+    (i64.add (get_local 243))
+    (i64.add (get_local 244))
+    (i64.add (get_local 245))
+    (i64.add (get_local 246))
+    (i64.add (get_local 247))
+    (i64.add (get_local 248))
+    (i64.add (get_local 249))
+    (i64.add (get_local 250))
+    (i64.add (get_local 251))
+    (i64.add (get_local 252))
+    (i64.add (get_local 253))
+    (i64.add (get_local 254))
+    (i64.add (get_local 255))
+    (i64.add (get_local 256))
+    (i64.add (get_local 257))
+    (i64.add (get_local 258))
+    (i64.add (get_local 259))
+    (i64.add (get_local 260))
+    (i64.add (get_local 261))
+    (i64.add (get_local 262))
+    (i64.add (get_local 263))
+    (i64.add (get_local 264))
+    (i64.add (get_local 265))
+    (i64.add (get_local 266))
+    (i64.add (get_local 267))
+    (i64.add (get_local 268))
+    (i64.add (get_local 269))
+    (i64.add (get_local 270))
+    (i64.add (get_local 271))
+    (i64.add (get_local 272))
+    (i64.add (get_local 273))
+    (i64.add (get_local 274))
+    (i64.add (get_local 275))
+    (i64.add (get_local 276))
+    (i64.add (get_local 277))
+    (i64.add (get_local 278))
+    (i64.add (get_local 279))
+    (i64.add (get_local 280))
+    (i64.add (get_local 281))
+    (i64.add (get_local 282))
+    (i64.add (get_local 283))
+    (i64.add (get_local 284))
+    (i64.add (get_local 285))
+    (i64.add (get_local 286))
+    (i64.add (get_local 287))
+    (i64.add (get_local 288))
+    (i64.add (get_local 289))
+    (i64.add (get_local 290))
+    (i64.add (get_local 291))
+    (i64.add (get_local 292))
+    (i64.add (get_local 293))
+    (i64.add (get_local 294))
+    (i64.add (get_local 295))
+    (i64.add (get_local 296))
+    (i64.add (get_local 297))
+    (i64.add (get_local 298))
+    (i64.add (get_local 299))
+    (i64.add (get_local 300))
+    (i64.add (get_local 301))
+    (i64.add (get_local 302))
+    (i64.add (get_local 303))
+    (i64.add (get_local 304))
+    (i64.add (get_local 305))
+    (i64.add (get_local 306))
+    (i64.add (get_local 307))
+    (i64.add (get_local 308))
+    (i64.add (get_local 309))
+    (i64.add (get_local 310))
+    (i64.add (get_local 311))
+    (i64.add (get_local 312))
+    (i64.add (get_local 313))
+    (i64.add (get_local 314))
+    (i64.add (get_local 315))
+    (i64.add (get_local 316))
+    (i64.add (get_local 317))
+    (i64.add (get_local 318))
+    (i64.add (get_local 319))
+    (i64.add (get_local 320))
+    (i64.add (get_local 321))
+    (i64.add (get_local 322))
+    (i64.add (get_local 323))
+    (i64.add (get_local 324))
+    (i64.add (get_local 325))
+    (i64.add (get_local 326))
+    (i64.add (get_local 327))
+    (i64.add (get_local 328))
+    (i64.add (get_local 329))
+    (i64.add (get_local 330))
+    (i64.add (get_local 331))
+    (i64.add (get_local 332))
+    (i64.add (get_local 333))
+    (i64.add (get_local 334))
+    (i64.add (get_local 335))
+    (i64.add (get_local 336))
+    (i64.add (get_local 337))
+    (i64.add (get_local 338))
+    (i64.add (get_local 339))
+    (i64.add (get_local 340))
+    (i64.add (get_local 341))
+    (i64.add (get_local 342))
+    (i64.add (get_local 343))
+    (i64.add (get_local 344))
+    (i64.add (get_local 345))
+    (i64.add (get_local 346))
+    (i64.add (get_local 347))
+    (i64.add (get_local 348))
+    (i64.add (get_local 349))
+    (i64.add (get_local 350))
+    (i64.add (get_local 351))
+    (i64.add (get_local 352))
+    (i64.add (get_local 353))
+    (i64.add (get_local 354))
+    (i64.add (get_local 355))
+    (i64.add (get_local 356))
+    (i64.add (get_local 357))
+    (i64.add (get_local 358))
+    (i64.add (get_local 359))
+    (i64.add (get_local 360))
+    (i64.add (get_local 361))
+    (i64.add (get_local 362))
+    (i64.add (get_local 363))
+    (i64.add (get_local 364))
+    (i64.add (get_local 365))
+    (i64.add (get_local 366))
+    (i64.add (get_local 367))
+    (i64.add (get_local 368))
+    (i64.add (get_local 369))
+    (i64.add (get_local 370))
+    (i64.add (get_local 371))
+    (i64.add (get_local 372))
+    (i64.add (get_local 373))
+    (i64.add (get_local 374))
+    (i64.add (get_local 375))
+    (i64.add (get_local 376))
+    (i64.add (get_local 377))
+    (i64.add (get_local 378))
+    (i64.add (get_local 379))
+    (i64.add (get_local 380))
+    (i64.add (get_local 381))
+    (i64.add (get_local 382))
+    (i64.add (get_local 383))
+    (i64.add (get_local 384))
+    (i64.add (get_local 385))
+    (i64.add (get_local 386))
+    (i64.add (get_local 387))
+    (i64.add (get_local 388))
+    (i64.add (get_local 389))
+    (i64.add (get_local 390))
+    (i64.add (get_local 391))
+    (i64.add (get_local 392))
+    (i64.add (get_local 393))
+    (i64.add (get_local 394))
+    (i64.add (get_local 395))
+    (i64.add (get_local 396))
+    (i64.add (get_local 397))
+    (i64.add (get_local 398))
+    (i64.add (get_local 399))
+    (i64.add (get_local 400))
+    (i64.add (get_local 401))
+    (i64.add (get_local 402))
+    (i64.add (get_local 403))
+    (i64.add (get_local 404))
+    (i64.add (get_local 405))
+    (i64.add (get_local 406))
+    (i64.add (get_local 407))
+    (i64.add (get_local 408))
+    (i64.add (get_local 409))
+    (i64.add (get_local 410))
+    (i64.add (get_local 411))
+    (i64.add (get_local 412))
+    (i64.add (get_local 413))
+    (i64.add (get_local 414))
+    (i64.add (get_local 415))
+    (i64.add (get_local 416))
+    (i64.add (get_local 417))
+    (i64.add (get_local 418))
+    (i64.add (get_local 419))
+    (i64.add (get_local 420))
+    (i64.add (get_local 421))
+    (i64.add (get_local 422))
+    (i64.add (get_local 423))
+    (i64.add (get_local 424))
+    (i64.add (get_local 425))
+    (i64.add (get_local 426))
+    (i64.add (get_local 427))
+    (i64.add (get_local 428))
+    (i64.add (get_local 429))
+    (i64.add (get_local 430))
+    (i64.add (get_local 431))
+    (i64.add (get_local 432))
+    (i64.add (get_local 433))
+    (i64.add (get_local 434))
+    (i64.add (get_local 435))
+    (i64.add (get_local 436))
+    (i64.add (get_local 437))
+    (i64.add (get_local 438))
+    (i64.add (get_local 439))
+    (i64.add (get_local 440))
+    (i64.add (get_local 441))
+    (i64.add (get_local 442))
+    (i64.add (get_local 443))
+    (i64.add (get_local 444))
+    (i64.add (get_local 445))
+    (i64.add (get_local 446))
+    (i64.add (get_local 447))
+    (i64.add (get_local 448))
+    (i64.add (get_local 449))
+    (i64.add (get_local 450))
+    (i64.add (get_local 451))
+    (i64.add (get_local 452))
+    (i64.add (get_local 453))
+    (i64.add (get_local 454))
+    (i64.add (get_local 455))
+    (i64.add (get_local 456))
+    (i64.add (get_local 457))
+    (i64.add (get_local 458))
+    (i64.add (get_local 459))
+    (i64.add (get_local 460))
+    (i64.add (get_local 461))
+    (i64.add (get_local 462))
+    (i64.add (get_local 463))
+    (i64.add (get_local 464))
+    (i64.add (get_local 465))
+    (i64.add (get_local 466))
+    (i64.add (get_local 467))
+    (i64.add (get_local 468))
+    (i64.add (get_local 469))
+    (i64.add (get_local 470))
+    (i64.add (get_local 471))
+    (i64.add (get_local 472))
+    (i64.add (get_local 473))
+    (i64.add (get_local 474))
+    (i64.add (get_local 475))
+    (i64.add (get_local 476))
+    (i64.add (get_local 477))
+    (i64.add (get_local 478))
+    (i64.add (get_local 479))
+    (i64.add (get_local 480))
+    (i64.add (get_local 481))
+    (i64.add (get_local 482))
+    (i64.add (get_local 483))
+    (i64.add (get_local 484))
+    (i64.add (get_local 485))
+    (i64.add (get_local 486))
+    (i64.add (get_local 487))
+    (i64.add (get_local 488))
+    (i64.add (get_local 489))
+    (i64.add (get_local 490))
+    (i64.add (get_local 491))
+    (i64.add (get_local 492))
+    (i64.add (get_local 493))
+    (i64.add (get_local 494))
+    (i64.add (get_local 495))
+    (i64.add (get_local 496))
+    (i64.add (get_local 497))
+    (i64.add (get_local 498))
+    (i64.add (get_local 499))
+    (i64.add (get_local 500))
+    (i64.add (get_local 501))
+    (i64.add (get_local 502))
+    (i64.add (get_local 503))
+    (i64.add (get_local 504))
+    (i64.add (get_local 505))
+    (i64.add (get_local 506))
+    (i64.add (get_local 507))
+    (i64.add (get_local 508))
+    (i64.add (get_local 509))
+    (i64.add (get_local 510))
+    (i64.add (get_local 511))
+    (i64.add (get_local 512))
+    (i64.add (get_local 513))
+    (i64.add (get_local 514))
+    (i64.add (get_local 515))
+    (i64.add (get_local 516))
+    (i64.add (get_local 517))
+    (i64.add (get_local 518))
+    (i64.add (get_local 519))
+    (i64.add (get_local 520))
+    (i64.add (get_local 521))
+    (i64.add (get_local 522))
+    (i64.add (get_local 523))
+    (i64.add (get_local 524))
+    (i64.add (get_local 525))
+    (i64.add (get_local 526))
+    (i64.add (get_local 527))
+    (i64.add (get_local 528))
+    (i64.add (get_local 529))
+    (i64.add (get_local 530))
+    (i64.add (get_local 531))
+    (i64.add (get_local 532))
+    (i64.add (get_local 533))
+    (i64.add (get_local 534))
+    (i64.add (get_local 535))
+    (i64.add (get_local 536))
+    (i64.add (get_local 537))
+    (i64.add (get_local 538))
+    (i64.add (get_local 539))
+    (i64.add (get_local 540))
+    (i64.add (get_local 541))
+    (i64.add (get_local 542))
+    (i64.add (get_local 543))
+    (i64.add (get_local 544))
+    (i64.add (get_local 545))
+    (i64.add (get_local 546))
+    (i64.add (get_local 547))
+    (i64.add (get_local 548))
+    (i64.add (get_local 549))
+    (i64.add (get_local 550))
+    (i64.add (get_local 551))
+    (i64.add (get_local 552))
+    (i64.add (get_local 553))
+    (i64.add (get_local 554))
+    (i64.add (get_local 555))
+    (i64.add (get_local 556))
+    (i64.add (get_local 557))
+    (i64.add (get_local 558))
+    (i64.add (get_local 559))
+    (i64.add (get_local 560))
+    (i64.add (get_local 561))
+    (i64.add (get_local 562))
+    (i64.add (get_local 563))
+    (i64.add (get_local 564))
+    (i64.add (get_local 565))
+    (i64.add (get_local 566))
+    (i64.add (get_local 567))
+    (i64.add (get_local 568))
+    (i64.add (get_local 569))
+    (i64.add (get_local 570))
+    (i64.add (get_local 571))
+    (i64.add (get_local 572))
+    (i64.add (get_local 573))
+    (i64.add (get_local 574))
+    (i64.add (get_local 575))
+    (i64.add (get_local 576))
+    (i64.add (get_local 577))
+    (i64.add (get_local 578))
+    (i64.add (get_local 579))
+    (i64.add (get_local 580))
+    (i64.add (get_local 581))
+    (i64.add (get_local 582))
+    (i64.add (get_local 583))
+    (i64.add (get_local 584))
+    (i64.add (get_local 585))
+    (i64.add (get_local 586))
+    (i64.add (get_local 587))
+    (i64.add (get_local 588))
+    (i64.add (get_local 589))
+    (i64.add (get_local 590))
+    (i64.add (get_local 591))
+    (i64.add (get_local 592))
+    (i64.add (get_local 593))
+    (i64.add (get_local 594))
+    (i64.add (get_local 595))
+    (i64.add (get_local 596))
+    (i64.add (get_local 597))
+    (i64.add (get_local 598))
+    (i64.add (get_local 599))
+    (i64.add (get_local 600))
+  )
+)
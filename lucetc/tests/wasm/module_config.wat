@@ -0,0 +1,6 @@
+(module
+  (memory 2)
+  (func $main (export "main")
+    nop
+  )
+)
@@ -0,0 +1,11 @@
+(module
+  (func $foo (import "env" "foo") (param i32) (result i32))
+  (memory (export "foo") 1)
+  ;; Named like the export of the next function.
+  (func $bar (param i32) (result i32)
+    (call $foo (get_local 0))
+  )
+  (func $main (export "bar") (param i32) (result i32)
+    (call $bar (get_local 0))
+  )
+)
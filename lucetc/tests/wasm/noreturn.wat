@@ -0,0 +1,18 @@
+(module
+  (func $trap (export "trap")
+    (unreachable)
+  )
+  (func $spin (export "spin")
+    (loop $l (br $l))
+  )
+  (func $early_return (export "early_return") (param i32)
+    (if (get_local 0) (then (return)))
+    (unreachable)
+  )
+  (func $loop_exit (export "loop_exit") (param i32)
+    (loop $l
+      (br_if 1 (get_local 0))
+      (br $l))
+    (unreachable)
+  )
+)
@@ -0,0 +1,16 @@
+(module
+  (func $add32 (export "add32") (param i32 i32) (result i32)
+    (i32.add (get_local 0) (get_local 1)))
+  (func $sub32 (export "sub32") (param i32 i32) (result i32)
+    (i32.sub (get_local 0) (get_local 1)))
+  (func $mul32 (export "mul32") (param i32 i32) (result i32)
+    (i32.mul (get_local 0) (get_local 1)))
+  (func $add64 (export "add64") (param i64 i64) (result i64)
+    (i64.add (get_local 0) (get_local 1)))
+  (func $sub64 (export "sub64") (param i64 i64) (result i64)
+    (i64.sub (get_local 0) (get_local 1)))
+  (func $mul64 (export "mul64") (param i64 i64) (result i64)
+    (i64.mul (get_local 0) (get_local 1)))
+  (func $and32 (export "and32") (param i32 i32) (result i32)
+    (i32.and (get_local 0) (get_local 1)))
+)
@@ -0,0 +1,9 @@
+;; Initializes pages 0, 2, and 5 of the heap and leaves the rest zeroed, so the sparse page
+;; table has a mix of pointers and nulls.
+(module
+  (memory 1)
+  (data (i32.const 0) "page zero")
+  (data (i32.const 8192) "page two")
+  (data (i32.const 20480) "page five")
+  (data (i32.const 24575) "!")
+)
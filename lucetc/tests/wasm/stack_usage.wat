@@ -0,0 +1,75 @@
+(module
+  (func $leaf (export "leaf") (result i64)
+    (i64.const 1)
+  )
+  ;; Every local is live across the call to $leaf, so they all get spilled to the stack.
+  (func $spills (export "spills") (param i64) (result i64)
+    (local i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64)
+    (set_local 1 (i64.add (get_local 0) (i64.const 0)))
+    (set_local 2 (i64.add (get_local 0) (i64.const 1)))
+    (set_local 3 (i64.add (get_local 0) (i64.const 2)))
+    (set_local 4 (i64.add (get_local 0) (i64.const 3)))
+    (set_local 5 (i64.add (get_local 0) (i64.const 4)))
+    (set_local 6 (i64.add (get_local 0) (i64.const 5)))
+    (set_local 7 (i64.add (get_local 0) (i64.const 6)))
+    (set_local 8 (i64.add (get_local 0) (i64.const 7)))
+    (set_local 9 (i64.add (get_local 0) (i64.const 8)))
+    (set_local 10 (i64.add (get_local 0) (i64.const 9)))
+    (set_local 11 (i64.add (get_local 0) (i64.const 10)))
+    (set_local 12 (i64.add (get_local 0) (i64.const 11)))
+    (set_local 13 (i64.add (get_local 0) (i64.const 12)))
+    (set_local 14 (i64.add (get_local 0) (i64.const 13)))
+    (set_local 15 (i64.add (get_local 0) (i64.const 14)))
+    (set_local 16 (i64.add (get_local 0) (i64.const 15)))
+    (set_local 17 (i64.add (get_local 0) (i64.const 16)))
+    (set_local 18 (i64.add (get_local 0) (i64.const 17)))
+    (set_local 19 (i64.add (get_local 0) (i64.const 18)))
+    (set_local 20 (i64.add (get_local 0) (i64.const 19)))
+    (set_local 21 (i64.add (get_local 0) (i64.const 20)))
+    (set_local 22 (i64.add (get_local 0) (i64.const 21)))
+    (set_local 23 (i64.add (get_local 0) (i64.const 22)))
+    (set_local 24 (i64.add (get_local 0) (i64.const 23)))
+    (set_local 25 (i64.add (get_local 0) (i64.const 24)))
+    (set_local 26 (i64.add (get_local 0) (i64.const 25)))
+    (set_local 27 (i64.add (get_local 0) (i64.const 26)))
+    (set_local 28 (i64.add (get_local 0) (i64.const 27)))
+    (set_local 29 (i64.add (get_local 0) (i64.const 28)))
+    (set_local 30 (i64.add (get_local 0) (i64.const 29)))
+    (set_local 31 (i64.add (get_local 0) (i64.const 30)))
+    (set_local 32 (i64.add (get_local 0) (i64.const 31)))
+    (set_local 33 (i64.add (get_local 0) (i64.const 32)))
+    (set_local 34 (i64.add (get_local 0) (i64.const 33)))
+    (set_local 35 (i64.add (get_local 0) (i64.const 34)))
+    (set_local 36 (i64.add (get_local 0) (i64.const 35)))
+    (set_local 37 (i64.add (get_local 0) (i64.const 36)))
+    (set_local 38 (i64.add (get_local 0) (i64.const 37)))
+    (set_local 39 (i64.add (get_local 0) (i64.const 38)))
+    (set_local 40 (i64.add (get_local 0) (i64.const 39)))
+    (set_local 41 (i64.add (get_local 0) (i64.const 40)))
+    (set_local 42 (i64.add (get_local 0) (i64.const 41)))
+    (set_local 43 (i64.add (get_local 0) (i64.const 42)))
+    (set_local 44 (i64.add (get_local 0) (i64.const 43)))
+    (set_local 45 (i64.add (get_local 0) (i64.const 44)))
+    (set_local 46 (i64.add (get_local 0) (i64.const 45)))
+    (set_local 47 (i64.add (get_local 0) (i64.const 46)))
+    (set_local 48 (i64.add (get_local 0) (i64.const 47)))
+    (set_local 49 (i64.add (get_local 0) (i64.const 48)))
+    (set_local 50 (i64.add (get_local 0) (i64.const 49)))
+    (set_local 51 (i64.add (get_local 0) (i64.const 50)))
+    (set_local 52 (i64.add (get_local 0) (i64.const 51)))
+    (set_local 53 (i64.add (get_local 0) (i64.const 52)))
+    (set_local 54 (i64.add (get_local 0) (i64.const 53)))
+    (set_local 55 (i64.add (get_local 0) (i64.const 54)))
+    (set_local 56 (i64.add (get_local 0) (i64.const 55)))
+    (set_local 57 (i64.add (get_local 0) (i64.const 56)))
+    (set_local 58 (i64.add (get_local 0) (i64.const 57)))
+    (set_local 59 (i64.add (get_local 0) (i64.const 58)))
+    (set_local 60 (i64.add (get_local 0) (i64.const 59)))
+    (set_local 61 (i64.add (get_local 0) (i64.const 60)))
+    (set_local 62 (i64.add (get_local 0) (i64.const 61)))
+    (set_local 63 (i64.add (get_local 0) (i64.const 62)))
+    (set_local 64 (i64.add (get_local 0) (i64.const 63)))
+    (drop (call $leaf))
+    (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (i64.add (get_local 1) (get_local 2)) (get_local 3)) (get_local 4)) (get_local 5)) (get_local 6)) (get_local 7)) (get_local 8)) (get_local 9)) (get_local 10)) (get_local 11)) (get_local 12)) (get_local 13)) (get_local 14)) (get_local 15)) (get_local 16)) (get_local 17)) (get_local 18)) (get_local 19)) (get_local 20)) (get_local 21)) (get_local 22)) (get_local 23)) (get_local 24)) (get_local 25)) (get_local 26)) (get_local 27)) (get_local 28)) (get_local 29)) (get_local 30)) (get_local 31)) (get_local 32)) (get_local 33)) (get_local 34)) (get_local 35)) (get_local 36)) (get_local 37)) (get_local 38)) (get_local 39)) (get_local 40)) (get_local 41)) (get_local 42)) (get_local 43)) (get_local 44)) (get_local 45)) (get_local 46)) (get_local 47)) (get_local 48)) (get_local 49)) (get_local 50)) (get_local 51)) (get_local 52)) (get_local 53)) (get_local 54)) (get_local 55)) (get_local 56)) (get_local 57)) (get_local 58)) (get_local 59)) (get_local 60)) (get_local 61)) (get_local 62)) (get_local 63)) (get_local 64))
+  )
+)
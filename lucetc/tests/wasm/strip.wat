@@ -0,0 +1,10 @@
+(module
+  (memory 1)
+  (func $a_rather_long_internal_function_name (param i32) (result i32)
+    (i32.div_u (i32.const 100) (get_local 0))
+  )
+  (func $main (export "main") (param i32) (result i32)
+    (call $a_rather_long_internal_function_name (get_local 0))
+  )
+  (data (i32.const 0) "stripped")
+)
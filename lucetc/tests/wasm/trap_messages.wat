@@ -0,0 +1,12 @@
+(module
+  (func $check (export "check") (param i32)
+    (if (i32.lt_s (get_local 0) (i32.const 0))
+      (then unreachable))
+  )
+  (func $other (export "other")
+    unreachable
+  )
+  (func $quiet (export "quiet") (result i32)
+    (i32.const 1)
+  )
+)
@@ -0,0 +1,7 @@
+(module
+  (func $inc (import "env" "inc") (result i32))
+  (func $add (export "add") (param i32 i32) (result i32)
+    (i32.add (get_local 0) (get_local 1)))
+  (func $divide (export "divide") (param i32 i32) (result i32)
+    (i32.div_s (get_local 0) (get_local 1)))
+)
@@ -0,0 +1,6 @@
+(module
+  (func $trunc (export "trunc") (param f32 f64) (result i32)
+    (i32.add
+      (i32.trunc_s/f32 (get_local 0))
+      (i32.wrap/i64 (i64.trunc_u/f64 (get_local 1)))))
+)
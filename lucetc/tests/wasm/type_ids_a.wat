@@ -0,0 +1,10 @@
+(module
+  (type $unary (func (param i32) (result i32)))
+  (type $binary (func (param i32 i32) (result i32)))
+  (func $call (export "call") (param i32 i32) (result i32)
+    (call_indirect (type $unary) (get_local 0) (get_local 1))
+  )
+  (func $inc (type $unary) (i32.add (get_local 0) (i32.const 1)))
+  (func $add (type $binary) (i32.add (get_local 0) (get_local 1)))
+  (table anyfunc (elem $inc $add))
+)
@@ -0,0 +1,9 @@
+(module
+  (type $binary (func (param i32 i32) (result i32)))
+  (type $unary (func (param i32) (result i32)))
+  (func $call (export "call") (param i32 i32) (result i32)
+    (call_indirect (type $unary) (get_local 0) (get_local 1))
+  )
+  (func $dec (type $unary) (i32.sub (get_local 0) (i32.const 1)))
+  (table anyfunc (elem $dec))
+)
@@ -0,0 +1,27 @@
+(module
+  (memory 1)
+  (func $add (export "add") (param i32 i32) (result i32)
+    (i32.add (get_local 0) (get_local 1))
+  )
+  (func $div_s (export "div_s") (param i64 i64) (result i64)
+    (i64.div_s (get_local 0) (get_local 1))
+  )
+  (func $mul_add (export "mul_add") (param f32 f32 f32) (result f32)
+    (f32.add (f32.mul (get_local 0) (get_local 1)) (get_local 2))
+  )
+  ;; Interleaves integer and floating-point parameters.
+  (func $scale (export "scale") (param i32 f64 i64 f64) (result f64)
+    (f64.mul
+      (f64.add (f64.convert_s/i32 (get_local 0)) (get_local 1))
+      (f64.add (f64.convert_s/i64 (get_local 2)) (get_local 3)))
+  )
+  (func $sqrt (export "sqrt") (param f64) (result f64)
+    (f64.sqrt (get_local 0))
+  )
+  (func $store (export "store") (param i32)
+    (i32.store (i32.const 16) (get_local 0))
+  )
+  (func $load (export "load") (result i32)
+    (i32.load (i32.const 16))
+  )
+)
@@ -0,0 +1,6 @@
+(module
+  (func $start (export "_start")
+  )
+  (func $initialize (export "_initialize")
+  )
+)
@@ -0,0 +1,9 @@
+(module
+  (global $base (mut i32) (i32.const 0))
+  (func $initialize (export "_initialize")
+    (set_global $base (i32.const 10))
+  )
+  (func $add (export "add") (param i32) (result i32)
+    (i32.add (get_local 0) (get_global $base))
+  )
+)